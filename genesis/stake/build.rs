@@ -0,0 +1,229 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Buildfile for the stake contract, to generate the ABI schema of the
+//! contract and the typed wrappers of its entrypoints.
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+use contracts_abi_schema::Entrypoint;
+
+/// The entrypoints of the contract, with the types of their argument and of
+/// the data they return or feed, the caller restriction checked by the state
+/// and the topics of the events they emit.
+const ENTRYPOINTS: &[Entrypoint] = &[
+    Entrypoint {
+        name: "stake",
+        argument: "dusk_core::stake::Stake",
+        returns: "()",
+        caller: None,
+        topics: &["stake"],
+    },
+    Entrypoint {
+        name: "unstake",
+        argument: "dusk_core::stake::Withdraw",
+        returns: "()",
+        caller: None,
+        topics: &["unstake"],
+    },
+    Entrypoint {
+        name: "withdraw",
+        argument: "dusk_core::stake::Withdraw",
+        returns: "()",
+        caller: None,
+        topics: &["withdraw"],
+    },
+    Entrypoint {
+        name: "stake_from_contract",
+        argument: "dusk_core::transfer::ReceiveFromContract",
+        returns: "()",
+        caller: None,
+        topics: &["stake"],
+    },
+    Entrypoint {
+        name: "unstake_from_contract",
+        argument: "dusk_core::stake::WithdrawToContract",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["unstake"],
+    },
+    Entrypoint {
+        name: "withdraw_from_contract",
+        argument: "dusk_core::stake::WithdrawToContract",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["withdraw"],
+    },
+    Entrypoint {
+        name: "get_stake",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "Option<dusk_core::stake::StakeData>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "get_stake_keys",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "Option<dusk_core::stake::StakeKeys>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "burnt_amount",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "get_version",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "block_event_digest",
+        argument: "u64",
+        returns: "Option<dusk_core::BlsScalar>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "state_version",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "get_config",
+        argument: "()",
+        returns: "dusk_core::stake::StakeConfig",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "export_summary",
+        argument: "(stake_contract::migration::ExportSummary, stake_contract::migration::ExportCursor, u64)",
+        returns: "stake_contract::migration::ExportChunk",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "stakes",
+        argument: "()",
+        returns: "(dusk_core::stake::StakeKeys, dusk_core::stake::StakeData)",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "prev_state_changes",
+        argument: "()",
+        returns: "(dusk_core::signatures::bls::PublicKey, Option<dusk_core::stake::StakeData>)",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "export_stakes",
+        argument: "(stake_contract::migration::ExportCursor, u64)",
+        returns: "(dusk_core::stake::StakeKeys, dusk_core::stake::StakeData)",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "before_state_transition",
+        argument: "()",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "set_config",
+        argument: "dusk_core::stake::StakeConfig",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "insert_stake",
+        argument: "(dusk_core::stake::StakeKeys, dusk_core::stake::StakeData)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "reward",
+        argument: "Vec<dusk_core::stake::Reward>",
+        returns: "()",
+        caller: None,
+        topics: &["reward"],
+    },
+    Entrypoint {
+        name: "slash",
+        argument: "(dusk_core::signatures::bls::PublicKey, Option<u64>)",
+        returns: "()",
+        caller: None,
+        topics: &["slash"],
+    },
+    Entrypoint {
+        name: "hard_slash",
+        argument: "(dusk_core::signatures::bls::PublicKey, Option<u64>, Option<u8>)",
+        returns: "()",
+        caller: None,
+        topics: &["hard_slash"],
+    },
+    Entrypoint {
+        name: "set_burnt_amount",
+        argument: "u64",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "import_stakes",
+        argument: "Vec<(dusk_core::stake::StakeKeys, dusk_core::stake::StakeData)>",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "finish_import",
+        argument: "stake_contract::migration::ExportSummary",
+        returns: "()",
+        caller: None,
+        topics: &["import"],
+    },
+    Entrypoint {
+        name: "migrate",
+        argument: "(u64, u64)",
+        returns: "()",
+        caller: None,
+        topics: &["migrate"],
+    },
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Ensure we run the build script again even if we change just the build.rs
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+
+    let source = fs::read_to_string("src/lib.rs")?;
+    let schema = contracts_abi_schema::schema(
+        &env::var("CARGO_PKG_NAME")?,
+        &env::var("CARGO_PKG_VERSION")?,
+        &source,
+        ENTRYPOINTS,
+    )?;
+
+    let wrappers = contracts_abi_schema::wrappers(&source, ENTRYPOINTS)?;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out_dir.join("abi.json"), schema)?;
+    fs::write(out_dir.join("wrap.rs"), wrappers)?;
+    Ok(())
+}
@@ -5,187 +5,292 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 #![cfg_attr(target_family = "wasm", no_std)]
-#![cfg(target_family = "wasm")]
 #![feature(arbitrary_self_types)]
-#![deny(unused_crate_dependencies)]
+#![cfg_attr(target_family = "wasm", deny(unused_crate_dependencies))]
 #![deny(unused_extern_crates)]
 
+#[cfg(target_family = "wasm")]
 extern crate alloc;
 
-use dusk_core::abi;
-use dusk_core::transfer::TRANSFER_CONTRACT;
+// The declared types of the entrypoints refer to the types of the contract by
+// the name of its crate
+#[cfg(target_family = "wasm")]
+extern crate self as stake_contract;
 
-mod state;
-use state::StakeState;
+/// The ABI schema of the contract in JSON, generated at build time from the
+/// exported functions.
+pub const ABI_SCHEMA: &str =
+    include_str!(concat!(env!("OUT_DIR"), "/abi.json"));
 
-static mut STATE: StakeState = StakeState::new();
+pub mod migration;
 
-// Transactions
+#[cfg(target_family = "wasm")]
+mod state;
+#[cfg(target_family = "wasm")]
+mod wrap;
 
-#[no_mangle]
-unsafe fn stake(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| {
-        assert_transfer_caller();
-        STATE.stake(arg)
-    })
+/// Convert a position or count given by a caller to a `usize`, saturating on
+/// 32-bit targets instead of truncating, so that the contract behaves the
+/// same on wasm32 and wasm64.
+#[cfg(target_family = "wasm")]
+fn saturating_usize(n: u64) -> usize {
+    usize::try_from(n).unwrap_or(usize::MAX)
 }
 
-#[no_mangle]
-unsafe fn unstake(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| {
-        assert_transfer_caller();
-        STATE.unstake(arg)
-    })
-}
+#[cfg(target_family = "wasm")]
+mod wasm {
+    use dusk_core::abi;
+    use dusk_core::transfer::TRANSFER_CONTRACT;
 
-#[no_mangle]
-unsafe fn withdraw(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| {
-        assert_transfer_caller();
-        STATE.withdraw(arg)
-    })
-}
+    use crate::state::StakeState;
+    use crate::wrap;
 
-#[no_mangle]
-unsafe fn stake_from_contract(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |receive| {
-        // Assert is called from the transfer contract
-        assert_transfer_caller();
-        // Assert is not called directly by "spend_and_execute"
-        // (it's supposed to be called by
-        // TRANSFER_CONTRACT::contract_to_contract ICC)
-        if abi::callstack().len() < 2 {
-            panic!("Cannot be called by a root ICC")
-        }
-        STATE.stake_from_contract(receive)
-    })
-}
+    static mut STATE: StakeState = StakeState::new();
 
-#[no_mangle]
-unsafe fn unstake_from_contract(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |unstake| STATE.unstake_from_contract(unstake))
-}
+    // Transactions
 
-#[no_mangle]
-unsafe fn withdraw_from_contract(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |rewards| STATE.withdraw_from_contract(rewards))
-}
+    #[no_mangle]
+    unsafe fn stake(arg_len: u32) -> u32 {
+        wrap::stake(arg_len, |arg| {
+            assert_transfer_caller();
+            assert_no_pending_migration();
+            STATE.stake(arg)
+        })
+    }
 
-// Queries
+    #[no_mangle]
+    unsafe fn unstake(arg_len: u32) -> u32 {
+        wrap::unstake(arg_len, |arg| {
+            assert_transfer_caller();
+            assert_no_pending_migration();
+            STATE.unstake(arg)
+        })
+    }
 
-#[no_mangle]
-unsafe fn get_stake(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |pk| STATE.get_stake(&pk).cloned())
-}
+    #[no_mangle]
+    unsafe fn withdraw(arg_len: u32) -> u32 {
+        wrap::withdraw(arg_len, |arg| {
+            assert_transfer_caller();
+            assert_no_pending_migration();
+            STATE.withdraw(arg)
+        })
+    }
 
-#[no_mangle]
-unsafe fn get_stake_keys(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |pk| STATE.get_stake_keys(&pk).cloned())
-}
+    #[no_mangle]
+    unsafe fn stake_from_contract(arg_len: u32) -> u32 {
+        wrap::stake_from_contract(arg_len, |receive| {
+            // Assert is called from the transfer contract
+            assert_transfer_caller();
+            // Assert is not called directly by "spend_and_execute"
+            // (it's supposed to be called by
+            // TRANSFER_CONTRACT::contract_to_contract ICC)
+            if abi::callstack().len() < 2 {
+                panic!("Cannot be called by a root ICC")
+            }
+            STATE.stake_from_contract(receive)
+        })
+    }
 
-#[no_mangle]
-unsafe fn burnt_amount(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.burnt_amount())
-}
+    #[no_mangle]
+    unsafe fn unstake_from_contract(arg_len: u32) -> u32 {
+        wrap::unstake_from_contract(arg_len, |unstake| {
+            STATE.unstake_from_contract(unstake)
+        })
+    }
 
-#[no_mangle]
-unsafe fn get_version(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.get_version())
-}
+    #[no_mangle]
+    unsafe fn withdraw_from_contract(arg_len: u32) -> u32 {
+        wrap::withdraw_from_contract(arg_len, |rewards| {
+            STATE.withdraw_from_contract(rewards)
+        })
+    }
 
-#[no_mangle]
-unsafe fn get_config(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.config().clone())
-}
+    // Queries
 
-// "Feeder" queries
+    #[no_mangle]
+    unsafe fn get_stake(arg_len: u32) -> u32 {
+        wrap::get_stake(arg_len, |pk| STATE.get_stake(&pk).cloned())
+    }
 
-#[no_mangle]
-unsafe fn stakes(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.stakes())
-}
+    #[no_mangle]
+    unsafe fn get_stake_keys(arg_len: u32) -> u32 {
+        wrap::get_stake_keys(arg_len, |pk| STATE.get_stake_keys(&pk).cloned())
+    }
 
-#[no_mangle]
-unsafe fn prev_state_changes(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.prev_state_changes())
-}
+    #[no_mangle]
+    unsafe fn burnt_amount(arg_len: u32) -> u32 {
+        wrap::burnt_amount(arg_len, |_: ()| STATE.burnt_amount())
+    }
 
-// "Management" transactions
+    #[no_mangle]
+    unsafe fn get_version(arg_len: u32) -> u32 {
+        wrap::get_version(arg_len, |_: ()| STATE.get_version())
+    }
 
-#[no_mangle]
-unsafe fn before_state_transition(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| {
-        assert_external_caller();
-        STATE.on_new_block()
-    })
-}
+    #[no_mangle]
+    unsafe fn block_event_digest(arg_len: u32) -> u32 {
+        wrap::block_event_digest(arg_len, |height| {
+            STATE.block_event_digest(height)
+        })
+    }
 
-#[no_mangle]
-unsafe fn set_config(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |config| {
-        assert_external_caller();
-        STATE.configure(config)
-    })
-}
+    #[no_mangle]
+    unsafe fn state_version(arg_len: u32) -> u32 {
+        wrap::state_version(arg_len, |_: ()| STATE.state_version())
+    }
 
-#[no_mangle]
-unsafe fn insert_stake(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(pk, stake_data)| {
-        assert_external_caller();
-        STATE.insert_stake(pk, stake_data)
-    })
-}
+    #[no_mangle]
+    unsafe fn get_config(arg_len: u32) -> u32 {
+        wrap::get_config(arg_len, |_: ()| STATE.config().clone())
+    }
 
-#[no_mangle]
-unsafe fn reward(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| {
-        assert_external_caller();
-        STATE.reward(arg);
-    })
-}
+    #[no_mangle]
+    unsafe fn export_summary(arg_len: u32) -> u32 {
+        wrap::export_summary(arg_len, |(summary, from, count_limit)| {
+            STATE.export_summary(summary, from, count_limit)
+        })
+    }
 
-#[no_mangle]
-unsafe fn slash(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(pk, value)| {
-        assert_external_caller();
-        STATE.slash(&pk, value);
-    })
-}
+    // "Feeder" queries
 
-#[no_mangle]
-unsafe fn hard_slash(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(pk, value, severity)| {
-        assert_external_caller();
-        STATE.hard_slash(&pk, value, severity);
-    })
-}
+    #[no_mangle]
+    unsafe fn stakes(arg_len: u32) -> u32 {
+        wrap::stakes(arg_len, |_: ()| STATE.stakes())
+    }
 
-#[no_mangle]
-unsafe fn set_burnt_amount(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |burnt_amount| {
-        assert_external_caller();
-        STATE.set_burnt_amount(burnt_amount)
-    })
-}
+    #[no_mangle]
+    unsafe fn prev_state_changes(arg_len: u32) -> u32 {
+        wrap::prev_state_changes(arg_len, |_: ()| STATE.prev_state_changes())
+    }
 
-/// Asserts the call is made via the transfer contract.
-///
-/// # Panics
-/// When the `caller` is not [`TRANSFER_CONTRACT`].
-fn assert_transfer_caller() {
-    const PANIC_MSG: &str = "Can only be called from the transfer contract";
-    if abi::caller().expect(PANIC_MSG) != TRANSFER_CONTRACT {
-        panic!("{PANIC_MSG}");
+    #[no_mangle]
+    unsafe fn export_stakes(arg_len: u32) -> u32 {
+        wrap::export_stakes(arg_len, |(from, count_limit)| {
+            STATE.export_stakes(from, count_limit)
+        })
     }
-}
 
-/// Asserts the call is made "from the outside", meaning that it's not an
-/// inter-contract call.
-///
-/// # Panics
-/// When the `caller` is not "uninitialized".
-fn assert_external_caller() {
-    if abi::caller().is_some() {
-        panic!("Can only be called from the outside the VM");
+    // "Management" transactions
+
+    #[no_mangle]
+    unsafe fn before_state_transition(arg_len: u32) -> u32 {
+        wrap::before_state_transition(arg_len, |_: ()| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.on_new_block()
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn set_config(arg_len: u32) -> u32 {
+        wrap::set_config(arg_len, |config| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.configure(config)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn insert_stake(arg_len: u32) -> u32 {
+        wrap::insert_stake(arg_len, |(pk, stake_data)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.insert_stake(pk, stake_data)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn reward(arg_len: u32) -> u32 {
+        wrap::reward(arg_len, |arg| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.reward(arg);
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn slash(arg_len: u32) -> u32 {
+        wrap::slash(arg_len, |(pk, value)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.slash(&pk, value);
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn hard_slash(arg_len: u32) -> u32 {
+        wrap::hard_slash(arg_len, |(pk, value, severity)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.hard_slash(&pk, value, severity);
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn set_burnt_amount(arg_len: u32) -> u32 {
+        wrap::set_burnt_amount(arg_len, |burnt_amount| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.set_burnt_amount(burnt_amount)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn import_stakes(arg_len: u32) -> u32 {
+        wrap::import_stakes(arg_len, |stakes| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.import_stakes(stakes)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn finish_import(arg_len: u32) -> u32 {
+        wrap::finish_import(arg_len, |summary| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.finish_import(summary)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn migrate(arg_len: u32) -> u32 {
+        wrap::migrate(arg_len, |(from, to)| {
+            assert_external_caller();
+            STATE.migrate(from, to)
+        })
+    }
+
+    /// Asserts the call is made via the transfer contract.
+    ///
+    /// # Panics
+    /// When the `caller` is not [`TRANSFER_CONTRACT`].
+    fn assert_transfer_caller() {
+        const PANIC_MSG: &str = "Can only be called from the transfer contract";
+        if abi::caller().expect(PANIC_MSG) != TRANSFER_CONTRACT {
+            panic!("{PANIC_MSG}");
+        }
+    }
+
+    /// Asserts the call is made "from the outside", meaning that it's not an
+    /// inter-contract call.
+    ///
+    /// # Panics
+    /// When the `caller` is not "uninitialized".
+    fn assert_external_caller() {
+        if abi::caller().is_some() {
+            panic!("Can only be called from the outside the VM");
+        }
+    }
+
+    /// Asserts the state isn't pending a migration to the layout of the running
+    /// code.
+    ///
+    /// # Panics
+    /// When the version of the state is not the current one.
+    fn assert_no_pending_migration() {
+        if unsafe { STATE.migration_pending() } {
+            panic!("The state is pending a migration");
+        }
     }
 }
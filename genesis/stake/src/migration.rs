@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the export and import of the state of the stake
+//! contract, used to migrate it to a new version of the contract.
+//!
+//! The stakes are exported with `export_stakes`, in chunks, and imported in
+//! the same order and in chunks of any size with `import_stakes` into the new
+//! contract, after its config and burnt amount are set with `set_config` and
+//! `set_burnt_amount`. Each chunk starts at an [`ExportCursor`], and
+//! `export_summary` chains the stakes of the same chunk onto the
+//! [`ExportSummary`] of the previous ones, returning the cursor of the next
+//! chunk in an [`ExportChunk`]. A call to `finish_import` with the summary of
+//! the last chunk then checks the imported state is the same as the exported
+//! one, against the summary chained by the new contract as it imported the
+//! stakes, and finishes the import.
+//!
+//! The layout of the state is also versioned, and a state at a previous
+//! version can be migrated in place with `migrate`. Importing an export of a
+//! previous format leaves the state at the version of its layout the export
+//! was made from, until it is migrated.
+
+use bytecheck::CheckBytes;
+use dusk_bytes::Serializable;
+use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
+use dusk_core::BlsScalar;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Version of the format of the exported state, bumped every time the items
+/// fed by `export_stakes` or covered by the checksum change, together with
+/// the version of the layout of the state.
+///
+/// Exports of version 1 store the default minimum stake of the config as
+/// zero.
+pub const EXPORT_VERSION: u32 = 2;
+
+/// Version of the layout of the state, bumped every time the state changes in
+/// a way that requires a migration step.
+pub const STATE_VERSION: u64 = 2;
+
+/// Position of a stake in the exported state, i.e. the compressed public key
+/// of its account, from which a chunk of the export starts.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Archive,
+    Serialize,
+    Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ExportCursor(pub [u8; BlsPublicKey::SIZE]);
+
+impl ExportCursor {
+    /// The cursor of the first stake of the export.
+    pub const START: Self = Self([0; BlsPublicKey::SIZE]);
+}
+
+/// Summary of the exported state, returned by `export_summary` for the stakes
+/// up to the end of a chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ExportSummary {
+    /// The version of the export format.
+    pub version: u32,
+    /// The number of stakes in the export.
+    pub items: u64,
+    /// Hash chained over the serialized stakes in order, then over the config
+    /// and the burnt amount once the last chunk is reached.
+    pub checksum: BlsScalar,
+}
+
+impl ExportSummary {
+    /// The summary of an export without any stake, onto which the stakes of
+    /// the first chunk are chained.
+    pub const EMPTY: Self = Self {
+        version: EXPORT_VERSION,
+        items: 0,
+        checksum: BlsScalar::zero(),
+    };
+}
+
+/// A chunk of the exported state, returned by `export_summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ExportChunk {
+    /// The summary of the export up to the end of the chunk.
+    pub summary: ExportSummary,
+    /// The cursor the next chunk starts from, or `None` if this chunk is the
+    /// last one.
+    pub next: Option<ExportCursor>,
+}
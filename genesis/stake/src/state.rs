@@ -13,12 +13,20 @@ use dusk_core::abi::{self, ContractId};
 use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
 use dusk_core::stake::{
     next_epoch, Reward, SlashEvent, Stake, StakeAmount, StakeConfig, StakeData,
-    StakeEvent, StakeFundOwner, StakeKeys, Withdraw, WithdrawToContract, EPOCH,
-    STAKE_CONTRACT,
+    StakeEvent, StakeFundOwner, StakeKeys, Withdraw, WithdrawToContract,
+    DEFAULT_MINIMUM_STAKE, EPOCH, STAKE_CONTRACT,
 };
 use dusk_core::transfer::{
     ContractToContract, ReceiveFromContract, TRANSFER_CONTRACT,
 };
+use dusk_core::BlsScalar;
+use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
+use rkyv::ser::serializers::AllocSerializer;
+
+use crate::migration::{
+    ExportChunk, ExportCursor, ExportSummary, EXPORT_VERSION, STATE_VERSION,
+};
+use crate::saturating_usize;
 
 /// Contract keeping track of each public key's stake.
 ///
@@ -35,10 +43,70 @@ pub struct StakeState {
     previous_block_state:
         BTreeMap<[u8; BlsPublicKey::SIZE], (Option<StakeData>, BlsPublicKey)>,
     stakes: BTreeMap<[u8; BlsPublicKey::SIZE], (StakeData, StakeKeys)>,
+    version: u64,
+    // The summary of the stakes imported so far, chained as they are
+    // imported, or `None` if no stake was imported.
+    imported: Option<ExportSummary>,
+    // Whether the import was checked and finished, after which no more stakes
+    // can be imported.
+    import_finished: bool,
+    // The digest of the events emitted in the current block, together with
+    // the height of the block.
+    event_digest: Option<(u64, BlsScalar)>,
+    // The digests of the events emitted in the most recent blocks, together
+    // with the height of the block.
+    event_digests: ConstGenericRingBuffer<(u64, BlsScalar), MAX_EVENT_DIGESTS>,
 }
 
 const STAKE_CONTRACT_VERSION: u64 = 8;
 
+/// Number of most recent blocks for which the digest of the emitted events is
+/// kept.
+const MAX_EVENT_DIGESTS: usize = 2 * EPOCH as usize;
+
+/// A step migrating the state from one version of its layout to the next.
+type MigrationStep = fn(&mut StakeState);
+
+/// The registered migration steps, keyed by the version they migrate the state
+/// from.
+const MIGRATION_STEPS: &[(u64, MigrationStep)] = &[(1, default_minimum_stake)];
+
+/// Migrate the state from version 1 to version 2 of its layout, by storing
+/// the default minimum stake in a config where version 1 stored it as zero.
+fn default_minimum_stake(state: &mut StakeState) {
+    if state.config.minimum_stake == 0 {
+        state.config.minimum_stake = DEFAULT_MINIMUM_STAKE;
+    }
+}
+
+/// Chain the hash of the given item onto the checksum of the items preceding
+/// it in an export.
+fn chain_checksum<T>(checksum: BlsScalar, item: &T) -> BlsScalar
+where
+    T: rkyv::Serialize<AllocSerializer<1024>>,
+{
+    let item_bytes = rkyv::to_bytes::<_, 1024>(item)
+        .expect("Serializing an exported item should succeed");
+
+    let mut bytes = checksum.to_bytes().to_vec();
+    bytes.extend_from_slice(&item_bytes);
+    abi::hash(bytes)
+}
+
+/// Chain the hash of an event, given by its topic and serialized data, onto the
+/// digest of the events preceding it in a block.
+fn chain_event_digest(
+    digest: BlsScalar,
+    topic: &str,
+    event_bytes: &[u8],
+) -> BlsScalar {
+    let mut bytes = digest.to_bytes().to_vec();
+    bytes.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(topic.as_bytes());
+    bytes.extend_from_slice(event_bytes);
+    abi::hash(bytes)
+}
+
 impl StakeState {
     pub const fn new() -> Self {
         Self {
@@ -46,6 +114,11 @@ impl StakeState {
             config: StakeConfig::new(),
             previous_block_state: BTreeMap::new(),
             stakes: BTreeMap::new(),
+            version: STATE_VERSION,
+            imported: None,
+            import_finished: false,
+            event_digest: None,
+            event_digests: ConstGenericRingBuffer::new(),
         }
     }
 
@@ -58,7 +131,8 @@ impl StakeState {
     }
 
     pub fn on_new_block(&mut self) {
-        self.previous_block_state.clear()
+        self.previous_block_state.clear();
+        self.finish_event_digest();
     }
 
     fn unwrap_account_owner(owner: &StakeFundOwner) -> BlsPublicKey {
@@ -137,12 +211,12 @@ impl StakeState {
                 StakeEvent::new(*keys, value)
             }
         };
-        abi::emit("stake", stake_event);
-
         let key = keys.account.to_bytes();
         self.previous_block_state
             .entry(key)
             .or_insert((prev_stake, account));
+
+        self.emit("stake", stake_event);
     }
 
     pub fn stake_from_contract(&mut self, recv: ReceiveFromContract) {
@@ -196,12 +270,12 @@ impl StakeState {
                 StakeEvent::new(*keys, value)
             }
         };
-        abi::emit("stake", stake_event);
-
         let key = keys.account.to_bytes();
         self.previous_block_state
             .entry(key)
             .or_insert((prev_stake, account));
+
+        self.emit("stake", stake_event);
     }
 
     pub fn unstake(&mut self, unstake: Withdraw) {
@@ -253,7 +327,6 @@ impl StakeState {
             StakeEvent::new(*keys, value)
         };
 
-        abi::emit("unstake", stake_event);
         if stake.total_funds() == 0 {
             // update the state accordingly
             loaded_stake.amount = None;
@@ -268,6 +341,8 @@ impl StakeState {
         self.previous_block_state
             .entry(key)
             .or_insert((prev_stake, account));
+
+        self.emit("unstake", stake_event);
     }
 
     pub fn unstake_from_contract(&mut self, unstake: WithdrawToContract) {
@@ -318,7 +393,6 @@ impl StakeState {
             StakeEvent::new(*keys, value)
         };
 
-        abi::emit("unstake", stake_event);
         if stake.total_funds() == 0 {
             // update the state accordingly
             loaded_stake.amount = None;
@@ -338,6 +412,8 @@ impl StakeState {
         self.previous_block_state
             .entry(key)
             .or_insert_with(|| (prev_stake, *account));
+
+        self.emit("unstake", stake_event);
     }
 
     pub fn withdraw(&mut self, withdraw: Withdraw) {
@@ -379,11 +455,13 @@ impl StakeState {
 
         // update the state accordingly
         loaded_stake.reward -= value;
-        abi::emit("withdraw", StakeEvent::new(*keys, value));
+        let withdraw_event = StakeEvent::new(*keys, value);
 
         if loaded_stake.reward == 0 && loaded_stake.amount.is_none() {
             self.stakes.remove(&account.to_bytes());
         }
+
+        self.emit("withdraw", withdraw_event);
     }
 
     pub fn withdraw_from_contract(&mut self, withdraw: WithdrawToContract) {
@@ -424,11 +502,13 @@ impl StakeState {
 
         // update the state accordingly
         loaded_stake.reward -= value;
-        abi::emit("withdraw", StakeEvent::new(*keys, value));
+        let withdraw_event = StakeEvent::new(*keys, value);
 
         if loaded_stake.reward == 0 && loaded_stake.amount.is_none() {
             self.stakes.remove(&account.to_bytes());
         }
+
+        self.emit("withdraw", withdraw_event);
     }
 
     /// Gets a reference to a stake.
@@ -495,7 +575,7 @@ impl StakeState {
             stake.reward += reward.value;
         }
         if !rewards.is_empty() {
-            abi::emit("reward", rewards);
+            self.emit("reward", rewards);
         }
     }
 
@@ -509,6 +589,89 @@ impl StakeState {
         STAKE_CONTRACT_VERSION
     }
 
+    /// Return the digest of the events emitted by the contract in the block at
+    /// the given height, or `None` if no event was emitted in the block or if
+    /// the block is too old.
+    ///
+    /// The digest of a block is a hash chain over its events, in the order
+    /// they were emitted, starting from zero.
+    pub fn block_event_digest(&self, block_height: u64) -> Option<BlsScalar> {
+        self.event_digest
+            .iter()
+            .chain(self.event_digests.iter().rev())
+            .find(|(height, _)| *height == block_height)
+            .map(|(_, digest)| *digest)
+    }
+
+    /// Emit an event under the given topic, chaining it onto the digest of
+    /// the events of the current block.
+    fn emit<E>(&mut self, topic: &str, event: E)
+    where
+        E: rkyv::Serialize<AllocSerializer<1024>>,
+    {
+        let event_bytes = rkyv::to_bytes::<_, 1024>(&event)
+            .expect("Serializing an event should succeed");
+
+        let block_height = abi::block_height();
+        let digest = match self.event_digest {
+            Some((height, digest)) if height == block_height => digest,
+            _ => {
+                self.finish_event_digest();
+                BlsScalar::zero()
+            }
+        };
+        let digest = chain_event_digest(digest, topic, &event_bytes);
+        self.event_digest = Some((block_height, digest));
+
+        abi::emit_raw(topic, event_bytes);
+    }
+
+    /// Move the digest of the events of the current block to the digests of
+    /// the most recent blocks.
+    fn finish_event_digest(&mut self) {
+        if let Some(event_digest) = self.event_digest.take() {
+            self.event_digests.push(event_digest);
+        }
+    }
+
+    /// Version of the layout of the state
+    pub fn state_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns true if the state is at a previous version of its layout, and
+    /// needs to be migrated before being used.
+    pub fn migration_pending(&self) -> bool {
+        self.version != STATE_VERSION
+    }
+
+    /// Migrate the state from the `from` version of its layout to the `to`
+    /// version, by running the registered migration steps in between in
+    /// order, and emit an event under the `migrate` topic.
+    ///
+    /// # Panics
+    /// Panics if the state is not at the `from` version, if `to` is not a
+    /// later version supported by the contract, or if a step is missing.
+    pub fn migrate(&mut self, from: u64, to: u64) {
+        if from != self.version {
+            panic!("The state is not at the version to migrate from");
+        }
+        if to <= from || to > STATE_VERSION {
+            panic!("Invalid version to migrate to");
+        }
+
+        for version in from..to {
+            let (_, step) = MIGRATION_STEPS
+                .iter()
+                .find(|(step_version, _)| *step_version == version)
+                .expect("There should be a migration step for every version");
+            step(self);
+            self.version = version + 1;
+        }
+
+        self.emit("migrate", (from, to));
+    }
+
     /// Slash the given `to_slash` amount from an `account`'s reward
     ///
     /// If the reward is less than the `to_slash` amount, then the reward is
@@ -553,13 +716,14 @@ impl StakeState {
             stake_amount.lock_amount(to_slash);
         }
 
+        let next_eligibility = stake_amount.eligibility;
         if to_slash > 0 || effective_faults > 0 {
-            abi::emit(
+            self.emit(
                 "slash",
                 SlashEvent {
                     account: *account,
                     value: to_slash,
-                    next_eligibility: stake_amount.eligibility,
+                    next_eligibility,
                 },
             );
         }
@@ -618,7 +782,7 @@ impl StakeState {
             self.burnt_amount += to_slash;
         }
 
-        abi::emit(
+        self.emit(
             "hard_slash",
             SlashEvent {
                 account: *account,
@@ -660,6 +824,144 @@ impl StakeState {
         .expect("Subtracting balance should succeed");
     }
 
+    /// Feeds the host with the stakes and their keys (up to `count_limit`
+    /// occurrences), starting from the stake at the given cursor, for
+    /// exporting the state.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
+    pub fn export_stakes(&self, from: ExportCursor, count_limit: u64) {
+        let iter = self.stakes.range(from.0..).map(|(_, stake)| stake);
+
+        if count_limit == 0 {
+            for (stake_data, keys) in iter {
+                abi::feed((*keys, *stake_data));
+            }
+        } else {
+            for (stake_data, keys) in iter.take(saturating_usize(count_limit)) {
+                abi::feed((*keys, *stake_data));
+            }
+        }
+    }
+
+    /// Chain the stakes of a chunk of the exported state (up to `count_limit`
+    /// occurrences), starting from the stake at the given cursor, onto the
+    /// summary of the chunks before it, and return the summary together
+    /// with the cursor of the next chunk. The last chunk also chains the
+    /// config and the burnt amount.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
+    ///
+    /// # Panics
+    /// The function will panic if the summary is of a different version of
+    /// the export format.
+    pub fn export_summary(
+        &self,
+        mut summary: ExportSummary,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> ExportChunk {
+        if summary.version != EXPORT_VERSION {
+            panic!("Unsupported export version");
+        }
+
+        let count_limit = match count_limit {
+            0 => usize::MAX,
+            count_limit => saturating_usize(count_limit),
+        };
+
+        let mut stakes = self.stakes.range(from.0..);
+        for (_, (stake_data, keys)) in stakes.by_ref().take(count_limit) {
+            summary.items += 1;
+            summary.checksum =
+                chain_checksum(summary.checksum, &(*keys, *stake_data));
+        }
+
+        match stakes.next() {
+            Some((key, _)) => ExportChunk {
+                summary,
+                next: Some(ExportCursor(*key)),
+            },
+            None => ExportChunk {
+                summary: self.chain_globals(summary),
+                next: None,
+            },
+        }
+    }
+
+    /// Import the given stakes of an exported state, in order.
+    ///
+    /// The config and the burnt amount are imported with `set_config` and
+    /// `set_burnt_amount` respectively. The stakes are chained onto the
+    /// summary of the import as they are imported, so that [`finish_import`]
+    /// only has to compare it.
+    ///
+    /// # Panics
+    /// The function will panic if the import is already finished, or if the
+    /// first stakes are imported on top of existing stakes.
+    ///
+    /// [`finish_import`]: Self::finish_import
+    pub fn import_stakes(&mut self, stakes: Vec<(StakeKeys, StakeData)>) {
+        if self.import_finished {
+            panic!("The import is already finished");
+        }
+        if self.imported.is_none() && !self.stakes.is_empty() {
+            panic!("The stakes can only be imported into a fresh contract");
+        }
+
+        let mut imported = self.imported.unwrap_or(ExportSummary::EMPTY);
+        for (keys, stake_data) in stakes {
+            imported.items += 1;
+            imported.checksum =
+                chain_checksum(imported.checksum, &(keys, stake_data));
+
+            self.insert_stake(keys, stake_data);
+        }
+        self.imported = Some(imported);
+    }
+
+    /// Check the imported state matches the exported state with the given
+    /// summary, finish the import, and emit an event under the `import`
+    /// topic.
+    ///
+    /// A summary of a previous version of the export format leaves the state
+    /// at the version of its layout the export was made from, pending a
+    /// migration with [`migrate`].
+    ///
+    /// # Panics
+    /// The function will panic if the import is already finished, if the
+    /// summary is of an unknown version of the export format, or if it
+    /// doesn't match the summary of the imported stakes, config and burnt
+    /// amount.
+    ///
+    /// [`migrate`]: Self::migrate
+    pub fn finish_import(&mut self, summary: ExportSummary) {
+        if self.import_finished {
+            panic!("The import is already finished");
+        }
+        if summary.version == 0 || summary.version > EXPORT_VERSION {
+            panic!("Unsupported export version");
+        }
+        let imported = ExportSummary {
+            version: summary.version,
+            ..self.chain_globals(self.imported.unwrap_or(ExportSummary::EMPTY))
+        };
+        if imported != summary {
+            panic!("The imported state doesn't match the exported state");
+        }
+
+        self.import_finished = true;
+        self.version = u64::from(summary.version);
+        self.emit("import", summary);
+    }
+
+    /// Chain the config and the burnt amount onto the checksum of the given
+    /// summary of the stakes.
+    fn chain_globals(&self, mut summary: ExportSummary) -> ExportSummary {
+        summary.checksum = chain_checksum(summary.checksum, &self.config);
+        summary.checksum = chain_checksum(summary.checksum, &self.burnt_amount);
+        summary
+    }
+
     /// Feeds the host with previous state of the changed provisioners.
     pub fn prev_state_changes(&self) {
         for (stake_data, account) in self.previous_block_state.values() {
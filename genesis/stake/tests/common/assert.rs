@@ -4,19 +4,15 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use contracts_test_kit::stake::get_stake;
+use contracts_test_kit::transfer::account;
 use dusk_bytes::Serializable;
 use dusk_core::abi::Event;
 use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
-use dusk_core::stake::{
-    Reward, SlashEvent, StakeData, StakeEvent, STAKE_CONTRACT,
-};
-use dusk_core::transfer::moonlight::AccountData;
-use dusk_core::transfer::TRANSFER_CONTRACT;
+use dusk_core::stake::{Reward, SlashEvent, StakeEvent};
 use dusk_vm::Session;
 use rkyv::{check_archived_root, Deserialize, Infallible};
 
-use super::utils::GAS_LIMIT;
-
 pub fn assert_stake_event<S>(
     events: &Vec<Event>,
     topic: S,
@@ -123,10 +119,8 @@ pub fn assert_stake(
     expected_locked: u64,
     expected_reward: u64,
 ) {
-    let stake_data: Option<StakeData> = session
-        .call(STAKE_CONTRACT, "get_stake", stake_pk, GAS_LIMIT)
-        .expect("Getting the stake should succeed")
-        .data;
+    let stake_data =
+        get_stake(session, stake_pk).expect("Getting the stake should succeed");
 
     if expected_total != 0 || expected_reward != 0 {
         let stake_data =
@@ -156,9 +150,7 @@ pub fn assert_moonlight(
     expected_balance: u64,
     expected_nonce: u64,
 ) {
-    let moonlight_account: AccountData = session
-        .call(TRANSFER_CONTRACT, "account", moonlight_pk, GAS_LIMIT)
-        .map(|r| r.data)
+    let moonlight_account = account(session, moonlight_pk)
         .expect("Getting the moonlight account should succeed");
     assert_eq!(
         moonlight_account.balance, expected_balance,
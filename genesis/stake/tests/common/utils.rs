@@ -4,99 +4,20 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use std::sync::mpsc;
-
+use contracts_test_kit::transfer::{chain_id, leaves_from_pos, opening, root};
 use dusk_core::transfer::data::TransactionData;
 use dusk_core::transfer::phoenix::{
-    Note, NoteLeaf, NoteOpening, NoteTreeItem, PublicKey as PhoenixPublicKey,
-    SecretKey as PhoenixSecretKey, Transaction as PhoenixTransaction,
-    ViewKey as PhoenixViewKey,
+    NoteTreeItem, PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+    Transaction as PhoenixTransaction,
 };
-use dusk_core::transfer::{Transaction, TRANSFER_CONTRACT};
-use dusk_core::{BlsScalar, LUX};
-use dusk_vm::{Error as VMError, Session};
+use dusk_core::transfer::Transaction;
+use dusk_core::LUX;
+use dusk_vm::Session;
 use rand::rngs::StdRng;
 use rusk_prover::LocalProver;
 
-pub const GAS_LIMIT: u64 = 0x100_000_000;
 pub const GAS_PRICE: u64 = LUX;
 
-pub fn leaves_from_height(
-    session: &mut Session,
-    height: u64,
-) -> Result<Vec<NoteLeaf>, VMError> {
-    let (feeder, receiver) = mpsc::channel();
-
-    session.feeder_call::<_, ()>(
-        TRANSFER_CONTRACT,
-        "leaves_from_height",
-        &height,
-        u64::MAX,
-        feeder,
-    )?;
-
-    Ok(receiver
-        .iter()
-        .map(|bytes| rkyv::from_bytes(&bytes).expect("Should return leaves"))
-        .collect())
-}
-
-pub fn leaves_from_pos(
-    session: &mut Session,
-    pos: u64,
-) -> Result<Vec<NoteLeaf>, VMError> {
-    let (feeder, receiver) = mpsc::channel();
-
-    session.feeder_call::<_, ()>(
-        TRANSFER_CONTRACT,
-        "leaves_from_pos",
-        &pos,
-        GAS_LIMIT,
-        feeder,
-    )?;
-
-    Ok(receiver
-        .iter()
-        .map(|bytes| rkyv::from_bytes(&bytes).expect("Should return leaves"))
-        .collect())
-}
-
-pub fn update_root(session: &mut Session) -> Result<(), VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "update_root", &(), GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-pub fn root(session: &mut Session) -> Result<BlsScalar, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "root", &(), GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-pub fn opening(
-    session: &mut Session,
-    pos: u64,
-) -> Result<Option<NoteOpening>, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "opening", &pos, GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-pub fn chain_id(session: &mut Session) -> Result<u8, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "chain_id", &(), GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-pub fn filter_notes_owned_by<I: IntoIterator<Item = Note>>(
-    vk: PhoenixViewKey,
-    iter: I,
-) -> Vec<Note> {
-    iter.into_iter()
-        .filter(|note| vk.owns(note.stealth_address()))
-        .collect()
-}
-
 /// Generate a TxCircuit given the sender secret-key, receiver public-key, the
 /// input note positions in the transaction tree and the new output-notes.
 pub fn create_transaction<const I: usize>(
@@ -7,6 +7,7 @@
 pub mod common;
 
 use common::assert::assert_slash_event;
+use dusk_bytes::Serializable;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
@@ -22,8 +23,9 @@ use dusk_core::{
         },
         TRANSFER_CONTRACT,
     },
+    BlsScalar,
 };
-use dusk_vm::{Error as VMError, VM};
+use dusk_vm::{Error as VMError, Session, VM};
 
 use crate::common::assert::assert_reward_event;
 use crate::common::init::instantiate;
@@ -246,3 +248,116 @@ fn stake_hard_slash() -> Result<(), VMError> {
 
     Ok(())
 }
+
+/// Checks the digest of the events of a block is the hash chain over the
+/// events the contract emitted in it, and that it is kept after the block.
+#[test]
+fn block_event_digest() -> Result<(), VMError> {
+    const BLOCK_HEIGHT: u64 = 1;
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let vm = &mut VM::ephemeral().expect("Creating ephemeral VM should work");
+
+    let sk = PhoenixSecretKey::random(rng);
+    let pk = PhoenixPublicKey::from(&sk);
+
+    let stake_sk = BlsSecretKey::random(rng);
+    let stake_pk = BlsPublicKey::from(&stake_sk);
+    let stake_pks = StakeKeys::single_key(stake_pk);
+
+    let mut session = instantiate(rng, vm, &pk, GENESIS_VALUE);
+
+    let digest = event_digest(&mut session, BLOCK_HEIGHT)?;
+    assert_eq!(digest, None, "No event should be emitted in the block yet");
+
+    let stake_amount = dusk(100.0);
+    let stake_data = StakeData {
+        reward: 0,
+        amount: Some(StakeAmount {
+            value: stake_amount,
+            eligibility: 0,
+            locked: 0,
+        }),
+        faults: 0,
+        hard_faults: 0,
+    };
+
+    session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "add_contract_balance",
+        &(STAKE_CONTRACT, stake_amount),
+        u64::MAX,
+    )?;
+    session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "insert_stake",
+        &(stake_pks, stake_data),
+        u64::MAX,
+    )?;
+
+    let rewards = vec![Reward {
+        account: stake_pk,
+        value: dusk(10.0),
+        reason: RewardReason::Other,
+    }];
+    let reward_receipt =
+        session.call::<_, ()>(STAKE_CONTRACT, "reward", &rewards, u64::MAX)?;
+    let slash_receipt = session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "slash",
+        &(stake_pk, Some(dusk(5.0))),
+        u64::MAX,
+    )?;
+
+    let expected_digest = reward_receipt
+        .events
+        .iter()
+        .chain(slash_receipt.events.iter())
+        .filter(|event| event.source == STAKE_CONTRACT)
+        .fold(BlsScalar::zero(), |digest, event| {
+            chain_event_digest(digest, &event.topic, &event.data)
+        });
+
+    let digest = event_digest(&mut session, BLOCK_HEIGHT)?;
+    assert_eq!(digest, Some(expected_digest));
+
+    session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "before_state_transition",
+        &(),
+        u64::MAX,
+    )?;
+
+    let digest = event_digest(&mut session, BLOCK_HEIGHT)?;
+    assert_eq!(
+        digest,
+        Some(expected_digest),
+        "The digest should be kept after the block"
+    );
+
+    Ok(())
+}
+
+fn event_digest(
+    session: &mut Session,
+    height: u64,
+) -> Result<Option<BlsScalar>, VMError> {
+    session
+        .call(STAKE_CONTRACT, "block_event_digest", &height, u64::MAX)
+        .map(|r| r.data)
+}
+
+/// Chain the hash of an event onto the digest of the events preceding it in a
+/// block, in the same way as the contract.
+fn chain_event_digest(
+    digest: BlsScalar,
+    topic: &str,
+    data: &[u8],
+) -> BlsScalar {
+    let mut bytes = digest.to_bytes().to_vec();
+    bytes.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(topic.as_bytes());
+    bytes.extend_from_slice(data);
+    BlsScalar::hash_to_scalar(&bytes)
+}
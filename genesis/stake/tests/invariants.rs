@@ -0,0 +1,728 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Randomized state-machine tests of the conservation of value across the
+//! transfer and stake contracts.
+//!
+//! Each case runs a sequence of steps generated by proptest, and checks
+//! after every step that the value held by the Moonlight accounts, the
+//! contracts and the unspent Phoenix notes equals the value at genesis plus
+//! the value minted, minus the value burned and paid in fees. Steps are free
+//! to fail, since a failed transaction must conserve value just as well, and
+//! a failing sequence is shrunk to the shortest one breaking the invariant.
+//!
+//! Proving the Phoenix transactions makes the cases slow, so the test is
+//! ignored by default, and run with `make invariants`. The number of cases
+//! is set with `PROPTEST_CASES`, and the maximum number of steps of a case
+//! with `INVARIANTS_MAX_STEPS`.
+
+use contracts_test_kit::notes::{filter_notes_owned_by, owned_notes_value};
+use contracts_test_kit::stake::{
+    add_reward, burnt_amount, get_stake, hard_slash, slash,
+};
+use contracts_test_kit::transfer::{
+    account, existing_nullifiers, leaves_from_height, opening, root,
+    sync_accounts, sync_contract_balances, update_root,
+};
+use contracts_test_kit::{Genesis, TestChain, CHAIN_ID, GAS_LIMIT};
+use dusk_core::abi::ContractId;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::signatures::schnorr::SecretKey as NoteSecretKey;
+use dusk_core::stake::DEFAULT_MINIMUM_STAKE;
+use dusk_core::transfer::data::ContractCall;
+use dusk_core::transfer::phoenix::{
+    Note, PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+    StealthAddress, Transaction as PhoenixTransaction,
+    ViewKey as PhoenixViewKey,
+};
+use dusk_core::transfer::withdraw::{
+    Withdraw, WithdrawReceiver, WithdrawReplayToken,
+};
+use dusk_core::transfer::{
+    ContractToAccount, ContractToContract, Transaction, TRANSFER_CONTRACT,
+};
+use dusk_core::{dusk, JubJubScalar, LUX};
+use dusk_vm::{execute, ExecutionConfig};
+use dusk_wallet_core::transaction::{
+    moonlight_stake, moonlight_stake_reward, moonlight_unstake,
+};
+use ff::Field;
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use proptest::test_runner::TestCaseError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rusk_prover::LocalProver;
+
+const MAX_STEPS: usize = 16;
+
+const ACCOUNTS: usize = 3;
+const PHOENIX_KEYS: usize = 2;
+
+const ACCOUNT_GENESIS_VALUE: u64 = dusk(1_000_000.0);
+const ALICE_GENESIS_VALUE: u64 = dusk(10_000.0);
+const MAX_VALUE: u64 = dusk(5_000.0);
+
+const GAS_PRICE: u64 = LUX;
+
+const ALICE_ID: ContractId = ContractId::from_bytes([3; 32]);
+const BOB_ID: ContractId = ContractId::from_bytes([5; 32]);
+
+const NO_CONFIG: ExecutionConfig = ExecutionConfig::DEFAULT;
+
+proptest! {
+    #[test]
+    #[ignore = "slow, run with `make invariants`"]
+    fn value_is_conserved(
+        seed in any::<u64>(),
+        steps in vec(step(), 1..=max_steps()),
+    ) {
+        run(seed, &steps)?;
+    }
+}
+
+/// The maximum number of steps of a case, read from `INVARIANTS_MAX_STEPS`,
+/// or [`MAX_STEPS`] if unset.
+fn max_steps() -> usize {
+    std::env::var("INVARIANTS_MAX_STEPS")
+        .ok()
+        .and_then(|steps| steps.parse().ok())
+        .unwrap_or(MAX_STEPS)
+}
+
+/// The keys of the participants of a run.
+struct Keys {
+    accounts: Vec<AccountSecretKey>,
+    stakes: Vec<AccountSecretKey>,
+    phoenix: Vec<PhoenixSecretKey>,
+}
+
+impl Keys {
+    fn random(rng: &mut StdRng) -> Self {
+        Self {
+            accounts: (0..ACCOUNTS)
+                .map(|_| AccountSecretKey::random(rng))
+                .collect(),
+            stakes: (0..ACCOUNTS)
+                .map(|_| AccountSecretKey::random(rng))
+                .collect(),
+            phoenix: (0..PHOENIX_KEYS)
+                .map(|_| PhoenixSecretKey::random(rng))
+                .collect(),
+        }
+    }
+
+    fn account_pk(&self, i: usize) -> AccountPublicKey {
+        AccountPublicKey::from(&self.accounts[i])
+    }
+
+    fn stake_pk(&self, i: usize) -> AccountPublicKey {
+        AccountPublicKey::from(&self.stakes[i])
+    }
+}
+
+/// The value expected to be in circulation, as tracked over a run.
+#[derive(Debug, Default)]
+struct Supply {
+    genesis: u64,
+    minted: u64,
+    burned: u64,
+    fees: u64,
+}
+
+impl Supply {
+    fn expected(&self) -> u64 {
+        self.genesis + self.minted - self.burned - self.fees
+    }
+}
+
+/// The receiver of a withdrawal from the alice contract.
+#[derive(Debug, Clone, Copy)]
+enum Receiver {
+    Moonlight(usize),
+    Phoenix(usize),
+}
+
+/// A step of a run. Transactions are sent from the Moonlight account `from`,
+/// or spend a note owned by the Phoenix key `from`, while rewards and slashes
+/// are made by the node, to the stake of the account `to`.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Transfer {
+        from: usize,
+        to: usize,
+        value: u64,
+    },
+    Deposit {
+        from: usize,
+        value: u64,
+    },
+    Withdraw {
+        from: usize,
+        to: Receiver,
+        value: u64,
+    },
+    Convert {
+        from: usize,
+        to: usize,
+        value: u64,
+    },
+    ContractToContract {
+        from: usize,
+        value: u64,
+    },
+    ContractToAccount {
+        from: usize,
+        to: usize,
+        value: u64,
+    },
+    Stake {
+        from: usize,
+        value: u64,
+    },
+    Unstake {
+        from: usize,
+        value: u64,
+    },
+    WithdrawReward {
+        from: usize,
+        value: u64,
+    },
+    Reward {
+        to: usize,
+        value: u64,
+    },
+    Slash {
+        to: usize,
+        value: Option<u64>,
+    },
+    HardSlash {
+        to: usize,
+        value: Option<u64>,
+    },
+    PhoenixTransfer {
+        from: usize,
+        note: Index,
+        to: usize,
+        value: u64,
+    },
+    PhoenixConvert {
+        from: usize,
+        note: Index,
+        to: usize,
+        value: u64,
+    },
+    NextBlock,
+}
+
+/// Generate a step of a run.
+fn step() -> impl Strategy<Value = Step> {
+    let account = || 0..ACCOUNTS;
+    let phoenix = || 0..PHOENIX_KEYS;
+    let value = || 0..=MAX_VALUE;
+    let receiver = prop_oneof![
+        account().prop_map(Receiver::Moonlight),
+        phoenix().prop_map(Receiver::Phoenix),
+    ];
+
+    prop_oneof![
+        (account(), account(), value())
+            .prop_map(|(from, to, value)| Step::Transfer { from, to, value }),
+        (account(), value())
+            .prop_map(|(from, value)| Step::Deposit { from, value }),
+        (account(), receiver, value())
+            .prop_map(|(from, to, value)| Step::Withdraw { from, to, value }),
+        (account(), phoenix(), value())
+            .prop_map(|(from, to, value)| Step::Convert { from, to, value }),
+        (account(), value())
+            .prop_map(|(from, value)| Step::ContractToContract { from, value }),
+        (account(), account(), value()).prop_map(|(from, to, value)| {
+            Step::ContractToAccount { from, to, value }
+        }),
+        // stakes around the minimum, so some of them fail
+        (
+            account(),
+            DEFAULT_MINIMUM_STAKE / 2..=DEFAULT_MINIMUM_STAKE * 4
+        )
+            .prop_map(|(from, value)| Step::Stake { from, value }),
+        (account(), value())
+            .prop_map(|(from, value)| Step::Unstake { from, value }),
+        (account(), value())
+            .prop_map(|(from, value)| Step::WithdrawReward { from, value }),
+        (account(), value()).prop_map(|(to, value)| Step::Reward { to, value }),
+        (account(), option::of(value()))
+            .prop_map(|(to, value)| Step::Slash { to, value }),
+        (account(), option::of(value()))
+            .prop_map(|(to, value)| Step::HardSlash { to, value }),
+        (phoenix(), any::<Index>(), phoenix(), value()).prop_map(
+            |(from, note, to, value)| Step::PhoenixTransfer {
+                from,
+                note,
+                to,
+                value
+            }
+        ),
+        (phoenix(), any::<Index>(), account(), value()).prop_map(
+            |(from, note, to, value)| Step::PhoenixConvert {
+                from,
+                note,
+                to,
+                value
+            }
+        ),
+        Just(Step::NextBlock),
+    ]
+}
+
+/// Run a sequence of steps, with the keys and randomness of the transactions
+/// generated from the given seed, checking the supply after each of them.
+fn run(seed: u64, steps: &[Step]) -> Result<(), TestCaseError> {
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let keys = Keys::random(rng);
+
+    let mut genesis = Genesis::new(CHAIN_ID)
+        .contract(
+            ALICE_ID,
+            include_bytes!(
+                "../../../target/wasm32-unknown-unknown/release/alice.wasm"
+            ),
+        )
+        .contract_with_init_arg(
+            BOB_ID,
+            include_bytes!(
+                "../../../target/wasm32-unknown-unknown/release/bob.wasm"
+            ),
+            1u8,
+        )
+        .contract_balance(ALICE_ID, ALICE_GENESIS_VALUE);
+    let mut supply = Supply {
+        genesis: ALICE_GENESIS_VALUE,
+        ..Supply::default()
+    };
+    for i in 0..ACCOUNTS {
+        genesis = genesis.account(keys.account_pk(i), ACCOUNT_GENESIS_VALUE);
+        supply.genesis += ACCOUNT_GENESIS_VALUE;
+    }
+    let chain = &mut TestChain::new(genesis);
+
+    assert_supply(chain, &keys, &supply, 0, None)?;
+
+    for (n, step) in steps.iter().enumerate() {
+        run_step(chain, rng, &keys, &mut supply, *step);
+        assert_supply(chain, &keys, &supply, n + 1, Some(*step))?;
+    }
+
+    Ok(())
+}
+
+/// Run a step on the chain, updating the expected supply with the value it
+/// minted, burned and paid in fees.
+fn run_step(
+    chain: &mut TestChain,
+    rng: &mut StdRng,
+    keys: &Keys,
+    supply: &mut Supply,
+    step: Step,
+) {
+    match step {
+        Step::Transfer { from, to, value } => {
+            let tx = moonlight_tx(chain, keys, from, Some(to), value, 0, None);
+            execute_tx(chain, supply, &tx);
+        }
+        Step::Deposit { from, value } => {
+            let call = ContractCall::new(ALICE_ID, "deposit")
+                .with_args(&value)
+                .expect("Serializing should succeed");
+            let tx =
+                moonlight_tx(chain, keys, from, None, 0, value, Some(call));
+            execute_tx(chain, supply, &tx);
+        }
+        Step::Withdraw { from, to, value } => {
+            let nonce = next_nonce(chain, keys, from);
+            let token = WithdrawReplayToken::Moonlight(nonce);
+            let withdraw = match to {
+                Receiver::Moonlight(to) => Withdraw::new(
+                    rng,
+                    &keys.accounts[to],
+                    ALICE_ID,
+                    value,
+                    WithdrawReceiver::Moonlight(keys.account_pk(to)),
+                    token,
+                ),
+                Receiver::Phoenix(to) => {
+                    let (address, note_sk) =
+                        stealth_address(rng, &keys.phoenix[to]);
+                    Withdraw::new(
+                        rng,
+                        &note_sk,
+                        ALICE_ID,
+                        value,
+                        WithdrawReceiver::Phoenix(address),
+                        token,
+                    )
+                }
+            };
+            let call = ContractCall::new(ALICE_ID, "withdraw")
+                .with_args(&withdraw)
+                .expect("Serializing should succeed");
+            let tx = moonlight_tx(chain, keys, from, None, 0, 0, Some(call));
+            execute_tx(chain, supply, &tx);
+        }
+        Step::Convert { from, to, value } => {
+            let nonce = next_nonce(chain, keys, from);
+            let (address, note_sk) = stealth_address(rng, &keys.phoenix[to]);
+            let call = ContractCall::new(TRANSFER_CONTRACT, "convert")
+                .with_args(&Withdraw::new(
+                    rng,
+                    &note_sk,
+                    TRANSFER_CONTRACT,
+                    value,
+                    WithdrawReceiver::Phoenix(address),
+                    WithdrawReplayToken::Moonlight(nonce),
+                ))
+                .expect("Serializing should succeed");
+            let tx =
+                moonlight_tx(chain, keys, from, None, 0, value, Some(call));
+            execute_tx(chain, supply, &tx);
+        }
+        Step::ContractToContract { from, value } => {
+            let call = ContractCall::new(ALICE_ID, "contract_to_contract")
+                .with_args(&ContractToContract {
+                    contract: BOB_ID,
+                    value,
+                    fn_name: String::from("recv_transfer"),
+                    data: vec![],
+                })
+                .expect("Serializing should succeed");
+            let tx = moonlight_tx(chain, keys, from, None, 0, 0, Some(call));
+            execute_tx(chain, supply, &tx);
+        }
+        Step::ContractToAccount { from, to, value } => {
+            let call = ContractCall::new(ALICE_ID, "contract_to_account")
+                .with_args(&ContractToAccount {
+                    account: keys.account_pk(to),
+                    value,
+                })
+                .expect("Serializing should succeed");
+            let tx = moonlight_tx(chain, keys, from, None, 0, 0, Some(call));
+            execute_tx(chain, supply, &tx);
+        }
+        Step::Stake { from, value } => {
+            let tx = moonlight_stake(
+                &keys.accounts[from],
+                &keys.stakes[from],
+                &keys.stakes[from],
+                value,
+                GAS_LIMIT,
+                GAS_PRICE,
+                next_nonce(chain, keys, from),
+                chain.chain_id(),
+            )
+            .expect("tx creation should pass");
+            execute_tx(chain, supply, &tx);
+        }
+        Step::Unstake { from, value } => {
+            let tx = moonlight_unstake(
+                rng,
+                &keys.accounts[from],
+                &keys.stakes[from],
+                &keys.stakes[from],
+                value,
+                GAS_LIMIT,
+                GAS_PRICE,
+                next_nonce(chain, keys, from),
+                chain.chain_id(),
+            )
+            .expect("tx creation should pass");
+            execute_tx(chain, supply, &tx);
+        }
+        Step::WithdrawReward { from, value } => {
+            let tx = moonlight_stake_reward(
+                rng,
+                &keys.accounts[from],
+                &keys.stakes[from],
+                &keys.stakes[from],
+                value,
+                GAS_LIMIT,
+                GAS_PRICE,
+                next_nonce(chain, keys, from),
+                chain.chain_id(),
+            )
+            .expect("tx creation should pass");
+            // rewards are only minted when they are withdrawn
+            if execute_tx(chain, supply, &tx) {
+                supply.minted += value;
+            }
+        }
+        Step::Reward { to, value } => {
+            add_reward(chain, &keys.stake_pk(to), value)
+                .expect("Rewarding should succeed");
+        }
+        Step::Slash { to, value } => {
+            // slashing only locks part of the stake, without burning it
+            if has_stake(chain, &keys.stake_pk(to)) {
+                slash(chain, &keys.stake_pk(to), value)
+                    .expect("Slashing should succeed");
+            }
+        }
+        Step::HardSlash { to, value } => {
+            if has_stake(chain, &keys.stake_pk(to)) {
+                let burnt_before = burnt_amount(chain)
+                    .expect("Querying the burnt amount should succeed");
+                hard_slash(chain, &keys.stake_pk(to), value, None)
+                    .expect("Hard slashing should succeed");
+                let burnt_after = burnt_amount(chain)
+                    .expect("Querying the burnt amount should succeed");
+                supply.burned += burnt_after - burnt_before;
+            }
+        }
+        Step::PhoenixTransfer {
+            from,
+            note,
+            to,
+            value,
+        } => {
+            let Some(note) = pick_note(chain, keys, from, note) else {
+                return;
+            };
+            let spend = Spend::Transfer { to, value };
+            if let Some(tx) = phoenix_tx(chain, rng, keys, from, note, spend) {
+                execute_tx(chain, supply, &tx);
+            }
+        }
+        Step::PhoenixConvert {
+            from,
+            note,
+            to,
+            value,
+        } => {
+            let Some(note) = pick_note(chain, keys, from, note) else {
+                return;
+            };
+            let nullifier = note.gen_nullifier(&keys.phoenix[from]);
+            let call = ContractCall::new(TRANSFER_CONTRACT, "convert")
+                .with_args(&Withdraw::new(
+                    rng,
+                    &keys.accounts[to],
+                    TRANSFER_CONTRACT,
+                    value,
+                    WithdrawReceiver::Moonlight(keys.account_pk(to)),
+                    WithdrawReplayToken::Phoenix(vec![nullifier]),
+                ))
+                .expect("Serializing should succeed");
+            let spend = Spend::Deposit { value, call };
+            if let Some(tx) = phoenix_tx(chain, rng, keys, from, note, spend) {
+                execute_tx(chain, supply, &tx);
+            }
+        }
+        Step::NextBlock => chain.next_block(),
+    }
+}
+
+/// Check the value in circulation on the chain is the expected one.
+fn assert_supply(
+    chain: &mut TestChain,
+    keys: &Keys,
+    supply: &Supply,
+    n: usize,
+    step: Option<Step>,
+) -> Result<(), TestCaseError> {
+    let accounts: u64 = sync_accounts(chain, 0, 0)
+        .expect("Syncing the accounts should succeed")
+        .iter()
+        .map(|(account, _)| account.balance)
+        .sum();
+
+    let contracts: u64 = sync_contract_balances(chain, 0, 0)
+        .expect("Syncing the contract balances should succeed")
+        .iter()
+        .map(|(_, balance)| balance)
+        .sum();
+
+    let phoenix: u64 = keys
+        .phoenix
+        .iter()
+        .map(|sk| {
+            owned_notes_value(
+                PhoenixViewKey::from(sk),
+                &unspent_notes(chain, sk),
+            )
+        })
+        .sum();
+
+    prop_assert_eq!(
+        accounts + contracts + phoenix,
+        supply.expected(),
+        "The value in circulation should be conserved after step {} ({:?}): \
+         {:?}, with {} in accounts, {} in contracts and {} in notes",
+        n,
+        step,
+        supply,
+        accounts,
+        contracts,
+        phoenix
+    );
+
+    Ok(())
+}
+
+/// Create a Moonlight transaction from the account `from`.
+fn moonlight_tx(
+    chain: &mut TestChain,
+    keys: &Keys,
+    from: usize,
+    to: Option<usize>,
+    value: u64,
+    deposit: u64,
+    call: Option<ContractCall>,
+) -> Transaction {
+    Transaction::moonlight(
+        &keys.accounts[from],
+        to.map(|to| keys.account_pk(to)),
+        value,
+        deposit,
+        GAS_LIMIT,
+        GAS_PRICE,
+        next_nonce(chain, keys, from),
+        chain.chain_id(),
+        call,
+    )
+    .expect("Creating moonlight transaction should succeed")
+}
+
+/// What a Phoenix transaction does with the value of the note it spends,
+/// besides paying for gas and returning the change to its owner.
+enum Spend {
+    /// Transfer value to the Phoenix key `to`.
+    Transfer { to: usize, value: u64 },
+    /// Deposit value to a call of the transaction.
+    Deposit { value: u64, call: ContractCall },
+}
+
+/// Create a Phoenix transaction spending a note owned by the Phoenix key
+/// `from`, or `None` if the note can't pay for it.
+fn phoenix_tx(
+    chain: &mut TestChain,
+    rng: &mut StdRng,
+    keys: &Keys,
+    from: usize,
+    note: Note,
+    spend: Spend,
+) -> Option<Transaction> {
+    let sender_sk = &keys.phoenix[from];
+    let sender_pk = PhoenixPublicKey::from(sender_sk);
+
+    // notes added in the current block are only provable against the root
+    // once it is updated
+    update_root(chain).expect("Updating the root should succeed");
+    let root = root(chain).expect("Getting the root should succeed");
+    let opening = opening(chain, *note.pos())
+        .expect("Querying the opening should succeed")
+        .expect("An opening should exist for a note in the tree");
+
+    let (receiver_pk, value, deposit, call) = match spend {
+        Spend::Transfer { to, value } => {
+            (PhoenixPublicKey::from(&keys.phoenix[to]), value, 0, None)
+        }
+        Spend::Deposit { value, call } => (sender_pk, 0, value, Some(call)),
+    };
+
+    PhoenixTransaction::new(
+        rng,
+        sender_sk,
+        &sender_pk,
+        &receiver_pk,
+        vec![(note, opening)],
+        root,
+        value,
+        true,
+        deposit,
+        GAS_LIMIT,
+        GAS_PRICE,
+        chain.chain_id(),
+        call,
+        &LocalProver,
+    )
+    .ok()
+    .map(Into::into)
+}
+
+/// Execute a transaction, charging its fee to the supply, and returning
+/// whether its call succeeded.
+///
+/// A transaction that is rejected altogether isn't charged any fee, and
+/// changes nothing.
+fn execute_tx(
+    chain: &mut TestChain,
+    supply: &mut Supply,
+    tx: &Transaction,
+) -> bool {
+    match execute(chain, tx, &NO_CONFIG) {
+        Ok(receipt) => {
+            supply.fees += receipt.gas_spent * GAS_PRICE;
+            receipt.data.is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+fn next_nonce(chain: &mut TestChain, keys: &Keys, i: usize) -> u64 {
+    account(chain, &keys.account_pk(i))
+        .expect("Getting the account should succeed")
+        .nonce
+        + 1
+}
+
+/// The notes owned by the given Phoenix key that aren't spent yet.
+fn unspent_notes(chain: &mut TestChain, sk: &PhoenixSecretKey) -> Vec<Note> {
+    let leaves =
+        leaves_from_height(chain, 0).expect("Getting the notes should succeed");
+    let notes = filter_notes_owned_by(
+        PhoenixViewKey::from(sk),
+        leaves.into_iter().map(|leaf| leaf.note),
+    );
+
+    let nullifiers = notes.iter().map(|note| note.gen_nullifier(sk)).collect();
+    let spent = existing_nullifiers(chain, nullifiers)
+        .expect("Querying the nullifiers should succeed");
+    notes
+        .into_iter()
+        .filter(|note| !spent.contains(&note.gen_nullifier(sk)))
+        .collect()
+}
+
+/// Pick one of the unspent notes of the Phoenix key `from`, if it has any.
+fn pick_note(
+    chain: &mut TestChain,
+    keys: &Keys,
+    from: usize,
+    index: Index,
+) -> Option<Note> {
+    let notes = unspent_notes(chain, &keys.phoenix[from]);
+    (!notes.is_empty()).then(|| index.get(&notes).clone())
+}
+
+fn has_stake(chain: &mut TestChain, pk: &AccountPublicKey) -> bool {
+    get_stake(chain, pk)
+        .expect("Querying the stake should succeed")
+        .is_some()
+}
+
+/// Generate a new stealth address of the given key, together with the
+/// secret key of the note sent to it.
+fn stealth_address(
+    rng: &mut StdRng,
+    sk: &PhoenixSecretKey,
+) -> (StealthAddress, NoteSecretKey) {
+    let pk = PhoenixPublicKey::from(sk);
+    let address = pk.gen_stealth_address(&JubJubScalar::random(&mut *rng));
+    let note_sk = sk.gen_note_sk(&address);
+    (address, note_sk)
+}
@@ -0,0 +1,389 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+pub mod common;
+
+use std::sync::mpsc;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use dusk_core::abi::ContractId;
+use dusk_core::{
+    dusk,
+    signatures::bls::{PublicKey as BlsPublicKey, SecretKey as BlsSecretKey},
+    stake::{
+        StakeAmount, StakeConfig, StakeData, StakeKeys, DEFAULT_MINIMUM_STAKE,
+        STAKE_CONTRACT,
+    },
+    transfer::phoenix::{
+        PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+    },
+};
+use dusk_vm::{ContractData, Error as VMError, Session, VM};
+use stake_contract::migration::{
+    ExportChunk, ExportCursor, ExportSummary, STATE_VERSION,
+};
+
+use crate::common::init::instantiate;
+
+const GENESIS_VALUE: u64 = dusk(1_000_000.0);
+
+const OWNER: [u8; 32] = [0; 32];
+
+const NEW_STAKE_ID: ContractId = {
+    let mut bytes = [0u8; 32];
+    bytes[0] = 0xFD;
+    ContractId::from_bytes(bytes)
+};
+
+/// Export the state of the stake contract in chunks, import it into a newly
+/// deployed stake contract, and check the import against the export.
+#[test]
+fn export_import_state() -> Result<(), VMError> {
+    const CHUNK_SIZE: u64 = 2;
+    const NUM_STAKES: u64 = 5;
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let vm = &mut VM::ephemeral().expect("Creating ephemeral VM should work");
+
+    let sk = PhoenixSecretKey::random(rng);
+    let pk = PhoenixPublicKey::from(&sk);
+
+    let mut session = instantiate(rng, vm, &pk, GENESIS_VALUE);
+
+    for i in 0..NUM_STAKES {
+        let stake_pk = BlsPublicKey::from(&BlsSecretKey::random(rng));
+        let stake_data = StakeData {
+            reward: i * dusk(1.0),
+            amount: Some(StakeAmount {
+                value: dusk(1_000.0),
+                eligibility: 0,
+                locked: 0,
+            }),
+            faults: 0,
+            hard_faults: 0,
+        };
+        session.call::<_, ()>(
+            STAKE_CONTRACT,
+            "insert_stake",
+            &(StakeKeys::single_key(stake_pk), stake_data),
+            u64::MAX,
+        )?;
+    }
+    session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "set_burnt_amount",
+        &dusk(42.0),
+        u64::MAX,
+    )?;
+
+    let stake_bytecode = include_bytes!(
+        "../../../target/dusk/wasm32-unknown-unknown/release/stake_contract.wasm"
+    );
+    session.deploy(
+        stake_bytecode,
+        ContractData::builder()
+            .owner(OWNER)
+            .contract_id(NEW_STAKE_ID),
+        u64::MAX,
+    )?;
+
+    let summary = summarize_export(&mut session, STAKE_CONTRACT)?;
+    assert_eq!(summary.items, NUM_STAKES, "All stakes should be counted");
+
+    // import the config and burnt amount, then the stakes in chunks
+    let config = session
+        .call::<_, StakeConfig>(STAKE_CONTRACT, "get_config", &(), u64::MAX)?
+        .data;
+    session.call::<_, ()>(NEW_STAKE_ID, "set_config", &config, u64::MAX)?;
+
+    let burnt_amount = session
+        .call::<_, u64>(STAKE_CONTRACT, "burnt_amount", &(), u64::MAX)?
+        .data;
+    session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "set_burnt_amount",
+        &burnt_amount,
+        u64::MAX,
+    )?;
+
+    // the import isn't complete until all stakes are imported
+    let result = session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "finish_import",
+        &summary,
+        u64::MAX,
+    );
+    assert!(
+        result.is_err(),
+        "Finishing an incomplete import should fail"
+    );
+
+    let stakes = export_stakes(
+        &mut session,
+        STAKE_CONTRACT,
+        ExportCursor::START,
+        CHUNK_SIZE,
+    )?;
+    let result = session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "import_stakes",
+        &stakes,
+        u64::MAX,
+    );
+    assert!(
+        result.is_err(),
+        "Importing on top of existing stakes should fail"
+    );
+
+    // chain the summary one chunk at a time, as the stakes are exported
+    let mut chunk_summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    let mut exported = 0;
+    while let Some(cursor) = from {
+        let stakes =
+            export_stakes(&mut session, STAKE_CONTRACT, cursor, CHUNK_SIZE)?;
+        exported += stakes.len() as u64;
+
+        session.call::<_, ()>(
+            NEW_STAKE_ID,
+            "import_stakes",
+            &stakes,
+            u64::MAX,
+        )?;
+
+        let chunk = export_summary(
+            &mut session,
+            STAKE_CONTRACT,
+            chunk_summary,
+            cursor,
+            CHUNK_SIZE,
+        )?;
+        chunk_summary = chunk.summary;
+        from = chunk.next;
+    }
+    assert_eq!(exported, NUM_STAKES, "All stakes should be exported");
+    assert_eq!(
+        chunk_summary, summary,
+        "Chaining the summary in chunks should not change it"
+    );
+
+    session.call::<_, ()>(NEW_STAKE_ID, "finish_import", &summary, u64::MAX)?;
+
+    let new_summary = summarize_export(&mut session, NEW_STAKE_ID)?;
+    assert_eq!(new_summary, summary);
+
+    let result =
+        session.call::<_, ()>(NEW_STAKE_ID, "import_stakes", &stakes, u64::MAX);
+    assert!(
+        result.is_err(),
+        "Importing after finishing the import should fail"
+    );
+
+    Ok(())
+}
+
+/// Checks the state is at the latest version of its layout, and that
+/// migrations from another version or to an unknown version are refused.
+#[test]
+fn migrate_state() -> Result<(), VMError> {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let vm = &mut VM::ephemeral().expect("Creating ephemeral VM should work");
+
+    let sk = PhoenixSecretKey::random(rng);
+    let pk = PhoenixPublicKey::from(&sk);
+
+    let mut session = instantiate(rng, vm, &pk, GENESIS_VALUE);
+
+    let version = state_version(&mut session, STAKE_CONTRACT)?;
+    assert_eq!(version, STATE_VERSION);
+
+    let result = session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "migrate",
+        &(STATE_VERSION - 1, STATE_VERSION),
+        u64::MAX,
+    );
+    assert!(
+        result.is_err(),
+        "Migrating from a version the state is not at should fail"
+    );
+
+    let result = session.call::<_, ()>(
+        STAKE_CONTRACT,
+        "migrate",
+        &(STATE_VERSION, STATE_VERSION + 1),
+        u64::MAX,
+    );
+    assert!(
+        result.is_err(),
+        "Migrating to an unknown version should fail"
+    );
+
+    let version = state_version(&mut session, STAKE_CONTRACT)?;
+    assert_eq!(version, STATE_VERSION, "The version should be unchanged");
+
+    Ok(())
+}
+
+/// Import an export of the first version of the format, storing the default
+/// minimum stake as zero, and check the imported state is pending a
+/// migration, refusing management calls until it is migrated to the latest
+/// version of its layout, which stores the default minimum stake.
+#[test]
+fn migrate_imported_state() -> Result<(), VMError> {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let vm = &mut VM::ephemeral().expect("Creating ephemeral VM should work");
+
+    let sk = PhoenixSecretKey::random(rng);
+    let pk = PhoenixPublicKey::from(&sk);
+
+    let mut session = instantiate(rng, vm, &pk, GENESIS_VALUE);
+
+    // the state of the old contract, as exported in the first version of the
+    // format
+    let config = StakeConfig {
+        minimum_stake: 0,
+        ..StakeConfig::new()
+    };
+    session.call::<_, ()>(STAKE_CONTRACT, "set_config", &config, u64::MAX)?;
+    let summary = ExportSummary {
+        version: 1,
+        ..summarize_export(&mut session, STAKE_CONTRACT)?
+    };
+
+    let stake_bytecode = include_bytes!(
+        "../../../target/dusk/wasm32-unknown-unknown/release/stake_contract.wasm"
+    );
+    session.deploy(
+        stake_bytecode,
+        ContractData::builder()
+            .owner(OWNER)
+            .contract_id(NEW_STAKE_ID),
+        u64::MAX,
+    )?;
+
+    let burnt_amount = session
+        .call::<_, u64>(STAKE_CONTRACT, "burnt_amount", &(), u64::MAX)?
+        .data;
+    let stakes =
+        export_stakes(&mut session, STAKE_CONTRACT, ExportCursor::START, 0)?;
+    session.call::<_, ()>(NEW_STAKE_ID, "set_config", &config, u64::MAX)?;
+    session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "set_burnt_amount",
+        &burnt_amount,
+        u64::MAX,
+    )?;
+    session.call::<_, ()>(NEW_STAKE_ID, "import_stakes", &stakes, u64::MAX)?;
+    session.call::<_, ()>(NEW_STAKE_ID, "finish_import", &summary, u64::MAX)?;
+
+    let version = state_version(&mut session, NEW_STAKE_ID)?;
+    assert_eq!(version, 1, "The state should be at the exported version");
+
+    let result = session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "set_burnt_amount",
+        &burnt_amount,
+        u64::MAX,
+    );
+    assert!(
+        result.is_err(),
+        "Management calls should fail while a migration is pending"
+    );
+
+    session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "migrate",
+        &(1u64, STATE_VERSION),
+        u64::MAX,
+    )?;
+
+    let version = state_version(&mut session, NEW_STAKE_ID)?;
+    assert_eq!(version, STATE_VERSION, "The state should be migrated");
+
+    let migrated_config = session
+        .call::<_, StakeConfig>(NEW_STAKE_ID, "get_config", &(), u64::MAX)?
+        .data;
+    assert_eq!(migrated_config.minimum_stake, DEFAULT_MINIMUM_STAKE);
+    assert_eq!(migrated_config.warnings, config.warnings);
+
+    session.call::<_, ()>(
+        NEW_STAKE_ID,
+        "set_burnt_amount",
+        &burnt_amount,
+        u64::MAX,
+    )?;
+
+    Ok(())
+}
+
+fn state_version(
+    session: &mut Session,
+    contract: ContractId,
+) -> Result<u64, VMError> {
+    session
+        .call(contract, "state_version", &(), u64::MAX)
+        .map(|r| r.data)
+}
+
+fn export_summary(
+    session: &mut Session,
+    contract: ContractId,
+    summary: ExportSummary,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<ExportChunk, VMError> {
+    session
+        .call(
+            contract,
+            "export_summary",
+            &(summary, from, count_limit),
+            u64::MAX,
+        )
+        .map(|r| r.data)
+}
+
+/// Return the summary of the whole exported state, chained in a single chunk.
+fn summarize_export(
+    session: &mut Session,
+    contract: ContractId,
+) -> Result<ExportSummary, VMError> {
+    let chunk = export_summary(
+        session,
+        contract,
+        ExportSummary::EMPTY,
+        ExportCursor::START,
+        0,
+    )?;
+    Ok(chunk.summary)
+}
+
+fn export_stakes(
+    session: &mut Session,
+    contract: ContractId,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<Vec<(StakeKeys, StakeData)>, VMError> {
+    let (feeder, receiver) = mpsc::channel();
+
+    session.feeder_call::<_, ()>(
+        contract,
+        "export_stakes",
+        &(from, count_limit),
+        u64::MAX,
+        feeder,
+    )?;
+
+    Ok(receiver
+        .iter()
+        .map(|bytes| rkyv::from_bytes(&bytes).expect("Should return stakes"))
+        .collect())
+}
@@ -4,15 +4,14 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use contracts_test_kit::stake::add_reward;
+use contracts_test_kit::{Genesis, CHAIN_ID, GAS_LIMIT};
 use dusk_core::dusk;
 use dusk_core::signatures::bls::{
     PublicKey as BlsPublicKey, SecretKey as BlsSecretKey,
 };
-use dusk_core::stake::{Reward, RewardReason, EPOCH, STAKE_CONTRACT};
-use dusk_core::transfer::TRANSFER_CONTRACT;
-use dusk_vm::{
-    execute, ContractData, Error as VMError, ExecutionConfig, Session, VM,
-};
+use dusk_core::stake::EPOCH;
+use dusk_vm::{execute, Error as VMError, ExecutionConfig, Session, VM};
 use dusk_wallet_core::transaction::{
     moonlight_stake, moonlight_stake_reward, moonlight_unstake,
 };
@@ -21,7 +20,6 @@ use rand::SeedableRng;
 
 pub mod common;
 use crate::common::assert::*;
-use crate::common::init::CHAIN_ID;
 use crate::common::utils::*;
 
 const GENESIS_VALUE: u64 = dusk(1_000_000.0);
@@ -358,7 +356,8 @@ fn withdraw_reward() -> Result<(), VMError> {
     assert_moonlight(&mut session, &moonlight_pk, moonlight_balance, nonce);
     // add a reward to the staked key
     const REWARD_AMOUNT: u64 = dusk(3.0);
-    add_reward(&mut session, &stake_pk, REWARD_AMOUNT)?;
+    let receipt = add_reward(&mut session, &stake_pk, REWARD_AMOUNT)?;
+    assert_reward_event(&receipt.events, "reward", &stake_pk, REWARD_AMOUNT);
     assert_stake(&mut session, &stake_pk, STAKE_VALUE, 0, REWARD_AMOUNT);
 
     // ------
@@ -434,74 +433,16 @@ fn withdraw_reward() -> Result<(), VMError> {
     Ok(())
 }
 
-fn add_reward(
-    session: &mut Session,
-    stake_pk: &BlsPublicKey,
-    reward: u64,
-) -> Result<(), VMError> {
-    let rewards = vec![Reward {
-        account: *stake_pk,
-        value: reward,
-        reason: RewardReason::Other,
-    }];
-
-    let receipt =
-        session.call::<_, ()>(STAKE_CONTRACT, "reward", &rewards, GAS_LIMIT)?;
-
-    assert_reward_event(&receipt.events, "reward", stake_pk, reward);
-
-    Ok(())
-}
-
 /// Instantiate the virtual machine with the transfer contract deployed, with a
 /// single moonlight account identified by the given public key, owning the
 /// genesis-value.
 fn instantiate(vm: &mut VM, moonlight_pk: &BlsPublicKey) -> Session {
-    // create a new session using an ephemeral vm
-    let mut session = vm.genesis_session(CHAIN_ID);
-
-    // deploy transfer-contract
-    const OWNER: [u8; 32] = [0; 32];
-    let transfer_bytecode = include_bytes!(
-        "../../../target/dusk/wasm64-unknown-unknown/release/transfer_contract.wasm"
-    );
-    session
-        .deploy(
-            transfer_bytecode,
-            ContractData::builder()
-                .owner(OWNER)
-                .contract_id(TRANSFER_CONTRACT),
-            GAS_LIMIT,
-        )
-        .expect("Deploying the transfer contract should succeed");
-
-    // deploy stake-contract
-    let stake_bytecode = include_bytes!(
-        "../../../target/dusk/wasm32-unknown-unknown/release/stake_contract.wasm"
-    );
-    session
-        .deploy(
-            stake_bytecode,
-            ContractData::builder()
-                .owner(OWNER)
-                .contract_id(STAKE_CONTRACT),
-            GAS_LIMIT,
-        )
-        .expect("Deploying the stake contract should succeed");
-
-    // insert genesis value to moonlight account
-    session
-        .call::<_, ()>(
-            TRANSFER_CONTRACT,
-            "add_account_balance",
-            &(*moonlight_pk, GENESIS_VALUE),
-            GAS_LIMIT,
-        )
-        .expect("Inserting genesis account should succeed");
+    let base = Genesis::new(CHAIN_ID)
+        .account(*moonlight_pk, GENESIS_VALUE)
+        .commit(vm)
+        .expect("Building the genesis state should succeed");
 
     // sets the block height for all subsequent operations to 1
-    let base = session.commit().expect("Committing should succeed");
-
     let mut session = vm
         .session(base, CHAIN_ID, 1)
         .expect("Instantiating new session should succeed");
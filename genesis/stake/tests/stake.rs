@@ -4,6 +4,9 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use contracts_test_kit::notes::filter_notes_owned_by;
+use contracts_test_kit::transfer::{leaves_from_height, update_root};
+use contracts_test_kit::{CHAIN_ID, GAS_LIMIT};
 use dusk_core::signatures::bls::{
     PublicKey as BlsPublicKey, SecretKey as BlsSecretKey,
 };
@@ -28,7 +31,7 @@ pub mod common;
 use crate::common::assert::{
     assert_reward_event, assert_stake, assert_stake_event,
 };
-use crate::common::init::{instantiate, CHAIN_ID};
+use crate::common::init::instantiate;
 use crate::common::utils::*;
 
 const GENESIS_VALUE: u64 = dusk(1_000_000.0);
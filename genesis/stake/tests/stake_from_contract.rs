@@ -4,30 +4,28 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use contracts_test_kit::stake::add_reward;
+use contracts_test_kit::{deploy_contract, Genesis, CHAIN_ID, GAS_LIMIT};
 use dusk_core::abi::{ContractError, ContractId};
 use dusk_core::dusk;
 use dusk_core::signatures::bls::{
     PublicKey as BlsPublicKey, SecretKey as BlsSecretKey,
 };
-use dusk_core::stake::{
-    Reward, RewardReason, Stake, DEFAULT_MINIMUM_STAKE, STAKE_CONTRACT,
-};
+use dusk_core::stake::{Stake, DEFAULT_MINIMUM_STAKE};
 use dusk_core::transfer::data::ContractCall;
 use dusk_core::transfer::{Transaction, TRANSFER_CONTRACT};
-use dusk_vm::{execute, ContractData, Error, ExecutionConfig, Session, VM};
+use dusk_vm::{execute, Error, ExecutionConfig, Session, VM};
 use dusk_wallet_core::transaction::moonlight_stake_reward;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
 pub mod common;
 use crate::common::assert::{assert_moonlight, assert_reward_event};
-use crate::common::init::CHAIN_ID;
 use crate::common::utils::*;
 
 const GENESIS_VALUE: u64 = dusk(1_000_000.0);
 const GENESIS_NONCE: u64 = 0;
 
-const OWNER: [u8; 32] = [0; 32];
 const ALICE_ID: ContractId = ContractId::from_bytes([3; 32]);
 const CHARLIE_ID: ContractId = ContractId::from_bytes([4; 32]);
 const REWARD_AMOUNT: u64 = dusk(3.0);
@@ -76,7 +74,8 @@ fn stake_from_contract() -> Result<(), Error> {
     // ------
     // add stake reward to second key set
 
-    add_reward(&mut session, &pk_2, REWARD_AMOUNT)?;
+    let receipt = add_reward(&mut session, &pk_2, REWARD_AMOUNT)?;
+    assert_reward_event(&receipt.events, "reward", &pk_2, REWARD_AMOUNT);
 
     // withdraw half of the reward to verify it's working
     nonce_2 += 1;
@@ -195,92 +194,23 @@ fn stake_from_contract() -> Result<(), Error> {
 /// single moonlight account identified by the given public key, owning the
 /// genesis-value.
 fn instantiate(vm: &mut VM, moonlight_pks: &[BlsPublicKey]) -> Session {
-    // create a new session using an ephemeral vm
-    let mut session = vm.genesis_session(CHAIN_ID);
-
-    // deploy transfer-contract
-    const OWNER: [u8; 32] = [0; 32];
-    let transfer_bytecode = include_bytes!(
-        "../../../target/dusk/wasm64-unknown-unknown/release/transfer_contract.wasm"
-    );
-    session
-        .deploy(
-            transfer_bytecode,
-            ContractData::builder()
-                .owner(OWNER)
-                .contract_id(TRANSFER_CONTRACT),
-            GAS_LIMIT,
-        )
-        .expect("Deploying the transfer contract should succeed");
-
-    // deploy stake-contract
-    let stake_bytecode = include_bytes!(
-        "../../../target/dusk/wasm32-unknown-unknown/release/stake_contract.wasm"
-    );
-    session
-        .deploy(
-            stake_bytecode,
-            ContractData::builder()
-                .owner(OWNER)
-                .contract_id(STAKE_CONTRACT),
-            GAS_LIMIT,
-        )
-        .expect("Deploying the stake contract should succeed");
-
-    // insert genesis value to moonlight accounts
+    let mut genesis = Genesis::new(CHAIN_ID);
     for pk in moonlight_pks {
-        session
-            .call::<_, ()>(
-                TRANSFER_CONTRACT,
-                "add_account_balance",
-                &(*pk, GENESIS_VALUE),
-                GAS_LIMIT,
-            )
-            .expect("Inserting genesis account should succeed");
-
-        // check that the moonlight account is initialized as expected
-        assert_moonlight(&mut session, pk, GENESIS_VALUE, GENESIS_NONCE);
+        genesis = genesis.account(*pk, GENESIS_VALUE);
     }
+    let base = genesis
+        .commit(vm)
+        .expect("Building the genesis state should succeed");
 
     // sets the block height for all subsequent operations to 1
-    let base = session.commit().expect("Committing should succeed");
-
-    let session = vm
+    let mut session = vm
         .session(base, CHAIN_ID, 1)
         .expect("Instantiating new session should succeed");
 
-    session
-}
-
-fn add_reward(
-    session: &mut Session,
-    stake_pk: &BlsPublicKey,
-    reward: u64,
-) -> Result<(), Error> {
-    let rewards = vec![Reward {
-        account: *stake_pk,
-        value: reward,
-        reason: RewardReason::Other,
-    }];
-
-    let receipt =
-        session.call::<_, ()>(STAKE_CONTRACT, "reward", &rewards, GAS_LIMIT)?;
-
-    assert_reward_event(&receipt.events, "reward", stake_pk, reward);
-
-    Ok(())
-}
-
-fn deploy_contract(
-    session: &mut Session,
-    id: ContractId,
-    bytecode: &[u8],
-) -> Result<(), Error> {
-    session.deploy(
-        bytecode,
-        ContractData::builder().owner(OWNER).contract_id(id),
-        GAS_LIMIT,
-    )?;
+    // check that the moonlight accounts are initialized as expected
+    for pk in moonlight_pks {
+        assert_moonlight(&mut session, pk, GENESIS_VALUE, GENESIS_NONCE);
+    }
 
-    Ok(())
+    session
 }
@@ -4,41 +4,491 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-/// Buildfile for the transfer contracts, to set the necessary environment
-/// variables.
-
-fn set_id_env_var(circuit: &rusk_profile::Circuit) {
-    println!(
-        "cargo:rustc-env=ID_{}={}",
-        circuit.name().to_uppercase(),
-        circuit.id_str()
-    );
+//! Buildfile for the transfer contracts, to generate the table of verifier
+//! data for the phoenix-circuits, the ABI schema of the contract and the
+//! typed wrappers of its entrypoints.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::{env, fs};
+
+use contracts_abi_schema::Entrypoint;
+
+/// The phoenix transaction circuits the verifier table is generated for, with
+/// their number of inputs and outputs. These are all the transaction circuits
+/// the rusk-profile provides: verifier data for other arities can only be
+/// provided at runtime with `set_verifier`.
+const TX_CIRCUITS: [(&str, usize, usize); 4] = [
+    ("TxCircuitOneTwo", 1, 2),
+    ("TxCircuitTwoTwo", 2, 2),
+    ("TxCircuitThreeTwo", 3, 2),
+    ("TxCircuitFourTwo", 4, 2),
+];
+
+/// The entrypoints of the contract, with the types of their argument and of
+/// the data they return or feed, the caller restriction checked by the state
+/// and the topics of the events they emit.
+const ENTRYPOINTS: &[Entrypoint] = &[
+    Entrypoint {
+        name: "mint",
+        argument: "dusk_core::transfer::withdraw::Withdraw",
+        returns: "()",
+        caller: Some("stake"),
+        topics: &["mint"],
+    },
+    Entrypoint {
+        name: "mint_to_contract",
+        argument: "dusk_core::transfer::ContractToContract",
+        returns: "()",
+        caller: Some("stake"),
+        topics: &["mint_c"],
+    },
+    Entrypoint {
+        name: "deposit",
+        argument: "u64",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["deposit"],
+    },
+    Entrypoint {
+        name: "withdraw",
+        argument: "dusk_core::transfer::withdraw::Withdraw",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["withdraw"],
+    },
+    Entrypoint {
+        name: "withdraw_scheduled",
+        argument: "transfer_contract::scheduled::ScheduledWithdraw",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["withdraw"],
+    },
+    Entrypoint {
+        name: "convert",
+        argument: "dusk_core::transfer::withdraw::Withdraw",
+        returns: "()",
+        caller: Some("transfer"),
+        topics: &["convert"],
+    },
+    Entrypoint {
+        name: "contract_to_contract",
+        argument: "dusk_core::transfer::ContractToContract",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["contract_to_contract"],
+    },
+    Entrypoint {
+        name: "contract_to_account",
+        argument: "dusk_core::transfer::ContractToAccount",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["contract_to_account"],
+    },
+    Entrypoint {
+        name: "create_escrow",
+        argument: "transfer_contract::escrow::CreateEscrow",
+        returns: "u64",
+        caller: Some("contract"),
+        topics: &["escrow_create"],
+    },
+    Entrypoint {
+        name: "release_escrow",
+        argument: "u64",
+        returns: "()",
+        caller: None,
+        topics: &["escrow_release"],
+    },
+    Entrypoint {
+        name: "cancel_escrow",
+        argument: "u64",
+        returns: "()",
+        caller: Some("contract"),
+        topics: &["escrow_cancel"],
+    },
+    Entrypoint {
+        name: "root",
+        argument: "()",
+        returns: "dusk_core::BlsScalar",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "account",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "dusk_core::transfer::moonlight::AccountData",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "accounts_root",
+        argument: "()",
+        returns: "dusk_core::BlsScalar",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "account_proof",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "Option<transfer_contract::accounts::AccountProof>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "contract_balance",
+        argument: "dusk_core::abi::ContractId",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "opening",
+        argument: "u64",
+        returns: "Option<dusk_core::transfer::phoenix::NoteOpening>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "existing_nullifiers",
+        argument: "Vec<dusk_core::BlsScalar>",
+        returns: "Vec<dusk_core::BlsScalar>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "nullifiers_root",
+        argument: "()",
+        returns: "dusk_core::BlsScalar",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "nullifier_root",
+        argument: "u64",
+        returns: "Option<dusk_core::BlsScalar>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "nullifier_proof",
+        argument: "dusk_core::BlsScalar",
+        returns: "transfer_contract::nullifiers::NullifierProof",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "block_event_digest",
+        argument: "u64",
+        returns: "Option<dusk_core::BlsScalar>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "escrow",
+        argument: "u64",
+        returns: "Option<transfer_contract::escrow::Escrow>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "validate_transaction",
+        argument: "dusk_core::transfer::Transaction",
+        returns: "transfer_contract::validation::ValidationReport",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "verifiers",
+        argument: "(u32, u32)",
+        returns: "Vec<transfer_contract::verifier::Verifier>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "state_version",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "export_summary",
+        argument: "(transfer_contract::migration::ExportSummary, transfer_contract::migration::ExportCursor, u64)",
+        returns: "transfer_contract::migration::ExportChunk",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "gas_price_info",
+        argument: "()",
+        returns: "transfer_contract::gas::GasPriceInfo",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "num_notes",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "chain_id",
+        argument: "()",
+        returns: "u8",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "leaves_from_height",
+        argument: "u64",
+        returns: "dusk_core::transfer::phoenix::NoteLeaf",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "leaves_from_pos",
+        argument: "u64",
+        returns: "dusk_core::transfer::phoenix::NoteLeaf",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sync",
+        argument: "(u64, u64)",
+        returns: "dusk_core::transfer::phoenix::NoteLeaf",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sync_nullifiers",
+        argument: "(u64, u64)",
+        returns: "dusk_core::BlsScalar",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sync_contract_balances",
+        argument: "(u64, u64)",
+        returns: "(dusk_core::abi::ContractId, u64)",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sync_accounts",
+        argument: "(u64, u64)",
+        returns: "(dusk_core::transfer::moonlight::AccountData, [u8; 96])",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sync_escrows",
+        argument: "(u64, u64)",
+        returns: "(u64, transfer_contract::escrow::Escrow)",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "export_state",
+        argument: "(transfer_contract::migration::ExportCursor, u64)",
+        returns: "transfer_contract::migration::StateItem",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "frozen_accounts",
+        argument: "()",
+        returns: "dusk_core::signatures::bls::PublicKey",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "spend_and_execute",
+        argument: "dusk_core::transfer::Transaction",
+        returns: "Result<Vec<u8>, dusk_core::abi::ContractError>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "refund",
+        argument: "u64",
+        returns: "transfer_contract::receipt::TransactionReceipt",
+        caller: None,
+        topics: &["phoenix", "moonlight", "receipt"],
+    },
+    Entrypoint {
+        name: "push_note",
+        argument: "(u64, dusk_core::transfer::phoenix::Note)",
+        returns: "Option<dusk_core::transfer::phoenix::Note>",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "update_root",
+        argument: "()",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "update_base_fee",
+        argument: "u64",
+        returns: "()",
+        caller: None,
+        topics: &["base_fee"],
+    },
+    Entrypoint {
+        name: "set_gas_config",
+        argument: "transfer_contract::gas::GasConfig",
+        returns: "()",
+        caller: None,
+        topics: &["gas_config"],
+    },
+    Entrypoint {
+        name: "add_account_balance",
+        argument: "(dusk_core::signatures::bls::PublicKey, u64)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sub_account_balance",
+        argument: "(dusk_core::signatures::bls::PublicKey, u64)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "insert_account",
+        argument: "(dusk_core::signatures::bls::PublicKey, dusk_core::transfer::moonlight::AccountData)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "import_state",
+        argument: "Vec<transfer_contract::migration::StateItem>",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "finish_import",
+        argument: "transfer_contract::migration::ExportSummary",
+        returns: "()",
+        caller: None,
+        topics: &["import"],
+    },
+    Entrypoint {
+        name: "set_verifier",
+        argument: "transfer_contract::verifier::SetVerifier",
+        returns: "()",
+        caller: None,
+        topics: &["set_verifier"],
+    },
+    Entrypoint {
+        name: "freeze_account",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "()",
+        caller: None,
+        topics: &["freeze"],
+    },
+    Entrypoint {
+        name: "unfreeze_account",
+        argument: "dusk_core::signatures::bls::PublicKey",
+        returns: "()",
+        caller: None,
+        topics: &["unfreeze"],
+    },
+    Entrypoint {
+        name: "add_contract_balance",
+        argument: "(dusk_core::abi::ContractId, u64)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "sub_contract_balance",
+        argument: "(dusk_core::abi::ContractId, u64)",
+        returns: "()",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "migrate",
+        argument: "(u64, u64)",
+        returns: "()",
+        caller: None,
+        topics: &["migrate"],
+    },
+];
+
+/// Write the table of verifier data to the output directory.
+fn write_table(table: String) -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out_dir.join("tx_circuit_verifiers.rs"), table)?;
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let keys_dir = rusk_profile::get_rusk_keys_dir()?;
+/// Generate the ABI schema of the contract and the typed wrappers of its
+/// entrypoints, and write them to the output directory.
+fn write_schema() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=src/lib.rs");
 
-    println!("Keys dir is {keys_dir:?}");
+    let source = fs::read_to_string("src/lib.rs")?;
+    let schema = contracts_abi_schema::schema(
+        &env::var("CARGO_PKG_NAME")?,
+        &env::var("CARGO_PKG_VERSION")?,
+        &source,
+        ENTRYPOINTS,
+    )?;
+
+    let wrappers = contracts_abi_schema::wrappers(&source, ENTRYPOINTS)?;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out_dir.join("abi.json"), schema)?;
+    fs::write(out_dir.join("wrap.rs"), wrappers)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Ensure we run the build script again even if we change just the build.rs
     println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rerun-if-changed=../Cargo.lock");
 
-    // Set RUSK_BUILT_KEYS_PATH for `.vd` resolver
-    println!(
-        "cargo:rustc-env=RUSK_BUILT_KEYS_PATH={}",
-        keys_dir.to_str().unwrap()
+    write_schema()?;
+
+    let mut table = String::from(
+        "/// Verifier data for the phoenix-circuits, as `(inputs, outputs, \
+         vd)`.\n\
+         const TX_CIRCUIT_VERIFIERS: &[(usize, usize, &[u8])] = &[\n",
     );
 
-    // Set the ID_[circuit_name] variables
-    let circuits = [
-        rusk_profile::Circuit::from_name("TxCircuitOneTwo")?,
-        rusk_profile::Circuit::from_name("TxCircuitTwoTwo")?,
-        rusk_profile::Circuit::from_name("TxCircuitThreeTwo")?,
-        rusk_profile::Circuit::from_name("TxCircuitFourTwo")?,
-    ];
-    for circuit in circuits {
-        set_id_env_var(&circuit);
+    // When building offline no verifier data is embedded in the contract, and
+    // the rusk-profile isn't needed
+    if env::var_os("CARGO_FEATURE_OFFLINE").is_some() {
+        table.push_str("];\n");
+        return write_table(table);
     }
 
-    Ok(())
+    let keys_dir = rusk_profile::get_rusk_keys_dir()?;
+    let circuits_dir = rusk_profile::get_rusk_circuits_dir()?;
+
+    println!("Keys dir is {keys_dir:?}");
+    println!("cargo:rerun-if-changed=../Cargo.lock");
+    // Also run it again when the circuits in the rusk-profile are regenerated
+    println!("cargo:rerun-if-changed={}", circuits_dir.display());
+
+    // Generate the table of verifier data indexed by the number of inputs and
+    // outputs
+    for (name, inputs, outputs) in TX_CIRCUITS {
+        let circuit = rusk_profile::Circuit::from_name(name)?;
+        let vd_path = keys_dir.join(format!("{}.vd", circuit.id_str()));
+        writeln!(
+            table,
+            "    ({inputs}, {outputs}, include_bytes!({vd_path:?})),"
+        )?;
+    }
+    table.push_str("];\n");
+
+    write_table(table)
 }
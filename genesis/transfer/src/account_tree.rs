@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use dusk_bytes::Serializable;
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::{abi, BlsScalar};
+
+use crate::accounts::{
+    account_hash_inputs, AccountOpening, AccountTreeItem, AccountsTree,
+};
+use crate::saturating_usize;
+
+/// The merkle tree committing to the state of all Moonlight accounts.
+///
+/// Accounts are assigned a position in the order they first appear in, and
+/// keep it for as long as the contract lives, since accounts are never
+/// removed. The leaf at that position is updated every time the balance or
+/// the nonce of the account changes.
+pub struct AccountTree {
+    tree: AccountsTree,
+    // The position of each account in the tree, keyed in the same way as the
+    // accounts in the state.
+    positions: BTreeMap<[u8; AccountPublicKey::SIZE], u64>,
+    // The keys of the accounts, with the index in this vector corresponding
+    // to the position of the account in the tree.
+    keys: Vec<[u8; AccountPublicKey::SIZE]>,
+}
+
+impl AccountTree {
+    /// Create a new empty tree.
+    pub const fn new() -> Self {
+        Self {
+            tree: AccountsTree::new(),
+            positions: BTreeMap::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Set the leaf of the given account to commit to the given data,
+    /// assigning it the next free position if it isn't in the tree yet.
+    pub fn update(&mut self, key: &AccountPublicKey, account: &AccountData) {
+        let key_bytes = key.to_bytes();
+        let pos = match self.positions.get(&key_bytes) {
+            Some(pos) => *pos,
+            None => {
+                let pos = self.keys.len() as u64;
+                self.positions.insert(key_bytes, pos);
+                self.keys.push(key_bytes);
+                pos
+            }
+        };
+
+        let hash =
+            abi::poseidon_hash(account_hash_inputs(key, account).to_vec());
+        let item = AccountTreeItem { hash, data: () };
+
+        self.tree.insert(pos, item);
+    }
+
+    /// Return the root of the merkle tree of accounts.
+    pub fn root(&self) -> BlsScalar {
+        self.tree.root().hash
+    }
+
+    /// Return the keys of the accounts in the tree, in the order of their
+    /// positions, starting from the given position.
+    pub fn keys(
+        &self,
+        from: u64,
+    ) -> impl Iterator<Item = &[u8; AccountPublicKey::SIZE]> {
+        let from = saturating_usize(from).min(self.keys.len());
+        self.keys[from..].iter()
+    }
+
+    /// Return the position and the merkle-opening of the given account, if
+    /// it is in the tree.
+    pub fn opening(
+        &self,
+        key: &AccountPublicKey,
+    ) -> Option<(u64, AccountOpening)> {
+        let pos = *self.positions.get(&key.to_bytes())?;
+        self.tree.opening(pos).map(|opening| (pos, opening))
+    }
+}
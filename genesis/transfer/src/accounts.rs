@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the merkle commitment over the Moonlight accounts.
+
+use bytecheck::CheckBytes;
+use dusk_bytes::Serializable;
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::BlsScalar;
+use dusk_poseidon::{Domain, Hash};
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Depth of the merkle tree of Moonlight accounts.
+pub const ACCOUNTS_TREE_DEPTH: usize = 17;
+
+/// The merkle tree committing to the Moonlight accounts.
+pub type AccountsTree = poseidon_merkle::Tree<(), ACCOUNTS_TREE_DEPTH>;
+
+/// The merkle opening for an account in the [`AccountsTree`].
+pub type AccountOpening = poseidon_merkle::Opening<(), ACCOUNTS_TREE_DEPTH>;
+
+/// The item stored in the leaves of the [`AccountsTree`].
+pub type AccountTreeItem = poseidon_merkle::Item<()>;
+
+/// Return the scalars whose poseidon hash is the leaf of the given account in
+/// the [`AccountsTree`], namely the hash of the compressed public key, the
+/// balance, and the nonce.
+#[must_use]
+pub fn account_hash_inputs(
+    key: &AccountPublicKey,
+    account: &AccountData,
+) -> [BlsScalar; 3] {
+    [
+        BlsScalar::hash_to_scalar(&key.to_bytes()),
+        BlsScalar::from(account.balance),
+        BlsScalar::from(account.nonce),
+    ]
+}
+
+/// The data of a Moonlight account together with the proof of its inclusion
+/// in the [`AccountsTree`].
+///
+/// There is no proof of exclusion: the leaves are ordered by the creation of
+/// the accounts rather than by their keys, so the absence of an account can't
+/// be proven against the root.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct AccountProof {
+    /// The balance and nonce of the account.
+    pub account: AccountData,
+    /// The position of the account in the tree.
+    pub position: u64,
+    /// The opening of the account's leaf.
+    pub opening: AccountOpening,
+}
+
+impl AccountProof {
+    /// Verify the proof is valid for the account with the given public key,
+    /// against the given accounts root.
+    #[must_use]
+    pub fn verify(&self, key: &AccountPublicKey, root: &BlsScalar) -> bool {
+        if self.opening.root().hash != *root {
+            return false;
+        }
+
+        let inputs = account_hash_inputs(key, &self.account);
+        let hash = Hash::digest(Domain::Other, &inputs)[0];
+
+        self.opening.verify(AccountTreeItem { hash, data: () })
+    }
+}
@@ -0,0 +1,73 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the escrow entries held by the transfer contract.
+
+use bytecheck::CheckBytes;
+use dusk_core::abi::ContractId;
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Topic for the event emitted when an escrow is created.
+pub const ESCROW_CREATE_TOPIC: &str = "escrow_create";
+/// Topic for the event emitted when an escrow is released to its beneficiary.
+pub const ESCROW_RELEASE_TOPIC: &str = "escrow_release";
+/// Topic for the event emitted when an escrow is cancelled.
+pub const ESCROW_CANCEL_TOPIC: &str = "escrow_cancel";
+
+/// The receiver of the value held in an escrow once it is released.
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub enum EscrowBeneficiary {
+    /// The value is added to the balance of a contract.
+    Contract(ContractId),
+    /// The value is added to the balance of a Moonlight account.
+    Account(AccountPublicKey),
+}
+
+/// The payload sent by a contract to the transfer contract to lock some of its
+/// funds in an escrow.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct CreateEscrow {
+    /// Receiver of the funds once the escrow is released.
+    pub beneficiary: EscrowBeneficiary,
+    /// Amount to lock in the escrow.
+    pub value: u64,
+    /// Block height from which the escrow can be released.
+    pub release_height: u64,
+    /// Contract allowed to cancel the escrow before the release height, if
+    /// any.
+    pub cancel_authority: Option<ContractId>,
+}
+
+/// An escrow entry, holding funds taken from a contract's balance until a
+/// given block height.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct Escrow {
+    /// The contract the funds were taken from.
+    pub owner: ContractId,
+    /// Receiver of the funds once the escrow is released.
+    pub beneficiary: EscrowBeneficiary,
+    /// Amount locked in the escrow.
+    pub value: u64,
+    /// Block height from which the escrow can be released.
+    pub release_height: u64,
+    /// Contract allowed to cancel the escrow before the release height, if
+    /// any.
+    pub cancel_authority: Option<ContractId>,
+}
+
+/// Event data emitted on the creation, release or cancellation of an escrow.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct EscrowEvent {
+    /// The identifier of the escrow.
+    pub id: u64,
+    /// The escrow entry.
+    pub escrow: Escrow,
+}
@@ -0,0 +1,13 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Topics of the events emitted when changing the set of frozen Moonlight
+//! accounts. The data of both events is the public key of the account.
+
+/// Topic for the event emitted when an account is frozen.
+pub const FREEZE_TOPIC: &str = "freeze";
+/// Topic for the event emitted when an account is unfrozen.
+pub const UNFREEZE_TOPIC: &str = "unfreeze";
@@ -0,0 +1,107 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the pricing of gas enforced by the transfer contract.
+//!
+//! Transactions are rejected if their gas price is below the minimum gas
+//! price, or below the base fee when one is configured. The base fee follows
+//! the block gas usage in the same way as in EIP-1559: it rises when a block
+//! uses more than the target amount of gas, and falls when it uses less, by at
+//! most `1 / BASE_FEE_MAX_CHANGE_DENOMINATOR` per block. The base-fee part of
+//! the fee of every transaction is burned.
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Topic for the event emitted when the gas configuration is changed.
+pub const GAS_CONFIG_TOPIC: &str = "gas_config";
+
+/// Topic for the event emitted when the base fee is updated.
+pub const BASE_FEE_TOPIC: &str = "base_fee";
+
+/// Bound on the change of the base fee from one block to the next, as a
+/// fraction of the base fee.
+pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// The configuration of the gas pricing, set with `set_gas_config`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct GasConfig {
+    /// The minimum gas price a transaction must pay. A gas price of zero is
+    /// always rejected.
+    pub min_gas_price: u64,
+    /// The amount of gas a block is expected to use, around which the base fee
+    /// is adjusted. The base fee is disabled if this is `None`.
+    pub block_gas_target: Option<u64>,
+}
+
+impl GasConfig {
+    /// The configuration of a newly deployed contract, rejecting only a gas
+    /// price of zero.
+    pub const DEFAULT: Self = Self {
+        min_gas_price: 1,
+        block_gas_target: None,
+    };
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The gas pricing in effect, returned by `gas_price_info`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Archive, Serialize, Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct GasPriceInfo {
+    /// The configuration of the gas pricing.
+    pub config: GasConfig,
+    /// The current base fee, or zero if it is disabled.
+    pub base_fee: u64,
+    /// The total value burned as base fee.
+    pub burned: u64,
+}
+
+impl GasPriceInfo {
+    /// The lowest gas price currently accepted for a transaction.
+    #[must_use]
+    pub fn min_accepted_gas_price(&self) -> u64 {
+        self.config.min_gas_price.max(self.base_fee).max(1)
+    }
+}
+
+/// Compute the base fee following a block that used `gas_used` gas, given the
+/// current base fee and the block gas target.
+///
+/// The result is never lower than `min_gas_price`.
+#[must_use]
+pub fn next_base_fee(
+    base_fee: u64,
+    gas_used: u64,
+    gas_target: u64,
+    min_gas_price: u64,
+) -> u64 {
+    let base_fee = u128::from(base_fee);
+    let gas_used = u128::from(gas_used);
+    let gas_target = u128::from(gas_target);
+    let denominator = u128::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+
+    let next = if gas_used > gas_target {
+        let delta =
+            base_fee * (gas_used - gas_target) / gas_target / denominator;
+        base_fee + delta.max(1)
+    } else {
+        let delta =
+            base_fee * (gas_target - gas_used) / gas_target / denominator;
+        base_fee - delta
+    };
+
+    u64::try_from(next).unwrap_or(u64::MAX).max(min_gas_price)
+}
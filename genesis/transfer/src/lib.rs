@@ -5,221 +5,478 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 #![cfg_attr(target_family = "wasm", no_std)]
-#![cfg(target_family = "wasm")]
 #![feature(arbitrary_self_types)]
-#![deny(unused_crate_dependencies)]
+#![cfg_attr(target_family = "wasm", deny(unused_crate_dependencies))]
 #![deny(unused_extern_crates)]
 
 extern crate alloc;
 
+// The declared types of the entrypoints refer to the types of the contract by
+// the name of its crate
+#[cfg(target_family = "wasm")]
+extern crate self as transfer_contract;
+
+/// The ABI schema of the contract in JSON, generated at build time from the
+/// exported functions.
+pub const ABI_SCHEMA: &str =
+    include_str!(concat!(env!("OUT_DIR"), "/abi.json"));
+
+pub mod accounts;
+pub mod escrow;
+pub mod freeze;
+pub mod gas;
+pub mod migration;
+pub mod nullifiers;
+pub mod receipt;
+pub mod scheduled;
+pub mod validation;
+pub mod verifier;
+
+#[cfg(target_family = "wasm")]
+mod account_tree;
+#[cfg(target_family = "wasm")]
 mod error;
+#[cfg(target_family = "wasm")]
+mod nullifier_tree;
+#[cfg(target_family = "wasm")]
 mod state;
+#[cfg(target_family = "wasm")]
 mod transitory;
+#[cfg(target_family = "wasm")]
 mod tree;
+#[cfg(target_family = "wasm")]
 mod verifier_data;
+#[cfg(target_family = "wasm")]
+mod wrap;
+
+/// Convert a position or count given by a caller to a `usize`, saturating on
+/// 32-bit targets instead of truncating, so that the contract behaves the
+/// same on wasm32 and wasm64.
+#[cfg(target_family = "wasm")]
+fn saturating_usize(n: u64) -> usize {
+    usize::try_from(n).unwrap_or(usize::MAX)
+}
 
-use dusk_core::abi;
-use dusk_core::stake::STAKE_CONTRACT;
-use state::TransferState;
+#[cfg(target_family = "wasm")]
+mod wasm {
+    use dusk_core::abi;
+    use dusk_core::stake::STAKE_CONTRACT;
 
-static mut STATE: TransferState = TransferState::new();
+    use crate::state::TransferState;
+    use crate::wrap;
 
-// Transactions
+    static mut STATE: TransferState = TransferState::new();
 
-#[no_mangle]
-unsafe fn mint(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.mint(arg))
-}
+    // Transactions
 
-#[no_mangle]
-unsafe fn mint_to_contract(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.mint_to_contract(arg))
-}
+    #[no_mangle]
+    unsafe fn mint(arg_len: u32) -> u32 {
+        wrap::mint(arg_len, |arg| STATE.mint(arg))
+    }
 
-#[no_mangle]
-unsafe fn deposit(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.deposit(arg))
-}
+    #[no_mangle]
+    unsafe fn mint_to_contract(arg_len: u32) -> u32 {
+        wrap::mint_to_contract(arg_len, |arg| STATE.mint_to_contract(arg))
+    }
 
-#[no_mangle]
-unsafe fn withdraw(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.withdraw(arg))
-}
+    #[no_mangle]
+    unsafe fn deposit(arg_len: u32) -> u32 {
+        wrap::deposit(arg_len, |arg| STATE.deposit(arg))
+    }
 
-#[no_mangle]
-unsafe fn convert(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.convert(arg))
-}
+    #[no_mangle]
+    unsafe fn withdraw(arg_len: u32) -> u32 {
+        wrap::withdraw(arg_len, |arg| STATE.withdraw(arg))
+    }
 
-#[no_mangle]
-unsafe fn contract_to_contract(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.contract_to_contract(arg))
-}
+    #[no_mangle]
+    unsafe fn withdraw_scheduled(arg_len: u32) -> u32 {
+        wrap::withdraw_scheduled(arg_len, |arg| STATE.withdraw_scheduled(arg))
+    }
 
-#[no_mangle]
-unsafe fn contract_to_account(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |arg| STATE.contract_to_account(arg))
-}
+    #[no_mangle]
+    unsafe fn convert(arg_len: u32) -> u32 {
+        wrap::convert(arg_len, |arg| STATE.convert(arg))
+    }
 
-// Queries
+    #[no_mangle]
+    unsafe fn contract_to_contract(arg_len: u32) -> u32 {
+        wrap::contract_to_contract(arg_len, |arg| {
+            STATE.contract_to_contract(arg)
+        })
+    }
 
-#[no_mangle]
-unsafe fn root(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.root())
-}
+    #[no_mangle]
+    unsafe fn contract_to_account(arg_len: u32) -> u32 {
+        wrap::contract_to_account(arg_len, |arg| STATE.contract_to_account(arg))
+    }
 
-#[no_mangle]
-unsafe fn account(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |key| STATE.account(&key))
-}
+    #[no_mangle]
+    unsafe fn create_escrow(arg_len: u32) -> u32 {
+        wrap::create_escrow(arg_len, |create| STATE.create_escrow(create))
+    }
 
-#[no_mangle]
-unsafe fn contract_balance(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |contract| STATE.contract_balance(&contract))
-}
+    #[no_mangle]
+    unsafe fn release_escrow(arg_len: u32) -> u32 {
+        wrap::release_escrow(arg_len, |id| STATE.release_escrow(id))
+    }
 
-#[no_mangle]
-unsafe fn opening(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |pos| STATE.opening(pos))
-}
+    #[no_mangle]
+    unsafe fn cancel_escrow(arg_len: u32) -> u32 {
+        wrap::cancel_escrow(arg_len, |id| STATE.cancel_escrow(id))
+    }
 
-#[no_mangle]
-unsafe fn existing_nullifiers(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |nullifiers| STATE.existing_nullifiers(nullifiers))
-}
+    // Queries
 
-#[no_mangle]
-unsafe fn num_notes(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.num_notes())
-}
+    #[no_mangle]
+    unsafe fn root(arg_len: u32) -> u32 {
+        wrap::root(arg_len, |_: ()| STATE.root())
+    }
 
-#[no_mangle]
-unsafe fn chain_id(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| STATE.chain_id())
-}
+    #[no_mangle]
+    unsafe fn account(arg_len: u32) -> u32 {
+        wrap::account(arg_len, |key| STATE.account(&key))
+    }
 
-// "Feeder" queries
+    #[no_mangle]
+    unsafe fn accounts_root(arg_len: u32) -> u32 {
+        wrap::accounts_root(arg_len, |_: ()| STATE.accounts_root())
+    }
 
-#[no_mangle]
-unsafe fn leaves_from_height(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |height| STATE.leaves_from_height(height))
-}
+    #[no_mangle]
+    unsafe fn account_proof(arg_len: u32) -> u32 {
+        wrap::account_proof(arg_len, |key| STATE.account_proof(&key))
+    }
 
-#[no_mangle]
-unsafe fn leaves_from_pos(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |pos| STATE.leaves_from_pos(pos))
-}
+    #[no_mangle]
+    unsafe fn contract_balance(arg_len: u32) -> u32 {
+        wrap::contract_balance(arg_len, |contract| {
+            STATE.contract_balance(&contract)
+        })
+    }
 
-#[no_mangle]
-unsafe fn sync(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(from, count_limint)| {
-        STATE.sync(from, count_limint)
-    })
-}
+    #[no_mangle]
+    unsafe fn opening(arg_len: u32) -> u32 {
+        wrap::opening(arg_len, |pos| STATE.opening(pos))
+    }
 
-#[no_mangle]
-unsafe fn sync_nullifiers(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(from, count_limint)| {
-        STATE.sync_nullifiers(from, count_limint)
-    })
-}
+    #[no_mangle]
+    unsafe fn existing_nullifiers(arg_len: u32) -> u32 {
+        wrap::existing_nullifiers(arg_len, |nullifiers| {
+            STATE.existing_nullifiers(nullifiers)
+        })
+    }
 
-#[no_mangle]
-unsafe fn sync_contract_balances(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(from, count_limint)| {
-        STATE.sync_contract_balances(from, count_limint)
-    })
-}
+    #[no_mangle]
+    unsafe fn nullifiers_root(arg_len: u32) -> u32 {
+        wrap::nullifiers_root(arg_len, |_: ()| STATE.nullifiers_root())
+    }
 
-#[no_mangle]
-unsafe fn sync_accounts(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(from, count_limint)| {
-        STATE.sync_accounts(from, count_limint)
-    })
-}
+    #[no_mangle]
+    unsafe fn nullifier_root(arg_len: u32) -> u32 {
+        wrap::nullifier_root(arg_len, |height| STATE.nullifier_root(height))
+    }
 
-// "Management" transactions
+    #[no_mangle]
+    unsafe fn nullifier_proof(arg_len: u32) -> u32 {
+        wrap::nullifier_proof(arg_len, |nullifier| {
+            STATE.nullifier_proof(&nullifier)
+        })
+    }
 
-#[no_mangle]
-unsafe fn spend_and_execute(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |tx| {
-        assert_external_caller();
-        STATE.spend_and_execute(tx)
-    })
-}
+    #[no_mangle]
+    unsafe fn block_event_digest(arg_len: u32) -> u32 {
+        wrap::block_event_digest(arg_len, |height| {
+            STATE.block_event_digest(height)
+        })
+    }
 
-#[no_mangle]
-unsafe fn refund(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |gas_spent| {
-        assert_external_caller();
-        STATE.refund(gas_spent)
-    })
-}
+    #[no_mangle]
+    unsafe fn escrow(arg_len: u32) -> u32 {
+        wrap::escrow(arg_len, |id| STATE.escrow(id))
+    }
 
-#[no_mangle]
-unsafe fn push_note(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(block_height, note)| {
-        assert_external_caller();
-        STATE.push_note(block_height, note)
-    })
-}
+    #[no_mangle]
+    unsafe fn validate_transaction(arg_len: u32) -> u32 {
+        wrap::validate_transaction(arg_len, |tx| STATE.validate_transaction(tx))
+    }
 
-#[no_mangle]
-unsafe fn update_root(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |_: ()| {
-        assert_external_caller();
-        STATE.update_root()
-    })
-}
+    #[no_mangle]
+    unsafe fn verifiers(arg_len: u32) -> u32 {
+        wrap::verifiers(arg_len, |(inputs, outputs)| {
+            STATE.verifiers(inputs, outputs)
+        })
+    }
 
-#[no_mangle]
-unsafe fn add_account_balance(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(key, value)| {
-        assert_external_caller();
-        STATE.add_account_balance(&key, value)
-    })
-}
+    #[no_mangle]
+    unsafe fn state_version(arg_len: u32) -> u32 {
+        wrap::state_version(arg_len, |_: ()| STATE.state_version())
+    }
 
-#[no_mangle]
-unsafe fn sub_account_balance(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(key, value)| {
-        assert_external_caller();
-        STATE.sub_account_balance(&key, value)
-    })
-}
+    #[no_mangle]
+    unsafe fn export_summary(arg_len: u32) -> u32 {
+        wrap::export_summary(arg_len, |(summary, from, count_limit)| {
+            STATE.export_summary(summary, from, count_limit)
+        })
+    }
 
-#[no_mangle]
-unsafe fn add_contract_balance(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(module, value)| {
-        assert_external_caller();
-        STATE.add_contract_balance(module, value)
-    })
-}
+    #[no_mangle]
+    unsafe fn gas_price_info(arg_len: u32) -> u32 {
+        wrap::gas_price_info(arg_len, |_: ()| STATE.gas_price_info())
+    }
 
-#[no_mangle]
-unsafe fn sub_contract_balance(arg_len: u32) -> u32 {
-    abi::wrap_call(arg_len, |(module, value)| {
-        assert_stake_caller();
-        STATE
-            .sub_contract_balance(&module, value)
-            .expect("Cannot subtract balance")
-    })
-}
+    #[no_mangle]
+    unsafe fn num_notes(arg_len: u32) -> u32 {
+        wrap::num_notes(arg_len, |_: ()| STATE.num_notes())
+    }
 
-fn assert_stake_caller() {
-    const PANIC_MSG: &str = "Can only be called by the stake contract";
-    if abi::caller().expect(PANIC_MSG) != STAKE_CONTRACT {
-        panic!("{PANIC_MSG}");
+    #[no_mangle]
+    unsafe fn chain_id(arg_len: u32) -> u32 {
+        wrap::chain_id(arg_len, |_: ()| STATE.chain_id())
+    }
+
+    // "Feeder" queries
+
+    #[no_mangle]
+    unsafe fn leaves_from_height(arg_len: u32) -> u32 {
+        wrap::leaves_from_height(arg_len, |height| {
+            STATE.leaves_from_height(height)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn leaves_from_pos(arg_len: u32) -> u32 {
+        wrap::leaves_from_pos(arg_len, |pos| STATE.leaves_from_pos(pos))
+    }
+
+    #[no_mangle]
+    unsafe fn sync(arg_len: u32) -> u32 {
+        wrap::sync(arg_len, |(from, count_limint)| {
+            STATE.sync(from, count_limint)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sync_nullifiers(arg_len: u32) -> u32 {
+        wrap::sync_nullifiers(arg_len, |(from, count_limint)| {
+            STATE.sync_nullifiers(from, count_limint)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sync_contract_balances(arg_len: u32) -> u32 {
+        wrap::sync_contract_balances(arg_len, |(from, count_limint)| {
+            STATE.sync_contract_balances(from, count_limint)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sync_accounts(arg_len: u32) -> u32 {
+        wrap::sync_accounts(arg_len, |(from, count_limint)| {
+            STATE.sync_accounts(from, count_limint)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sync_escrows(arg_len: u32) -> u32 {
+        wrap::sync_escrows(arg_len, |(from, count_limint)| {
+            STATE.sync_escrows(from, count_limint)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn export_state(arg_len: u32) -> u32 {
+        wrap::export_state(arg_len, |(from, count_limit)| {
+            STATE.export_state(from, count_limit)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn frozen_accounts(arg_len: u32) -> u32 {
+        wrap::frozen_accounts(arg_len, |_: ()| STATE.frozen_accounts())
+    }
+
+    // "Management" transactions
+
+    #[no_mangle]
+    unsafe fn spend_and_execute(arg_len: u32) -> u32 {
+        wrap::spend_and_execute(arg_len, |tx| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.spend_and_execute(tx)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn refund(arg_len: u32) -> u32 {
+        wrap::refund(arg_len, |gas_spent| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.refund(gas_spent)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn push_note(arg_len: u32) -> u32 {
+        wrap::push_note(arg_len, |(block_height, note)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.push_note(block_height, note)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn update_root(arg_len: u32) -> u32 {
+        wrap::update_root(arg_len, |_: ()| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.update_root()
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn update_base_fee(arg_len: u32) -> u32 {
+        wrap::update_base_fee(arg_len, |gas_used| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.update_base_fee(gas_used)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn set_gas_config(arg_len: u32) -> u32 {
+        wrap::set_gas_config(arg_len, |config| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.set_gas_config(config)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn add_account_balance(arg_len: u32) -> u32 {
+        wrap::add_account_balance(arg_len, |(key, value)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.add_account_balance(&key, value)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sub_account_balance(arg_len: u32) -> u32 {
+        wrap::sub_account_balance(arg_len, |(key, value)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.sub_account_balance(&key, value)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn insert_account(arg_len: u32) -> u32 {
+        wrap::insert_account(arg_len, |(key, account)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.insert_account(&key, account)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn import_state(arg_len: u32) -> u32 {
+        wrap::import_state(arg_len, |items| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.import_state(items)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn finish_import(arg_len: u32) -> u32 {
+        wrap::finish_import(arg_len, |summary| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.finish_import(summary)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn set_verifier(arg_len: u32) -> u32 {
+        wrap::set_verifier(arg_len, |set| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.set_verifier(set)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn freeze_account(arg_len: u32) -> u32 {
+        wrap::freeze_account(arg_len, |key| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.freeze_account(&key)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn unfreeze_account(arg_len: u32) -> u32 {
+        wrap::unfreeze_account(arg_len, |key| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.unfreeze_account(&key)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn add_contract_balance(arg_len: u32) -> u32 {
+        wrap::add_contract_balance(arg_len, |(module, value)| {
+            assert_external_caller();
+            assert_no_pending_migration();
+            STATE.add_contract_balance(module, value)
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn sub_contract_balance(arg_len: u32) -> u32 {
+        wrap::sub_contract_balance(arg_len, |(module, value)| {
+            assert_stake_caller();
+            STATE
+                .sub_contract_balance(&module, value)
+                .expect("Cannot subtract balance")
+        })
+    }
+
+    #[no_mangle]
+    unsafe fn migrate(arg_len: u32) -> u32 {
+        wrap::migrate(arg_len, |(from, to)| {
+            assert_external_caller();
+            STATE.migrate(from, to)
+        })
+    }
+
+    fn assert_stake_caller() {
+        const PANIC_MSG: &str = "Can only be called by the stake contract";
+        if abi::caller().expect(PANIC_MSG) != STAKE_CONTRACT {
+            panic!("{PANIC_MSG}");
+        }
+    }
+
+    /// Asserts the state isn't pending a migration to the layout of the
+    /// running code.
+    ///
+    /// # Panics
+    /// When the version of the state is not the current one.
+    fn assert_no_pending_migration() {
+        if unsafe { STATE.migration_pending() } {
+            panic!("The state is pending a migration");
+        }
     }
-}
 
-/// Asserts the call is made "from the outside", meaning that it's not an
-/// inter-contract call.
-///
-/// # Panics
-/// When the `caller` is not "uninitialized".
-fn assert_external_caller() {
-    if abi::caller().is_some() {
-        panic!("Can only be called from the outside the VM");
+    /// Asserts the call is made "from the outside", meaning that it's not an
+    /// inter-contract call.
+    ///
+    /// # Panics
+    /// When the `caller` is not "uninitialized".
+    fn assert_external_caller() {
+        if abi::caller().is_some() {
+            panic!("Can only be called from the outside the VM");
+        }
     }
 }
@@ -0,0 +1,175 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the export and import of the state of the transfer
+//! contract, used to migrate it to a new version of the contract.
+//!
+//! The state is exported as a sequence of [`StateItem`]s with
+//! `export_state`, in chunks, and imported in the same order and in chunks of
+//! any size with `import_state` into the new contract. Each chunk starts at an
+//! [`ExportCursor`], and `export_summary` chains the items of the same chunk
+//! onto the [`ExportSummary`] of the previous ones, returning the cursor of
+//! the next chunk in an [`ExportChunk`]. A call to `finish_import` with the
+//! summary of the last chunk then checks the imported items are the same as
+//! the exported ones, against the summary chained by the new contract as it
+//! imported them, and finishes the import.
+//!
+//! The layout of the state is also versioned, and a state at a previous
+//! version can be migrated in place with `migrate`.
+
+use bytecheck::CheckBytes;
+use dusk_bytes::Serializable;
+use dusk_core::abi::ContractId;
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::transfer::phoenix::NoteLeaf;
+use dusk_core::BlsScalar;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::escrow::Escrow;
+use crate::gas::GasPriceInfo;
+use crate::verifier::Verifier;
+
+/// Version of the export format, bumped every time [`StateItem`] changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Topic for the event emitted when an import is finished.
+pub const IMPORT_TOPIC: &str = "import";
+
+/// Version of the layout of the state, bumped every time the state changes in
+/// a way that requires a migration step.
+pub const STATE_VERSION: u64 = 1;
+
+/// Topic for the event emitted when the state is migrated, with the versions
+/// it is migrated from and to.
+pub const MIGRATE_TOPIC: &str = "migrate";
+
+/// A single item of the exported state.
+///
+/// Items are exported in the order they must be imported in, which is the
+/// order of the variants below.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[allow(clippy::large_enum_variant)]
+pub enum StateItem {
+    /// The gas pricing in effect.
+    GasPricing(GasPriceInfo),
+    /// The identifier the next escrow will be created with.
+    NextEscrowId(u64),
+    /// A leaf of the tree of notes, in order of position.
+    Note(NoteLeaf),
+    /// A recent root of the tree of notes, from oldest to newest.
+    Root(BlsScalar),
+    /// A spent nullifier, in the order they were spent.
+    Nullifier(BlsScalar),
+    /// A recent root of the nullifiers tree with the height of its block, from
+    /// oldest to newest.
+    NullifierRoot(u64, BlsScalar),
+    /// A Moonlight account with the compressed bytes of its public key, in the
+    /// order of their position in the tree of accounts.
+    Account([u8; AccountPublicKey::SIZE], AccountData),
+    /// The balance of a contract.
+    ContractBalance(ContractId, u64),
+    /// The hash of the replay key of a settled scheduled withdrawal, with its
+    /// expiry height.
+    ScheduledWithdrawal(u64, BlsScalar),
+    /// An escrow entry with its identifier.
+    Escrow(u64, Escrow),
+    /// A frozen Moonlight account.
+    FrozenAccount(AccountPublicKey),
+    /// Verifier data scheduled for the phoenix-circuit with the given number
+    /// of inputs and outputs.
+    Verifier(u32, u32, Verifier),
+}
+
+/// Position of an item of the exported state, from which a chunk of the
+/// export starts.
+///
+/// There is a variant for each variant of [`StateItem`], in the same order,
+/// holding the position or the key of the item in its part of the state, so
+/// that a chunk resumes from it without walking the items before it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Archive,
+    Serialize,
+    Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub enum ExportCursor {
+    /// The gas pricing, i.e. the start of the export.
+    GasPricing,
+    /// The identifier of the next escrow.
+    NextEscrowId,
+    /// The leaf of the tree of notes at the given position.
+    Note(u64),
+    /// The recent root of the tree of notes at the given index, from oldest
+    /// to newest.
+    Root(u64),
+    /// The nullifier spent at the given index, in spending order.
+    Nullifier(u64),
+    /// The recent root of the nullifiers tree at the given index, from
+    /// oldest to newest.
+    NullifierRoot(u64),
+    /// The Moonlight account at the given position of the tree of accounts.
+    Account(u64),
+    /// The balance of the given contract.
+    ContractBalance(ContractId),
+    /// The settled scheduled withdrawal with the given expiry height and
+    /// replay key hash.
+    ScheduledWithdrawal(u64, BlsScalar),
+    /// The escrow with the given identifier.
+    Escrow(u64),
+    /// The frozen Moonlight account with the given compressed public key.
+    FrozenAccount([u8; AccountPublicKey::SIZE]),
+    /// The verifier data at the given index of the ones scheduled for the
+    /// phoenix-circuit with the given number of inputs and outputs.
+    Verifier(u32, u32, u64),
+}
+
+impl ExportCursor {
+    /// The cursor of the first item of the export.
+    pub const START: Self = Self::GasPricing;
+}
+
+/// Summary of the exported state, returned by `export_summary` for the items
+/// up to the end of a chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ExportSummary {
+    /// The version of the export format.
+    pub version: u32,
+    /// The number of items in the export.
+    pub items: u64,
+    /// Hash chained over the serialized items, in order.
+    pub checksum: BlsScalar,
+}
+
+impl ExportSummary {
+    /// The summary of an export without any item, onto which the items of
+    /// the first chunk are chained.
+    pub const EMPTY: Self = Self {
+        version: EXPORT_VERSION,
+        items: 0,
+        checksum: BlsScalar::zero(),
+    };
+}
+
+/// A chunk of the exported state, returned by `export_summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ExportChunk {
+    /// The summary of the export up to the end of the chunk.
+    pub summary: ExportSummary,
+    /// The cursor the next chunk starts from, or `None` if this chunk is the
+    /// last one.
+    pub next: Option<ExportCursor>,
+}
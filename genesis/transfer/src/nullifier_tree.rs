@@ -0,0 +1,159 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use dusk_core::{abi, BlsScalar};
+
+use crate::nullifiers::{
+    NullifierLeaf, NullifierOpening, NullifierProof, NullifierTreeItem,
+    NullifiersTree,
+};
+use crate::saturating_usize;
+
+/// The sentinel leaf, inserted at position 0 when the first nullifier is.
+const SENTINEL_LEAF: NullifierLeaf = NullifierLeaf {
+    value: BlsScalar::zero(),
+    next: BlsScalar::zero(),
+};
+
+/// The set of spent nullifiers, backed by an indexed merkle tree.
+///
+/// The tree is append only. A new nullifier is pushed as a new leaf, and the
+/// leaf preceding it in the sorted set is updated to point to it.
+pub struct NullifierTree {
+    tree: NullifiersTree,
+    // The leaves of the tree, with the index in this vector corresponding to
+    // the position of the leaf in the merkle-tree.
+    leaves: Vec<NullifierLeaf>,
+    // The position of the leaf of each nullifier, sorted by nullifier. The
+    // sentinel is not included.
+    positions: BTreeMap<BlsScalar, u64>,
+}
+
+impl NullifierTree {
+    /// Create a new empty set.
+    pub const fn new() -> Self {
+        Self {
+            tree: NullifiersTree::new(),
+            leaves: Vec::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /// Insert a nullifier in the set, returning whether it wasn't already
+    /// present.
+    ///
+    /// # Panics
+    /// When the nullifier is zero, since that is reserved for the sentinel.
+    pub fn insert(&mut self, nullifier: BlsScalar) -> bool {
+        if nullifier == BlsScalar::zero() {
+            panic!("The zero nullifier is reserved");
+        }
+        if self.positions.contains_key(&nullifier) {
+            return false;
+        }
+
+        if self.leaves.is_empty() {
+            self.push_leaf(SENTINEL_LEAF);
+        }
+
+        let low_pos = self.low_position(&nullifier);
+        let low_leaf = &mut self.leaves[low_pos as usize];
+
+        let leaf = NullifierLeaf {
+            value: nullifier,
+            next: low_leaf.next,
+        };
+        low_leaf.next = nullifier;
+
+        let low_leaf = *low_leaf;
+        self.set_leaf(low_pos, low_leaf);
+
+        let pos = self.push_leaf(leaf);
+        self.positions.insert(nullifier, pos);
+
+        true
+    }
+
+    /// Returns true if the nullifier is in the set.
+    pub fn contains(&self, nullifier: &BlsScalar) -> bool {
+        self.positions.contains_key(nullifier)
+    }
+
+    /// Return an iterator through the nullifiers in the set, in ascending
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = &BlsScalar> {
+        self.positions.keys()
+    }
+
+    /// Return an iterator through the nullifiers in the set, in the order they
+    /// were inserted in, starting from the nullifier inserted at the given
+    /// index.
+    pub fn iter_inserted(&self, from: u64) -> impl Iterator<Item = &BlsScalar> {
+        // skip the sentinel leaf
+        let from = saturating_usize(from)
+            .saturating_add(1)
+            .min(self.leaves.len());
+        self.leaves[from..].iter().map(|leaf| &leaf.value)
+    }
+
+    /// Return the root of the merkle tree of nullifiers.
+    pub fn root(&self) -> BlsScalar {
+        self.tree.root().hash
+    }
+
+    /// Return the proof of membership or non-membership of the given
+    /// nullifier in the set.
+    pub fn proof(&self, nullifier: &BlsScalar) -> NullifierProof {
+        if self.leaves.is_empty() {
+            return NullifierProof::Empty;
+        }
+
+        match self.positions.get(nullifier) {
+            Some(pos) => NullifierProof::Spent {
+                leaf: self.leaves[*pos as usize],
+                opening: self.opening(*pos),
+            },
+            None => {
+                let low_pos = self.low_position(nullifier);
+                NullifierProof::Unspent {
+                    low_leaf: self.leaves[low_pos as usize],
+                    opening: self.opening(low_pos),
+                }
+            }
+        }
+    }
+
+    /// The position of the leaf of the greatest nullifier smaller than the
+    /// given one, or of the sentinel if there is none.
+    fn low_position(&self, nullifier: &BlsScalar) -> u64 {
+        self.positions
+            .range(..nullifier)
+            .next_back()
+            .map(|(_, pos)| *pos)
+            .unwrap_or(0)
+    }
+
+    fn opening(&self, pos: u64) -> NullifierOpening {
+        self.tree
+            .opening(pos)
+            .expect("There should be a leaf at the given position")
+    }
+
+    fn push_leaf(&mut self, leaf: NullifierLeaf) -> u64 {
+        let pos = self.leaves.len() as u64;
+        self.leaves.push(leaf);
+        self.set_leaf(pos, leaf);
+        pos
+    }
+
+    fn set_leaf(&mut self, pos: u64, leaf: NullifierLeaf) {
+        let hash = abi::poseidon_hash(leaf.hash_inputs().to_vec());
+        self.tree.insert(pos, NullifierTreeItem { hash, data: () });
+    }
+}
@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the merkle commitment over the set of spent nullifiers.
+//!
+//! The nullifiers are stored in an indexed merkle tree: leaves are appended in
+//! the order the nullifiers are spent, and each leaf also commits to the next
+//! greater nullifier in the set, forming a sorted linked list. A nullifier is
+//! proven to be unspent by opening the leaf of the greatest nullifier smaller
+//! than it, and showing the next nullifier in the list is greater than it.
+//!
+//! The first leaf of a non-empty tree is a sentinel with value zero, making
+//! zero a reserved value that is never spent.
+
+use bytecheck::CheckBytes;
+use dusk_core::BlsScalar;
+use dusk_poseidon::{Domain, Hash};
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Depth of the merkle tree of nullifiers.
+pub const NULLIFIERS_TREE_DEPTH: usize = 17;
+
+/// The merkle tree committing to the spent nullifiers.
+pub type NullifiersTree = poseidon_merkle::Tree<(), NULLIFIERS_TREE_DEPTH>;
+
+/// The merkle opening for a leaf in the [`NullifiersTree`].
+pub type NullifierOpening = poseidon_merkle::Opening<(), NULLIFIERS_TREE_DEPTH>;
+
+/// The item stored in the leaves of the [`NullifiersTree`].
+pub type NullifierTreeItem = poseidon_merkle::Item<()>;
+
+/// A leaf of the [`NullifiersTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct NullifierLeaf {
+    /// The spent nullifier, or zero for the sentinel leaf.
+    pub value: BlsScalar,
+    /// The next greater nullifier in the set, or zero if there is none.
+    pub next: BlsScalar,
+}
+
+impl NullifierLeaf {
+    /// The scalars whose poseidon hash is the item of the leaf in the tree.
+    #[must_use]
+    pub fn hash_inputs(&self) -> [BlsScalar; 2] {
+        [self.value, self.next]
+    }
+
+    /// Returns true if the nullifier lies strictly between the value of the
+    /// leaf and the next nullifier in the set.
+    #[must_use]
+    pub fn precedes(&self, nullifier: &BlsScalar) -> bool {
+        self.value < *nullifier
+            && (self.next == BlsScalar::zero() || *nullifier < self.next)
+    }
+}
+
+/// Proof of the membership or non-membership of a nullifier in the set of
+/// spent nullifiers.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub enum NullifierProof {
+    /// No nullifier has been spent yet, and the tree is empty.
+    Empty,
+    /// The nullifier has been spent, and this is the opening of its leaf.
+    Spent {
+        /// The leaf of the nullifier.
+        leaf: NullifierLeaf,
+        /// The opening of the leaf.
+        opening: NullifierOpening,
+    },
+    /// The nullifier hasn't been spent, and this is the opening of the leaf
+    /// preceding it in the sorted set.
+    Unspent {
+        /// The leaf preceding the nullifier.
+        low_leaf: NullifierLeaf,
+        /// The opening of the preceding leaf.
+        opening: NullifierOpening,
+    },
+}
+
+impl NullifierProof {
+    /// Verify the proof for the given nullifier against the given nullifiers
+    /// root, returning whether the nullifier has been spent.
+    ///
+    /// Returns `None` if the proof is invalid.
+    #[must_use]
+    pub fn verify(
+        &self,
+        nullifier: &BlsScalar,
+        root: &BlsScalar,
+    ) -> Option<bool> {
+        let (leaf, opening, spent) = match self {
+            Self::Empty => {
+                return (NullifiersTree::new().root().hash == *root)
+                    .then_some(false);
+            }
+            Self::Spent { leaf, opening } => {
+                // the sentinel leaf doesn't prove zero has been spent
+                if leaf.value != *nullifier || *nullifier == BlsScalar::zero() {
+                    return None;
+                }
+                (leaf, opening, true)
+            }
+            Self::Unspent { low_leaf, opening } => {
+                if !low_leaf.precedes(nullifier) {
+                    return None;
+                }
+                (low_leaf, opening, false)
+            }
+        };
+
+        if opening.root().hash != *root {
+            return None;
+        }
+
+        let hash = Hash::digest(Domain::Other, &leaf.hash_inputs())[0];
+        opening
+            .verify(NullifierTreeItem { hash, data: () })
+            .then_some(spent)
+    }
+}
@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the receipt of a transaction, summarizing the effects of
+//! its execution on the state of the transfer contract.
+
+use alloc::vec::Vec;
+
+use bytecheck::CheckBytes;
+use dusk_core::abi::{ContractError, ContractId};
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::phoenix::Note;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Topic for the event emitted with the receipt of a transaction.
+pub const RECEIPT_TOPIC: &str = "receipt";
+
+/// What happened to the deposit of a transaction.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub enum DepositStatus {
+    /// The transaction carried no deposit.
+    None,
+    /// The deposit was taken by the contract it was destined to.
+    Taken {
+        /// The contract that took the deposit.
+        contract: ContractId,
+        /// The value of the deposit.
+        value: u64,
+    },
+    /// The deposit wasn't taken, and was refunded together with the unspent
+    /// gas.
+    Refunded {
+        /// The contract the deposit was destined to.
+        contract: ContractId,
+        /// The value of the deposit.
+        value: u64,
+    },
+}
+
+/// The receipt of a transaction, returned by `refund` and emitted under the
+/// [`RECEIPT_TOPIC`].
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct TransactionReceipt {
+    /// What happened to the deposit.
+    pub deposit: DepositStatus,
+    /// The notes appended to the tree by the transaction, in order. This
+    /// includes the outputs of a Phoenix transaction, the notes created by
+    /// withdrawals, and the refund note.
+    pub notes: Vec<Note>,
+    /// The change in balance of every Moonlight account whose balance was
+    /// changed by the transaction, in the order they were first changed.
+    pub account_deltas: Vec<(AccountPublicKey, i128)>,
+    /// The change in balance of every contract whose balance was changed by
+    /// the transaction, in the order they were first changed.
+    pub contract_deltas: Vec<(ContractId, i128)>,
+    /// The gas spent by the transaction.
+    pub gas_spent: u64,
+    /// The value refunded for the unspent gas, i.e. the unspent gas times the
+    /// gas price.
+    pub gas_refund: u64,
+    /// The result of the contract call, if the transaction made one.
+    pub call_result: Option<Result<Vec<u8>, ContractError>>,
+}
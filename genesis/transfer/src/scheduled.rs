@@ -0,0 +1,193 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to withdrawals that are authorized ahead of time, and are not
+//! bound to the transaction that ends up executing them.
+
+use alloc::vec::Vec;
+
+use bytecheck::CheckBytes;
+use dusk_bytes::Serializable;
+use dusk_core::abi::ContractId;
+use dusk_core::transfer::withdraw::{
+    WithdrawReceiver, WithdrawSecretKey, WithdrawSignature,
+};
+use dusk_core::BlsScalar;
+use rand::{CryptoRng, RngCore};
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Prefix of the signature message of a [`ScheduledWithdraw`], separating it
+/// from the message signed for a regular `Withdraw`.
+const SIGNATURE_DOMAIN: &[u8] = b"scheduled-withdraw";
+
+/// Withdrawal information, proving the intent of a user to withdraw from a
+/// contract up to a given block height.
+///
+/// Contrary to a `Withdraw`, whose replay protection is tied to the
+/// transaction it is included in, a scheduled withdrawal is protected by a
+/// one-time `id` chosen by the signer and an `expiry` height, and is bound to
+/// a chain by its `chain_id`. This allows a contract to settle the withdrawal
+/// in any later transaction, e.g. when paying out on a schedule or when
+/// triggered by a keeper.
+///
+/// The transfer contract records every pair of `id` and `expiry` it settles
+/// for a given contract and receiver, and refuses to settle it again. The
+/// record is pruned once the expiry has passed, since the withdrawal can't be
+/// settled anymore. Signing the same `id` with another `expiry` authorizes
+/// another withdrawal.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ScheduledWithdraw {
+    chain_id: u8,
+    contract: ContractId,
+    value: u64,
+    receiver: WithdrawReceiver,
+    expiry: u64,
+    id: u64,
+    signature: WithdrawSignature,
+}
+
+impl ScheduledWithdraw {
+    /// Create a new scheduled withdrawal on the chain with the given ID, valid
+    /// up to and including the block at height `expiry`.
+    ///
+    /// # Panics
+    /// When the receiver does not match the secret key passed.
+    #[must_use]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<'a, R: RngCore + CryptoRng>(
+        rng: &mut R,
+        sk: impl Into<WithdrawSecretKey<'a>>,
+        chain_id: u8,
+        contract: ContractId,
+        value: u64,
+        receiver: WithdrawReceiver,
+        expiry: u64,
+        id: u64,
+    ) -> Self {
+        let mut withdraw = Self {
+            chain_id,
+            contract,
+            value,
+            receiver,
+            expiry,
+            id,
+            signature: WithdrawSignature::Moonlight(Default::default()),
+        };
+
+        let msg = withdraw.signature_message();
+
+        match (sk.into(), &receiver) {
+            (WithdrawSecretKey::Phoenix(sk), WithdrawReceiver::Phoenix(_)) => {
+                let digest = BlsScalar::hash_to_scalar(&msg);
+                withdraw.signature = sk.sign(rng, digest).into();
+            }
+            (
+                WithdrawSecretKey::Moonlight(sk),
+                WithdrawReceiver::Moonlight(_),
+            ) => {
+                withdraw.signature = sk.sign(&msg).into();
+            }
+            (WithdrawSecretKey::Phoenix(_), _) => {
+                panic!("Moonlight receiver with phoenix signer");
+            }
+            (WithdrawSecretKey::Moonlight(_), _) => {
+                panic!("Phoenix receiver with moonlight signer");
+            }
+        }
+
+        withdraw
+    }
+
+    /// The ID of the chain the withdrawal can be settled on.
+    #[must_use]
+    pub fn chain_id(&self) -> u8 {
+        self.chain_id
+    }
+
+    /// The contract to withdraw from.
+    #[must_use]
+    pub fn contract(&self) -> &ContractId {
+        &self.contract
+    }
+
+    /// The amount to withdraw.
+    #[must_use]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The receiver of the value.
+    #[must_use]
+    pub fn receiver(&self) -> &WithdrawReceiver {
+        &self.receiver
+    }
+
+    /// The last block height at which the withdrawal can be settled.
+    #[must_use]
+    pub fn expiry(&self) -> u64 {
+        self.expiry
+    }
+
+    /// The one-time identifier of the withdrawal.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Signature of the withdrawal.
+    #[must_use]
+    pub fn signature(&self) -> &WithdrawSignature {
+        &self.signature
+    }
+
+    /// Return the message that is used as the input to the signature.
+    #[must_use]
+    pub fn signature_message(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(SIGNATURE_DOMAIN);
+
+        bytes.push(self.chain_id);
+        bytes.extend(self.contract.as_bytes());
+        bytes.extend(self.value.to_bytes());
+
+        match self.receiver {
+            WithdrawReceiver::Phoenix(address) => {
+                bytes.extend(address.to_bytes());
+            }
+            WithdrawReceiver::Moonlight(account) => {
+                bytes.extend(account.to_bytes());
+            }
+        }
+
+        bytes.extend(self.expiry.to_bytes());
+        bytes.extend(self.id.to_bytes());
+
+        bytes
+    }
+
+    /// Return the bytes identifying the withdrawal for replay protection,
+    /// namely the contract, the receiver, the `expiry` and the `id`.
+    #[must_use]
+    pub fn replay_key(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend(self.contract.as_bytes());
+
+        match self.receiver {
+            WithdrawReceiver::Phoenix(address) => {
+                bytes.extend(address.to_bytes());
+            }
+            WithdrawReceiver::Moonlight(account) => {
+                bytes.extend(account.to_bytes());
+            }
+        }
+
+        bytes.extend(self.expiry.to_bytes());
+        bytes.extend(self.id.to_bytes());
+
+        bytes
+    }
+}
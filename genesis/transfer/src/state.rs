@@ -4,17 +4,43 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use crate::account_tree::AccountTree;
+use crate::accounts::AccountProof;
 use crate::error::Error;
+use crate::escrow::{
+    CreateEscrow, Escrow, EscrowBeneficiary, EscrowEvent, ESCROW_CANCEL_TOPIC,
+    ESCROW_CREATE_TOPIC, ESCROW_RELEASE_TOPIC,
+};
+use crate::freeze::{FREEZE_TOPIC, UNFREEZE_TOPIC};
+use crate::gas::{
+    next_base_fee, GasConfig, GasPriceInfo, BASE_FEE_TOPIC, GAS_CONFIG_TOPIC,
+};
+use crate::migration::{
+    ExportChunk, ExportCursor, ExportSummary, StateItem, EXPORT_VERSION,
+    IMPORT_TOPIC, MIGRATE_TOPIC, STATE_VERSION,
+};
+use crate::nullifier_tree::NullifierTree;
+use crate::nullifiers::NullifierProof;
+use crate::receipt::{DepositStatus, TransactionReceipt, RECEIPT_TOPIC};
+use crate::saturating_usize;
+use crate::scheduled::ScheduledWithdraw;
 use crate::tree::Tree;
+use crate::validation::{CheckResult, ValidationReport};
+use crate::verifier::{
+    SetVerifier, Verifier, SET_VERIFIER_TOPIC, VERIFIER_TRANSITION_WINDOW,
+};
 use crate::verifier_data::tx_circuit_verifier;
 
 use alloc::collections::btree_map::Entry;
 use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
+use core::ops::Bound;
 
+use dusk_bytes::Serializable;
 use dusk_core::stake::EPOCH;
 use dusk_core::transfer::MINT_CONTRACT_TOPIC;
 use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
+use rkyv::ser::serializers::AllocSerializer;
 
 use dusk_core::abi::{self, ContractError, ContractId};
 use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
@@ -51,6 +77,60 @@ const EMPTY_ACCOUNT: AccountData = AccountData {
     balance: 0,
 };
 
+/// The lowest contract ID, before the balance of any contract in an export.
+const FIRST_CONTRACT: ContractId = ContractId::from_bytes([0; 32]);
+
+/// A step migrating the state from one version of its layout to the next.
+type MigrationStep = fn(&mut TransferState);
+
+/// The registered migration steps, keyed by the version they migrate the state
+/// from.
+const MIGRATION_STEPS: &[(u64, MigrationStep)] = &[];
+
+const PANIC_NULLIFIER_SPENT: &str =
+    "A provided nullifier has already been spent";
+const PANIC_ACCOUNT_FROZEN: &str = "The account is frozen";
+
+/// Checks the signature of a moonlight transaction is valid and made by its
+/// sender.
+fn check_signature(tx: &MoonlightTransaction) -> Result<(), &'static str> {
+    if !abi::verify_bls(tx.signature_message(), *tx.sender(), *tx.signature()) {
+        return Err("Invalid signature!");
+    }
+    Ok(())
+}
+
+/// The total value carried by a moonlight transaction, i.e. the sum of the
+/// value, the deposit, and gas_limit * gas_price.
+fn moonlight_total_value(tx: &MoonlightTransaction) -> u64 {
+    tx.value() + tx.deposit() + tx.gas_limit() * tx.gas_price()
+}
+
+/// Chain the hash of the given item onto the checksum of the items preceding
+/// it in an export.
+fn chain_checksum(checksum: BlsScalar, item: &StateItem) -> BlsScalar {
+    let item_bytes = rkyv::to_bytes::<_, 1024>(item)
+        .expect("Serializing a state item should succeed");
+
+    let mut bytes = checksum.to_bytes().to_vec();
+    bytes.extend_from_slice(&item_bytes);
+    abi::hash(bytes)
+}
+
+/// Chain the hash of an event, given by its topic and serialized data, onto the
+/// digest of the events preceding it in a block.
+fn chain_event_digest(
+    digest: BlsScalar,
+    topic: &str,
+    event_bytes: &[u8],
+) -> BlsScalar {
+    let mut bytes = digest.to_bytes().to_vec();
+    bytes.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(topic.as_bytes());
+    bytes.extend_from_slice(event_bytes);
+    abi::hash(bytes)
+}
+
 fn contract_fn_sender(fn_name: &str, contract: ContractId) -> Sender {
     let mut bytes = [0u8; 128];
 
@@ -66,35 +146,85 @@ fn contract_fn_sender(fn_name: &str, contract: ContractId) -> Sender {
 
 pub struct TransferState {
     tree: Tree,
-    nullifiers: BTreeSet<BlsScalar>,
+    nullifiers: NullifierTree,
     roots: ConstGenericRingBuffer<BlsScalar, MAX_ROOTS>,
+    // The roots of the nullifiers tree at the end of the most recent blocks,
+    // together with the height of the block.
+    nullifier_roots: ConstGenericRingBuffer<(u64, BlsScalar), MAX_ROOTS>,
     // NOTE: we should never remove entries from this list, since the entries
     //       contain the nonce of the given account. Doing so opens the account
     //       up to replay attacks.
-    accounts: BTreeMap<[u8; 193], AccountData>,
+    //
+    // Accounts are keyed by the compressed form of their public key, since
+    // `AccountPublicKey` doesn't `impl Ord`.
+    accounts: BTreeMap<[u8; AccountPublicKey::SIZE], AccountData>,
+    // Merkle commitment over the entries in `accounts`, kept in sync with
+    // every change to them.
+    account_tree: AccountTree,
     contract_balances: BTreeMap<ContractId, u64>,
+    // Hashes of the replay keys of the scheduled withdrawals that have been
+    // settled, indexed by their expiry height. They are pruned once the expiry
+    // has passed, since the withdrawals can't be settled anymore.
+    scheduled_withdrawals: BTreeMap<u64, BTreeSet<BlsScalar>>,
+    escrows: BTreeMap<u64, Escrow>,
+    next_escrow_id: u64,
+    // Moonlight accounts that can neither send nor receive funds, keyed in the
+    // same way as `accounts`.
+    frozen_accounts: BTreeMap<[u8; AccountPublicKey::SIZE], AccountPublicKey>,
+    // Verifier data of the phoenix-circuits, keyed by number of inputs and
+    // outputs and sorted by activation height. Circuits without an active
+    // entry use the verifier data embedded at compile time.
+    verifiers: BTreeMap<(u32, u32), Vec<Verifier>>,
+    gas_config: GasConfig,
+    // The current base fee, or zero if the base fee is disabled.
+    base_fee: u64,
+    // Total value burned as base fee.
+    burned: u64,
+    // Version of the layout of the state.
+    version: u64,
+    // The summary of the items imported so far, chained as they are imported,
+    // or `None` if no item was imported.
+    imported: Option<ExportSummary>,
+    // Whether the import was checked and finished, after which no more items
+    // can be imported.
+    import_finished: bool,
+    // The digest of the events emitted in the current block, together with
+    // the height of the block.
+    event_digest: Option<(u64, BlsScalar)>,
+    // The digests of the events emitted in the most recent blocks, together
+    // with the height of the block.
+    event_digests: ConstGenericRingBuffer<(u64, BlsScalar), MAX_ROOTS>,
 }
 
 impl TransferState {
     pub const fn new() -> TransferState {
         TransferState {
             tree: Tree::new(),
-            nullifiers: BTreeSet::new(),
+            nullifiers: NullifierTree::new(),
             roots: ConstGenericRingBuffer::new(),
+            nullifier_roots: ConstGenericRingBuffer::new(),
             accounts: BTreeMap::new(),
+            account_tree: AccountTree::new(),
             contract_balances: BTreeMap::new(),
+            scheduled_withdrawals: BTreeMap::new(),
+            escrows: BTreeMap::new(),
+            next_escrow_id: 0,
+            frozen_accounts: BTreeMap::new(),
+            verifiers: BTreeMap::new(),
+            gas_config: GasConfig::DEFAULT,
+            base_fee: 0,
+            burned: 0,
+            version: STATE_VERSION,
+            imported: None,
+            import_finished: false,
+            event_digest: None,
+            event_digests: ConstGenericRingBuffer::new(),
         }
     }
 
     /// Checks the [`Withdraw`] is correct, and mints the amount of the
     /// withdrawal.
     fn mint_withdrawal(&mut self, fn_name: &str, withdraw: &Withdraw) {
-        let contract = withdraw.contract();
-        let value = withdraw.value();
-
-        let msg = withdraw.signature_message();
-        let signature = withdraw.signature();
-
         match withdraw.token() {
             WithdrawReplayToken::Phoenix(nullifiers) => {
                 let phoenix_tx = transitory::phoenix_transaction();
@@ -114,7 +244,28 @@ impl TransferState {
             }
         }
 
-        match withdraw.receiver() {
+        self.credit_withdrawal(
+            fn_name,
+            withdraw.contract(),
+            withdraw.value(),
+            withdraw.receiver(),
+            withdraw.signature_message(),
+            withdraw.signature(),
+        );
+    }
+
+    /// Checks the signature of a withdrawal is made by its receiver, and mints
+    /// the value to the receiver.
+    fn credit_withdrawal(
+        &mut self,
+        fn_name: &str,
+        contract: &ContractId,
+        value: u64,
+        receiver: &WithdrawReceiver,
+        msg: Vec<u8>,
+        signature: &WithdrawSignature,
+    ) {
+        match receiver {
             WithdrawReceiver::Phoenix(address) => {
                 let signature = match signature {
                     WithdrawSignature::Phoenix(s) => s,
@@ -133,7 +284,9 @@ impl TransferState {
                 let sender = contract_fn_sender(fn_name, *contract);
 
                 let note = Note::transparent_stealth(*address, value, sender);
-                self.push_note_current_height(note);
+                if let Some(note) = self.push_note_current_height(note) {
+                    transitory::push_withdrawal_note(note);
+                }
             }
             WithdrawReceiver::Moonlight(account) => {
                 let signature = match signature {
@@ -147,11 +300,15 @@ impl TransferState {
                     panic!("Invalid signature");
                 }
 
-                let account_bytes = account.to_raw_bytes();
-                let account =
+                let account_bytes = account.to_bytes();
+                self.assert_not_frozen(&account_bytes);
+
+                let account_data =
                     self.accounts.entry(account_bytes).or_insert(EMPTY_ACCOUNT);
 
-                account.balance += value;
+                account_data.balance += value;
+                self.update_account_leaf(account);
+                transitory::record_account_delta(account, value.into());
             }
         }
     }
@@ -178,7 +335,7 @@ impl TransferState {
 
         self.mint_withdrawal("mint", &mint);
 
-        abi::emit(MINT_TOPIC, WithdrawEvent::from(mint));
+        self.emit(MINT_TOPIC, WithdrawEvent::from(mint));
     }
 
     /// Mint more Dusk to be owned by a contract.
@@ -200,6 +357,7 @@ impl TransferState {
             self.contract_balances.entry(mint.contract).or_insert(0);
 
         *receiver_balance += mint.value;
+        transitory::record_contract_delta(mint.contract, mint.value.into());
 
         let receive = ReceiveFromContract {
             contract: STAKE_CONTRACT,
@@ -215,7 +373,7 @@ impl TransferState {
             receiver: mint.contract,
             value: mint.value,
         };
-        abi::emit(MINT_CONTRACT_TOPIC, mint_event);
+        self.emit(MINT_CONTRACT_TOPIC, mint_event);
     }
 
     /// Withdraw from a contract's balance to a Phoenix note or a Moonlight
@@ -249,7 +407,74 @@ impl TransferState {
 
         self.mint_withdrawal("withdraw", &withdraw);
 
-        abi::emit(WITHDRAW_TOPIC, WithdrawEvent::from(withdraw));
+        self.emit(WITHDRAW_TOPIC, WithdrawEvent::from(withdraw));
+    }
+
+    /// Withdraw from a contract's balance using a [`ScheduledWithdraw`], i.e.
+    /// a withdrawal that was signed ahead of the transaction settling it.
+    ///
+    /// The withdrawal is valid on the chain it was signed for, up to and
+    /// including the block at its `expiry` height, and each pair of `id` and
+    /// `expiry` can only be settled once for the given contract and receiver.
+    ///
+    /// # Panics
+    /// This can only be called by the contract specified, and only if said
+    /// contract has enough balance. It also panics if the withdrawal was
+    /// signed for another chain, has expired, or its `id` has already been
+    /// used with the same `expiry`.
+    pub fn withdraw_scheduled(&mut self, withdraw: ScheduledWithdraw) {
+        let contract = withdraw.contract();
+
+        let caller = abi::caller()
+            .expect("A withdrawal must happen in the context of a transaction");
+        if *contract != caller {
+            panic!("The \"withdraw_scheduled\" function can only be called by the specified contract.");
+        }
+
+        if withdraw.chain_id() != self.chain_id() {
+            panic!("The withdrawal was signed for another chain");
+        }
+
+        if abi::block_height() > withdraw.expiry() {
+            panic!("The withdrawal has expired");
+        }
+
+        let replay_key = abi::hash(withdraw.replay_key());
+        if !self
+            .scheduled_withdrawals
+            .entry(withdraw.expiry())
+            .or_default()
+            .insert(replay_key)
+        {
+            panic!("The withdrawal ID has already been used");
+        }
+
+        let value = withdraw.value();
+
+        if self.contract_balance(contract) < value {
+            panic!("The contract doesn't have enough balance");
+        }
+
+        self.sub_contract_balance(contract, value)
+            .expect("Subtracting balance from contract should succeed");
+
+        self.credit_withdrawal(
+            "withdraw_scheduled",
+            contract,
+            value,
+            withdraw.receiver(),
+            withdraw.signature_message(),
+            withdraw.signature(),
+        );
+
+        self.emit(
+            WITHDRAW_TOPIC,
+            WithdrawEvent {
+                sender: *contract,
+                receiver: *withdraw.receiver(),
+                value,
+            },
+        );
     }
 
     /// Takes the deposit addressed to this contract, and immediately withdraws
@@ -306,7 +531,7 @@ impl TransferState {
                 self.mint_withdrawal("convert", &convert);
                 deposit.set_taken();
 
-                abi::emit(
+                self.emit(
                     CONVERT_TOPIC,
                     ConvertEvent::from_withdraw_and_sender(sender, &convert),
                 );
@@ -358,7 +583,7 @@ impl TransferState {
                 self.add_contract_balance(deposit_contract, deposit_value);
                 deposit.set_taken();
 
-                abi::emit(
+                self.emit(
                     DEPOSIT_TOPIC,
                     DepositEvent {
                         sender,
@@ -414,6 +639,10 @@ impl TransferState {
 
         *receiver_balance += transfer.value;
 
+        let value = i128::from(transfer.value);
+        transitory::record_contract_delta(sender_contract, -value);
+        transitory::record_contract_delta(transfer.contract, value);
+
         let receive = ReceiveFromContract {
             contract: sender_contract,
             value: transfer.value,
@@ -423,7 +652,7 @@ impl TransferState {
         abi::call::<_, ()>(transfer.contract, &transfer.fn_name, &receive)
             .expect("Calling receiver should succeed");
 
-        abi::emit(
+        self.emit(
             CONTRACT_TO_CONTRACT_TOPIC,
             ContractToContractEvent {
                 sender: sender_contract,
@@ -440,8 +669,8 @@ impl TransferState {
     ///
     /// # Panics
     /// The function will panic if it is not being called by a contract, if it
-    /// is called by the transfer contract itself, or if the calling contract
-    /// doesn't have enough funds.
+    /// is called by the transfer contract itself, if the calling contract
+    /// doesn't have enough funds, or if the receiving account is frozen.
     pub fn contract_to_account(&mut self, transfer: ContractToAccount) {
         let sender_contract = abi::caller()
             .expect("A transfer to an account must happen in the context of a transaction");
@@ -450,6 +679,9 @@ impl TransferState {
             panic!("Cannot be called directly by the transfer contract");
         }
 
+        let account_bytes = transfer.account.to_bytes();
+        self.assert_not_frozen(&account_bytes);
+
         let sender_balance = self
             .contract_balances
             .get_mut(&sender_contract)
@@ -459,15 +691,19 @@ impl TransferState {
             panic!("Caller must have enough balance");
         }
 
-        let account = self
-            .accounts
-            .entry(transfer.account.to_raw_bytes())
-            .or_insert(EMPTY_ACCOUNT);
+        let account =
+            self.accounts.entry(account_bytes).or_insert(EMPTY_ACCOUNT);
 
         *sender_balance -= transfer.value;
         account.balance += transfer.value;
 
-        abi::emit(
+        self.update_account_leaf(&transfer.account);
+
+        let value = i128::from(transfer.value);
+        transitory::record_contract_delta(sender_contract, -value);
+        transitory::record_account_delta(&transfer.account, value);
+
+        self.emit(
             CONTRACT_TO_ACCOUNT_TOPIC,
             ContractToAccountEvent {
                 sender: sender_contract,
@@ -477,6 +713,114 @@ impl TransferState {
         );
     }
 
+    /// Lock funds from a contract's balance in an escrow, returning the
+    /// identifier of the newly created entry.
+    ///
+    /// The funds are released to the beneficiary by a call to
+    /// [`Self::release_escrow`] at or after the release height, or returned to
+    /// the contract by a call to [`Self::cancel_escrow`] made by the cancel
+    /// authority before it.
+    ///
+    /// # Panics
+    /// The function will panic if it is not being called by a contract, if it
+    /// is called by the transfer contract itself, or if the calling contract
+    /// doesn't have enough funds.
+    pub fn create_escrow(&mut self, create: CreateEscrow) -> u64 {
+        let owner = abi::caller().expect(
+            "An escrow must be created in the context of a transaction",
+        );
+
+        if owner == TRANSFER_CONTRACT {
+            panic!("Cannot be called directly by the transfer contract");
+        }
+
+        if self.contract_balance(&owner) < create.value {
+            panic!("Caller must have enough balance");
+        }
+
+        self.sub_contract_balance(&owner, create.value)
+            .expect("Subtracting balance from contract should succeed");
+
+        let id = self.next_escrow_id;
+        self.next_escrow_id += 1;
+
+        let escrow = Escrow {
+            owner,
+            beneficiary: create.beneficiary,
+            value: create.value,
+            release_height: create.release_height,
+            cancel_authority: create.cancel_authority,
+        };
+        self.escrows.insert(id, escrow.clone());
+
+        self.emit(ESCROW_CREATE_TOPIC, EscrowEvent { id, escrow });
+
+        id
+    }
+
+    /// Release the funds held in an escrow to its beneficiary.
+    ///
+    /// This can be called by anyone, as long as the release height of the
+    /// escrow has been reached.
+    ///
+    /// # Panics
+    /// The function will panic if the escrow doesn't exist or if its release
+    /// height hasn't been reached yet.
+    pub fn release_escrow(&mut self, id: u64) {
+        let escrow = self.escrows.get(&id).expect("The escrow should exist");
+
+        if abi::block_height() < escrow.release_height {
+            panic!("The escrow cannot be released yet");
+        }
+
+        let escrow = self.escrows.remove(&id).expect("The escrow should exist");
+
+        match escrow.beneficiary {
+            EscrowBeneficiary::Contract(contract) => {
+                self.add_contract_balance(contract, escrow.value);
+            }
+            EscrowBeneficiary::Account(account) => {
+                let account_bytes = account.to_bytes();
+                self.assert_not_frozen(&account_bytes);
+
+                let account_data =
+                    self.accounts.entry(account_bytes).or_insert(EMPTY_ACCOUNT);
+                account_data.balance += escrow.value;
+                self.update_account_leaf(&account);
+                transitory::record_account_delta(&account, escrow.value.into());
+            }
+        }
+
+        self.emit(ESCROW_RELEASE_TOPIC, EscrowEvent { id, escrow });
+    }
+
+    /// Cancel an escrow, returning the funds it holds to the contract that
+    /// created it.
+    ///
+    /// # Panics
+    /// The function will panic if the escrow doesn't exist, if it has no
+    /// cancel authority or the caller is not said authority, or if its release
+    /// height has already been reached.
+    pub fn cancel_escrow(&mut self, id: u64) {
+        let escrow = self.escrows.get(&id).expect("The escrow should exist");
+
+        let authority = escrow
+            .cancel_authority
+            .expect("The escrow cannot be cancelled");
+        if abi::caller() != Some(authority) {
+            panic!("Only the cancel authority can cancel the escrow");
+        }
+
+        if abi::block_height() >= escrow.release_height {
+            panic!("The escrow can no longer be cancelled");
+        }
+
+        let escrow = self.escrows.remove(&id).expect("The escrow should exist");
+        self.add_contract_balance(escrow.owner, escrow.value);
+
+        self.emit(ESCROW_CANCEL_TOPIC, EscrowEvent { id, escrow });
+    }
+
     /// The top level transaction execution function.
     ///
     /// This will emplace the deposit in the state, if it exists - making it
@@ -500,8 +844,8 @@ impl TransferState {
         &mut self,
         tx: Transaction,
     ) -> Result<Vec<u8>, ContractError> {
-        if tx.gas_price() == 0 {
-            panic!("Gas price too low!");
+        if let Err(msg) = self.check_gas_price(&tx) {
+            panic!("{msg}");
         }
 
         transitory::put_transaction(tx);
@@ -514,7 +858,10 @@ impl TransferState {
 
         match tx.call() {
             Some(call) => {
-                abi::call_raw(call.contract, &call.fn_name, &call.fn_args)
+                let result =
+                    abi::call_raw(call.contract, &call.fn_name, &call.fn_args);
+                transitory::set_call_result(result.clone());
+                result
             }
             None => Ok(Vec::new()),
         }
@@ -530,26 +877,26 @@ impl TransferState {
     /// result in a panic. The contract expects the environment to roll back any
     /// change in state.
     fn spend_phoenix(&mut self, phoenix_tx: &PhoenixTransaction) {
-        if phoenix_tx.chain_id() != self.chain_id() {
-            panic!("The tx must target the correct chain");
+        if let Err(msg) = self.check_chain_id(phoenix_tx.chain_id()) {
+            panic!("{msg}");
         }
 
         // panic if the root is invalid
-        if !self.root_exists(phoenix_tx.root()) {
-            panic!("Root not found in the state!");
+        if let Err(msg) = self.check_root(phoenix_tx) {
+            panic!("{msg}");
         }
 
         // append the nullifiers to the set, and panic if an equal one has
         // already been inserted
         for nullifier in phoenix_tx.nullifiers() {
             if !self.nullifiers.insert(*nullifier) {
-                panic!("A provided nullifier has already been spent");
+                panic!("{PANIC_NULLIFIER_SPENT}");
             }
         }
 
         // verify the phoenix-circuit
-        if !verify_tx_proof(phoenix_tx) {
-            panic!("Invalid transaction proof!");
+        if let Err(msg) = self.check_tx_proof(phoenix_tx) {
+            panic!("{msg}");
         }
 
         // append the output notes to the phoenix-notes tree
@@ -573,17 +920,13 @@ impl TransferState {
     /// result in a panic. The contract expects the environment to roll back any
     /// change in state.
     fn spend_moonlight(&mut self, moonlight_tx: &MoonlightTransaction) {
-        if moonlight_tx.chain_id() != self.chain_id() {
-            panic!("The tx must target the correct chain");
+        if let Err(msg) = self.check_chain_id(moonlight_tx.chain_id()) {
+            panic!("{msg}");
         }
 
         // check the signature is valid and made by `sender`
-        if !abi::verify_bls(
-            moonlight_tx.signature_message(),
-            *moonlight_tx.sender(),
-            *moonlight_tx.signature(),
-        ) {
-            panic!("Invalid signature!");
+        if let Err(msg) = check_signature(moonlight_tx) {
+            panic!("{msg}");
         }
 
         // check `sender` has the funds necessary to suppress the total value
@@ -596,54 +939,185 @@ impl TransferState {
         // Afterwards, we simply deduct the total amount of the transaction from
         // the balance, increment the nonce, and rely on `refund` to be called
         // after a successful exit.
-        //
-        // TODO: this is expensive, maybe we should address the fact that
-        //       `AccountPublicKey` doesn't `impl Ord` so we can just use it
-        //       directly as a key in the `BTreeMap`
-        let sender_bytes = moonlight_tx.sender().to_raw_bytes();
-
-        // the total value carried by a transaction is the sum of the value, the
-        // deposit, and gas_limit * gas_price.
-        let total_value = moonlight_tx.value()
-            + moonlight_tx.deposit()
-            + moonlight_tx.gas_limit() * moonlight_tx.gas_price();
-
-        match self.accounts.get_mut(&sender_bytes) {
-            Some(account) => {
-                if total_value > account.balance {
-                    panic!("Account doesn't have enough funds");
-                }
+        let sender_bytes = moonlight_tx.sender().to_bytes();
 
-                // NOTE: exhausting the nonce is nearly impossible, since it
-                //       requires performing more than 18 quintillion
-                //       transactions. Since this number is so large, we also
-                //       skip overflow checks.
-                let incremented_nonce = account.nonce + 1;
-                if moonlight_tx.nonce() < incremented_nonce {
-                    panic!("Already used nonce");
-                }
-                if moonlight_tx.nonce() > incremented_nonce {
-                    panic!("{PANIC_NONCE_NOT_READY}",);
-                }
+        // frozen accounts can't send, receive, or be refunded funds
+        if let Err(msg) = self.check_not_frozen(moonlight_tx) {
+            panic!("{msg}");
+        }
 
-                account.balance -= total_value;
-                account.nonce = moonlight_tx.nonce();
-            }
-            None => panic!("Account has no funds"),
+        if let Err(msg) = self.check_funds(moonlight_tx) {
+            panic!("{msg}");
+        }
+        if let Err(msg) = self.check_nonce(moonlight_tx) {
+            panic!("{msg}");
         }
 
+        let account = self
+            .accounts
+            .get_mut(&sender_bytes)
+            .expect("The sender account should exist");
+        let total_value = moonlight_total_value(moonlight_tx);
+        account.balance -= total_value;
+        account.nonce = moonlight_tx.nonce();
+        self.update_account_leaf(moonlight_tx.sender());
+        transitory::record_account_delta(
+            moonlight_tx.sender(),
+            -i128::from(total_value),
+        );
+
         // add the value to the receiver account
         if moonlight_tx.value() > 0 {
             // if the receiver has no entry, we simply instantiate a new one
             // with a zero nonce and balance.
-            let receiver_bytes =
-                moonlight_tx.receiver().map(|rcvr| rcvr.to_raw_bytes());
+            let receiver =
+                moonlight_tx.receiver().unwrap_or(moonlight_tx.sender());
             let account = self
                 .accounts
-                .entry(receiver_bytes.unwrap_or(sender_bytes))
+                .entry(receiver.to_bytes())
                 .or_insert(EMPTY_ACCOUNT);
             account.balance += moonlight_tx.value();
+            self.update_account_leaf(receiver);
+            transitory::record_account_delta(
+                receiver,
+                moonlight_tx.value().into(),
+            );
+        }
+    }
+
+    /// Run the checks performed when spending the given transaction against
+    /// the current state, without applying it, and report their outcome.
+    ///
+    /// The checks are all run independently of each other, so a report may
+    /// contain more than one failure.
+    pub fn validate_transaction(&self, tx: Transaction) -> ValidationReport {
+        let mut report = ValidationReport {
+            gas_price: self.check_gas_price(&tx).into(),
+            chain_id: CheckResult::Skipped,
+            signature: CheckResult::Skipped,
+            frozen: CheckResult::Skipped,
+            funds: CheckResult::Skipped,
+            nonce: CheckResult::Skipped,
+            root: CheckResult::Skipped,
+            nullifiers: CheckResult::Skipped,
+            proof: CheckResult::Skipped,
+            max_fee: tx.gas_limit().saturating_mul(tx.gas_price()),
+        };
+
+        match &tx {
+            Transaction::Phoenix(tx) => {
+                report.chain_id = self.check_chain_id(tx.chain_id()).into();
+                report.root = self.check_root(tx).into();
+                report.nullifiers = self.check_nullifiers(tx).into();
+                report.proof = self.check_tx_proof(tx).into();
+            }
+            Transaction::Moonlight(tx) => {
+                report.chain_id = self.check_chain_id(tx.chain_id()).into();
+                report.signature = check_signature(tx).into();
+                report.frozen = self.check_not_frozen(tx).into();
+                report.funds = self.check_funds(tx).into();
+                report.nonce = self.check_nonce(tx).into();
+            }
+        }
+
+        report
+    }
+
+    /// Checks the gas price of the transaction is not below the minimum
+    /// accepted gas price.
+    fn check_gas_price(&self, tx: &Transaction) -> Result<(), &'static str> {
+        if tx.gas_price() < self.gas_price_info().min_accepted_gas_price() {
+            return Err("Gas price too low!");
+        }
+        Ok(())
+    }
+
+    /// Checks the transaction targets the chain the contract is deployed on.
+    fn check_chain_id(&self, chain_id: u8) -> Result<(), &'static str> {
+        if chain_id != self.chain_id() {
+            return Err("The tx must target the correct chain");
+        }
+        Ok(())
+    }
+
+    /// Checks the root of a phoenix transaction has been a root of the notes
+    /// tree.
+    fn check_root(&self, tx: &PhoenixTransaction) -> Result<(), &'static str> {
+        if !self.root_exists(tx.root()) {
+            return Err("Root not found in the state!");
+        }
+        Ok(())
+    }
+
+    /// Checks none of the nullifiers of a phoenix transaction has already been
+    /// spent, including by the transaction itself.
+    fn check_nullifiers(
+        &self,
+        tx: &PhoenixTransaction,
+    ) -> Result<(), &'static str> {
+        let mut tx_nullifiers = BTreeSet::new();
+        for nullifier in tx.nullifiers() {
+            if self.nullifiers.contains(nullifier)
+                || !tx_nullifiers.insert(nullifier)
+            {
+                return Err(PANIC_NULLIFIER_SPENT);
+            }
         }
+        Ok(())
+    }
+
+    /// Checks none of the accounts involved in a moonlight transaction is
+    /// frozen.
+    fn check_not_frozen(
+        &self,
+        tx: &MoonlightTransaction,
+    ) -> Result<(), &'static str> {
+        let mut keys = Vec::from([tx.sender(), tx.refund_address()]);
+        keys.extend(tx.receiver());
+
+        for key in keys {
+            if self.frozen_accounts.contains_key(&key.to_bytes()) {
+                return Err(PANIC_ACCOUNT_FROZEN);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the sender of a moonlight transaction has the funds necessary to
+    /// suppress the total value available in the transaction.
+    fn check_funds(
+        &self,
+        tx: &MoonlightTransaction,
+    ) -> Result<(), &'static str> {
+        match self.accounts.get(&tx.sender().to_bytes()) {
+            Some(account) => {
+                if moonlight_total_value(tx) > account.balance {
+                    return Err("Account doesn't have enough funds");
+                }
+                Ok(())
+            }
+            None => Err("Account has no funds"),
+        }
+    }
+
+    /// Checks the nonce of a moonlight transaction is the one following the
+    /// nonce of the sender.
+    fn check_nonce(
+        &self,
+        tx: &MoonlightTransaction,
+    ) -> Result<(), &'static str> {
+        // NOTE: exhausting the nonce is nearly impossible, since it
+        //       requires performing more than 18 quintillion
+        //       transactions. Since this number is so large, we also
+        //       skip overflow checks.
+        let incremented_nonce = self.account(tx.sender()).nonce + 1;
+        if tx.nonce() < incremented_nonce {
+            return Err("Already used nonce");
+        }
+        if tx.nonce() > incremented_nonce {
+            return Err(PANIC_NONCE_NOT_READY);
+        }
+        Ok(())
     }
 
     /// Refund the previously performed transaction, taking into account the
@@ -651,8 +1125,11 @@ impl TransferState {
     /// the contract. The note produced will be refunded to the address present
     /// in the fee structure.
     ///
+    /// Returns the [`TransactionReceipt`] of the transaction, which is also
+    /// emitted under the [`RECEIPT_TOPIC`].
+    ///
     /// This function guarantees that it will not panic.
-    pub fn refund(&mut self, gas_spent: u64) {
+    pub fn refund(&mut self, gas_spent: u64) -> TransactionReceipt {
         let ongoing = transitory::take_ongoing();
 
         // If there is a deposit still available on the call to this function,
@@ -661,12 +1138,37 @@ impl TransferState {
             Deposit::Available { value, .. } => Some(value),
             _ => None,
         };
+        let deposit_status = match ongoing.deposit {
+            Deposit::Available { target, value, .. } => {
+                DepositStatus::Refunded {
+                    contract: target,
+                    value,
+                }
+            }
+            Deposit::Taken { target, value, .. } => DepositStatus::Taken {
+                contract: target,
+                value,
+            },
+            Deposit::None => DepositStatus::None,
+        };
 
         let mut memo = Vec::new();
         if let Some(m) = ongoing.tx.memo() {
             memo = m.to_vec();
         }
 
+        let gas_refund =
+            (ongoing.tx.gas_limit() - gas_spent) * ongoing.tx.gas_price();
+
+        // the base-fee part of the fee is burned
+        self.burned = self
+            .burned
+            .saturating_add(gas_spent.saturating_mul(self.base_fee));
+
+        let mut receipt_notes = ongoing.notes.clone();
+        receipt_notes.extend(ongoing.withdrawal_notes);
+        let mut account_deltas = ongoing.account_deltas;
+
         // the unspent gas is refunded to the refund-address specified in the
         // fee for both phoenix and moonlight transactions.
         //
@@ -683,8 +1185,9 @@ impl TransferState {
                 // if the refund-value is 0, we don't push the note onto the
                 // tree and the refund-note will be None
                 let refund_note = self.push_note_current_height(remainder_note);
+                receipt_notes.extend(refund_note.clone());
 
-                abi::emit(
+                self.emit(
                     PHOENIX_TOPIC,
                     PhoenixTransactionEvent {
                         nullifiers: tx.nullifiers().to_vec(),
@@ -696,16 +1199,20 @@ impl TransferState {
                 );
             }
             Transaction::Moonlight(tx) => {
-                let remaining_gas = tx.gas_limit() - gas_spent;
-                let refund = remaining_gas * tx.gas_price()
-                    + deposit.unwrap_or_default();
+                let refund = gas_refund + deposit.unwrap_or_default();
 
                 let refund_account = self
                     .accounts
-                    .entry(tx.refund_address().to_raw_bytes())
+                    .entry(tx.refund_address().to_bytes())
                     .or_insert(EMPTY_ACCOUNT);
 
                 refund_account.balance += refund;
+                self.update_account_leaf(tx.refund_address());
+                transitory::add_delta(
+                    &mut account_deltas,
+                    *tx.refund_address(),
+                    refund.into(),
+                );
 
                 let refund_info =
                     if refund > 0 && tx.refund_address() != tx.sender() {
@@ -714,7 +1221,7 @@ impl TransferState {
                         None
                     };
 
-                abi::emit(
+                self.emit(
                     MOONLIGHT_TOPIC,
                     MoonlightTransactionEvent {
                         sender: *tx.sender(),
@@ -727,6 +1234,26 @@ impl TransferState {
                 );
             }
         }
+
+        let receipt = TransactionReceipt {
+            deposit: deposit_status,
+            notes: receipt_notes,
+            account_deltas: account_deltas
+                .into_iter()
+                .filter(|(_, delta)| *delta != 0)
+                .collect(),
+            contract_deltas: ongoing
+                .contract_deltas
+                .into_iter()
+                .filter(|(_, delta)| *delta != 0)
+                .collect(),
+            gas_spent,
+            gas_refund,
+            call_result: ongoing.call_result,
+        };
+        self.emit(RECEIPT_TOPIC, receipt.clone());
+
+        receipt
     }
 
     /// Feeds the host with the leaves in the tree, starting from the given
@@ -755,57 +1282,621 @@ impl TransferState {
                 abi::feed(leaf.clone());
             }
         } else {
-            for leaf in iter.take(count_limit as usize) {
+            for leaf in iter.take(saturating_usize(count_limit)) {
                 abi::feed(leaf.clone());
             }
         }
     }
 
     pub fn sync_nullifiers(&self, from: u64, count_limit: u64) {
-        let iter = self.nullifiers.iter().skip(from as usize);
+        let iter = self.nullifiers.iter().skip(saturating_usize(from));
         if count_limit == 0 {
             for n in iter {
                 abi::feed(*n);
             }
         } else {
-            for n in iter.take(count_limit as usize) {
+            for n in iter.take(saturating_usize(count_limit)) {
                 abi::feed(*n);
             }
         }
     }
 
     pub fn sync_contract_balances(&self, from: u64, count_limit: u64) {
-        let iter = self.contract_balances.iter().skip(from as usize);
+        let iter = self.contract_balances.iter().skip(saturating_usize(from));
 
         if count_limit == 0 {
             for (contract, balance) in iter {
                 abi::feed((*contract, *balance));
             }
         } else {
-            for (contract, balance) in iter.take(count_limit as usize) {
+            for (contract, balance) in iter.take(saturating_usize(count_limit))
+            {
                 abi::feed((*contract, *balance));
             }
         }
     }
 
+    /// Feeds the host with the account data (up to `count_limit`
+    /// occurrences), starting from the given `from` position, together with
+    /// the compressed bytes of the account's public key.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
     pub fn sync_accounts(&self, from: u64, count_limit: u64) {
-        let iter = self.accounts.iter().skip(from as usize);
+        let iter = self.accounts.iter().skip(saturating_usize(from));
 
         if count_limit == 0 {
             for (key, account) in iter {
                 abi::feed((account.clone(), *key));
             }
         } else {
-            for (key, account) in iter.take(count_limit as usize) {
+            for (key, account) in iter.take(saturating_usize(count_limit)) {
                 abi::feed((account.clone(), *key));
             }
         }
     }
 
-    /// Update the root for of the tree.
+    /// Feeds the host with the escrow entries (up to `count_limit`
+    /// occurrences), starting from the given `from` identifier.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
+    pub fn sync_escrows(&self, from: u64, count_limit: u64) {
+        let iter = self.escrows.range(from..);
+
+        if count_limit == 0 {
+            for (id, escrow) in iter {
+                abi::feed((*id, escrow.clone()));
+            }
+        } else {
+            for (id, escrow) in iter.take(saturating_usize(count_limit)) {
+                abi::feed((*id, escrow.clone()));
+            }
+        }
+    }
+
+    /// Feeds the host with the items of the exported state (up to
+    /// `count_limit` occurrences), starting from the item at the given
+    /// cursor.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
+    pub fn export_state(&self, from: ExportCursor, count_limit: u64) {
+        let iter = self.state_items(from).map(|(_, item)| item);
+
+        if count_limit == 0 {
+            for item in iter {
+                abi::feed(item);
+            }
+        } else {
+            for item in iter.take(saturating_usize(count_limit)) {
+                abi::feed(item);
+            }
+        }
+    }
+
+    /// Chain the items of a chunk of the exported state (up to `count_limit`
+    /// occurrences), starting from the item at the given cursor, onto the
+    /// summary of the chunks before it, and return the summary together
+    /// with the cursor of the next chunk.
+    ///
+    /// If `count_limit` is 0 there is no occurrences limit
+    ///
+    /// # Panics
+    /// The function will panic if the summary is of a different version of
+    /// the export format.
+    pub fn export_summary(
+        &self,
+        mut summary: ExportSummary,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> ExportChunk {
+        if summary.version != EXPORT_VERSION {
+            panic!("Unsupported export version");
+        }
+
+        let count_limit = match count_limit {
+            0 => usize::MAX,
+            count_limit => saturating_usize(count_limit),
+        };
+
+        let mut items = self.state_items(from);
+        for (_, item) in items.by_ref().take(count_limit) {
+            summary.items += 1;
+            summary.checksum = chain_checksum(summary.checksum, &item);
+        }
+
+        ExportChunk {
+            summary,
+            next: items.next().map(|(cursor, _)| cursor),
+        }
+    }
+
+    /// Import the given items of an exported state, in order.
+    ///
+    /// This is intended for migrating the state of a previous version of the
+    /// contract to a freshly deployed one, and the items must be imported in
+    /// the order they are exported in for the trees to be rebuilt identically.
+    /// The items are chained onto the summary of the import as they are
+    /// imported, so that [`finish_import`] only has to compare it.
+    ///
+    /// # Panics
+    /// The function will panic if the import is already finished, or if the
+    /// first items are imported on top of an existing state.
+    ///
+    /// [`finish_import`]: Self::finish_import
+    pub fn import_state(&mut self, items: Vec<StateItem>) {
+        if self.import_finished {
+            panic!("The import is already finished");
+        }
+        if self.imported.is_none() && !self.is_empty() {
+            panic!("The state can only be imported into a fresh contract");
+        }
+
+        let mut imported = self.imported.unwrap_or(ExportSummary::EMPTY);
+        for item in items {
+            imported.items += 1;
+            imported.checksum = chain_checksum(imported.checksum, &item);
+
+            match item {
+                StateItem::GasPricing(info) => {
+                    self.gas_config = info.config;
+                    self.base_fee = info.base_fee;
+                    self.burned = info.burned;
+                }
+                StateItem::NextEscrowId(id) => self.next_escrow_id = id,
+                StateItem::Note(leaf) => {
+                    self.tree.push(leaf);
+                }
+                StateItem::Root(root) => self.roots.push(root),
+                StateItem::Nullifier(nullifier) => {
+                    self.nullifiers.insert(nullifier);
+                }
+                StateItem::NullifierRoot(block_height, root) => {
+                    self.nullifier_roots.push((block_height, root))
+                }
+                StateItem::Account(key_bytes, account) => {
+                    let key = AccountPublicKey::from_bytes(&key_bytes)
+                        .expect("The account key should be valid");
+                    self.insert_account(&key, account);
+                }
+                StateItem::ContractBalance(contract, balance) => {
+                    self.contract_balances.insert(contract, balance);
+                }
+                StateItem::ScheduledWithdrawal(expiry, replay_key) => {
+                    self.scheduled_withdrawals
+                        .entry(expiry)
+                        .or_default()
+                        .insert(replay_key);
+                }
+                StateItem::Escrow(id, escrow) => {
+                    self.escrows.insert(id, escrow);
+                }
+                StateItem::FrozenAccount(key) => {
+                    self.frozen_accounts.insert(key.to_bytes(), key);
+                }
+                StateItem::Verifier(inputs, outputs, verifier) => {
+                    self.verifiers
+                        .entry((inputs, outputs))
+                        .or_default()
+                        .push(verifier);
+                }
+            }
+        }
+        self.imported = Some(imported);
+    }
+
+    /// Check the imported items match the exported state with the given
+    /// summary, finish the import, and emit an event under the
+    /// [`IMPORT_TOPIC`].
+    ///
+    /// # Panics
+    /// The function will panic if the import is already finished, if the
+    /// summary is of a different version of the export format, or if it
+    /// doesn't match the summary of the imported items.
+    pub fn finish_import(&mut self, summary: ExportSummary) {
+        if self.import_finished {
+            panic!("The import is already finished");
+        }
+        if summary.version != EXPORT_VERSION {
+            panic!("Unsupported export version");
+        }
+        if self.imported.unwrap_or(ExportSummary::EMPTY) != summary {
+            panic!("The imported state doesn't match the exported state");
+        }
+
+        self.import_finished = true;
+        self.emit(IMPORT_TOPIC, summary);
+    }
+
+    /// Returns true if the state holds nothing besides its global values,
+    /// i.e. if it is the state of a freshly deployed contract.
+    fn is_empty(&self) -> bool {
+        self.tree.leaves_len() == 0
+            && self.roots.is_empty()
+            && self.nullifiers.iter().next().is_none()
+            && self.nullifier_roots.is_empty()
+            && self.accounts.is_empty()
+            && self.contract_balances.is_empty()
+            && self.scheduled_withdrawals.is_empty()
+            && self.escrows.is_empty()
+            && self.frozen_accounts.is_empty()
+            && self.verifiers.is_empty()
+    }
+
+    /// Return the version of the layout of the state.
+    pub fn state_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns true if the state is at a previous version of its layout, and
+    /// needs to be migrated before being used.
+    pub fn migration_pending(&self) -> bool {
+        self.version != STATE_VERSION
+    }
+
+    /// Migrate the state from the `from` version of its layout to the `to`
+    /// version, by running the registered migration steps in between in
+    /// order, and emit an event under the [`MIGRATE_TOPIC`].
+    ///
+    /// # Panics
+    /// The function will panic if the state is not at the `from` version, if
+    /// `to` is not a later version supported by the contract, or if a step is
+    /// missing.
+    pub fn migrate(&mut self, from: u64, to: u64) {
+        if from != self.version {
+            panic!("The state is not at the version to migrate from");
+        }
+        if to <= from || to > STATE_VERSION {
+            panic!("Invalid version to migrate to");
+        }
+
+        for version in from..to {
+            let (_, step) = MIGRATION_STEPS
+                .iter()
+                .find(|(step_version, _)| *step_version == version)
+                .expect("There should be a migration step for every version");
+            step(self);
+            self.version = version + 1;
+        }
+
+        self.emit(MIGRATE_TOPIC, (from, to));
+    }
+
+    /// Return an iterator through the items of the exported state together
+    /// with their cursors, in the order they must be imported in, starting
+    /// from the item at the given cursor.
+    ///
+    /// Each part of the state is resumed from the position or the key of the
+    /// cursor if the cursor is in that part, walked from its start if it comes
+    /// after the cursor, and skipped if it comes before.
+    fn state_items(
+        &self,
+        from: ExportCursor,
+    ) -> impl Iterator<Item = (ExportCursor, StateItem)> + '_ {
+        use ExportCursor as Cursor;
+
+        let globals = [
+            (
+                Cursor::GasPricing,
+                StateItem::GasPricing(self.gas_price_info()),
+            ),
+            (
+                Cursor::NextEscrowId,
+                StateItem::NextEscrowId(self.next_escrow_id),
+            ),
+        ]
+        .into_iter()
+        .filter(move |(cursor, _)| *cursor >= from);
+
+        let notes_from = match from {
+            Cursor::Note(pos) => Some(pos),
+            _ if from < Cursor::Note(0) => Some(0),
+            _ => None,
+        };
+        let notes = notes_from
+            .into_iter()
+            .flat_map(|pos| self.tree.leaves_pos(pos))
+            .map(|leaf| {
+                (
+                    Cursor::Note(*leaf.note.pos()),
+                    StateItem::Note(leaf.clone()),
+                )
+            });
+
+        let roots_from = match from {
+            Cursor::Root(index) => Some(index),
+            _ if from < Cursor::Root(0) => Some(0),
+            _ => None,
+        };
+        let roots = roots_from
+            .into_iter()
+            .flat_map(|index| index..self.roots.len() as u64)
+            .filter_map(|index| {
+                let root = self.roots.get(saturating_usize(index))?;
+                Some((Cursor::Root(index), StateItem::Root(*root)))
+            });
+
+        let nullifiers_from = match from {
+            Cursor::Nullifier(index) => Some(index),
+            _ if from < Cursor::Nullifier(0) => Some(0),
+            _ => None,
+        };
+        let nullifiers = nullifiers_from
+            .into_iter()
+            .flat_map(|index| self.nullifiers.iter_inserted(index).zip(index..))
+            .map(|(nullifier, index)| {
+                (Cursor::Nullifier(index), StateItem::Nullifier(*nullifier))
+            });
+
+        let nullifier_roots_from = match from {
+            Cursor::NullifierRoot(index) => Some(index),
+            _ if from < Cursor::NullifierRoot(0) => Some(0),
+            _ => None,
+        };
+        let nullifier_roots = nullifier_roots_from
+            .into_iter()
+            .flat_map(|index| index..self.nullifier_roots.len() as u64)
+            .filter_map(|index| {
+                let (block_height, root) =
+                    self.nullifier_roots.get(saturating_usize(index))?;
+                let item = StateItem::NullifierRoot(*block_height, *root);
+                Some((Cursor::NullifierRoot(index), item))
+            });
+
+        let accounts_from = match from {
+            Cursor::Account(pos) => Some(pos),
+            _ if from < Cursor::Account(0) => Some(0),
+            _ => None,
+        };
+        let accounts = accounts_from
+            .into_iter()
+            .flat_map(|pos| self.account_tree.keys(pos).zip(pos..))
+            .map(|(key, pos)| {
+                let account = self.accounts.get(key).cloned();
+                let item =
+                    StateItem::Account(*key, account.unwrap_or(EMPTY_ACCOUNT));
+                (Cursor::Account(pos), item)
+            });
+
+        let balances_from = match from {
+            Cursor::ContractBalance(contract) => {
+                Some(Bound::Included(contract))
+            }
+            _ if from < Cursor::ContractBalance(FIRST_CONTRACT) => {
+                Some(Bound::Unbounded)
+            }
+            _ => None,
+        };
+        let balances = balances_from
+            .into_iter()
+            .flat_map(|start| {
+                self.contract_balances.range((start, Bound::Unbounded))
+            })
+            .map(|(contract, balance)| {
+                (
+                    Cursor::ContractBalance(*contract),
+                    StateItem::ContractBalance(*contract, *balance),
+                )
+            });
+
+        let scheduled_from = match from {
+            Cursor::ScheduledWithdrawal(expiry, replay_key) => {
+                Some((expiry, Bound::Included(replay_key)))
+            }
+            _ if from < Cursor::ScheduledWithdrawal(0, BlsScalar::zero()) => {
+                Some((0, Bound::Unbounded))
+            }
+            _ => None,
+        };
+        let scheduled = scheduled_from
+            .into_iter()
+            .flat_map(move |(from_expiry, from_key)| {
+                self.scheduled_withdrawals.range(from_expiry..).flat_map(
+                    move |(expiry, replay_keys)| {
+                        let start = if *expiry == from_expiry {
+                            from_key
+                        } else {
+                            Bound::Unbounded
+                        };
+                        replay_keys
+                            .range((start, Bound::Unbounded))
+                            .map(move |replay_key| (*expiry, *replay_key))
+                    },
+                )
+            })
+            .map(|(expiry, replay_key)| {
+                (
+                    Cursor::ScheduledWithdrawal(expiry, replay_key),
+                    StateItem::ScheduledWithdrawal(expiry, replay_key),
+                )
+            });
+
+        let escrows_from = match from {
+            Cursor::Escrow(id) => Some(id),
+            _ if from < Cursor::Escrow(0) => Some(0),
+            _ => None,
+        };
+        let escrows = escrows_from
+            .into_iter()
+            .flat_map(|id| self.escrows.range(id..))
+            .map(|(id, escrow)| {
+                (Cursor::Escrow(*id), StateItem::Escrow(*id, escrow.clone()))
+            });
+
+        let frozen_from = match from {
+            Cursor::FrozenAccount(key) => Some(Bound::Included(key)),
+            _ if from < Cursor::FrozenAccount([0; AccountPublicKey::SIZE]) => {
+                Some(Bound::Unbounded)
+            }
+            _ => None,
+        };
+        let frozen = frozen_from
+            .into_iter()
+            .flat_map(|start| {
+                self.frozen_accounts.range((start, Bound::Unbounded))
+            })
+            .map(|(key_bytes, key)| {
+                (
+                    Cursor::FrozenAccount(*key_bytes),
+                    StateItem::FrozenAccount(*key),
+                )
+            });
+
+        let verifiers_from = match from {
+            Cursor::Verifier(inputs, outputs, index) => {
+                Some((inputs, outputs, index))
+            }
+            _ if from < Cursor::Verifier(0, 0, 0) => Some((0, 0, 0)),
+            _ => None,
+        };
+        let verifiers = verifiers_from
+            .into_iter()
+            .flat_map(move |(from_inputs, from_outputs, from_index)| {
+                let from_circuit = (from_inputs, from_outputs);
+                self.verifiers.range(from_circuit..).flat_map(
+                    move |(circuit, verifiers)| {
+                        let start = if *circuit == from_circuit {
+                            from_index
+                        } else {
+                            0
+                        };
+                        let verifiers = verifiers
+                            .get(saturating_usize(start)..)
+                            .unwrap_or_default();
+                        verifiers.iter().zip(start..).map(
+                            move |(verifier, index)| {
+                                (*circuit, index, verifier)
+                            },
+                        )
+                    },
+                )
+            })
+            .map(|((inputs, outputs), index, verifier)| {
+                (
+                    Cursor::Verifier(inputs, outputs, index),
+                    StateItem::Verifier(inputs, outputs, verifier.clone()),
+                )
+            });
+
+        globals
+            .chain(notes)
+            .chain(roots)
+            .chain(nullifiers)
+            .chain(nullifier_roots)
+            .chain(accounts)
+            .chain(balances)
+            .chain(scheduled)
+            .chain(escrows)
+            .chain(frozen)
+            .chain(verifiers)
+    }
+
+    /// Update the root for of the tree, and record the root of the
+    /// nullifiers tree for the current block.
+    ///
+    /// This also prunes the settled scheduled withdrawals expiring at or before
+    /// the current block, since they can't be settled in later blocks.
     pub fn update_root(&mut self) {
+        let block_height = abi::block_height();
+
         let root = self.tree.root();
         self.roots.push(root);
+
+        let nullifiers_root = self.nullifiers.root();
+        self.nullifier_roots.push((block_height, nullifiers_root));
+
+        self.scheduled_withdrawals = self
+            .scheduled_withdrawals
+            .split_off(&block_height.saturating_add(1));
+
+        self.finish_event_digest();
+    }
+
+    /// Return the digest of the events emitted by the contract in the block at
+    /// the given height, or `None` if no event was emitted in the block or if
+    /// the block is too old.
+    ///
+    /// The digest of a block is a hash chain over its events, in the order
+    /// they were emitted, starting from zero.
+    pub fn block_event_digest(&self, block_height: u64) -> Option<BlsScalar> {
+        self.event_digest
+            .iter()
+            .chain(self.event_digests.iter().rev())
+            .find(|(height, _)| *height == block_height)
+            .map(|(_, digest)| *digest)
+    }
+
+    /// Emit an event under the given topic, chaining it onto the digest of
+    /// the events of the current block.
+    fn emit<E>(&mut self, topic: &str, event: E)
+    where
+        E: rkyv::Serialize<AllocSerializer<1024>>,
+    {
+        let event_bytes = rkyv::to_bytes::<_, 1024>(&event)
+            .expect("Serializing an event should succeed");
+
+        let block_height = abi::block_height();
+        let digest = match self.event_digest {
+            Some((height, digest)) if height == block_height => digest,
+            _ => {
+                self.finish_event_digest();
+                BlsScalar::zero()
+            }
+        };
+        let digest = chain_event_digest(digest, topic, &event_bytes);
+        self.event_digest = Some((block_height, digest));
+
+        abi::emit_raw(topic, event_bytes);
+    }
+
+    /// Move the digest of the events of the current block to the digests of
+    /// the most recent blocks.
+    fn finish_event_digest(&mut self) {
+        if let Some(event_digest) = self.event_digest.take() {
+            self.event_digests.push(event_digest);
+        }
+    }
+
+    /// Set the configuration of the gas pricing.
+    ///
+    /// Enabling the base fee starts it at the minimum gas price, unless it is
+    /// already higher, and disabling it resets it to zero.
+    ///
+    /// # Panics
+    /// The function will panic if the block gas target is zero.
+    pub fn set_gas_config(&mut self, config: GasConfig) {
+        if config.block_gas_target == Some(0) {
+            panic!("The block gas target must not be zero");
+        }
+
+        self.base_fee = match config.block_gas_target {
+            Some(_) => self.base_fee.max(config.min_gas_price),
+            None => 0,
+        };
+        self.gas_config = config;
+
+        self.emit(GAS_CONFIG_TOPIC, config);
+    }
+
+    /// Update the base fee from the gas used by the last block. This does
+    /// nothing if the base fee is disabled.
+    pub fn update_base_fee(&mut self, gas_used: u64) {
+        if let Some(gas_target) = self.gas_config.block_gas_target {
+            self.base_fee = next_base_fee(
+                self.base_fee,
+                gas_used,
+                gas_target,
+                self.gas_config.min_gas_price,
+            );
+            self.emit(BASE_FEE_TOPIC, self.base_fee);
+        }
+    }
+
+    /// Return the gas pricing in effect.
+    pub fn gas_price_info(&self) -> GasPriceInfo {
+        GasPriceInfo {
+            config: self.gas_config,
+            base_fee: self.base_fee,
+            burned: self.burned,
+        }
     }
 
     /// Get the root of the tree.
@@ -823,6 +1914,27 @@ impl TransferState {
         self.tree.opening(pos)
     }
 
+    /// Get the current root of the nullifiers tree.
+    pub fn nullifiers_root(&self) -> BlsScalar {
+        self.nullifiers.root()
+    }
+
+    /// Get the root of the nullifiers tree recorded at the end of the block
+    /// with the given height, if it is among the most recent ones.
+    pub fn nullifier_root(&self, block_height: u64) -> Option<BlsScalar> {
+        self.nullifier_roots
+            .iter()
+            .rev()
+            .find(|(height, _)| *height == block_height)
+            .map(|(_, root)| *root)
+    }
+
+    /// Get the proof of membership or non-membership of the given nullifier
+    /// against the current root of the nullifiers tree.
+    pub fn nullifier_proof(&self, nullifier: &BlsScalar) -> NullifierProof {
+        self.nullifiers.proof(nullifier)
+    }
+
     /// Takes some nullifiers and returns a vector containing the ones that
     /// already exists in the contract
     pub fn existing_nullifiers(
@@ -831,12 +1943,12 @@ impl TransferState {
     ) -> Vec<BlsScalar> {
         nullifiers
             .into_iter()
-            .filter_map(|n| self.nullifiers.get(&n).map(|_| n))
+            .filter(|n| self.nullifiers.contains(n))
             .collect()
     }
 
     pub fn account(&self, key: &AccountPublicKey) -> AccountData {
-        let key_bytes = key.to_raw_bytes();
+        let key_bytes = key.to_bytes();
         self.accounts
             .get(&key_bytes)
             .cloned()
@@ -844,18 +1956,213 @@ impl TransferState {
     }
 
     pub fn add_account_balance(&mut self, key: &AccountPublicKey, value: u64) {
-        let key_bytes = key.to_raw_bytes();
+        let key_bytes = key.to_bytes();
         let account = self.accounts.entry(key_bytes).or_insert(EMPTY_ACCOUNT);
         account.balance = account.balance.saturating_add(value);
+        self.update_account_leaf(key);
     }
 
     pub fn sub_account_balance(&mut self, key: &AccountPublicKey, value: u64) {
-        let key_bytes = key.to_raw_bytes();
+        let key_bytes = key.to_bytes();
         if let Some(account) = self.accounts.get_mut(&key_bytes) {
             account.balance = account.balance.saturating_sub(value);
+            self.update_account_leaf(key);
         }
     }
 
+    /// Sets the data - balance and nonce - of the given account, overwriting
+    /// any existing entry.
+    ///
+    /// This is intended for migrating the accounts of a previous version of the
+    /// contract, and as such preserves their nonces.
+    pub fn insert_account(
+        &mut self,
+        key: &AccountPublicKey,
+        account: AccountData,
+    ) {
+        self.accounts.insert(key.to_bytes(), account);
+        self.update_account_leaf(key);
+    }
+
+    /// Return the root of the merkle tree of Moonlight accounts.
+    pub fn accounts_root(&self) -> BlsScalar {
+        self.account_tree.root()
+    }
+
+    /// Return the data of the given account together with the proof of its
+    /// inclusion in the tree of accounts, if the account exists.
+    ///
+    /// Only inclusion can be proven: the accounts are placed in the tree in
+    /// the order they are created, so there is no proof that an account
+    /// doesn't exist, and `None` is not verifiable against the root.
+    pub fn account_proof(
+        &self,
+        key: &AccountPublicKey,
+    ) -> Option<AccountProof> {
+        let account = self.accounts.get(&key.to_bytes())?.clone();
+        let (position, opening) = self.account_tree.opening(key)?;
+
+        Some(AccountProof {
+            account,
+            position,
+            opening,
+        })
+    }
+
+    /// Update the leaf of the given account in the tree of accounts to commit
+    /// to its current data.
+    fn update_account_leaf(&mut self, key: &AccountPublicKey) {
+        let account = self.account(key);
+        self.account_tree.update(key, &account);
+    }
+
+    /// Return the escrow entry with the given identifier, if it exists.
+    pub fn escrow(&self, id: u64) -> Option<Escrow> {
+        self.escrows.get(&id).cloned()
+    }
+
+    /// Freeze a Moonlight account, preventing it from sending or receiving
+    /// funds until it is unfrozen.
+    pub fn freeze_account(&mut self, key: &AccountPublicKey) {
+        if self.frozen_accounts.insert(key.to_bytes(), *key).is_none() {
+            self.emit(FREEZE_TOPIC, *key);
+        }
+    }
+
+    /// Unfreeze a previously frozen Moonlight account.
+    pub fn unfreeze_account(&mut self, key: &AccountPublicKey) {
+        if self.frozen_accounts.remove(&key.to_bytes()).is_some() {
+            self.emit(UNFREEZE_TOPIC, *key);
+        }
+    }
+
+    /// Feeds the host with the public keys of the frozen accounts.
+    pub fn frozen_accounts(&self) {
+        for key in self.frozen_accounts.values() {
+            abi::feed(*key);
+        }
+    }
+
+    /// Panics if the account with the given key bytes is frozen.
+    fn assert_not_frozen(&self, key_bytes: &[u8; AccountPublicKey::SIZE]) {
+        if self.frozen_accounts.contains_key(key_bytes) {
+            panic!("{PANIC_ACCOUNT_FROZEN}");
+        }
+    }
+
+    /// Schedule new verifier data for the phoenix-circuit with the given
+    /// number of inputs and outputs, to be used from the activation height.
+    ///
+    /// The verifier data it replaces is still accepted for
+    /// [`VERIFIER_TRANSITION_WINDOW`] blocks after the activation height.
+    ///
+    /// # Panics
+    /// The function will panic if the activation height is lower than the
+    /// current block height, or not higher than the one of the last verifier
+    /// scheduled for the circuit.
+    pub fn set_verifier(&mut self, set: SetVerifier) {
+        if set.activation_height < abi::block_height() {
+            panic!("The activation height cannot be in the past");
+        }
+
+        let verifiers =
+            self.verifiers.entry((set.inputs, set.outputs)).or_default();
+
+        if let Some(last) = verifiers.last() {
+            if set.activation_height <= last.activation_height {
+                panic!("The activation height must be after the one of the last scheduled verifier");
+            }
+        }
+
+        verifiers.push(Verifier {
+            circuit_id: set.circuit_id,
+            vd: set.vd.clone(),
+            activation_height: set.activation_height,
+        });
+
+        self.emit(SET_VERIFIER_TOPIC, set);
+    }
+
+    /// Return the verifier data scheduled for the phoenix-circuit with the
+    /// given number of inputs and outputs, sorted by activation height.
+    pub fn verifiers(&self, inputs: u32, outputs: u32) -> Vec<Verifier> {
+        self.verifiers
+            .get(&(inputs, outputs))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Return the verifier data accepted at the current block height for the
+    /// phoenix-circuit with the given number of inputs and outputs.
+    ///
+    /// This is the verifier data with the highest activation height that has
+    /// been reached, together with the one it replaced if still within the
+    /// transition window.
+    fn tx_verifiers(&self, inputs: usize, outputs: usize) -> Vec<&[u8]> {
+        let block_height = abi::block_height();
+
+        let embedded = tx_circuit_verifier(inputs, outputs);
+        let scheduled = self
+            .verifiers
+            .get(&(inputs as u32, outputs as u32))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut vds = Vec::new();
+
+        match scheduled
+            .iter()
+            .rposition(|v| v.activation_height <= block_height)
+        {
+            None => vds.extend(embedded),
+            Some(i) => {
+                let active = &scheduled[i];
+                vds.push(active.vd.as_slice());
+
+                let window_end =
+                    active.activation_height + VERIFIER_TRANSITION_WINDOW;
+                if block_height < window_end {
+                    match i {
+                        0 => vds.extend(embedded),
+                        _ => vds.push(scheduled[i - 1].vd.as_slice()),
+                    }
+                }
+            }
+        }
+
+        vds
+    }
+
+    /// Checks the proof of a phoenix transaction against any of the verifier
+    /// data accepted for its number of inputs and outputs.
+    fn check_tx_proof(
+        &self,
+        tx: &PhoenixTransaction,
+    ) -> Result<(), &'static str> {
+        // fetch the verifier data
+        let num_inputs = tx.nullifiers().len();
+        let num_outputs = tx.outputs().len();
+        let vds = self.tx_verifiers(num_inputs, num_outputs);
+        if vds.is_empty() {
+            return Err(
+                "No circuit available for given number of inputs and outputs!",
+            );
+        }
+
+        // verify the proof
+        let valid = vds.into_iter().any(|vd| {
+            abi::verify_plonk(
+                vd.to_vec(),
+                tx.proof().to_vec(),
+                tx.public_inputs(),
+            )
+        });
+        if !valid {
+            return Err("Invalid transaction proof!");
+        }
+        Ok(())
+    }
+
     /// Return the balance of a given contract.
     pub fn contract_balance(&self, contract_id: &ContractId) -> u64 {
         self.contract_balances
@@ -875,6 +2182,7 @@ impl TransferState {
                 *v += value
             }
         }
+        transitory::record_contract_delta(contract, value.into());
     }
 
     pub(crate) fn sub_contract_balance(
@@ -890,6 +2198,10 @@ impl TransferState {
                     Err(Error::NotEnoughBalance)
                 } else {
                     *balance = bal;
+                    transitory::record_contract_delta(
+                        *address,
+                        -i128::from(value),
+                    );
 
                     Ok(())
                 }
@@ -917,17 +2229,6 @@ impl TransferState {
     }
 }
 
-fn verify_tx_proof(tx: &PhoenixTransaction) -> bool {
-    // fetch the verifier data
-    let num_inputs = tx.nullifiers().len();
-    let vd = tx_circuit_verifier(num_inputs)
-        .expect("No circuit available for given number of inputs!")
-        .to_vec();
-
-    // verify the proof
-    abi::verify_plonk(vd, tx.proof().to_vec(), tx.public_inputs())
-}
-
 #[cfg(test)]
 mod test_transfer {
     use super::*;
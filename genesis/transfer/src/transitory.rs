@@ -8,15 +8,15 @@
 //! context of a transaction in [`spend_and_execute`] and then discarded or
 //! considered void after a call to [`refund`].
 //!
-//! [`spend_and_execute`]: crate::spend_and_execute
-//! [`refund`]: crate::refund
+//! [`spend_and_execute`]: crate::wasm::spend_and_execute
+//! [`refund`]: crate::wasm::refund
 
 use core::mem;
 use core::ptr::{self, addr_of_mut};
 
 use alloc::vec::Vec;
 
-use dusk_core::abi::ContractId;
+use dusk_core::abi::{ContractError, ContractId};
 use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
 use dusk_core::transfer::{
     moonlight::Transaction as MoonlightTransaction,
@@ -77,6 +77,14 @@ pub struct OngoingTransaction {
     pub deposit: Deposit,
     /// The notes that have been inserted into the tree.
     pub notes: Vec<Note>,
+    /// The notes that have been inserted into the tree by withdrawals.
+    pub withdrawal_notes: Vec<Note>,
+    /// The change in balance of the accounts touched by the transaction.
+    pub account_deltas: Vec<(AccountPublicKey, i128)>,
+    /// The change in balance of the contracts touched by the transaction.
+    pub contract_deltas: Vec<(ContractId, i128)>,
+    /// The result of the contract call, if one was made.
+    pub call_result: Option<Result<Vec<u8>, ContractError>>,
 }
 
 static mut CURRENT_TX: Option<OngoingTransaction> = None;
@@ -114,6 +122,10 @@ pub fn put_transaction(tx: impl Into<Transaction>) {
             tx,
             deposit,
             notes: Vec::new(),
+            withdrawal_notes: Vec::new(),
+            account_deltas: Vec::new(),
+            contract_deltas: Vec::new(),
+            call_result: None,
         });
     }
 }
@@ -141,6 +153,58 @@ pub fn push_note(note: Note) {
     }
 }
 
+/// Push a note created by a withdrawal into the ongoing cache, if there is an
+/// ongoing transaction.
+pub fn push_withdrawal_note(note: Note) {
+    if let Some(ongoing) = ongoing_mut() {
+        ongoing.withdrawal_notes.push(note);
+    }
+}
+
+/// Record a change in the balance of an account, if there is an ongoing
+/// transaction.
+pub fn record_account_delta(key: &AccountPublicKey, delta: i128) {
+    if let Some(ongoing) = ongoing_mut() {
+        add_delta(&mut ongoing.account_deltas, *key, delta);
+    }
+}
+
+/// Record a change in the balance of a contract, if there is an ongoing
+/// transaction.
+pub fn record_contract_delta(contract: ContractId, delta: i128) {
+    if let Some(ongoing) = ongoing_mut() {
+        add_delta(&mut ongoing.contract_deltas, contract, delta);
+    }
+}
+
+/// Add a change in balance to the entry of the given owner in the list of
+/// deltas, inserting it at the end if it isn't present.
+pub fn add_delta<T: PartialEq>(
+    deltas: &mut Vec<(T, i128)>,
+    owner: T,
+    delta: i128,
+) {
+    match deltas.iter_mut().find(|(o, _)| *o == owner) {
+        Some((_, d)) => *d += delta,
+        None => deltas.push((owner, delta)),
+    }
+}
+
+/// Set the result of the contract call made by the ongoing transaction.
+pub fn set_call_result(result: Result<Vec<u8>, ContractError>) {
+    unsafe {
+        CURRENT_TX
+            .as_mut()
+            .expect("There must be an ongoing transaction")
+            .call_result = Some(result);
+    }
+}
+
+/// Get a mutable reference to the ongoing transaction, if there is one.
+fn ongoing_mut() -> Option<&'static mut OngoingTransaction> {
+    unsafe { CURRENT_TX.as_mut() }
+}
+
 /// Get a reference of the current ongoing transaction.
 pub fn transaction() -> &'static Transaction {
     unsafe {
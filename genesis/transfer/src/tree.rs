@@ -11,6 +11,8 @@ use dusk_core::transfer::phoenix::{
 };
 use dusk_core::{abi, BlsScalar};
 
+use crate::saturating_usize;
+
 /// The merkle tree that holds all phoenix-notes.
 ///
 /// This tree is append only. When a note is spend its `nullifier` will be
@@ -103,7 +105,7 @@ impl Tree {
         // leaves are strictly increasing in block-height. If this ever changes
         // - such as in the case of a sparsely populated tree - we should
         // annotate the tree and use `Tree::walk` instead.
-        let pos = pos as usize;
+        let pos = saturating_usize(pos);
         if self.leaves.len() < pos {
             return self.leaves[..0].iter();
         }
@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the validation of a transaction against the current state
+//! of the transfer contract, without applying it.
+
+use alloc::string::String;
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// The outcome of a single check performed on a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub enum CheckResult {
+    /// The check passed.
+    Passed,
+    /// The check failed, with the message the transaction would panic with.
+    Failed(String),
+    /// The check doesn't apply to the transaction model.
+    Skipped,
+}
+
+impl CheckResult {
+    /// Returns true if the check didn't fail.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        !matches!(self, Self::Failed(_))
+    }
+}
+
+impl From<Result<(), &str>> for CheckResult {
+    fn from(result: Result<(), &str>) -> Self {
+        match result {
+            Ok(()) => Self::Passed,
+            Err(msg) => Self::Failed(msg.into()),
+        }
+    }
+}
+
+/// Report of the checks performed by `validate_transaction` on a
+/// transaction, in the same order as they are performed when spending it.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ValidationReport {
+    /// The gas price is not zero.
+    pub gas_price: CheckResult,
+    /// The transaction targets the chain the contract is deployed on.
+    pub chain_id: CheckResult,
+    /// The Moonlight signature is valid and made by the sender.
+    pub signature: CheckResult,
+    /// None of the Moonlight accounts involved is frozen.
+    pub frozen: CheckResult,
+    /// The Moonlight sender has enough funds to cover the transaction.
+    pub funds: CheckResult,
+    /// The Moonlight nonce is the one following the sender's current nonce.
+    pub nonce: CheckResult,
+    /// The Phoenix root is a known root of the notes tree.
+    pub root: CheckResult,
+    /// None of the Phoenix nullifiers has already been spent.
+    pub nullifiers: CheckResult,
+    /// The Phoenix proof is valid.
+    pub proof: CheckResult,
+    /// The maximum fee that can be charged for the transaction, i.e. the gas
+    /// limit times the gas price.
+    pub max_fee: u64,
+}
+
+impl ValidationReport {
+    /// Returns true if all checks passed or were skipped, i.e. if the
+    /// transaction would be accepted in the current state.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        [
+            &self.gas_price,
+            &self.chain_id,
+            &self.signature,
+            &self.frozen,
+            &self.funds,
+            &self.nonce,
+            &self.root,
+            &self.nullifiers,
+            &self.proof,
+        ]
+        .into_iter()
+        .all(CheckResult::is_ok)
+    }
+}
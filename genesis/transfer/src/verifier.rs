@@ -0,0 +1,51 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Types related to the verifier data of the phoenix-circuits stored in the
+//! state of the transfer contract.
+
+use alloc::vec::Vec;
+
+use bytecheck::CheckBytes;
+use dusk_core::stake::EPOCH;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Topic for the event emitted when a verifier is scheduled.
+pub const SET_VERIFIER_TOPIC: &str = "set_verifier";
+
+/// Number of blocks after the activation of a verifier during which the
+/// verifier it replaces is still accepted.
+pub const VERIFIER_TRANSITION_WINDOW: u64 = EPOCH;
+
+/// The payload sent to the transfer contract to schedule new verifier data
+/// for the phoenix-circuit with the given number of inputs and outputs.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct SetVerifier {
+    /// The identifier of the circuit in the rusk-profile.
+    pub circuit_id: [u8; 32],
+    /// Number of inputs of the circuit.
+    pub inputs: u32,
+    /// Number of outputs of the circuit.
+    pub outputs: u32,
+    /// The verifier data of the circuit.
+    pub vd: Vec<u8>,
+    /// Block height from which the verifier data is used.
+    pub activation_height: u64,
+}
+
+/// Verifier data for a phoenix-circuit, as stored in the state of the
+/// transfer contract.
+#[derive(Debug, Clone, PartialEq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct Verifier {
+    /// The identifier of the circuit in the rusk-profile.
+    pub circuit_id: [u8; 32],
+    /// The verifier data of the circuit.
+    pub vd: Vec<u8>,
+    /// Block height from which the verifier data is used.
+    pub activation_height: u64,
+}
@@ -4,41 +4,24 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-// Note: all ID environment variables are set in the contracts build script
-const TX_CIRCUIT_1_2_VERIFIER: &[u8] = include_bytes!(concat!(
-    env!("RUSK_BUILT_KEYS_PATH"),
-    "/",
-    env!("ID_TXCIRCUITONETWO"),
-    ".vd"
-));
-const TX_CIRCUIT_2_2_VERIFIER: &[u8] = include_bytes!(concat!(
-    env!("RUSK_BUILT_KEYS_PATH"),
-    "/",
-    env!("ID_TXCIRCUITTWOTWO"),
-    ".vd"
-));
-const TX_CIRCUIT_3_2_VERIFIER: &[u8] = include_bytes!(concat!(
-    env!("RUSK_BUILT_KEYS_PATH"),
-    "/",
-    env!("ID_TXCIRCUITTHREETWO"),
-    ".vd"
-));
-const TX_CIRCUIT_4_2_VERIFIER: &[u8] = include_bytes!(concat!(
-    env!("RUSK_BUILT_KEYS_PATH"),
-    "/",
-    env!("ID_TXCIRCUITFOURTWO"),
-    ".vd"
-));
+// Note: the table is generated by the contracts build script, from the
+// phoenix-circuits stored in the rusk-profile
+include!(concat!(env!("OUT_DIR"), "/tx_circuit_verifiers.rs"));
 
-/// Verifier data for the phoenix-circuits.
-pub const fn tx_circuit_verifier(inputs: usize) -> Option<&'static [u8]> {
-    let vd = match inputs {
-        1 => TX_CIRCUIT_1_2_VERIFIER,
-        2 => TX_CIRCUIT_2_2_VERIFIER,
-        3 => TX_CIRCUIT_3_2_VERIFIER,
-        4 => TX_CIRCUIT_4_2_VERIFIER,
-        _ => return None,
-    };
+/// Verifier data for the phoenix-circuit with the given number of inputs and
+/// outputs.
+pub const fn tx_circuit_verifier(
+    inputs: usize,
+    outputs: usize,
+) -> Option<&'static [u8]> {
+    let mut i = 0;
+    while i < TX_CIRCUIT_VERIFIERS.len() {
+        let (circuit_inputs, circuit_outputs, vd) = TX_CIRCUIT_VERIFIERS[i];
+        if circuit_inputs == inputs && circuit_outputs == outputs {
+            return Some(vd);
+        }
+        i += 1;
+    }
 
-    Some(vd)
+    None
 }
@@ -0,0 +1,12 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use alloc::vec::Vec;
+
+// Note: the wrappers are generated by the contracts build script, from the
+// types declared for every entrypoint, so that a declaration not matching the
+// closure handling the call fails to compile
+include!(concat!(env!("OUT_DIR"), "/wrap.rs"));
@@ -4,117 +4,86 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+//! Helpers the test kit doesn't provide: the export of the state of any
+//! deployment of the transfer contract, and the chaining of the event
+//! digests.
+
 use std::sync::mpsc;
 
+use dusk_bytes::Serializable;
 use dusk_core::abi::ContractId;
-use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
-use dusk_core::transfer::moonlight::AccountData;
-use dusk_core::transfer::phoenix::{Note, NoteLeaf, ViewKey as PhoenixViewKey};
-use dusk_core::transfer::TRANSFER_CONTRACT;
 use dusk_core::BlsScalar;
 use dusk_vm::{Error as VMError, Session};
+use transfer_contract::migration::{
+    ExportChunk, ExportCursor, ExportSummary, StateItem,
+};
 
 const GAS_LIMIT: u64 = 0x10_000_000;
 
-pub fn contract_balance(
-    session: &mut Session,
-    contract: ContractId,
-) -> Result<u64, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "contract_balance", &contract, GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-pub fn chain_id(session: &mut Session) -> Result<u8, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "chain_id", &(), GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-// moonlight helper functions
-
-pub fn account(
-    session: &mut Session,
-    pk: &AccountPublicKey,
-) -> Result<AccountData, VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "account", pk, GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-// phoenix helper functions
-
-pub fn new_owned_notes_value(
-    session: &mut Session,
-    height: u64,
-    vk: PhoenixViewKey,
-) -> u64 {
-    let leaves = leaves_from_height(session, height)
-        .expect("fetching notes from the height should work");
-    let owned_notes =
-        filter_notes_owned_by(vk, leaves.into_iter().map(|leaf| leaf.note));
-    owned_notes_value(vk, &owned_notes)
+/// Chain the hash of an event onto the digest of the events preceding it in a
+/// block, in the same way as the contract.
+pub fn chain_event_digest(
+    digest: BlsScalar,
+    topic: &str,
+    data: &[u8],
+) -> BlsScalar {
+    let mut bytes = digest.to_bytes().to_vec();
+    bytes.extend_from_slice(&(topic.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(topic.as_bytes());
+    bytes.extend_from_slice(data);
+    BlsScalar::hash_to_scalar(&bytes)
 }
 
-pub fn owned_notes_value<'a, I: IntoIterator<Item = &'a Note>>(
-    vk: PhoenixViewKey,
-    notes: I,
-) -> u64 {
-    notes.into_iter().fold(0, |acc, note| {
-        acc + if vk.owns(note.stealth_address()) {
-            note.value(Some(&vk)).unwrap()
-        } else {
-            0
-        }
-    })
-}
-
-pub fn leaves_from_height(
+pub fn export_state(
     session: &mut Session,
-    height: u64,
-) -> Result<Vec<NoteLeaf>, VMError> {
+    contract: ContractId,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<Vec<StateItem>, VMError> {
     let (feeder, receiver) = mpsc::channel();
 
     session.feeder_call::<_, ()>(
-        TRANSFER_CONTRACT,
-        "leaves_from_height",
-        &height,
+        contract,
+        "export_state",
+        &(from, count_limit),
         GAS_LIMIT,
         feeder,
     )?;
 
     Ok(receiver
         .iter()
-        .map(|bytes| rkyv::from_bytes(&bytes).expect("Should return leaves"))
+        .map(|bytes| rkyv::from_bytes(&bytes).expect("Should return items"))
         .collect())
 }
 
-pub fn update_root(session: &mut Session) -> Result<(), VMError> {
-    session
-        .call(TRANSFER_CONTRACT, "update_root", &(), GAS_LIMIT)
-        .map(|r| r.data)
-}
-
-/// Returns vector of notes owned by a given view key.
-pub fn filter_notes_owned_by<I: IntoIterator<Item = Note>>(
-    vk: PhoenixViewKey,
-    iter: I,
-) -> Vec<Note> {
-    iter.into_iter()
-        .filter(|note| vk.owns(note.stealth_address()))
-        .collect()
-}
-
-pub fn existing_nullifiers(
+pub fn export_summary(
     session: &mut Session,
-    nullifiers: &Vec<BlsScalar>,
-) -> Result<Vec<BlsScalar>, VMError> {
+    contract: ContractId,
+    summary: ExportSummary,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<ExportChunk, VMError> {
     session
         .call(
-            TRANSFER_CONTRACT,
-            "existing_nullifiers",
-            &nullifiers.clone(),
+            contract,
+            "export_summary",
+            &(summary, from, count_limit),
             GAS_LIMIT,
         )
         .map(|r| r.data)
 }
+
+/// Return the summary of the whole exported state, chained in a single chunk.
+pub fn summarize_export(
+    session: &mut Session,
+    contract: ContractId,
+) -> Result<ExportSummary, VMError> {
+    let chunk = export_summary(
+        session,
+        contract,
+        ExportSummary::EMPTY,
+        ExportCursor::START,
+        0,
+    )?;
+    Ok(chunk.summary)
+}
@@ -4,12 +4,22 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use contracts_test_kit::notes::{filter_notes_owned_by, owned_notes_value};
+use contracts_test_kit::transfer::{
+    account, account_proof, accounts_root, block_event_digest as event_digest,
+    chain_id, contract_balance, escrow, existing_nullifiers, frozen_accounts,
+    gas_price_info, leaves_from_height, state_version, sync_accounts,
+    update_root, validate_transaction,
+};
+use dusk_bytes::Serializable;
 use dusk_core::abi::{ContractError, ContractId};
 use dusk_core::signatures::bls::{
     PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
 };
 use dusk_core::transfer::data::{ContractCall, TransactionData};
-use dusk_core::transfer::moonlight::Transaction as MoonlightTransaction;
+use dusk_core::transfer::moonlight::{
+    AccountData, Transaction as MoonlightTransaction,
+};
 use dusk_core::transfer::phoenix::{
     Note, PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
     ViewKey as PhoenixViewKey,
@@ -18,18 +28,28 @@ use dusk_core::transfer::withdraw::{
     Withdraw, WithdrawReceiver, WithdrawReplayToken,
 };
 use dusk_core::transfer::{
-    ContractToAccount, ContractToContract, Transaction, TRANSFER_CONTRACT,
+    ContractToAccount, ContractToContract, Transaction, PANIC_NONCE_NOT_READY,
+    TRANSFER_CONTRACT,
 };
-use dusk_core::{dusk, JubJubScalar, LUX};
+use dusk_core::{dusk, BlsScalar, JubJubScalar, LUX};
 use dusk_vm::{execute, ContractData, ExecutionConfig, Session, VM};
 use ff::Field;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use transfer_contract::escrow::{CreateEscrow, EscrowBeneficiary};
+use transfer_contract::gas::GasConfig;
+use transfer_contract::migration::{
+    ExportCursor, ExportSummary, StateItem, STATE_VERSION,
+};
+use transfer_contract::receipt::{
+    DepositStatus, TransactionReceipt, RECEIPT_TOPIC,
+};
+use transfer_contract::scheduled::ScheduledWithdraw;
+use transfer_contract::validation::CheckResult;
 
 pub mod common;
 use crate::common::utils::{
-    account, chain_id, contract_balance, existing_nullifiers,
-    filter_notes_owned_by, leaves_from_height, owned_notes_value, update_root,
+    chain_event_digest, export_state, export_summary, summarize_export,
 };
 
 const MOONLIGHT_GENESIS_VALUE: u64 = dusk(1_000.0);
@@ -48,6 +68,11 @@ const BOB_ID: ContractId = {
     bytes[0] = 0xFB;
     ContractId::from_bytes(bytes)
 };
+const NEW_TRANSFER_ID: ContractId = {
+    let mut bytes = [0u8; 32];
+    bytes[0] = 0xFC;
+    ContractId::from_bytes(bytes)
+};
 
 const OWNER: [u8; 32] = [0; 32];
 const CHAIN_ID: u8 = 0xFA;
@@ -207,6 +232,151 @@ fn transfer() {
     );
 }
 
+/// Check the proofs of the moonlight accounts verify against the accounts
+/// root, and that they follow the changes made by a transfer.
+#[test]
+fn account_proofs() {
+    const TRANSFER_VALUE: u64 = dusk(1.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
+
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_sender_pk);
+
+    let root = accounts_root(session).expect("Getting the root should succeed");
+    let proof = account_proof(session, &moonlight_sender_pk)
+        .expect("Getting the proof should succeed")
+        .expect("The sender account should exist");
+
+    assert_eq!(proof.account.balance, MOONLIGHT_GENESIS_VALUE);
+    assert_eq!(proof.account.nonce, MOONLIGHT_GENESIS_NONCE);
+    assert!(
+        proof.verify(&moonlight_sender_pk, &root),
+        "The proof should verify against the root"
+    );
+    assert!(
+        !proof.verify(&moonlight_receiver_pk, &root),
+        "The proof should not verify for another account"
+    );
+    assert!(
+        account_proof(session, &moonlight_receiver_pk)
+            .expect("Getting the proof should succeed")
+            .is_none(),
+        "There should be no proof for an account that doesn't exist"
+    );
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sender_sk,
+        Some(moonlight_receiver_pk),
+        TRANSFER_VALUE,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        None::<TransactionData>,
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let gas_spent = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed")
+        .gas_spent;
+
+    let new_root =
+        accounts_root(session).expect("Getting the root should succeed");
+    assert_ne!(root, new_root, "The root should change after a transfer");
+    assert!(
+        !proof.verify(&moonlight_sender_pk, &new_root),
+        "The old proof should not verify against the new root"
+    );
+
+    let sender_proof = account_proof(session, &moonlight_sender_pk)
+        .expect("Getting the proof should succeed")
+        .expect("The sender account should exist");
+    let receiver_proof = account_proof(session, &moonlight_receiver_pk)
+        .expect("Getting the proof should succeed")
+        .expect("The receiver account should exist");
+
+    assert_eq!(
+        sender_proof.account.balance,
+        MOONLIGHT_GENESIS_VALUE - gas_spent - TRANSFER_VALUE
+    );
+    assert_eq!(sender_proof.account.nonce, MOONLIGHT_GENESIS_NONCE + 1);
+    assert_eq!(receiver_proof.account.balance, TRANSFER_VALUE);
+    assert!(sender_proof.verify(&moonlight_sender_pk, &new_root));
+    assert!(receiver_proof.verify(&moonlight_receiver_pk, &new_root));
+}
+
+/// Validate moonlight transactions without executing them.
+#[test]
+fn validate_transfer() {
+    const TRANSFER_VALUE: u64 = dusk(1.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
+
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_sender_pk);
+
+    let create_transaction = |value: u64, nonce: u64| {
+        Transaction::moonlight(
+            &moonlight_sender_sk,
+            Some(moonlight_receiver_pk),
+            value,
+            0,
+            GAS_LIMIT,
+            LUX,
+            nonce,
+            CHAIN_ID,
+            None::<TransactionData>,
+        )
+        .expect("Creating moonlight transaction should succeed")
+    };
+
+    let transaction = create_transaction(TRANSFER_VALUE, 1);
+    let report = validate_transaction(session, &transaction)
+        .expect("Validating the transaction should succeed");
+    assert!(report.is_valid(), "The transaction should be valid");
+    assert_eq!(report.signature, CheckResult::Passed);
+    assert_eq!(report.proof, CheckResult::Skipped);
+    assert_eq!(report.max_fee, GAS_LIMIT * LUX);
+
+    let transaction = create_transaction(TRANSFER_VALUE, 2);
+    let report = validate_transaction(session, &transaction)
+        .expect("Validating the transaction should succeed");
+    assert!(!report.is_valid(), "The transaction should be invalid");
+    assert_eq!(report.funds, CheckResult::Passed);
+    assert_eq!(
+        report.nonce,
+        CheckResult::Failed(PANIC_NONCE_NOT_READY.into())
+    );
+
+    let transaction = create_transaction(MOONLIGHT_GENESIS_VALUE, 1);
+    let report = validate_transaction(session, &transaction)
+        .expect("Validating the transaction should succeed");
+    assert!(!report.is_valid(), "The transaction should be invalid");
+    assert_eq!(
+        report.funds,
+        CheckResult::Failed("Account doesn't have enough funds".into())
+    );
+    assert_eq!(report.nonce, CheckResult::Passed);
+
+    // validating doesn't change the state
+    let sender_account = account(session, &moonlight_sender_pk)
+        .expect("Getting the sender account should succeed");
+    assert_eq!(sender_account.balance, MOONLIGHT_GENESIS_VALUE);
+    assert_eq!(sender_account.nonce, MOONLIGHT_GENESIS_NONCE);
+}
+
 /// Perform a transfer between moonlight accounts, where the left-over gas is
 /// refunded to a different account than the sender.
 #[test]
@@ -279,10 +449,11 @@ fn transfer_with_refund() {
     );
 }
 
-/// Checks if a transaction fails when the gas-price is 0.
+/// Export the state of the transfer contract in chunks, import it into a
+/// newly deployed transfer contract, and check the import against the export.
 #[test]
-fn transfer_gas_fails() {
-    const TRANSFER_VALUE: u64 = dusk(1.0);
+fn export_import_state() {
+    const CHUNK_SIZE: u64 = 2;
 
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
@@ -294,169 +465,621 @@ fn transfer_gas_fails() {
 
     let session = &mut instantiate(&moonlight_sender_pk);
 
-    // make sure the receiver has 0 balance before the tx
-    let receiver_account = account(session, &moonlight_receiver_pk)
-        .expect("Getting the receiver account should succeed");
-    assert_eq!(
-        receiver_account.balance, 0,
-        "The receiver account should be empty"
-    );
-
     let transaction = Transaction::moonlight(
         &moonlight_sender_sk,
         Some(moonlight_receiver_pk),
-        TRANSFER_VALUE,
+        dusk(1.0),
         0,
         GAS_LIMIT,
-        0,
+        LUX,
         MOONLIGHT_GENESIS_NONCE + 1,
         CHAIN_ID,
         None::<TransactionData>,
     )
     .expect("Creating moonlight transaction should succeed");
+    execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
+    update_root(session).expect("Updating the root should succeed");
 
-    let result = execute(session, &transaction, &NO_CONFIG);
+    let transfer_bytecode = include_bytes!(
+        "../../../target/dusk/wasm64-unknown-unknown/release/transfer_contract.wasm"
+    );
+    session
+        .deploy(
+            transfer_bytecode,
+            ContractData::builder()
+                .owner(OWNER)
+                .contract_id(NEW_TRANSFER_ID),
+            GAS_LIMIT,
+        )
+        .expect("Deploying the new transfer contract should succeed");
 
+    let summary = summarize_export(session, TRANSFER_CONTRACT)
+        .expect("Querying the export summary should succeed");
+
+    let items = export_state(
+        session,
+        TRANSFER_CONTRACT,
+        ExportCursor::START,
+        CHUNK_SIZE,
+    )
+    .expect("Exporting the state should succeed");
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "import_state",
+        &items,
+        GAS_LIMIT,
+    );
     assert!(
         result.is_err(),
-        "Transaction should fail due to zero gas price"
+        "Importing on top of an existing state should fail"
     );
 
-    // Since the transaction failed, balances should remain the same
-    let sender_account = account(session, &moonlight_sender_pk)
-        .expect("Getting the sender account should succeed");
-    let receiver_account = account(session, &moonlight_receiver_pk)
-        .expect("Getting the receiver account should succeed");
-
+    // chain the summary one chunk at a time, as the items are exported
+    let mut chunk_summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    let mut exported = 0;
+    while let Some(cursor) = from {
+        let items =
+            export_state(session, TRANSFER_CONTRACT, cursor, CHUNK_SIZE)
+                .expect("Exporting the state should succeed");
+        exported += items.len() as u64;
+
+        session
+            .call::<_, ()>(NEW_TRANSFER_ID, "import_state", &items, GAS_LIMIT)
+            .expect("Importing the state should succeed");
+
+        let chunk = export_summary(
+            session,
+            TRANSFER_CONTRACT,
+            chunk_summary,
+            cursor,
+            CHUNK_SIZE,
+        )
+        .expect("Querying the export summary should succeed");
+        chunk_summary = chunk.summary;
+        from = chunk.next;
+    }
+    assert_eq!(exported, summary.items, "All items should be exported");
     assert_eq!(
-        sender_account.balance, MOONLIGHT_GENESIS_VALUE,
-        "The sender account should still have the genesis value"
+        chunk_summary, summary,
+        "Chaining the summary in chunks should not change it"
     );
-    assert_eq!(
-        receiver_account.balance, 0,
-        "The receiver account should still be empty"
+
+    let wrong_summary = ExportSummary {
+        items: summary.items + 1,
+        ..summary
+    };
+    let result = session.call::<_, ()>(
+        NEW_TRANSFER_ID,
+        "finish_import",
+        &wrong_summary,
+        GAS_LIMIT,
+    );
+    assert!(
+        result.is_err(),
+        "Finishing with a wrong summary should fail"
+    );
+
+    session
+        .call::<_, ()>(NEW_TRANSFER_ID, "finish_import", &summary, GAS_LIMIT)
+        .expect("Finishing the import should succeed");
+
+    let new_summary = summarize_export(session, NEW_TRANSFER_ID)
+        .expect("Querying the export summary should succeed");
+    assert_eq!(new_summary, summary);
+
+    let result = session.call::<_, ()>(
+        NEW_TRANSFER_ID,
+        "import_state",
+        &items,
+        GAS_LIMIT,
+    );
+    assert!(
+        result.is_err(),
+        "Importing after finishing the import should fail"
     );
 }
 
-/// Performs a simple contract-call.
+/// Checks the digest of the events of a block is the hash chain over the
+/// events the contract emitted in it, and that it is kept after the block.
 #[test]
-fn alice_ping() {
+fn block_event_digest() {
+    const BLOCK_HEIGHT: u64 = 1;
+
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
-    let moonlight_sk = AccountSecretKey::random(rng);
-    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
 
-    let session = &mut instantiate(&moonlight_pk);
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
 
-    let contract_call = ContractCall::new(ALICE_ID, "ping");
+    let session = &mut instantiate(&moonlight_sender_pk);
+
+    let digest = event_digest(session, BLOCK_HEIGHT)
+        .expect("Querying the event digest should succeed");
+    assert_eq!(digest, None, "No event should be emitted in the block yet");
 
     let transaction = Transaction::moonlight(
-        &moonlight_sk,
-        None,
-        0,
+        &moonlight_sender_sk,
+        Some(moonlight_receiver_pk),
+        dusk(1.0),
         0,
         GAS_LIMIT,
         LUX,
         MOONLIGHT_GENESIS_NONCE + 1,
         CHAIN_ID,
-        Some(contract_call),
+        None::<TransactionData>,
     )
     .expect("Creating moonlight transaction should succeed");
 
-    let gas_spent = execute(session, &transaction, &NO_CONFIG)
-        .expect("Transaction should succeed")
-        .gas_spent;
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
 
-    println!("CONTRACT PING: {} gas", gas_spent);
+    let expected_digest = receipt
+        .events
+        .iter()
+        .filter(|event| event.source == TRANSFER_CONTRACT)
+        .fold(BlsScalar::zero(), |digest, event| {
+            chain_event_digest(digest, &event.topic, &event.data)
+        });
 
-    let moonlight_account = account(session, &moonlight_pk)
-        .expect("Getting the account should succeed");
+    let digest = event_digest(session, BLOCK_HEIGHT)
+        .expect("Querying the event digest should succeed");
+    assert_eq!(digest, Some(expected_digest));
+
+    update_root(session).expect("Updating the root should succeed");
 
+    let digest = event_digest(session, BLOCK_HEIGHT)
+        .expect("Querying the event digest should succeed");
     assert_eq!(
-        moonlight_account.balance,
-        MOONLIGHT_GENESIS_VALUE - gas_spent,
-        "The account should decrease by the amount spent"
+        digest,
+        Some(expected_digest),
+        "The digest should be kept after the block"
     );
 }
 
-/// Convert moonlight DUSK into phoenix DUSK.
+/// Checks the state is at the latest version of its layout, and that
+/// migrations from another version or to an unknown version are refused.
 #[test]
-fn convert_to_phoenix() {
-    const CONVERSION_VALUE: u64 = dusk(1.0);
-
+fn migrate_state() {
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
-    let phoenix_sk = PhoenixSecretKey::random(rng);
-    let phoenix_vk = PhoenixViewKey::from(&phoenix_sk);
-    let phoenix_pk = PhoenixPublicKey::from(&phoenix_sk);
+    let moonlight_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
 
-    let moonlight_sk = AccountSecretKey::random(rng);
-    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+    let session = &mut instantiate(&moonlight_pk);
 
-    let mut session = &mut instantiate(&moonlight_pk);
+    let version = state_version(session)
+        .expect("Querying the state version should succeed");
+    assert_eq!(version, STATE_VERSION);
 
-    // make sure that the phoenix-key doesn't own any notes yet
-    let leaves = leaves_from_height(session, 0)
-        .expect("getting the notes should succeed");
-    let notes = filter_notes_owned_by(
-        phoenix_vk,
-        leaves.into_iter().map(|leaf| leaf.note),
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "migrate",
+        &(STATE_VERSION - 1, STATE_VERSION),
+        GAS_LIMIT,
+    );
+    assert!(
+        result.is_err(),
+        "Migrating from a version the state is not at should fail"
     );
-    assert_eq!(notes.len(), 0, "There should be no notes at this height");
 
-    // generate a new note stealth-address and note-sk for the conversion
-    let address =
-        phoenix_pk.gen_stealth_address(&JubJubScalar::random(&mut *rng));
-    let note_sk = phoenix_sk.gen_note_sk(&address);
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "migrate",
+        &(STATE_VERSION, STATE_VERSION + 1),
+        GAS_LIMIT,
+    );
+    assert!(
+        result.is_err(),
+        "Migrating to an unknown version should fail"
+    );
 
-    // the moonlight replay token
-    let nonce = MOONLIGHT_GENESIS_NONCE + 1;
+    let version = state_version(session)
+        .expect("Querying the state version should succeed");
+    assert_eq!(version, STATE_VERSION, "The version should be unchanged");
+}
 
-    // a conversion is a deposit into the transfer-contract paired with a
-    // withdrawal
-    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "convert")
-        .with_args(&Withdraw::new(
-            rng,
-            &note_sk,
-            TRANSFER_CONTRACT,
-            // set the conversion-value as a withdrawal
-            CONVERSION_VALUE,
-            WithdrawReceiver::Phoenix(address),
-            WithdrawReplayToken::Moonlight(nonce),
-        ))
-        .expect("should serialize conversion correctly");
+/// Checks transactions paying less than the minimum gas price or the base fee
+/// are rejected, and that the base fee follows the block gas usage.
+#[test]
+fn gas_pricing() {
+    const BLOCK_GAS_TARGET: u64 = 1_000_000;
 
-    let tx = Transaction::moonlight(
-        &moonlight_sk,
-        None,
-        0,
-        // set the conversion-value as the deposit
-        CONVERSION_VALUE,
-        GAS_LIMIT,
-        LUX,
-        nonce,
-        CHAIN_ID,
-        Some(contract_call),
-    )
-    .expect("Creating moonlight transaction should succeed");
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
-    let gas_spent = execute(&mut session, &tx, &NO_CONFIG)
-        .expect("Executing transaction should succeed")
-        .gas_spent;
-    update_root(session).expect("Updating the root should succeed");
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
 
-    println!("CONVERT TO PHOENIX: {} gas", gas_spent);
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
 
-    let moonlight_account = account(&mut session, &moonlight_pk)
-        .expect("Getting account should succeed");
+    let session = &mut instantiate(&moonlight_sender_pk);
 
-    assert_eq!(
-        moonlight_account.balance,
-        MOONLIGHT_GENESIS_VALUE - gas_spent - CONVERSION_VALUE,
-        "The moonlight account should have had the conversion value subtracted along with gas spent"
+    let create_transaction = |gas_price: u64| {
+        Transaction::moonlight(
+            &moonlight_sender_sk,
+            Some(moonlight_receiver_pk),
+            dusk(1.0),
+            0,
+            GAS_LIMIT,
+            gas_price,
+            MOONLIGHT_GENESIS_NONCE + 1,
+            CHAIN_ID,
+            None::<TransactionData>,
+        )
+        .expect("Creating moonlight transaction should succeed")
+    };
+
+    let info =
+        gas_price_info(session).expect("Querying the gas price should succeed");
+    assert_eq!(info.config, GasConfig::DEFAULT);
+    assert_eq!(info.min_accepted_gas_price(), 1);
+
+    // a block gas target of zero is rejected
+    let config = GasConfig {
+        min_gas_price: 2 * LUX,
+        block_gas_target: Some(0),
+    };
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "set_gas_config",
+        &config,
+        GAS_LIMIT,
+    );
+    assert!(
+        result.is_err(),
+        "Setting a zero block gas target should fail"
     );
 
-    let leaves = leaves_from_height(session, 1)
+    let config = GasConfig {
+        block_gas_target: Some(BLOCK_GAS_TARGET),
+        ..config
+    };
+    session
+        .call::<_, ()>(TRANSFER_CONTRACT, "set_gas_config", &config, GAS_LIMIT)
+        .expect("Setting the gas config should succeed");
+
+    // a full block raises the base fee, by at least one
+    session
+        .call::<_, ()>(
+            TRANSFER_CONTRACT,
+            "update_base_fee",
+            &(2 * BLOCK_GAS_TARGET),
+            GAS_LIMIT,
+        )
+        .expect("Updating the base fee should succeed");
+
+    let info =
+        gas_price_info(session).expect("Querying the gas price should succeed");
+    assert_eq!(info.config, config);
+    assert_eq!(info.base_fee, 3 * LUX);
+    assert_eq!(info.min_accepted_gas_price(), 3 * LUX);
+
+    let transaction = create_transaction(2 * LUX);
+    let report = validate_transaction(session, &transaction)
+        .expect("Validating the transaction should succeed");
+    assert_eq!(
+        report.gas_price,
+        CheckResult::Failed("Gas price too low!".into())
+    );
+    let result = execute(session, &transaction, &NO_CONFIG);
+    assert!(result.is_err(), "Paying less than the base fee should fail");
+
+    let transaction = create_transaction(4 * LUX);
+    let gas_spent = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed")
+        .gas_spent;
+
+    let info =
+        gas_price_info(session).expect("Querying the gas price should succeed");
+    assert_eq!(
+        info.burned,
+        gas_spent * 3 * LUX,
+        "The base-fee part of the fee should be burned"
+    );
+}
+
+/// Checks that a transfer to a frozen account fails, and succeeds again once
+/// the account is unfrozen.
+#[test]
+fn transfer_frozen_account() {
+    const TRANSFER_VALUE: u64 = dusk(1.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
+
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_sender_pk);
+
+    session
+        .call::<_, ()>(
+            TRANSFER_CONTRACT,
+            "freeze_account",
+            &moonlight_receiver_pk,
+            GAS_LIMIT,
+        )
+        .expect("Freezing the account should succeed");
+
+    let frozen = frozen_accounts(session)
+        .expect("Getting the frozen accounts should succeed");
+    assert_eq!(frozen, vec![moonlight_receiver_pk]);
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sender_sk,
+        Some(moonlight_receiver_pk),
+        TRANSFER_VALUE,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        None::<TransactionData>,
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let result = execute(session, &transaction, &NO_CONFIG);
+    assert!(
+        result.is_err(),
+        "Transaction should fail due to the receiver being frozen"
+    );
+
+    let receiver_account = account(session, &moonlight_receiver_pk)
+        .expect("Getting the receiver account should succeed");
+    assert_eq!(
+        receiver_account.balance, 0,
+        "The receiver account should still be empty"
+    );
+
+    session
+        .call::<_, ()>(
+            TRANSFER_CONTRACT,
+            "unfreeze_account",
+            &moonlight_receiver_pk,
+            GAS_LIMIT,
+        )
+        .expect("Unfreezing the account should succeed");
+
+    let frozen = frozen_accounts(session)
+        .expect("Getting the frozen accounts should succeed");
+    assert!(frozen.is_empty(), "No account should be frozen");
+
+    execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed once the receiver is unfrozen");
+
+    let receiver_account = account(session, &moonlight_receiver_pk)
+        .expect("Getting the receiver account should succeed");
+    assert_eq!(
+        receiver_account.balance, TRANSFER_VALUE,
+        "The receiver account should have the transferred value"
+    );
+}
+
+/// Inserts an account as done when migrating from a previous version of the
+/// contract, and checks it is both queryable and fed by `sync_accounts` under
+/// its compressed public key.
+#[test]
+fn insert_account() {
+    const INSERTED_VALUE: u64 = dusk(42.0);
+    const INSERTED_NONCE: u64 = 7;
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+    let inserted_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    let inserted_account = AccountData {
+        balance: INSERTED_VALUE,
+        nonce: INSERTED_NONCE,
+    };
+
+    session
+        .call::<_, ()>(
+            TRANSFER_CONTRACT,
+            "insert_account",
+            &(inserted_pk, inserted_account.clone()),
+            GAS_LIMIT,
+        )
+        .expect("Inserting the account should succeed");
+
+    let queried_account = account(session, &inserted_pk)
+        .expect("Getting the account should succeed");
+    assert_eq!(queried_account, inserted_account);
+
+    let accounts = sync_accounts(session, 0, 0)
+        .expect("Syncing the accounts should succeed");
+    assert_eq!(accounts.len(), 2, "There should be two accounts");
+    assert!(
+        accounts.contains(&(inserted_account, inserted_pk.to_bytes())),
+        "The inserted account should be fed with its compressed key"
+    );
+}
+
+/// Checks if a transaction fails when the gas-price is 0.
+#[test]
+fn transfer_gas_fails() {
+    const TRANSFER_VALUE: u64 = dusk(1.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sender_sk = AccountSecretKey::random(rng);
+    let moonlight_sender_pk = AccountPublicKey::from(&moonlight_sender_sk);
+
+    let moonlight_receiver_pk =
+        AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_sender_pk);
+
+    // make sure the receiver has 0 balance before the tx
+    let receiver_account = account(session, &moonlight_receiver_pk)
+        .expect("Getting the receiver account should succeed");
+    assert_eq!(
+        receiver_account.balance, 0,
+        "The receiver account should be empty"
+    );
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sender_sk,
+        Some(moonlight_receiver_pk),
+        TRANSFER_VALUE,
+        0,
+        GAS_LIMIT,
+        0,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        None::<TransactionData>,
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let result = execute(session, &transaction, &NO_CONFIG);
+
+    assert!(
+        result.is_err(),
+        "Transaction should fail due to zero gas price"
+    );
+
+    // Since the transaction failed, balances should remain the same
+    let sender_account = account(session, &moonlight_sender_pk)
+        .expect("Getting the sender account should succeed");
+    let receiver_account = account(session, &moonlight_receiver_pk)
+        .expect("Getting the receiver account should succeed");
+
+    assert_eq!(
+        sender_account.balance, MOONLIGHT_GENESIS_VALUE,
+        "The sender account should still have the genesis value"
+    );
+    assert_eq!(
+        receiver_account.balance, 0,
+        "The receiver account should still be empty"
+    );
+}
+
+/// Performs a simple contract-call.
+#[test]
+fn alice_ping() {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    let contract_call = ContractCall::new(ALICE_ID, "ping");
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let gas_spent = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed")
+        .gas_spent;
+
+    println!("CONTRACT PING: {} gas", gas_spent);
+
+    let moonlight_account = account(session, &moonlight_pk)
+        .expect("Getting the account should succeed");
+
+    assert_eq!(
+        moonlight_account.balance,
+        MOONLIGHT_GENESIS_VALUE - gas_spent,
+        "The account should decrease by the amount spent"
+    );
+}
+
+/// Convert moonlight DUSK into phoenix DUSK.
+#[test]
+fn convert_to_phoenix() {
+    const CONVERSION_VALUE: u64 = dusk(1.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let phoenix_sk = PhoenixSecretKey::random(rng);
+    let phoenix_vk = PhoenixViewKey::from(&phoenix_sk);
+    let phoenix_pk = PhoenixPublicKey::from(&phoenix_sk);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let mut session = &mut instantiate(&moonlight_pk);
+
+    // make sure that the phoenix-key doesn't own any notes yet
+    let leaves = leaves_from_height(session, 0)
+        .expect("getting the notes should succeed");
+    let notes = filter_notes_owned_by(
+        phoenix_vk,
+        leaves.into_iter().map(|leaf| leaf.note),
+    );
+    assert_eq!(notes.len(), 0, "There should be no notes at this height");
+
+    // generate a new note stealth-address and note-sk for the conversion
+    let address =
+        phoenix_pk.gen_stealth_address(&JubJubScalar::random(&mut *rng));
+    let note_sk = phoenix_sk.gen_note_sk(&address);
+
+    // the moonlight replay token
+    let nonce = MOONLIGHT_GENESIS_NONCE + 1;
+
+    // a conversion is a deposit into the transfer-contract paired with a
+    // withdrawal
+    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "convert")
+        .with_args(&Withdraw::new(
+            rng,
+            &note_sk,
+            TRANSFER_CONTRACT,
+            // set the conversion-value as a withdrawal
+            CONVERSION_VALUE,
+            WithdrawReceiver::Phoenix(address),
+            WithdrawReplayToken::Moonlight(nonce),
+        ))
+        .expect("should serialize conversion correctly");
+
+    let tx = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        // set the conversion-value as the deposit
+        CONVERSION_VALUE,
+        GAS_LIMIT,
+        LUX,
+        nonce,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let gas_spent = execute(&mut session, &tx, &NO_CONFIG)
+        .expect("Executing transaction should succeed")
+        .gas_spent;
+    update_root(session).expect("Updating the root should succeed");
+
+    println!("CONVERT TO PHOENIX: {} gas", gas_spent);
+
+    let moonlight_account = account(&mut session, &moonlight_pk)
+        .expect("Getting account should succeed");
+
+    assert_eq!(
+        moonlight_account.balance,
+        MOONLIGHT_GENESIS_VALUE - gas_spent - CONVERSION_VALUE,
+        "The moonlight account should have had the conversion value subtracted along with gas spent"
+    );
+
+    let leaves = leaves_from_height(session, 1)
         .expect("getting the notes should succeed");
     let notes = filter_notes_owned_by(
         phoenix_vk,
@@ -471,11 +1094,138 @@ fn convert_to_phoenix() {
     );
 }
 
-/// Converting phoenix DUSK into moonlight DUSK with a moonlight transaction
-/// should fail.
+/// Converting phoenix DUSK into moonlight DUSK with a moonlight transaction
+/// should fail.
+#[test]
+fn convert_to_moonlight_fails() {
+    const CONVERSION_VALUE: u64 = dusk(10.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let phoenix_sk = PhoenixSecretKey::random(rng);
+    let phoenix_vk = PhoenixViewKey::from(&phoenix_sk);
+    let phoenix_pk = PhoenixPublicKey::from(&phoenix_sk);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let mut session = &mut instantiate(&moonlight_pk);
+
+    // Add a phoenix note with the conversion-value
+    let value_blinder = JubJubScalar::random(&mut *rng);
+    let sender_blinder = [
+        JubJubScalar::random(&mut *rng),
+        JubJubScalar::random(&mut *rng),
+    ];
+    let note = Note::obfuscated(
+        rng,
+        &phoenix_pk,
+        &phoenix_pk,
+        CONVERSION_VALUE,
+        value_blinder,
+        sender_blinder,
+    );
+    // get the nullifier for later check
+    let nullifier = note.gen_nullifier(&phoenix_sk);
+    // push genesis phoenix note to the contract
+    session
+        .call::<_, Note>(
+            TRANSFER_CONTRACT,
+            "push_note",
+            &(0u64, note),
+            GAS_LIMIT,
+        )
+        .expect("Pushing genesis note should succeed");
+
+    // update the root after the notes have been inserted
+    update_root(&mut session).expect("Updating the root should succeed");
+
+    // make sure that the phoenix-key doesn't own any notes yet
+    let leaves = leaves_from_height(session, 0)
+        .expect("getting the notes should succeed");
+    let notes = filter_notes_owned_by(
+        phoenix_vk,
+        leaves.into_iter().map(|leaf| leaf.note),
+    );
+    assert_eq!(notes.len(), 1, "There should be one note at this height");
+
+    // a conversion is a deposit into the transfer-contract paired with a
+    // withdrawal
+    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "convert")
+        .with_args(&Withdraw::new(
+            rng,
+            &moonlight_sk,
+            TRANSFER_CONTRACT,
+            // set the conversion-value as a withdrawal
+            CONVERSION_VALUE,
+            WithdrawReceiver::Moonlight(moonlight_pk),
+            WithdrawReplayToken::Phoenix(vec![
+                notes[0].gen_nullifier(&phoenix_sk)
+            ]),
+        ))
+        .expect("should serialize conversion correctly");
+
+    let tx = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        // set the conversion-value as the deposit
+        CONVERSION_VALUE,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let receipt = execute(&mut session, &tx, &NO_CONFIG)
+        .expect("Executing TX should succeed");
+
+    // check that the transaction execution panicked with the correct message
+    assert!(receipt.data.is_err());
+    assert_eq!(
+        format!("{}", receipt.data.unwrap_err()),
+        String::from("Panic: Expected Phoenix TX, found Moonlight"),
+        "The attempted conversion from phoenix to moonlight when paying gas with moonlight should error"
+    );
+    assert_eq!(
+        receipt.gas_spent,
+        GAS_LIMIT * LUX,
+        "The max gas should have been spent"
+    );
+
+    update_root(session).expect("Updating the root should succeed");
+
+    println!("CONVERT TO MOONLIGHT: {} gas", receipt.gas_spent);
+
+    let moonlight_account = account(&mut session, &moonlight_pk)
+        .expect("Getting account should succeed");
+
+    assert_eq!(
+        moonlight_account.balance,
+        MOONLIGHT_GENESIS_VALUE - receipt.gas_spent,
+        "Since the conversion fails, the moonlight account should only have the gas-spent deducted"
+    );
+
+    let leaves = leaves_from_height(session, 1)
+        .expect("getting the notes should succeed");
+    assert_eq!(leaves.len(), 0, "no new leaves should have been created");
+
+    let nullifier = vec![nullifier];
+    let existing_nullifers = existing_nullifiers(session, nullifier.clone())
+        .expect("Querrying the nullifiers should work");
+    assert!(
+        existing_nullifers.is_empty(),
+        "the note shouldn't have been nullified"
+    );
+}
+
+/// Attempts to convert moonlight DUSK into phoenix DUSK but fails due to not
+/// targeting the correct contract for the conversion.
 #[test]
-fn convert_to_moonlight_fails() {
-    const CONVERSION_VALUE: u64 = dusk(10.0);
+fn convert_wrong_contract_targeted() {
+    const CONVERSION_VALUE: u64 = dusk(1.0);
 
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
@@ -488,57 +1238,33 @@ fn convert_to_moonlight_fails() {
 
     let mut session = &mut instantiate(&moonlight_pk);
 
-    // Add a phoenix note with the conversion-value
-    let value_blinder = JubJubScalar::random(&mut *rng);
-    let sender_blinder = [
-        JubJubScalar::random(&mut *rng),
-        JubJubScalar::random(&mut *rng),
-    ];
-    let note = Note::obfuscated(
-        rng,
-        &phoenix_pk,
-        &phoenix_pk,
-        CONVERSION_VALUE,
-        value_blinder,
-        sender_blinder,
-    );
-    // get the nullifier for later check
-    let nullifier = note.gen_nullifier(&phoenix_sk);
-    // push genesis phoenix note to the contract
-    session
-        .call::<_, Note>(
-            TRANSFER_CONTRACT,
-            "push_note",
-            &(0u64, note),
-            GAS_LIMIT,
-        )
-        .expect("Pushing genesis note should succeed");
-
-    // update the root after the notes have been inserted
-    update_root(&mut session).expect("Updating the root should succeed");
-
     // make sure that the phoenix-key doesn't own any notes yet
-    let leaves = leaves_from_height(session, 0)
+    let leaves = leaves_from_height(session, 1)
         .expect("getting the notes should succeed");
     let notes = filter_notes_owned_by(
         phoenix_vk,
         leaves.into_iter().map(|leaf| leaf.note),
     );
-    assert_eq!(notes.len(), 1, "There should be one note at this height");
+    assert_eq!(notes.len(), 0, "There should be no notes at this height");
+
+    // generate a new note stealth-address and note-sk for the conversion
+    let address =
+        phoenix_pk.gen_stealth_address(&JubJubScalar::random(&mut *rng));
+    let note_sk = phoenix_sk.gen_note_sk(&address);
+
+    // the moonlight replay token
+    let nonce = MOONLIGHT_GENESIS_NONCE + 1;
 
-    // a conversion is a deposit into the transfer-contract paired with a
-    // withdrawal
     let contract_call = ContractCall::new(TRANSFER_CONTRACT, "convert")
         .with_args(&Withdraw::new(
             rng,
-            &moonlight_sk,
-            TRANSFER_CONTRACT,
-            // set the conversion-value as a withdrawal
+            &note_sk,
+            // this should be the transfer contract, but we're testing the
+            // "wrong target" case
+            ALICE_ID,
             CONVERSION_VALUE,
-            WithdrawReceiver::Moonlight(moonlight_pk),
-            WithdrawReplayToken::Phoenix(vec![
-                notes[0].gen_nullifier(&phoenix_sk)
-            ]),
+            WithdrawReceiver::Phoenix(address),
+            WithdrawReplayToken::Moonlight(nonce),
         ))
         .expect("should serialize conversion correctly");
 
@@ -546,151 +1272,356 @@ fn convert_to_moonlight_fails() {
         &moonlight_sk,
         None,
         0,
-        // set the conversion-value as the deposit
         CONVERSION_VALUE,
         GAS_LIMIT,
         LUX,
-        MOONLIGHT_GENESIS_NONCE + 1,
+        nonce,
         CHAIN_ID,
         Some(contract_call),
     )
     .expect("Creating moonlight transaction should succeed");
 
     let receipt = execute(&mut session, &tx, &NO_CONFIG)
-        .expect("Executing TX should succeed");
+        .expect("Executing transaction should succeed");
+    update_root(session).expect("Updating the root should succeed");
+
+    let res = receipt.data;
+    let gas_spent = receipt.gas_spent;
+
+    assert!(matches!(res, Err(_)), "The contract call should error");
+
+    let moonlight_account = account(&mut session, &moonlight_pk)
+        .expect("Getting account should succeed");
 
-    // check that the transaction execution panicked with the correct message
-    assert!(receipt.data.is_err());
     assert_eq!(
-        format!("{}", receipt.data.unwrap_err()),
-        String::from("Panic: Expected Phoenix TX, found Moonlight"),
-        "The attempted conversion from phoenix to moonlight when paying gas with moonlight should error"
+        moonlight_account.balance,
+        MOONLIGHT_GENESIS_VALUE - gas_spent,
+        "The moonlight account should have only the gas spent subtracted"
+    );
+
+    let leaves = leaves_from_height(session, 1)
+        .expect("getting the notes should succeed");
+    let notes = filter_notes_owned_by(
+        phoenix_vk,
+        leaves.into_iter().map(|leaf| leaf.note),
+    );
+
+    assert!(notes.is_empty(), "A new note should not been created");
+}
+
+/// In this test we call Alice's `contract_to_contract` function, targeting Bob
+/// as the receiver of the transfer. The gas will be paid in moonlight.
+#[test]
+fn contract_to_contract() {
+    const TRANSFER_VALUE: u64 = ALICE_GENESIS_VALUE / 2;
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    // make sure bob contract has no balance prior to the tx
+    let bob_balance = contract_balance(session, BOB_ID)
+        .expect("Querying the contract balance should succeed");
+    assert_eq!(bob_balance, 0, "Bob must have an initial balance of zero");
+
+    let contract_call = ContractCall::new(ALICE_ID, "contract_to_contract")
+        .with_args(&ContractToContract {
+            contract: BOB_ID,
+            value: TRANSFER_VALUE,
+            fn_name: String::from("recv_transfer"),
+            data: vec![],
+        })
+        .expect("Serializing should succeed");
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
+    let gas_spent = receipt.gas_spent;
+
+    println!("SEND TO CONTRACT: {:?}", receipt.data);
+    println!("SEND TO CONTRACT: {gas_spent} gas");
+
+    let moonlight_account = account(session, &moonlight_pk)
+        .expect("Getting the account should succeed");
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
+    let bob_balance = contract_balance(session, BOB_ID)
+        .expect("Querying the contract balance should succeed");
+
+    assert_eq!(
+        moonlight_account.balance,
+        MOONLIGHT_GENESIS_VALUE - gas_spent,
+        "The account should have decreased by the gas spent"
     );
     assert_eq!(
-        receipt.gas_spent,
-        GAS_LIMIT * LUX,
-        "The max gas should have been spent"
+        alice_balance,
+        ALICE_GENESIS_VALUE - TRANSFER_VALUE,
+        "Alice's balance should have decreased by the transfer value"
+    );
+    assert_eq!(
+        bob_balance, TRANSFER_VALUE,
+        "Bob's balance must have increased by the transfer value"
     );
+}
 
-    update_root(session).expect("Updating the root should succeed");
+/// In this test we call the Alice contract to trigger a transfer of funds into
+/// a moonlight account, the gas will be paid with moonlight.
+#[test]
+fn contract_to_account() {
+    const TRANSFER_VALUE: u64 = ALICE_GENESIS_VALUE / 2;
 
-    println!("CONVERT TO MOONLIGHT: {} gas", receipt.gas_spent);
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
-    let moonlight_account = account(&mut session, &moonlight_pk)
-        .expect("Getting account should succeed");
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    let contract_call = ContractCall::new(ALICE_ID, "contract_to_account")
+        .with_args(&ContractToAccount {
+            account: moonlight_pk,
+            value: TRANSFER_VALUE,
+        })
+        .expect("Serializing should succeed");
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
+    let gas_spent = receipt.gas_spent;
+
+    println!("SEND TO ACCOUNT: {:?}", receipt.data);
+    println!("SEND TO ACCOUNT: {gas_spent} gas");
+
+    let moonlight_account = account(session, &moonlight_pk)
+        .expect("Getting the account should succeed");
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
 
     assert_eq!(
         moonlight_account.balance,
-        MOONLIGHT_GENESIS_VALUE - receipt.gas_spent,
-        "Since the conversion fails, the moonlight account should only have the gas-spent deducted"
+        MOONLIGHT_GENESIS_VALUE
+            - gas_spent
+            + TRANSFER_VALUE,
+        "The account's balance should have decreased by the spent gas and increased by the transfer value"
+    );
+    assert_eq!(
+        alice_balance,
+        ALICE_GENESIS_VALUE - TRANSFER_VALUE,
+        "Alice's balance should have decreased by the transfer value"
     );
+}
+
+/// Checks the receipt emitted by `refund` records the balance changes and the
+/// result of the call made by a transaction.
+#[test]
+fn contract_to_account_receipt() {
+    const TRANSFER_VALUE: u64 = dusk(5.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let receiver_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    let contract_call = ContractCall::new(ALICE_ID, "contract_to_account")
+        .with_args(&ContractToAccount {
+            account: receiver_pk,
+            value: TRANSFER_VALUE,
+        })
+        .expect("Serializing should succeed");
+
+    let transaction = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 1,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
 
-    let leaves = leaves_from_height(session, 1)
-        .expect("getting the notes should succeed");
-    assert_eq!(leaves.len(), 0, "no new leaves should have been created");
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
+    let gas_spent = receipt.gas_spent;
 
-    let nullifier = vec![nullifier];
-    let existing_nullifers = existing_nullifiers(session, &nullifier)
-        .expect("Querrying the nullifiers should work");
+    let event = receipt
+        .events
+        .iter()
+        .find(|event| event.topic == RECEIPT_TOPIC)
+        .expect("The receipt should be emitted");
+    let tx_receipt: TransactionReceipt =
+        rkyv::from_bytes(&event.data).expect("Should be a valid receipt");
+
+    assert_eq!(tx_receipt.deposit, DepositStatus::None);
+    assert!(tx_receipt.notes.is_empty(), "No notes should be created");
+    assert_eq!(tx_receipt.gas_spent, gas_spent);
+    assert_eq!(tx_receipt.gas_refund, (GAS_LIMIT - gas_spent) * LUX);
     assert!(
-        existing_nullifers.is_empty(),
-        "the note shouldn't have been nullified"
+        matches!(tx_receipt.call_result, Some(Ok(_))),
+        "The call should succeed"
+    );
+    assert_eq!(
+        tx_receipt.account_deltas,
+        vec![
+            (moonlight_pk, -i128::from(gas_spent * LUX)),
+            (receiver_pk, i128::from(TRANSFER_VALUE)),
+        ],
+        "The sender should pay the gas and the receiver get the value"
+    );
+    assert_eq!(
+        tx_receipt.contract_deltas,
+        vec![(ALICE_ID, -i128::from(TRANSFER_VALUE))],
+        "Alice should pay the transferred value"
     );
 }
 
-/// Attempts to convert moonlight DUSK into phoenix DUSK but fails due to not
-/// targeting the correct contract for the conversion.
+/// In this test a moonlight account signs a withdrawal from the alice contract
+/// ahead of time, which is then settled by a transaction paid for by another
+/// account. Settling the same withdrawal a second time should fail.
 #[test]
-fn convert_wrong_contract_targeted() {
-    const CONVERSION_VALUE: u64 = dusk(1.0);
+fn contract_withdraw_scheduled() {
+    const WITHDRAW_VALUE: u64 = dusk(10.0);
+    const WITHDRAW_ID: u64 = 42;
 
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
-    let phoenix_sk = PhoenixSecretKey::random(rng);
-    let phoenix_vk = PhoenixViewKey::from(&phoenix_sk);
-    let phoenix_pk = PhoenixPublicKey::from(&phoenix_sk);
-
     let moonlight_sk = AccountSecretKey::random(rng);
     let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
 
-    let mut session = &mut instantiate(&moonlight_pk);
-
-    // make sure that the phoenix-key doesn't own any notes yet
-    let leaves = leaves_from_height(session, 1)
-        .expect("getting the notes should succeed");
-    let notes = filter_notes_owned_by(
-        phoenix_vk,
-        leaves.into_iter().map(|leaf| leaf.note),
-    );
-    assert_eq!(notes.len(), 0, "There should be no notes at this height");
+    let receiver_sk = AccountSecretKey::random(rng);
+    let receiver_pk = AccountPublicKey::from(&receiver_sk);
 
-    // generate a new note stealth-address and note-sk for the conversion
-    let address =
-        phoenix_pk.gen_stealth_address(&JubJubScalar::random(&mut *rng));
-    let note_sk = phoenix_sk.gen_note_sk(&address);
+    let session = &mut instantiate(&moonlight_pk);
 
-    // the moonlight replay token
-    let nonce = MOONLIGHT_GENESIS_NONCE + 1;
+    let withdraw = ScheduledWithdraw::new(
+        rng,
+        &receiver_sk,
+        CHAIN_ID,
+        ALICE_ID,
+        WITHDRAW_VALUE,
+        WithdrawReceiver::Moonlight(receiver_pk),
+        10,
+        WITHDRAW_ID,
+    );
+    let withdraw_bytes = rkyv::to_bytes::<_, 1024>(&withdraw)
+        .expect("Serializing the withdrawal should succeed")
+        .to_vec();
 
-    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "convert")
-        .with_args(&Withdraw::new(
-            rng,
-            &note_sk,
-            // this should be the transfer contract, but we're testing the
-            // "wrong target" case
-            ALICE_ID,
-            CONVERSION_VALUE,
-            WithdrawReceiver::Phoenix(address),
-            WithdrawReplayToken::Moonlight(nonce),
-        ))
-        .expect("should serialize conversion correctly");
+    let contract_call = ContractCall::new(ALICE_ID, "withdraw_scheduled")
+        .with_args(&withdraw_bytes)
+        .expect("Serializing should succeed");
 
-    let tx = Transaction::moonlight(
+    let transaction = Transaction::moonlight(
         &moonlight_sk,
         None,
         0,
-        CONVERSION_VALUE,
+        0,
         GAS_LIMIT,
         LUX,
-        nonce,
+        MOONLIGHT_GENESIS_NONCE + 1,
         CHAIN_ID,
-        Some(contract_call),
+        Some(contract_call.clone()),
     )
     .expect("Creating moonlight transaction should succeed");
 
-    let receipt = execute(&mut session, &tx, &NO_CONFIG)
-        .expect("Executing transaction should succeed");
-    update_root(session).expect("Updating the root should succeed");
-
-    let res = receipt.data;
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
     let gas_spent = receipt.gas_spent;
 
-    assert!(matches!(res, Err(_)), "The contract call should error");
+    println!("WITHDRAW SCHEDULED: {gas_spent} gas");
 
-    let moonlight_account = account(&mut session, &moonlight_pk)
-        .expect("Getting account should succeed");
+    assert!(receipt.data.is_ok(), "The withdrawal should be settled");
+
+    let receiver_account = account(session, &receiver_pk)
+        .expect("Getting the account should succeed");
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
 
     assert_eq!(
-        moonlight_account.balance,
-        MOONLIGHT_GENESIS_VALUE - gas_spent,
-        "The moonlight account should have only the gas spent subtracted"
+        receiver_account.balance, WITHDRAW_VALUE,
+        "The receiver should have received the withdrawn value"
+    );
+    assert_eq!(
+        alice_balance,
+        ALICE_GENESIS_VALUE - WITHDRAW_VALUE,
+        "Alice's balance should have decreased by the withdrawn value"
     );
 
-    let leaves = leaves_from_height(session, 1)
-        .expect("getting the notes should succeed");
-    let notes = filter_notes_owned_by(
-        phoenix_vk,
-        leaves.into_iter().map(|leaf| leaf.note),
+    // replaying the same withdrawal in a new transaction must fail
+    let transaction = Transaction::moonlight(
+        &moonlight_sk,
+        None,
+        0,
+        0,
+        GAS_LIMIT,
+        LUX,
+        MOONLIGHT_GENESIS_NONCE + 2,
+        CHAIN_ID,
+        Some(contract_call),
+    )
+    .expect("Creating moonlight transaction should succeed");
+
+    let receipt = execute(session, &transaction, &NO_CONFIG)
+        .expect("Transaction should succeed");
+
+    assert!(
+        matches!(receipt.data, Err(_)),
+        "Replaying the withdrawal should fail"
     );
 
-    assert!(notes.is_empty(), "A new note should not been created");
+    let receiver_account = account(session, &receiver_pk)
+        .expect("Getting the account should succeed");
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
+
+    assert_eq!(
+        receiver_account.balance, WITHDRAW_VALUE,
+        "The receiver's balance should be unchanged"
+    );
+    assert_eq!(
+        alice_balance,
+        ALICE_GENESIS_VALUE - WITHDRAW_VALUE,
+        "Alice's balance should be unchanged"
+    );
 }
 
-/// In this test we call Alice's `contract_to_contract` function, targeting Bob
-/// as the receiver of the transfer. The gas will be paid in moonlight.
+/// A scheduled withdrawal settled after its expiry height should fail.
 #[test]
-fn contract_to_contract() {
-    const TRANSFER_VALUE: u64 = ALICE_GENESIS_VALUE / 2;
+fn contract_withdraw_scheduled_expired() {
+    const WITHDRAW_VALUE: u64 = dusk(10.0);
 
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
@@ -699,18 +1630,24 @@ fn contract_to_contract() {
 
     let session = &mut instantiate(&moonlight_pk);
 
-    // make sure bob contract has no balance prior to the tx
-    let bob_balance = contract_balance(session, BOB_ID)
-        .expect("Querying the contract balance should succeed");
-    assert_eq!(bob_balance, 0, "Bob must have an initial balance of zero");
+    // the session is at block height 1, so a withdrawal expiring at height 0
+    // is no longer valid
+    let withdraw = ScheduledWithdraw::new(
+        rng,
+        &moonlight_sk,
+        CHAIN_ID,
+        ALICE_ID,
+        WITHDRAW_VALUE,
+        WithdrawReceiver::Moonlight(moonlight_pk),
+        0,
+        1,
+    );
+    let withdraw_bytes = rkyv::to_bytes::<_, 1024>(&withdraw)
+        .expect("Serializing the withdrawal should succeed")
+        .to_vec();
 
-    let contract_call = ContractCall::new(ALICE_ID, "contract_to_contract")
-        .with_args(&ContractToContract {
-            contract: BOB_ID,
-            value: TRANSFER_VALUE,
-            fn_name: String::from("recv_transfer"),
-            data: vec![],
-        })
+    let contract_call = ContractCall::new(ALICE_ID, "withdraw_scheduled")
+        .with_args(&withdraw_bytes)
         .expect("Serializing should succeed");
 
     let transaction = Transaction::moonlight(
@@ -730,37 +1667,31 @@ fn contract_to_contract() {
         .expect("Transaction should succeed");
     let gas_spent = receipt.gas_spent;
 
-    println!("SEND TO CONTRACT: {:?}", receipt.data);
-    println!("SEND TO CONTRACT: {gas_spent} gas");
+    assert!(
+        matches!(receipt.data, Err(_)),
+        "Settling an expired withdrawal should fail"
+    );
 
     let moonlight_account = account(session, &moonlight_pk)
         .expect("Getting the account should succeed");
     let alice_balance = contract_balance(session, ALICE_ID)
         .expect("Querying the contract balance should succeed");
-    let bob_balance = contract_balance(session, BOB_ID)
-        .expect("Querying the contract balance should succeed");
 
     assert_eq!(
         moonlight_account.balance,
         MOONLIGHT_GENESIS_VALUE - gas_spent,
-        "The account should have decreased by the gas spent"
+        "The account's balance should only decrease by the gas spent"
     );
     assert_eq!(
-        alice_balance,
-        ALICE_GENESIS_VALUE - TRANSFER_VALUE,
-        "Alice's balance should have decreased by the transfer value"
-    );
-    assert_eq!(
-        bob_balance, TRANSFER_VALUE,
-        "Bob's balance must have increased by the transfer value"
+        alice_balance, ALICE_GENESIS_VALUE,
+        "Alice's balance should be unchanged"
     );
 }
 
-/// In this test we call the Alice contract to trigger a transfer of funds into
-/// a moonlight account, the gas will be paid with moonlight.
+/// A scheduled withdrawal signed for another chain should fail.
 #[test]
-fn contract_to_account() {
-    const TRANSFER_VALUE: u64 = ALICE_GENESIS_VALUE / 2;
+fn contract_withdraw_scheduled_other_chain() {
+    const WITHDRAW_VALUE: u64 = dusk(10.0);
 
     let rng = &mut StdRng::seed_from_u64(0xfeeb);
 
@@ -769,11 +1700,22 @@ fn contract_to_account() {
 
     let session = &mut instantiate(&moonlight_pk);
 
-    let contract_call = ContractCall::new(ALICE_ID, "contract_to_account")
-        .with_args(&ContractToAccount {
-            account: moonlight_pk,
-            value: TRANSFER_VALUE,
-        })
+    let withdraw = ScheduledWithdraw::new(
+        rng,
+        &moonlight_sk,
+        CHAIN_ID + 1,
+        ALICE_ID,
+        WITHDRAW_VALUE,
+        WithdrawReceiver::Moonlight(moonlight_pk),
+        10,
+        1,
+    );
+    let withdraw_bytes = rkyv::to_bytes::<_, 1024>(&withdraw)
+        .expect("Serializing the withdrawal should succeed")
+        .to_vec();
+
+    let contract_call = ContractCall::new(ALICE_ID, "withdraw_scheduled")
+        .with_args(&withdraw_bytes)
         .expect("Serializing should succeed");
 
     let transaction = Transaction::moonlight(
@@ -791,27 +1733,229 @@ fn contract_to_account() {
 
     let receipt = execute(session, &transaction, &NO_CONFIG)
         .expect("Transaction should succeed");
-    let gas_spent = receipt.gas_spent;
 
-    println!("SEND TO ACCOUNT: {:?}", receipt.data);
-    println!("SEND TO ACCOUNT: {gas_spent} gas");
+    assert!(
+        matches!(receipt.data, Err(_)),
+        "Settling a withdrawal signed for another chain should fail"
+    );
+
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
+    assert_eq!(
+        alice_balance, ALICE_GENESIS_VALUE,
+        "Alice's balance should be unchanged"
+    );
+}
+
+/// The settled scheduled withdrawals should be pruned by `update_root` once
+/// their expiry height has passed, and the others kept.
+#[test]
+fn contract_withdraw_scheduled_pruned() {
+    const WITHDRAW_VALUE: u64 = dusk(10.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    // the session is at block height 1, so the first withdrawal can't be
+    // settled after this block, while the second one can. Both use the same
+    // ID, which is only replay protected together with the expiry.
+    for (nonce, expiry) in [(1, 1), (2, 10)] {
+        let withdraw = ScheduledWithdraw::new(
+            rng,
+            &moonlight_sk,
+            CHAIN_ID,
+            ALICE_ID,
+            WITHDRAW_VALUE,
+            WithdrawReceiver::Moonlight(moonlight_pk),
+            expiry,
+            0,
+        );
+        let withdraw_bytes = rkyv::to_bytes::<_, 1024>(&withdraw)
+            .expect("Serializing the withdrawal should succeed")
+            .to_vec();
+
+        let contract_call = ContractCall::new(ALICE_ID, "withdraw_scheduled")
+            .with_args(&withdraw_bytes)
+            .expect("Serializing should succeed");
+
+        let transaction = Transaction::moonlight(
+            &moonlight_sk,
+            None,
+            0,
+            0,
+            GAS_LIMIT,
+            LUX,
+            MOONLIGHT_GENESIS_NONCE + nonce,
+            CHAIN_ID,
+            Some(contract_call),
+        )
+        .expect("Creating moonlight transaction should succeed");
+
+        let receipt = execute(session, &transaction, &NO_CONFIG)
+            .expect("Transaction should succeed");
+        assert!(receipt.data.is_ok(), "The withdrawal should be settled");
+    }
+
+    let settled = |session: &mut Session| -> Vec<u64> {
+        export_state(session, TRANSFER_CONTRACT, ExportCursor::START, 0)
+            .expect("Exporting the state should succeed")
+            .into_iter()
+            .filter_map(|item| match item {
+                StateItem::ScheduledWithdrawal(expiry, _) => Some(expiry),
+                _ => None,
+            })
+            .collect()
+    };
+
+    assert_eq!(settled(session), [1, 10]);
+
+    update_root(session).expect("Updating the root should succeed");
+
+    assert_eq!(
+        settled(session),
+        [10],
+        "Only the expired withdrawal should be pruned"
+    );
+}
+
+/// In this test the alice contract locks funds in two escrows: one that can be
+/// released immediately to a moonlight account, and one that is cancelled by
+/// alice before its release height is reached.
+#[test]
+fn contract_escrow() {
+    const ESCROW_VALUE: u64 = dusk(10.0);
+
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let moonlight_sk = AccountSecretKey::random(rng);
+    let moonlight_pk = AccountPublicKey::from(&moonlight_sk);
+
+    let receiver_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+
+    let session = &mut instantiate(&moonlight_pk);
+
+    // the session is at block height 1, so the first escrow is releasable
+    // right away, while the second one isn't
+    let escrows = [
+        CreateEscrow {
+            beneficiary: EscrowBeneficiary::Account(receiver_pk),
+            value: ESCROW_VALUE,
+            release_height: 1,
+            cancel_authority: Some(ALICE_ID),
+        },
+        CreateEscrow {
+            beneficiary: EscrowBeneficiary::Contract(BOB_ID),
+            value: ESCROW_VALUE,
+            release_height: 100,
+            cancel_authority: Some(ALICE_ID),
+        },
+    ];
+
+    let mut nonce = MOONLIGHT_GENESIS_NONCE;
+    let mut execute_call = |session: &mut Session, call: ContractCall| {
+        nonce += 1;
+        let transaction = Transaction::moonlight(
+            &moonlight_sk,
+            None,
+            0,
+            0,
+            GAS_LIMIT,
+            LUX,
+            nonce,
+            CHAIN_ID,
+            Some(call),
+        )
+        .expect("Creating moonlight transaction should succeed");
+
+        execute(session, &transaction, &NO_CONFIG)
+            .expect("Transaction should succeed")
+    };
+
+    for create in &escrows {
+        let create_bytes = rkyv::to_bytes::<_, 1024>(create)
+            .expect("Serializing the escrow should succeed")
+            .to_vec();
+        let contract_call = ContractCall::new(ALICE_ID, "create_escrow")
+            .with_args(&create_bytes)
+            .expect("Serializing should succeed");
+
+        let receipt = execute_call(session, contract_call);
+        assert!(receipt.data.is_ok(), "Creating the escrow should succeed");
+    }
 
-    let moonlight_account = account(session, &moonlight_pk)
-        .expect("Getting the account should succeed");
     let alice_balance = contract_balance(session, ALICE_ID)
         .expect("Querying the contract balance should succeed");
+    assert_eq!(
+        alice_balance,
+        ALICE_GENESIS_VALUE - 2 * ESCROW_VALUE,
+        "Alice's balance should have decreased by the escrowed values"
+    );
+
+    for (id, create) in escrows.iter().enumerate() {
+        let entry = escrow(session, id as u64)
+            .expect("Querying the escrow should succeed")
+            .expect("The escrow should exist");
+        assert_eq!(entry.owner, ALICE_ID);
+        assert_eq!(entry.beneficiary, create.beneficiary);
+        assert_eq!(entry.value, create.value);
+    }
+
+    // anyone can release the first escrow
+    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "release_escrow")
+        .with_args(&0u64)
+        .expect("Serializing should succeed");
+    let receipt = execute_call(session, contract_call);
+    assert!(receipt.data.is_ok(), "Releasing the escrow should succeed");
 
+    let receiver_account = account(session, &receiver_pk)
+        .expect("Getting the account should succeed");
     assert_eq!(
-        moonlight_account.balance,
-        MOONLIGHT_GENESIS_VALUE
-            - gas_spent
-            + TRANSFER_VALUE,
-        "The account's balance should have decreased by the spent gas and increased by the transfer value"
+        receiver_account.balance, ESCROW_VALUE,
+        "The beneficiary should have received the escrowed value"
+    );
+    assert!(
+        escrow(session, 0)
+            .expect("Querying the escrow should succeed")
+            .is_none(),
+        "The released escrow should be removed"
     );
+
+    // the second escrow can't be released yet
+    let contract_call = ContractCall::new(TRANSFER_CONTRACT, "release_escrow")
+        .with_args(&1u64)
+        .expect("Serializing should succeed");
+    let receipt = execute_call(session, contract_call);
+    assert!(
+        matches!(receipt.data, Err(_)),
+        "Releasing the escrow before its height should fail"
+    );
+
+    // but alice can cancel it
+    let contract_call = ContractCall::new(ALICE_ID, "cancel_escrow")
+        .with_args(&1u64)
+        .expect("Serializing should succeed");
+    let receipt = execute_call(session, contract_call);
+    assert!(receipt.data.is_ok(), "Cancelling the escrow should succeed");
+
+    let alice_balance = contract_balance(session, ALICE_ID)
+        .expect("Querying the contract balance should succeed");
+    let bob_balance = contract_balance(session, BOB_ID)
+        .expect("Querying the contract balance should succeed");
     assert_eq!(
         alice_balance,
-        ALICE_GENESIS_VALUE - TRANSFER_VALUE,
-        "Alice's balance should have decreased by the transfer value"
+        ALICE_GENESIS_VALUE - ESCROW_VALUE,
+        "Alice should have been returned the cancelled escrow's value"
+    );
+    assert_eq!(bob_balance, 0, "Bob should not have received anything");
+    assert!(
+        escrow(session, 1)
+            .expect("Querying the escrow should succeed")
+            .is_none(),
+        "The cancelled escrow should be removed"
     );
 }
 
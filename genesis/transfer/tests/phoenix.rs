@@ -6,8 +6,13 @@
 
 use std::sync::mpsc;
 
-pub mod common;
-
+use contracts_test_kit::notes::{
+    filter_notes_owned_by, new_owned_notes_value, owned_notes_value,
+};
+use contracts_test_kit::transfer::{
+    account, chain_id, contract_balance, existing_nullifiers,
+    leaves_from_height, update_root,
+};
 use dusk_core::abi::ContractId;
 use dusk_core::dusk;
 use dusk_core::signatures::bls::{
@@ -33,12 +38,8 @@ use ff::Field;
 use rand::rngs::StdRng;
 use rand::{CryptoRng, RngCore, SeedableRng};
 use rusk_prover::LocalProver;
-
-use crate::common::utils::{
-    account, chain_id, contract_balance, existing_nullifiers,
-    filter_notes_owned_by, leaves_from_height, new_owned_notes_value,
-    owned_notes_value, update_root,
-};
+use transfer_contract::nullifiers::NullifierProof;
+use transfer_contract::verifier::{SetVerifier, Verifier};
 
 const PHOENIX_GENESIS_VALUE: u64 = dusk(1_200.0);
 const ALICE_GENESIS_VALUE: u64 = dusk(2_000.0);
@@ -281,8 +282,9 @@ fn transfer_1_2() {
     // check that the genesis note has been nullified
     let input_nullifier =
         gen_nullifiers(session, [input_note_pos], &phoenix_sender_sk);
-    let existing_nullifers = existing_nullifiers(session, &input_nullifier)
-        .expect("Querrying the nullifiers should work");
+    let existing_nullifers =
+        existing_nullifiers(session, input_nullifier.clone())
+            .expect("Querrying the nullifiers should work");
     assert_eq!(input_nullifier, existing_nullifers);
 
     // the sender's balance has decreased
@@ -388,8 +390,9 @@ fn transfer_2_2() {
     // check that the genesis notes have been nullified
     let input_nullifiers =
         gen_nullifiers(session, input_notes_pos, &phoenix_sender_sk);
-    let existing_nullifers = existing_nullifiers(session, &input_nullifiers)
-        .expect("Querying the nullifiers should work");
+    let existing_nullifers =
+        existing_nullifiers(session, input_nullifiers.clone())
+            .expect("Querying the nullifiers should work");
     assert_eq!(input_nullifiers, existing_nullifers);
 
     // the sender's balance has decreased
@@ -496,8 +499,9 @@ fn transfer_3_2() {
     // check that the genesis notes have been nullified
     let input_nullifiers =
         gen_nullifiers(session, input_notes_pos, &phoenix_sender_sk);
-    let existing_nullifers = existing_nullifiers(session, &input_nullifiers)
-        .expect("Querrying the nullifiers should work");
+    let existing_nullifers =
+        existing_nullifiers(session, input_nullifiers.clone())
+            .expect("Querrying the nullifiers should work");
     assert_eq!(input_nullifiers, existing_nullifers);
 
     // the sender's balance has decreased
@@ -604,8 +608,9 @@ fn transfer_4_2() {
     // check that the genesis notes have been nullified
     let input_nullifiers =
         gen_nullifiers(session, input_notes_pos, &phoenix_sender_sk);
-    let existing_nullifers = existing_nullifiers(session, &input_nullifiers)
-        .expect("Querrying the nullifiers should work");
+    let existing_nullifers =
+        existing_nullifiers(session, input_nullifiers.clone())
+            .expect("Querrying the nullifiers should work");
     assert_eq!(input_nullifiers, existing_nullifers);
 
     // the sender's balance has decreased
@@ -708,7 +713,8 @@ fn transfer_gas_fails() {
     );
 
     assert_eq!(
-        new_owned_notes_value(session, 0, phoenix_sender_vk),
+        new_owned_notes_value(session, 0, phoenix_sender_vk)
+            .expect("Querying the notes should succeed"),
         PHOENIX_GENESIS_VALUE,
         "The sender should still own the genesis value"
     );
@@ -1483,6 +1489,182 @@ fn contract_to_account() {
     );
 }
 
+/// Check the proofs of the nullifiers in the nullifiers tree, before and after
+/// a note is spent.
+#[test]
+fn nullifier_proofs() {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let phoenix_sender_sk = PhoenixSecretKey::random(rng);
+    let phoenix_sender_pk = PhoenixPublicKey::from(&phoenix_sender_sk);
+
+    let phoenix_receiver_pk =
+        PhoenixPublicKey::from(&PhoenixSecretKey::random(rng));
+
+    let session = &mut instantiate::<1>(rng, &phoenix_sender_sk);
+
+    let input_note_pos = 0;
+    let input_nullifier =
+        gen_nullifiers(session, [input_note_pos], &phoenix_sender_sk)[0];
+    let other_nullifier = BlsScalar::random(&mut *rng);
+
+    // before any note is spent the tree is empty
+    let genesis_root = nullifier_root(session, 0)
+        .expect("Getting the nullifier root should succeed")
+        .expect("The genesis nullifier root should be recorded");
+    let proof = nullifier_proof(session, &input_nullifier)
+        .expect("Getting the proof should succeed");
+    assert_eq!(proof, NullifierProof::Empty);
+    assert_eq!(proof.verify(&input_nullifier, &genesis_root), Some(false));
+
+    let tx = create_phoenix_transaction(
+        rng,
+        session,
+        &phoenix_sender_sk,
+        &phoenix_sender_pk,
+        &phoenix_receiver_pk,
+        GAS_LIMIT,
+        GAS_PRICE,
+        [input_note_pos],
+        42,
+        true,
+        0,
+        None::<ContractCall>,
+    );
+
+    execute(session, &tx, &NO_CONFIG).expect("Executing TX should succeed");
+    update_root(session).expect("Updating the root should succeed");
+
+    let root = nullifiers_root(session)
+        .expect("Getting the nullifiers root should succeed");
+    assert_ne!(root, genesis_root, "The root should change after a spend");
+    assert_eq!(
+        nullifier_root(session, 1)
+            .expect("Getting the nullifier root should succeed"),
+        Some(root),
+        "The root should be recorded for the block"
+    );
+
+    let spent_proof = nullifier_proof(session, &input_nullifier)
+        .expect("Getting the proof should succeed");
+    assert_eq!(
+        spent_proof.verify(&input_nullifier, &root),
+        Some(true),
+        "The input nullifier should be proven spent"
+    );
+    assert_eq!(
+        spent_proof.verify(&other_nullifier, &root),
+        None,
+        "The proof should not be valid for another nullifier"
+    );
+    assert_eq!(
+        spent_proof.verify(&input_nullifier, &genesis_root),
+        None,
+        "The proof should not be valid against an old root"
+    );
+
+    let unspent_proof = nullifier_proof(session, &other_nullifier)
+        .expect("Getting the proof should succeed");
+    assert_eq!(
+        unspent_proof.verify(&other_nullifier, &root),
+        Some(false),
+        "Another nullifier should be proven unspent"
+    );
+    assert_eq!(
+        unspent_proof.verify(&input_nullifier, &root),
+        None,
+        "The proof should not be valid for a spent nullifier"
+    );
+}
+
+/// Schedule verifier data for the 1-2 circuit and check a transaction is
+/// still accepted once it is active.
+#[test]
+fn set_verifier() {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let phoenix_sender_sk = PhoenixSecretKey::random(rng);
+    let phoenix_sender_pk = PhoenixPublicKey::from(&phoenix_sender_sk);
+
+    let phoenix_receiver_pk =
+        PhoenixPublicKey::from(&PhoenixSecretKey::random(rng));
+
+    let session = &mut instantiate::<1>(rng, &phoenix_sender_sk);
+
+    let circuit = rusk_profile::Circuit::from_name("TxCircuitOneTwo")
+        .expect("The circuit should be stored in the rusk-profile");
+    let vd = circuit
+        .get_verifier()
+        .expect("The verifier data should be stored in the rusk-profile");
+
+    let set = SetVerifier {
+        circuit_id: *circuit.id(),
+        inputs: 1,
+        outputs: 2,
+        vd,
+        activation_height: 0,
+    };
+
+    // the activation height cannot be in the past
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "set_verifier",
+        &set,
+        GAS_LIMIT,
+    );
+    assert!(result.is_err(), "Setting a past verifier should fail");
+
+    let set = SetVerifier {
+        activation_height: 1,
+        ..set
+    };
+    session
+        .call::<_, ()>(TRANSFER_CONTRACT, "set_verifier", &set, GAS_LIMIT)
+        .expect("Setting the verifier should succeed");
+
+    let verifiers = verifiers(session, 1, 2)
+        .expect("Querying the verifiers should succeed");
+    assert_eq!(
+        verifiers,
+        vec![Verifier {
+            circuit_id: set.circuit_id,
+            vd: set.vd.clone(),
+            activation_height: 1,
+        }]
+    );
+
+    // the same activation height cannot be scheduled twice
+    let result = session.call::<_, ()>(
+        TRANSFER_CONTRACT,
+        "set_verifier",
+        &set,
+        GAS_LIMIT,
+    );
+    assert!(result.is_err(), "Setting a verifier twice should fail");
+
+    let tx = create_phoenix_transaction(
+        rng,
+        session,
+        &phoenix_sender_sk,
+        &phoenix_sender_pk,
+        &phoenix_receiver_pk,
+        GAS_LIMIT,
+        GAS_PRICE,
+        [0],
+        42,
+        true,
+        0,
+        None::<ContractCall>,
+    );
+
+    let receipt =
+        execute(session, &tx, &NO_CONFIG).expect("Executing TX should succeed");
+    assert!(
+        receipt.data.is_ok(),
+        "The transaction should be accepted with the verifier active"
+    );
+}
+
 // ----------------
 // helper functions
 
@@ -1527,6 +1709,45 @@ fn opening(
         .map(|r| r.data)
 }
 
+fn nullifiers_root(session: &mut Session) -> Result<BlsScalar, VMError> {
+    session
+        .call(TRANSFER_CONTRACT, "nullifiers_root", &(), GAS_LIMIT)
+        .map(|r| r.data)
+}
+
+fn nullifier_root(
+    session: &mut Session,
+    height: u64,
+) -> Result<Option<BlsScalar>, VMError> {
+    session
+        .call(TRANSFER_CONTRACT, "nullifier_root", &height, GAS_LIMIT)
+        .map(|r| r.data)
+}
+
+fn nullifier_proof(
+    session: &mut Session,
+    nullifier: &BlsScalar,
+) -> Result<NullifierProof, VMError> {
+    session
+        .call(TRANSFER_CONTRACT, "nullifier_proof", nullifier, GAS_LIMIT)
+        .map(|r| r.data)
+}
+
+fn verifiers(
+    session: &mut Session,
+    inputs: u32,
+    outputs: u32,
+) -> Result<Vec<Verifier>, VMError> {
+    session
+        .call(
+            TRANSFER_CONTRACT,
+            "verifiers",
+            &(inputs, outputs),
+            GAS_LIMIT,
+        )
+        .map(|r| r.data)
+}
+
 fn gen_nullifiers(
     session: &mut Session,
     notes_pos: impl AsRef<[u64]>,
@@ -33,6 +33,21 @@ mod wasm {
         abi::wrap_call(arg_len, |arg| STATE.withdraw(arg))
     }
 
+    #[no_mangle]
+    unsafe fn withdraw_scheduled(arg_len: u32) -> u32 {
+        abi::wrap_call(arg_len, |arg| STATE.withdraw_scheduled(arg))
+    }
+
+    #[no_mangle]
+    unsafe fn create_escrow(arg_len: u32) -> u32 {
+        abi::wrap_call(arg_len, |arg| STATE.create_escrow(arg))
+    }
+
+    #[no_mangle]
+    unsafe fn cancel_escrow(arg_len: u32) -> u32 {
+        abi::wrap_call(arg_len, |arg| STATE.cancel_escrow(arg))
+    }
+
     #[no_mangle]
     unsafe fn deposit(arg_len: u32) -> u32 {
         abi::wrap_call(arg_len, |arg| STATE.deposit(arg))
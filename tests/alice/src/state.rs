@@ -4,6 +4,8 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use alloc::vec::Vec;
+
 use dusk_core::abi::{self, ContractId};
 use dusk_core::stake::Stake;
 use dusk_core::transfer::{
@@ -25,6 +27,24 @@ impl Alice {
             .expect("Transparent withdrawal transaction should succeed");
     }
 
+    /// Settles a scheduled withdrawal, passed as its serialized bytes.
+    pub fn withdraw_scheduled(&mut self, withdraw: Vec<u8>) {
+        abi::call_raw(TRANSFER_CONTRACT, "withdraw_scheduled", &withdraw)
+            .expect("Scheduled withdrawal transaction should succeed");
+    }
+
+    /// Creates an escrow, passed as the serialized bytes of its creation
+    /// payload.
+    pub fn create_escrow(&mut self, create: Vec<u8>) {
+        abi::call_raw(TRANSFER_CONTRACT, "create_escrow", &create)
+            .expect("Creating the escrow should succeed");
+    }
+
+    pub fn cancel_escrow(&mut self, id: u64) {
+        abi::call::<_, ()>(TRANSFER_CONTRACT, "cancel_escrow", &id)
+            .expect("Cancelling the escrow should succeed");
+    }
+
     pub fn deposit(&mut self, value: u64) {
         let _: () = abi::call(TRANSFER_CONTRACT, "deposit", &value)
             .expect("Transparent deposit transaction should succeed");
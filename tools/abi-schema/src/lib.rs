@@ -0,0 +1,330 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Generator of the ABI schema of the genesis contracts, used by their build
+//! scripts.
+//!
+//! The argument and return types of an entrypoint only exist implicitly in
+//! the closure handling its call, so they are declared by the build script
+//! together with the topics of the events the entrypoint emits, and the
+//! caller restriction checked by the state of the contract, if any. The rest
+//! of the schema is read from the source of the contract: the exported
+//! functions, whether they are feeders, and the caller restriction they
+//! assert. The declared entrypoints must match the exported functions
+//! exactly, so the schema can't silently go out of date.
+//!
+//! The declared types are checked by the compiler: [`wrappers`] generates a
+//! wrapper of `abi::wrap_call` for every entrypoint, taking a closure of the
+//! declared types, and every exported function must handle its call through
+//! its wrapper. The type of the items fed by a feeder isn't part of the
+//! closure, and is not checked.
+//!
+//! The tools working with the contracts read the functions back from the
+//! generated schema with [`functions`].
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// The section comment of the feeder entrypoints in the source of the
+/// contracts.
+const FEEDER_SECTION: &str = "// \"Feeder\" queries";
+
+/// The other section comments of the entrypoints.
+const OTHER_SECTIONS: [&str; 3] = [
+    "// Transactions",
+    "// Queries",
+    "// \"Management\" transactions",
+];
+
+/// The functions asserting the caller of an entrypoint, with the name of the
+/// restriction in the schema.
+const CALLER_ASSERTIONS: [(&str, &str); 3] = [
+    ("assert_transfer_caller()", "transfer"),
+    ("assert_stake_caller()", "stake"),
+    ("assert_external_caller()", "external"),
+];
+
+/// The declaration of an entrypoint of a contract.
+pub struct Entrypoint {
+    /// The name of the exported function.
+    pub name: &'static str,
+    /// The rkyv type of the argument of the function.
+    pub argument: &'static str,
+    /// The rkyv type returned by the function, or of every item fed by it if
+    /// it is a feeder.
+    pub returns: &'static str,
+    /// The caller restriction checked by the state of the contract, for
+    /// functions that don't assert one: `stake`, `transfer`, or `contract`
+    /// for any contract.
+    pub caller: Option<&'static str>,
+    /// The topics of the events the function emits.
+    pub topics: &'static [&'static str],
+}
+
+/// The schema of a contract.
+#[derive(Serialize, Deserialize)]
+struct Schema {
+    contract: String,
+    version: String,
+    functions: Vec<Function>,
+}
+
+/// A function of a contract, as listed in its schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Function {
+    /// The name of the exported function.
+    pub name: String,
+    /// The rkyv type of the argument of the function.
+    pub argument: String,
+    /// The rkyv type returned by the function, or of every item fed by it if
+    /// it is a feeder.
+    pub returns: String,
+    /// Whether the function is a feeder.
+    pub feeder: bool,
+    /// The caller restriction of the function: `any`, `external`,
+    /// `transfer`, `stake`, or `contract` for any contract.
+    pub caller: String,
+    /// The topics of the events the function emits.
+    pub topics: Vec<String>,
+}
+
+/// An exported function, as read from the source of a contract.
+struct Export {
+    name: String,
+    feeder: bool,
+    caller: &'static str,
+}
+
+/// Generate the JSON schema of a contract from the source of its `lib.rs`
+/// and the declaration of its entrypoints.
+///
+/// # Errors
+/// Returns an error if the declared entrypoints don't match the functions
+/// exported in the source, or if a function has more than one caller
+/// restriction.
+pub fn schema(
+    contract: &str,
+    version: &str,
+    source: &str,
+    entrypoints: &[Entrypoint],
+) -> Result<String, String> {
+    let exports = exports(source)?;
+    check_declared(&exports, entrypoints)?;
+
+    // the functions are listed in the order they are exported in
+    let mut functions = Vec::with_capacity(exports.len());
+    for export in exports {
+        let entrypoint = entrypoints
+            .iter()
+            .find(|entrypoint| entrypoint.name == export.name)
+            .expect("The entrypoint should be declared");
+        let caller = match (export.caller, entrypoint.caller) {
+            (caller, None) => caller,
+            ("any", Some(caller)) => caller,
+            (_, Some(_)) => {
+                return Err(format!(
+                    "The function `{}` has more than one caller",
+                    export.name
+                ))
+            }
+        };
+
+        functions.push(Function {
+            name: export.name,
+            argument: String::from(entrypoint.argument),
+            returns: String::from(entrypoint.returns),
+            feeder: export.feeder,
+            caller: String::from(caller),
+            topics: entrypoint
+                .topics
+                .iter()
+                .map(|topic| String::from(*topic))
+                .collect(),
+        });
+    }
+
+    let schema = Schema {
+        contract: String::from(contract),
+        version: String::from(version),
+        functions,
+    };
+    let mut json = serde_json::to_string_pretty(&schema)
+        .map_err(|err| format!("Serializing the schema failed: {err}"))?;
+    json.push('\n');
+
+    Ok(json)
+}
+
+/// Generate the typed wrappers of `abi::wrap_call` for the declared
+/// entrypoints of a contract, from the source of its `lib.rs`.
+///
+/// There is a function for every entrypoint, with the same name, taking the
+/// length of the argument and a closure from the declared argument type to
+/// the declared return type, or to `()` for feeders. The generated code is
+/// meant to be included in a `wrap` module of the contract, and the declared
+/// types are resolved from there, so the contract must be able to refer to
+/// its own types by the name of its crate.
+///
+/// # Errors
+/// Returns an error if the declared entrypoints don't match the functions
+/// exported in the source, or if a function doesn't handle its call through
+/// its wrapper.
+pub fn wrappers(
+    source: &str,
+    entrypoints: &[Entrypoint],
+) -> Result<String, String> {
+    let exports = exports(source)?;
+    check_declared(&exports, entrypoints)?;
+
+    let mut wrappers = String::from(
+        "// Generated by the build script from the declared entrypoints.\n",
+    );
+    for export in exports {
+        let entrypoint = entrypoints
+            .iter()
+            .find(|entrypoint| entrypoint.name == export.name)
+            .expect("The entrypoint should be declared");
+
+        let returns = match (export.feeder, entrypoint.returns) {
+            (true, _) | (false, "()") => String::new(),
+            (false, returns) => format!(" -> {returns}"),
+        };
+        wrappers.push_str(&format!(
+            "\npub fn {name}<F>(arg_len: u32, f: F) -> u32\n\
+             where\n    \
+             F: Fn({argument}){returns},\n\
+             {{\n    \
+             dusk_core::abi::wrap_call(arg_len, f)\n\
+             }}\n",
+            name = export.name,
+            argument = entrypoint.argument,
+        ));
+    }
+
+    Ok(wrappers)
+}
+
+/// Read the functions listed in a schema generated with [`schema`], in the
+/// order they are exported in.
+///
+/// # Panics
+/// Panics if the schema wasn't generated with [`schema`].
+#[must_use]
+pub fn functions(schema: &str) -> Vec<Function> {
+    let schema: Schema = serde_json::from_str(schema)
+        .unwrap_or_else(|err| panic!("The schema should be valid: {err}"));
+    schema.functions
+}
+
+/// Check the declared entrypoints match the exported functions exactly.
+fn check_declared(
+    exports: &[Export],
+    entrypoints: &[Entrypoint],
+) -> Result<(), String> {
+    let exported: BTreeSet<&str> =
+        exports.iter().map(|export| export.name.as_str()).collect();
+    let declared: BTreeSet<&str> = entrypoints
+        .iter()
+        .map(|entrypoint| entrypoint.name)
+        .collect();
+    if declared.len() != entrypoints.len() {
+        return Err(String::from("An entrypoint is declared more than once"));
+    }
+    if let Some(name) = exported.difference(&declared).next() {
+        return Err(format!("The exported function `{name}` isn't declared"));
+    }
+    if let Some(name) = declared.difference(&exported).next() {
+        return Err(format!("The declared entrypoint `{name}` isn't exported"));
+    }
+
+    Ok(())
+}
+
+/// Read the functions exported with `#[no_mangle]` from the source of a
+/// contract, in order.
+///
+/// A function is a feeder if it is in the `"Feeder" queries` section, and
+/// its caller restriction is the one asserted in its body, if any. Its body
+/// must handle the call through the wrapper of the same name.
+fn exports(source: &str) -> Result<Vec<Export>, String> {
+    let mut exports = Vec::new();
+
+    let mut lines = source.lines();
+    let mut in_feeder_section = false;
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+
+        // any other section comment ends the feeder section
+        if line == FEEDER_SECTION {
+            in_feeder_section = true;
+        } else if OTHER_SECTIONS.contains(&line) {
+            in_feeder_section = false;
+        }
+
+        if line != "#[no_mangle]" {
+            continue;
+        }
+
+        let signature = lines
+            .next()
+            .map(str::trim)
+            .ok_or("Expected a function after `#[no_mangle]`")?;
+        let name = signature
+            .strip_prefix("unsafe fn ")
+            .and_then(|rest| rest.split('(').next())
+            .ok_or_else(|| format!("Unexpected exported item `{signature}`"))?;
+
+        // collect the body of the function, until its braces are balanced
+        let mut body = String::from(signature);
+        let mut depth = brace_depth(signature);
+        while depth > 0 {
+            let line = lines
+                .next()
+                .ok_or_else(|| format!("Unterminated function `{name}`"))?;
+            depth += brace_depth(line);
+            body.push_str(line);
+        }
+
+        if !body.contains(&format!("wrap::{name}(arg_len")) {
+            return Err(format!(
+                "The function `{name}` doesn't call `wrap::{name}`"
+            ));
+        }
+
+        let mut callers = CALLER_ASSERTIONS
+            .iter()
+            .filter(|(assertion, _)| body.contains(assertion))
+            .map(|(_, caller)| *caller);
+        let caller = callers.next().unwrap_or("any");
+        if callers.next().is_some() {
+            return Err(format!(
+                "The function `{name}` asserts more than one caller"
+            ));
+        }
+
+        exports.push(Export {
+            name: String::from(name),
+            feeder: in_feeder_section,
+            caller,
+        });
+    }
+
+    Ok(exports)
+}
+
+/// The change in the depth of braces over a line of code.
+fn brace_depth(line: &str) -> i64 {
+    line.chars().fold(0, |depth, c| match c {
+        '{' => depth + 1,
+        '}' => depth - 1,
+        _ => depth,
+    })
+}
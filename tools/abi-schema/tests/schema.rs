@@ -0,0 +1,223 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_abi_schema::{functions, schema, wrappers, Entrypoint, Function};
+
+const SOURCE: &str = r#"
+// Queries
+
+#[no_mangle]
+unsafe fn value(arg_len: u32) -> u32 {
+    wrap::value(arg_len, |_: ()| STATE.value())
+}
+
+// "Feeder" queries
+
+#[no_mangle]
+unsafe fn values(arg_len: u32) -> u32 {
+    wrap::values(arg_len, |(from, count_limit)| {
+        STATE.values(from, count_limit)
+    })
+}
+
+// "Management" transactions
+
+#[no_mangle]
+unsafe fn set_value(arg_len: u32) -> u32 {
+    wrap::set_value(arg_len, |value| {
+        assert_external_caller();
+        STATE.set_value(value)
+    })
+}
+
+fn assert_external_caller() {
+    if abi::caller().is_some() {
+        panic!("Can only be called from the outside the VM");
+    }
+}
+"#;
+
+const ENTRYPOINTS: &[Entrypoint] = &[
+    Entrypoint {
+        name: "value",
+        argument: "()",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "values",
+        argument: "(u64, u64)",
+        returns: "u64",
+        caller: None,
+        topics: &[],
+    },
+    Entrypoint {
+        name: "set_value",
+        argument: "u64",
+        returns: "()",
+        caller: None,
+        topics: &["value"],
+    },
+];
+
+#[test]
+fn generate() {
+    let schema = schema("test-contract", "0.1.0", SOURCE, ENTRYPOINTS)
+        .expect("Generating the schema should succeed");
+
+    let expected = r#"{
+  "contract": "test-contract",
+  "version": "0.1.0",
+  "functions": [
+    {
+      "name": "value",
+      "argument": "()",
+      "returns": "u64",
+      "feeder": false,
+      "caller": "any",
+      "topics": []
+    },
+    {
+      "name": "values",
+      "argument": "(u64, u64)",
+      "returns": "u64",
+      "feeder": true,
+      "caller": "any",
+      "topics": []
+    },
+    {
+      "name": "set_value",
+      "argument": "u64",
+      "returns": "()",
+      "feeder": false,
+      "caller": "external",
+      "topics": [
+        "value"
+      ]
+    }
+  ]
+}
+"#;
+    assert_eq!(schema, expected);
+}
+
+#[test]
+fn read_functions() {
+    let schema = schema("test-contract", "0.1.0", SOURCE, ENTRYPOINTS)
+        .expect("Generating the schema should succeed");
+
+    let function = |entrypoint: &Entrypoint, feeder, caller: &str| Function {
+        name: String::from(entrypoint.name),
+        argument: String::from(entrypoint.argument),
+        returns: String::from(entrypoint.returns),
+        feeder,
+        caller: String::from(caller),
+        topics: entrypoint
+            .topics
+            .iter()
+            .map(|topic| String::from(*topic))
+            .collect(),
+    };
+    assert_eq!(
+        functions(&schema),
+        vec![
+            function(&ENTRYPOINTS[0], false, "any"),
+            function(&ENTRYPOINTS[1], true, "any"),
+            function(&ENTRYPOINTS[2], false, "external"),
+        ]
+    );
+}
+
+#[test]
+fn undeclared_entrypoint() {
+    let result = schema("test-contract", "0.1.0", SOURCE, &ENTRYPOINTS[..2]);
+    assert_eq!(
+        result,
+        Err(String::from(
+            "The exported function `set_value` isn't declared"
+        ))
+    );
+}
+
+#[test]
+fn double_caller() {
+    let entrypoints = [
+        Entrypoint {
+            name: "value",
+            argument: "()",
+            returns: "u64",
+            caller: None,
+            topics: &[],
+        },
+        Entrypoint {
+            name: "values",
+            argument: "(u64, u64)",
+            returns: "u64",
+            caller: None,
+            topics: &[],
+        },
+        Entrypoint {
+            name: "set_value",
+            argument: "u64",
+            returns: "()",
+            caller: Some("contract"),
+            topics: &["value"],
+        },
+    ];
+
+    let result = schema("test-contract", "0.1.0", SOURCE, &entrypoints);
+    assert_eq!(
+        result,
+        Err(String::from(
+            "The function `set_value` has more than one caller"
+        ))
+    );
+}
+
+#[test]
+fn generate_wrappers() {
+    let wrappers = wrappers(SOURCE, ENTRYPOINTS)
+        .expect("Generating the wrappers should succeed");
+
+    let expected = r#"// Generated by the build script from the declared entrypoints.
+
+pub fn value<F>(arg_len: u32, f: F) -> u32
+where
+    F: Fn(()) -> u64,
+{
+    dusk_core::abi::wrap_call(arg_len, f)
+}
+
+pub fn values<F>(arg_len: u32, f: F) -> u32
+where
+    F: Fn((u64, u64)),
+{
+    dusk_core::abi::wrap_call(arg_len, f)
+}
+
+pub fn set_value<F>(arg_len: u32, f: F) -> u32
+where
+    F: Fn(u64),
+{
+    dusk_core::abi::wrap_call(arg_len, f)
+}
+"#;
+    assert_eq!(wrappers, expected);
+}
+
+#[test]
+fn unwrapped_call() {
+    let source = SOURCE.replace("wrap::set_value(", "abi::wrap_call(");
+
+    let result = wrappers(&source, ENTRYPOINTS);
+    assert_eq!(
+        result,
+        Err(String::from(
+            "The function `set_value` doesn't call `wrap::set_value`"
+        ))
+    );
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+
+use dusk_core::abi::ContractId;
+use dusk_vm::Error as VMError;
+
+/// The errors returned by the clients.
+#[derive(Debug)]
+pub enum Error {
+    /// The VM failed to execute a call.
+    VM(VMError),
+    /// An item fed by the given feeder function could not be deserialized.
+    InvalidFeedItem(&'static str),
+    /// The data of an event emitted under the given topic could not be
+    /// deserialized.
+    InvalidEvent(String),
+    /// An event was emitted by a genesis contract under a topic it doesn't
+    /// use.
+    UnknownTopic {
+        /// The contract that emitted the event.
+        source: ContractId,
+        /// The topic of the event.
+        topic: String,
+    },
+}
+
+impl From<VMError> for Error {
+    fn from(e: VMError) -> Self {
+        Self::VM(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &self)
+    }
+}
+
+impl std::error::Error for Error {}
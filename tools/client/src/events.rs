@@ -0,0 +1,242 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Typed decoding of the events emitted by the genesis contracts.
+//!
+//! Every event is decoded according to its source and topic into the type
+//! the contract emits under that topic. Events emitted by other contracts are
+//! left to the caller.
+
+use bytecheck::CheckBytes;
+use dusk_core::abi::{ContractId, Event};
+use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
+use dusk_core::stake::{Reward, SlashEvent, StakeEvent, STAKE_CONTRACT};
+use dusk_core::transfer::{
+    ContractToAccountEvent, ContractToContractEvent, ConvertEvent,
+    DepositEvent, MoonlightTransactionEvent, PhoenixTransactionEvent,
+    WithdrawEvent, CONTRACT_TO_ACCOUNT_TOPIC, CONTRACT_TO_CONTRACT_TOPIC,
+    CONVERT_TOPIC, DEPOSIT_TOPIC, MINT_CONTRACT_TOPIC, MINT_TOPIC,
+    MOONLIGHT_TOPIC, PHOENIX_TOPIC, TRANSFER_CONTRACT, WITHDRAW_TOPIC,
+};
+use rkyv::de::deserializers::SharedDeserializeMap;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize};
+use stake_contract::migration::ExportSummary as StakeExportSummary;
+use transfer_contract::escrow::{
+    EscrowEvent, ESCROW_CANCEL_TOPIC, ESCROW_CREATE_TOPIC, ESCROW_RELEASE_TOPIC,
+};
+use transfer_contract::freeze::{FREEZE_TOPIC, UNFREEZE_TOPIC};
+use transfer_contract::gas::{GasConfig, BASE_FEE_TOPIC, GAS_CONFIG_TOPIC};
+use transfer_contract::migration::{
+    ExportSummary, IMPORT_TOPIC, MIGRATE_TOPIC,
+};
+use transfer_contract::receipt::{TransactionReceipt, RECEIPT_TOPIC};
+use transfer_contract::verifier::{SetVerifier, SET_VERIFIER_TOPIC};
+
+use crate::Error;
+
+/// An event emitted by one of the genesis contracts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenesisEvent {
+    /// An event emitted by the transfer contract.
+    Transfer(TransferContractEvent),
+    /// An event emitted by the stake contract.
+    Stake(StakeContractEvent),
+}
+
+impl GenesisEvent {
+    /// Decode the given event, returning `None` if it wasn't emitted by one
+    /// of the genesis contracts.
+    ///
+    /// # Errors
+    /// Returns an error if the event was emitted by a genesis contract under
+    /// a topic it doesn't use, or if its data doesn't deserialize to the type
+    /// emitted under its topic.
+    pub fn decode(event: &Event) -> Result<Option<Self>, Error> {
+        let event = match event.source {
+            TRANSFER_CONTRACT => Self::Transfer(TransferContractEvent::decode(
+                &event.topic,
+                &event.data,
+            )?),
+            STAKE_CONTRACT => Self::Stake(StakeContractEvent::decode(
+                &event.topic,
+                &event.data,
+            )?),
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+}
+
+/// Decode the events emitted by the genesis contracts among the given ones,
+/// for example the events of a call receipt, skipping the events of other
+/// contracts.
+///
+/// # Errors
+/// Returns an error if any event of a genesis contract fails to decode, see
+/// [`GenesisEvent::decode`].
+pub fn decode_events<'a, I>(events: I) -> Result<Vec<GenesisEvent>, Error>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    events
+        .into_iter()
+        .filter_map(|event| GenesisEvent::decode(event).transpose())
+        .collect()
+}
+
+/// An event emitted by the transfer contract, by its topic.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferContractEvent {
+    /// Emitted under [`MINT_TOPIC`].
+    Mint(WithdrawEvent),
+    /// Emitted under [`MINT_CONTRACT_TOPIC`].
+    MintToContract(ContractToContractEvent),
+    /// Emitted under [`WITHDRAW_TOPIC`].
+    Withdraw(WithdrawEvent),
+    /// Emitted under [`CONVERT_TOPIC`].
+    Convert(ConvertEvent),
+    /// Emitted under [`DEPOSIT_TOPIC`].
+    Deposit(DepositEvent),
+    /// Emitted under [`CONTRACT_TO_CONTRACT_TOPIC`].
+    ContractToContract(ContractToContractEvent),
+    /// Emitted under [`CONTRACT_TO_ACCOUNT_TOPIC`].
+    ContractToAccount(ContractToAccountEvent),
+    /// Emitted under [`ESCROW_CREATE_TOPIC`].
+    EscrowCreate(EscrowEvent),
+    /// Emitted under [`ESCROW_RELEASE_TOPIC`].
+    EscrowRelease(EscrowEvent),
+    /// Emitted under [`ESCROW_CANCEL_TOPIC`].
+    EscrowCancel(EscrowEvent),
+    /// Emitted under [`PHOENIX_TOPIC`].
+    Phoenix(PhoenixTransactionEvent),
+    /// Emitted under [`MOONLIGHT_TOPIC`].
+    Moonlight(MoonlightTransactionEvent),
+    /// Emitted under [`RECEIPT_TOPIC`].
+    Receipt(TransactionReceipt),
+    /// Emitted under [`IMPORT_TOPIC`].
+    Import(ExportSummary),
+    /// Emitted under [`MIGRATE_TOPIC`], with the versions migrated from and
+    /// to.
+    Migrate(u64, u64),
+    /// Emitted under [`GAS_CONFIG_TOPIC`].
+    GasConfig(GasConfig),
+    /// Emitted under [`BASE_FEE_TOPIC`].
+    BaseFee(u64),
+    /// Emitted under [`FREEZE_TOPIC`].
+    Freeze(BlsPublicKey),
+    /// Emitted under [`UNFREEZE_TOPIC`].
+    Unfreeze(BlsPublicKey),
+    /// Emitted under [`SET_VERIFIER_TOPIC`].
+    SetVerifier(SetVerifier),
+}
+
+impl TransferContractEvent {
+    /// Decode the data of an event emitted by the transfer contract under the
+    /// given topic.
+    ///
+    /// # Errors
+    /// Returns an error if the transfer contract doesn't use the topic, or if
+    /// the data doesn't deserialize to the type emitted under the topic.
+    pub fn decode(topic: &str, data: &[u8]) -> Result<Self, Error> {
+        let event = match topic {
+            MINT_TOPIC => Self::Mint(decode(topic, data)?),
+            MINT_CONTRACT_TOPIC => Self::MintToContract(decode(topic, data)?),
+            WITHDRAW_TOPIC => Self::Withdraw(decode(topic, data)?),
+            CONVERT_TOPIC => Self::Convert(decode(topic, data)?),
+            DEPOSIT_TOPIC => Self::Deposit(decode(topic, data)?),
+            CONTRACT_TO_CONTRACT_TOPIC => {
+                Self::ContractToContract(decode(topic, data)?)
+            }
+            CONTRACT_TO_ACCOUNT_TOPIC => {
+                Self::ContractToAccount(decode(topic, data)?)
+            }
+            ESCROW_CREATE_TOPIC => Self::EscrowCreate(decode(topic, data)?),
+            ESCROW_RELEASE_TOPIC => Self::EscrowRelease(decode(topic, data)?),
+            ESCROW_CANCEL_TOPIC => Self::EscrowCancel(decode(topic, data)?),
+            PHOENIX_TOPIC => Self::Phoenix(decode(topic, data)?),
+            MOONLIGHT_TOPIC => Self::Moonlight(decode(topic, data)?),
+            RECEIPT_TOPIC => Self::Receipt(decode(topic, data)?),
+            IMPORT_TOPIC => Self::Import(decode(topic, data)?),
+            MIGRATE_TOPIC => {
+                let (from, to) = decode(topic, data)?;
+                Self::Migrate(from, to)
+            }
+            GAS_CONFIG_TOPIC => Self::GasConfig(decode(topic, data)?),
+            BASE_FEE_TOPIC => Self::BaseFee(decode(topic, data)?),
+            FREEZE_TOPIC => Self::Freeze(decode(topic, data)?),
+            UNFREEZE_TOPIC => Self::Unfreeze(decode(topic, data)?),
+            SET_VERIFIER_TOPIC => Self::SetVerifier(decode(topic, data)?),
+            _ => return Err(unknown_topic(TRANSFER_CONTRACT, topic)),
+        };
+        Ok(event)
+    }
+}
+
+/// An event emitted by the stake contract, by its topic.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StakeContractEvent {
+    /// Emitted under the `stake` topic.
+    Stake(StakeEvent),
+    /// Emitted under the `unstake` topic.
+    Unstake(StakeEvent),
+    /// Emitted under the `withdraw` topic.
+    Withdraw(StakeEvent),
+    /// Emitted under the `reward` topic.
+    Reward(Vec<Reward>),
+    /// Emitted under the `slash` topic.
+    Slash(SlashEvent),
+    /// Emitted under the `hard_slash` topic.
+    HardSlash(SlashEvent),
+    /// Emitted under the `import` topic, with the summary of the imported
+    /// state.
+    Import(StakeExportSummary),
+    /// Emitted under the `migrate` topic, with the versions migrated from and
+    /// to.
+    Migrate(u64, u64),
+}
+
+impl StakeContractEvent {
+    /// Decode the data of an event emitted by the stake contract under the
+    /// given topic.
+    ///
+    /// # Errors
+    /// Returns an error if the stake contract doesn't use the topic, or if
+    /// the data doesn't deserialize to the type emitted under the topic.
+    pub fn decode(topic: &str, data: &[u8]) -> Result<Self, Error> {
+        let event = match topic {
+            "stake" => Self::Stake(decode(topic, data)?),
+            "unstake" => Self::Unstake(decode(topic, data)?),
+            "withdraw" => Self::Withdraw(decode(topic, data)?),
+            "reward" => Self::Reward(decode(topic, data)?),
+            "slash" => Self::Slash(decode(topic, data)?),
+            "hard_slash" => Self::HardSlash(decode(topic, data)?),
+            "import" => Self::Import(decode(topic, data)?),
+            "migrate" => {
+                let (from, to) = decode(topic, data)?;
+                Self::Migrate(from, to)
+            }
+            _ => return Err(unknown_topic(STAKE_CONTRACT, topic)),
+        };
+        Ok(event)
+    }
+}
+
+fn decode<T>(topic: &str, data: &[u8]) -> Result<T, Error>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, SharedDeserializeMap>
+        + for<'b> CheckBytes<DefaultValidator<'b>>,
+{
+    rkyv::from_bytes(data).map_err(|_| Error::InvalidEvent(topic.into()))
+}
+
+fn unknown_topic(source: ContractId, topic: &str) -> Error {
+    Error::UnknownTopic {
+        source,
+        topic: topic.into(),
+    }
+}
@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::marker::PhantomData;
+use std::sync::mpsc;
+
+use bytecheck::CheckBytes;
+use rkyv::de::deserializers::SharedDeserializeMap;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize};
+
+use crate::Error;
+
+/// Iterator over the items fed by a feeder function of a contract.
+///
+/// The items are deserialized as they are iterated over, yielding an error
+/// for each item that doesn't deserialize to the type of the feed.
+pub struct Feed<T> {
+    fn_name: &'static str,
+    items: mpsc::IntoIter<Vec<u8>>,
+    _item: PhantomData<T>,
+}
+
+impl<T> Feed<T> {
+    pub(crate) fn new(
+        fn_name: &'static str,
+        receiver: mpsc::Receiver<Vec<u8>>,
+    ) -> Self {
+        Self {
+            fn_name,
+            items: receiver.into_iter(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> Iterator for Feed<T>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, SharedDeserializeMap>
+        + for<'b> CheckBytes<DefaultValidator<'b>>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|bytes| {
+            rkyv::from_bytes(&bytes)
+                .map_err(|_| Error::InvalidFeedItem(self.fn_name))
+        })
+    }
+}
@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Typed client for the genesis contracts.
+//!
+//! The [`TransferClient`] and [`StakeClient`] wrap a [`Session`] and expose
+//! one method per entrypoint of the transfer and stake contracts, taking and
+//! returning the types the contracts expect, instead of function names and
+//! guessed argument types. Feeder entrypoints return a [`Feed`], iterating
+//! over the items fed by the contract, and the [`events`] module decodes the
+//! events emitted by the contracts by their topic.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod error;
+mod feed;
+mod stake;
+mod transfer;
+
+pub mod events;
+
+pub use error::Error;
+pub use feed::Feed;
+pub use stake::StakeClient;
+pub use transfer::TransferClient;
+
+use std::sync::mpsc;
+
+use bytecheck::CheckBytes;
+use dusk_core::abi::{ContractId, StandardBufSerializer};
+use dusk_vm::{CallReceipt, Session};
+use rkyv::de::deserializers::SharedDeserializeMap;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Infallible, Serialize};
+
+/// The gas limit of the calls made by a client, unless set otherwise.
+pub const DEFAULT_GAS_LIMIT: u64 = 0x100_000_000;
+
+/// Caller of the functions of a contract in a session, shared by the
+/// clients.
+struct Caller<'a> {
+    session: &'a mut Session,
+    contract: ContractId,
+    gas_limit: u64,
+}
+
+impl<'a> Caller<'a> {
+    fn new(session: &'a mut Session, contract: ContractId) -> Self {
+        Self {
+            session,
+            contract,
+            gas_limit: DEFAULT_GAS_LIMIT,
+        }
+    }
+
+    /// Call the given function of the contract, returning the whole receipt
+    /// of the call.
+    fn call<A, R>(
+        &mut self,
+        fn_name: &str,
+        fn_arg: &A,
+    ) -> Result<CallReceipt<R>, Error>
+    where
+        A: for<'b> Serialize<StandardBufSerializer<'b>>,
+        R: Archive,
+        R::Archived: Deserialize<R, Infallible>
+            + for<'b> CheckBytes<DefaultValidator<'b>>,
+    {
+        Ok(self
+            .session
+            .call(self.contract, fn_name, fn_arg, self.gas_limit)?)
+    }
+
+    /// Call the given function of the contract, returning only the data
+    /// returned by the call.
+    fn query<A, R>(&mut self, fn_name: &str, fn_arg: &A) -> Result<R, Error>
+    where
+        A: for<'b> Serialize<StandardBufSerializer<'b>>,
+        R: Archive,
+        R::Archived: Deserialize<R, Infallible>
+            + for<'b> CheckBytes<DefaultValidator<'b>>,
+    {
+        self.call(fn_name, fn_arg).map(|receipt| receipt.data)
+    }
+
+    /// Call the given feeder function of the contract, returning a [`Feed`]
+    /// over the items it fed.
+    fn feed<A, T>(
+        &mut self,
+        fn_name: &'static str,
+        fn_arg: &A,
+    ) -> Result<Feed<T>, Error>
+    where
+        A: for<'b> Serialize<StandardBufSerializer<'b>>,
+        T: Archive,
+        T::Archived: Deserialize<T, SharedDeserializeMap>
+            + for<'b> CheckBytes<DefaultValidator<'b>>,
+    {
+        let (feeder, receiver) = mpsc::channel();
+
+        self.session.feeder_call::<_, ()>(
+            self.contract,
+            fn_name,
+            fn_arg,
+            self.gas_limit,
+            feeder,
+        )?;
+
+        Ok(Feed::new(fn_name, receiver))
+    }
+}
@@ -0,0 +1,266 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
+use dusk_core::stake::{
+    Reward, Stake, StakeConfig, StakeData, StakeKeys, Withdraw,
+    WithdrawToContract, STAKE_CONTRACT,
+};
+use dusk_core::transfer::ReceiveFromContract;
+use dusk_core::BlsScalar;
+use dusk_vm::{CallReceipt, Session};
+use stake_contract::migration::{ExportChunk, ExportCursor, ExportSummary};
+
+use crate::{Caller, Error, Feed};
+
+/// Client of the stake contract, calling it in the given session.
+///
+/// Queries return the data returned by the contract, feeder queries return a
+/// [`Feed`] over the items fed by the contract, and transactions return the
+/// whole receipt of the call, with its events and the gas spent.
+pub struct StakeClient<'a> {
+    caller: Caller<'a>,
+}
+
+impl<'a> StakeClient<'a> {
+    /// Create a client calling the stake contract in the given session,
+    /// with the [`DEFAULT_GAS_LIMIT`](crate::DEFAULT_GAS_LIMIT).
+    pub fn new(session: &'a mut Session) -> Self {
+        Self {
+            caller: Caller::new(session, STAKE_CONTRACT),
+        }
+    }
+
+    /// Set the gas limit of the calls made by the client.
+    #[must_use]
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.caller.gas_limit = gas_limit;
+        self
+    }
+
+    // Transactions
+
+    /// Stake Dusk from a Moonlight account, only callable by the transfer
+    /// contract.
+    pub fn stake(&mut self, stake: &Stake) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("stake", stake)
+    }
+
+    /// Unstake Dusk to a Moonlight account or a note, only callable by the
+    /// transfer contract.
+    pub fn unstake(
+        &mut self,
+        unstake: &Withdraw,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("unstake", unstake)
+    }
+
+    /// Withdraw the rewards to a Moonlight account or a note, only callable by
+    /// the transfer contract.
+    pub fn withdraw(
+        &mut self,
+        withdraw: &Withdraw,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("withdraw", withdraw)
+    }
+
+    /// Stake Dusk from a contract, only callable by the transfer contract.
+    pub fn stake_from_contract(
+        &mut self,
+        receive: &ReceiveFromContract,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("stake_from_contract", receive)
+    }
+
+    /// Unstake Dusk to the calling contract.
+    pub fn unstake_from_contract(
+        &mut self,
+        unstake: &WithdrawToContract,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("unstake_from_contract", unstake)
+    }
+
+    /// Withdraw the rewards to the calling contract.
+    pub fn withdraw_from_contract(
+        &mut self,
+        withdraw: &WithdrawToContract,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("withdraw_from_contract", withdraw)
+    }
+
+    // Queries
+
+    /// Return the stake of the given account, if it exists.
+    pub fn get_stake(
+        &mut self,
+        pk: &BlsPublicKey,
+    ) -> Result<Option<StakeData>, Error> {
+        self.caller.query("get_stake", pk)
+    }
+
+    /// Return the keys of the stake of the given account, if it exists.
+    pub fn get_stake_keys(
+        &mut self,
+        pk: &BlsPublicKey,
+    ) -> Result<Option<StakeKeys>, Error> {
+        self.caller.query("get_stake_keys", pk)
+    }
+
+    /// Return the total amount of Dusk burnt by slashes.
+    pub fn burnt_amount(&mut self) -> Result<u64, Error> {
+        self.caller.query("burnt_amount", &())
+    }
+
+    /// Return the version of the stake contract.
+    pub fn get_version(&mut self) -> Result<u64, Error> {
+        self.caller.query("get_version", &())
+    }
+
+    /// Return the digest of the events emitted at the given block height, if
+    /// it is still stored.
+    pub fn block_event_digest(
+        &mut self,
+        block_height: u64,
+    ) -> Result<Option<BlsScalar>, Error> {
+        self.caller.query("block_event_digest", &block_height)
+    }
+
+    /// Return the version of the layout of the state.
+    pub fn state_version(&mut self) -> Result<u64, Error> {
+        self.caller.query("state_version", &())
+    }
+
+    /// Return the configuration of the stake contract.
+    pub fn get_config(&mut self) -> Result<StakeConfig, Error> {
+        self.caller.query("get_config", &())
+    }
+
+    /// Chain the stakes of the chunk of the exported state starting at the
+    /// given cursor, at most `count_limit` of them, onto the summary of the
+    /// chunks before it, and return the summary with the cursor of the next
+    /// chunk.
+    pub fn export_summary(
+        &mut self,
+        summary: ExportSummary,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> Result<ExportChunk, Error> {
+        self.caller
+            .query("export_summary", &(summary, from, count_limit))
+    }
+
+    // "Feeder" queries
+
+    /// Feed all the stakes with their keys.
+    pub fn stakes(&mut self) -> Result<Feed<(StakeKeys, StakeData)>, Error> {
+        self.caller.feed("stakes", &())
+    }
+
+    /// Feed the previous state of the stakes changed in the current block.
+    pub fn prev_state_changes(
+        &mut self,
+    ) -> Result<Feed<(BlsPublicKey, Option<StakeData>)>, Error> {
+        self.caller.feed("prev_state_changes", &())
+    }
+
+    /// Feed at most `count_limit` stakes with their keys, from the given
+    /// cursor.
+    pub fn export_stakes(
+        &mut self,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> Result<Feed<(StakeKeys, StakeData)>, Error> {
+        self.caller.feed("export_stakes", &(from, count_limit))
+    }
+
+    // "Management" transactions
+
+    /// Start a new block, clearing the changes of the previous one.
+    pub fn before_state_transition(
+        &mut self,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("before_state_transition", &())
+    }
+
+    /// Set the configuration of the stake contract.
+    pub fn set_config(
+        &mut self,
+        config: &StakeConfig,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("set_config", config)
+    }
+
+    /// Insert a stake with the given keys and data.
+    pub fn insert_stake(
+        &mut self,
+        keys: StakeKeys,
+        stake_data: StakeData,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("insert_stake", &(keys, stake_data))
+    }
+
+    /// Reward the given accounts.
+    pub fn reward(
+        &mut self,
+        rewards: Vec<Reward>,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("reward", &rewards)
+    }
+
+    /// Slash the given value from the reward of the given account.
+    pub fn slash(
+        &mut self,
+        pk: &BlsPublicKey,
+        value: Option<u64>,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("slash", &(*pk, value))
+    }
+
+    /// Slash the given value from the stake of the given account, with the
+    /// given severity.
+    pub fn hard_slash(
+        &mut self,
+        pk: &BlsPublicKey,
+        value: Option<u64>,
+        severity: Option<u8>,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("hard_slash", &(*pk, value, severity))
+    }
+
+    /// Set the total amount of Dusk burnt by slashes.
+    pub fn set_burnt_amount(
+        &mut self,
+        burnt_amount: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("set_burnt_amount", &burnt_amount)
+    }
+
+    /// Import stakes of an exported state.
+    pub fn import_stakes(
+        &mut self,
+        stakes: Vec<(StakeKeys, StakeData)>,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("import_stakes", &stakes)
+    }
+
+    /// Finish an import, checking the imported state against the summary of
+    /// the export.
+    pub fn finish_import(
+        &mut self,
+        summary: &ExportSummary,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("finish_import", summary)
+    }
+
+    /// Migrate the state from the given layout version to the other.
+    pub fn migrate(
+        &mut self,
+        from: u64,
+        to: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("migrate", &(from, to))
+    }
+}
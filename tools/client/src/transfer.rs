@@ -0,0 +1,505 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use dusk_bytes::Serializable;
+use dusk_core::abi::{ContractError, ContractId};
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::transfer::phoenix::{Note, NoteLeaf, NoteOpening};
+use dusk_core::transfer::withdraw::Withdraw;
+use dusk_core::transfer::{
+    ContractToAccount, ContractToContract, Transaction, TRANSFER_CONTRACT,
+};
+use dusk_core::BlsScalar;
+use dusk_vm::{CallReceipt, Session};
+use transfer_contract::accounts::AccountProof;
+use transfer_contract::escrow::{CreateEscrow, Escrow};
+use transfer_contract::gas::{GasConfig, GasPriceInfo};
+use transfer_contract::migration::{
+    ExportChunk, ExportCursor, ExportSummary, StateItem,
+};
+use transfer_contract::nullifiers::NullifierProof;
+use transfer_contract::receipt::TransactionReceipt;
+use transfer_contract::scheduled::ScheduledWithdraw;
+use transfer_contract::validation::ValidationReport;
+use transfer_contract::verifier::{SetVerifier, Verifier};
+
+use crate::{Caller, Error, Feed};
+
+/// Client of the transfer contract, calling it in the given session.
+///
+/// Queries return the data returned by the contract, feeder queries return a
+/// [`Feed`] over the items fed by the contract, and transactions return the
+/// whole receipt of the call, with its events and the gas spent.
+pub struct TransferClient<'a> {
+    caller: Caller<'a>,
+}
+
+impl<'a> TransferClient<'a> {
+    /// Create a client calling the transfer contract in the given session,
+    /// with the [`DEFAULT_GAS_LIMIT`](crate::DEFAULT_GAS_LIMIT).
+    pub fn new(session: &'a mut Session) -> Self {
+        Self {
+            caller: Caller::new(session, TRANSFER_CONTRACT),
+        }
+    }
+
+    /// Set the gas limit of the calls made by the client.
+    #[must_use]
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.caller.gas_limit = gas_limit;
+        self
+    }
+
+    // Transactions
+
+    /// Mint Dusk to an account or a note, only callable by the stake contract.
+    pub fn mint(&mut self, mint: &Withdraw) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("mint", mint)
+    }
+
+    /// Mint Dusk to a contract, only callable by the stake contract.
+    pub fn mint_to_contract(
+        &mut self,
+        mint: &ContractToContract,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("mint_to_contract", mint)
+    }
+
+    /// Take the deposit of the current transaction, only callable by a
+    /// contract.
+    pub fn deposit(&mut self, value: u64) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("deposit", &value)
+    }
+
+    /// Withdraw Dusk from the balance of the calling contract.
+    pub fn withdraw(
+        &mut self,
+        withdraw: &Withdraw,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("withdraw", withdraw)
+    }
+
+    /// Withdraw Dusk from the balance of the calling contract, at a later
+    /// block height.
+    pub fn withdraw_scheduled(
+        &mut self,
+        withdraw: &ScheduledWithdraw,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("withdraw_scheduled", withdraw)
+    }
+
+    /// Convert Dusk between Phoenix notes and a Moonlight account.
+    pub fn convert(
+        &mut self,
+        convert: &Withdraw,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("convert", convert)
+    }
+
+    /// Transfer Dusk from the calling contract to another contract.
+    pub fn contract_to_contract(
+        &mut self,
+        transfer: &ContractToContract,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("contract_to_contract", transfer)
+    }
+
+    /// Transfer Dusk from the calling contract to a Moonlight account.
+    pub fn contract_to_account(
+        &mut self,
+        transfer: &ContractToAccount,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("contract_to_account", transfer)
+    }
+
+    /// Lock Dusk of the calling contract in an escrow, returning its
+    /// identifier.
+    pub fn create_escrow(
+        &mut self,
+        create: &CreateEscrow,
+    ) -> Result<CallReceipt<u64>, Error> {
+        self.caller.call("create_escrow", create)
+    }
+
+    /// Release the escrow with the given identifier to its receiver.
+    pub fn release_escrow(
+        &mut self,
+        id: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("release_escrow", &id)
+    }
+
+    /// Return the escrow with the given identifier to its creator.
+    pub fn cancel_escrow(&mut self, id: u64) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("cancel_escrow", &id)
+    }
+
+    // Queries
+
+    /// Return the root of the tree of notes.
+    pub fn root(&mut self) -> Result<BlsScalar, Error> {
+        self.caller.query("root", &())
+    }
+
+    /// Return the data of the given Moonlight account.
+    pub fn account(
+        &mut self,
+        pk: &AccountPublicKey,
+    ) -> Result<AccountData, Error> {
+        self.caller.query("account", pk)
+    }
+
+    /// Return the root of the tree of Moonlight accounts.
+    pub fn accounts_root(&mut self) -> Result<BlsScalar, Error> {
+        self.caller.query("accounts_root", &())
+    }
+
+    /// Return the proof of inclusion of the given Moonlight account in the
+    /// tree of accounts, if it exists.
+    pub fn account_proof(
+        &mut self,
+        pk: &AccountPublicKey,
+    ) -> Result<Option<AccountProof>, Error> {
+        self.caller.query("account_proof", pk)
+    }
+
+    /// Return the balance of the given contract.
+    pub fn contract_balance(
+        &mut self,
+        contract: ContractId,
+    ) -> Result<u64, Error> {
+        self.caller.query("contract_balance", &contract)
+    }
+
+    /// Return the opening of the note at the given position in the tree, if
+    /// it exists.
+    pub fn opening(&mut self, pos: u64) -> Result<Option<NoteOpening>, Error> {
+        self.caller.query("opening", &pos)
+    }
+
+    /// Return the given nullifiers that were already spent.
+    pub fn existing_nullifiers(
+        &mut self,
+        nullifiers: Vec<BlsScalar>,
+    ) -> Result<Vec<BlsScalar>, Error> {
+        self.caller.query("existing_nullifiers", &nullifiers)
+    }
+
+    /// Return the root of the tree of spent nullifiers.
+    pub fn nullifiers_root(&mut self) -> Result<BlsScalar, Error> {
+        self.caller.query("nullifiers_root", &())
+    }
+
+    /// Return the root of the tree of spent nullifiers at the given block
+    /// height, if it is still stored.
+    pub fn nullifier_root(
+        &mut self,
+        block_height: u64,
+    ) -> Result<Option<BlsScalar>, Error> {
+        self.caller.query("nullifier_root", &block_height)
+    }
+
+    /// Return the proof of the given nullifier being spent or not.
+    pub fn nullifier_proof(
+        &mut self,
+        nullifier: &BlsScalar,
+    ) -> Result<NullifierProof, Error> {
+        self.caller.query("nullifier_proof", nullifier)
+    }
+
+    /// Return the digest of the events emitted at the given block height, if
+    /// it is still stored.
+    pub fn block_event_digest(
+        &mut self,
+        block_height: u64,
+    ) -> Result<Option<BlsScalar>, Error> {
+        self.caller.query("block_event_digest", &block_height)
+    }
+
+    /// Return the escrow with the given identifier, if it exists.
+    pub fn escrow(&mut self, id: u64) -> Result<Option<Escrow>, Error> {
+        self.caller.query("escrow", &id)
+    }
+
+    /// Validate the given transaction without executing it.
+    pub fn validate_transaction(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<ValidationReport, Error> {
+        self.caller.query("validate_transaction", tx)
+    }
+
+    /// Return the verifiers scheduled for the Phoenix circuit with the given
+    /// number of inputs and outputs.
+    pub fn verifiers(
+        &mut self,
+        inputs: u32,
+        outputs: u32,
+    ) -> Result<Vec<Verifier>, Error> {
+        self.caller.query("verifiers", &(inputs, outputs))
+    }
+
+    /// Return the version of the layout of the state.
+    pub fn state_version(&mut self) -> Result<u64, Error> {
+        self.caller.query("state_version", &())
+    }
+
+    /// Chain the items of the chunk of the exported state starting at the
+    /// given cursor, at most `count_limit` of them, onto the summary of the
+    /// chunks before it, and return the summary with the cursor of the next
+    /// chunk.
+    pub fn export_summary(
+        &mut self,
+        summary: ExportSummary,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> Result<ExportChunk, Error> {
+        self.caller
+            .query("export_summary", &(summary, from, count_limit))
+    }
+
+    /// Return the minimum gas price and the base fee of the current block.
+    pub fn gas_price_info(&mut self) -> Result<GasPriceInfo, Error> {
+        self.caller.query("gas_price_info", &())
+    }
+
+    /// Return the number of notes in the tree.
+    pub fn num_notes(&mut self) -> Result<u64, Error> {
+        self.caller.query("num_notes", &())
+    }
+
+    /// Return the chain ID of the chain.
+    pub fn chain_id(&mut self) -> Result<u8, Error> {
+        self.caller.query("chain_id", &())
+    }
+
+    // "Feeder" queries
+
+    /// Feed the leaves of the tree of notes from the given block height.
+    pub fn leaves_from_height(
+        &mut self,
+        block_height: u64,
+    ) -> Result<Feed<NoteLeaf>, Error> {
+        self.caller.feed("leaves_from_height", &block_height)
+    }
+
+    /// Feed the leaves of the tree of notes from the given position.
+    pub fn leaves_from_pos(
+        &mut self,
+        pos: u64,
+    ) -> Result<Feed<NoteLeaf>, Error> {
+        self.caller.feed("leaves_from_pos", &pos)
+    }
+
+    /// Feed at most `count_limit` leaves of the tree of notes, from the
+    /// given position.
+    pub fn sync(
+        &mut self,
+        from: u64,
+        count_limit: u64,
+    ) -> Result<Feed<NoteLeaf>, Error> {
+        self.caller.feed("sync", &(from, count_limit))
+    }
+
+    /// Feed at most `count_limit` spent nullifiers, from the given index.
+    pub fn sync_nullifiers(
+        &mut self,
+        from: u64,
+        count_limit: u64,
+    ) -> Result<Feed<BlsScalar>, Error> {
+        self.caller.feed("sync_nullifiers", &(from, count_limit))
+    }
+
+    /// Feed at most `count_limit` contract balances, from the given index.
+    pub fn sync_contract_balances(
+        &mut self,
+        from: u64,
+        count_limit: u64,
+    ) -> Result<Feed<(ContractId, u64)>, Error> {
+        self.caller
+            .feed("sync_contract_balances", &(from, count_limit))
+    }
+
+    /// Feed at most `count_limit` Moonlight accounts with their serialized
+    /// public key, from the given index.
+    pub fn sync_accounts(
+        &mut self,
+        from: u64,
+        count_limit: u64,
+    ) -> Result<Feed<(AccountData, [u8; AccountPublicKey::SIZE])>, Error> {
+        self.caller.feed("sync_accounts", &(from, count_limit))
+    }
+
+    /// Feed at most `count_limit` escrows with their identifier, from the
+    /// given index.
+    pub fn sync_escrows(
+        &mut self,
+        from: u64,
+        count_limit: u64,
+    ) -> Result<Feed<(u64, Escrow)>, Error> {
+        self.caller.feed("sync_escrows", &(from, count_limit))
+    }
+
+    /// Feed at most `count_limit` items of the exported state, from the given
+    /// cursor.
+    pub fn export_state(
+        &mut self,
+        from: ExportCursor,
+        count_limit: u64,
+    ) -> Result<Feed<StateItem>, Error> {
+        self.caller.feed("export_state", &(from, count_limit))
+    }
+
+    /// Feed the frozen Moonlight accounts.
+    pub fn frozen_accounts(&mut self) -> Result<Feed<AccountPublicKey>, Error> {
+        self.caller.feed("frozen_accounts", &())
+    }
+
+    // "Management" transactions
+
+    /// Spend the inputs of the given transaction and execute its contract
+    /// call.
+    pub fn spend_and_execute(
+        &mut self,
+        tx: &Transaction,
+    ) -> Result<CallReceipt<Result<Vec<u8>, ContractError>>, Error> {
+        self.caller.call("spend_and_execute", tx)
+    }
+
+    /// Refund the unspent gas of the current transaction.
+    pub fn refund(
+        &mut self,
+        gas_spent: u64,
+    ) -> Result<CallReceipt<TransactionReceipt>, Error> {
+        self.caller.call("refund", &gas_spent)
+    }
+
+    /// Push a note to the tree at the given block height.
+    pub fn push_note(
+        &mut self,
+        block_height: u64,
+        note: Note,
+    ) -> Result<CallReceipt<Option<Note>>, Error> {
+        self.caller.call("push_note", &(block_height, note))
+    }
+
+    /// Update the roots of the trees, finishing the current block.
+    pub fn update_root(&mut self) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("update_root", &())
+    }
+
+    /// Update the base fee with the gas used in the current block.
+    pub fn update_base_fee(
+        &mut self,
+        gas_used: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("update_base_fee", &gas_used)
+    }
+
+    /// Set the configuration of the gas price.
+    pub fn set_gas_config(
+        &mut self,
+        config: &GasConfig,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("set_gas_config", config)
+    }
+
+    /// Add the given value to the balance of a Moonlight account.
+    pub fn add_account_balance(
+        &mut self,
+        pk: &AccountPublicKey,
+        value: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("add_account_balance", &(*pk, value))
+    }
+
+    /// Subtract the given value from the balance of a Moonlight account.
+    pub fn sub_account_balance(
+        &mut self,
+        pk: &AccountPublicKey,
+        value: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("sub_account_balance", &(*pk, value))
+    }
+
+    /// Insert a Moonlight account with the given data.
+    pub fn insert_account(
+        &mut self,
+        pk: &AccountPublicKey,
+        account: AccountData,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("insert_account", &(*pk, account))
+    }
+
+    /// Import items of an exported state.
+    pub fn import_state(
+        &mut self,
+        items: Vec<StateItem>,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("import_state", &items)
+    }
+
+    /// Finish an import, checking the imported state against the summary of
+    /// the export.
+    pub fn finish_import(
+        &mut self,
+        summary: &ExportSummary,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("finish_import", summary)
+    }
+
+    /// Schedule new verifier data for a Phoenix circuit.
+    pub fn set_verifier(
+        &mut self,
+        set: &SetVerifier,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("set_verifier", set)
+    }
+
+    /// Freeze a Moonlight account.
+    pub fn freeze_account(
+        &mut self,
+        pk: &AccountPublicKey,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("freeze_account", pk)
+    }
+
+    /// Unfreeze a Moonlight account.
+    pub fn unfreeze_account(
+        &mut self,
+        pk: &AccountPublicKey,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("unfreeze_account", pk)
+    }
+
+    /// Add the given value to the balance of a contract.
+    pub fn add_contract_balance(
+        &mut self,
+        contract: ContractId,
+        value: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("add_contract_balance", &(contract, value))
+    }
+
+    /// Subtract the given value from the balance of a contract.
+    pub fn sub_contract_balance(
+        &mut self,
+        contract: ContractId,
+        value: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("sub_contract_balance", &(contract, value))
+    }
+
+    /// Migrate the state from the given layout version to the other.
+    pub fn migrate(
+        &mut self,
+        from: u64,
+        to: u64,
+    ) -> Result<CallReceipt<()>, Error> {
+        self.caller.call("migrate", &(from, to))
+    }
+}
@@ -0,0 +1,103 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_client::events::{
+    decode_events, GenesisEvent, StakeContractEvent,
+};
+use contracts_client::{Error, StakeClient, TransferClient};
+use contracts_test_kit::{Genesis, TestChain, CHAIN_ID};
+use dusk_core::dusk;
+use dusk_core::signatures::bls::{
+    PublicKey as BlsPublicKey, SecretKey as BlsSecretKey,
+};
+use dusk_core::stake::{
+    Reward, RewardReason, StakeAmount, StakeData, StakeKeys, STAKE_CONTRACT,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const GENESIS_VALUE: u64 = dusk(1_000_000.0);
+const STAKE_VALUE: u64 = dusk(1_000.0);
+
+#[test]
+fn transfer_client() -> Result<(), Error> {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let sk = BlsSecretKey::random(rng);
+    let pk = BlsPublicKey::from(&sk);
+
+    let mut chain =
+        TestChain::new(Genesis::new(CHAIN_ID).account(pk, GENESIS_VALUE));
+    let mut client = TransferClient::new(&mut chain);
+
+    assert_eq!(client.account(&pk)?.balance, GENESIS_VALUE);
+
+    let accounts = client
+        .sync_accounts(0, 10)?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(accounts.len(), 1, "There should be a single account");
+    assert_eq!(accounts[0].0.balance, GENESIS_VALUE);
+
+    client.add_contract_balance(STAKE_CONTRACT, STAKE_VALUE)?;
+    assert_eq!(client.contract_balance(STAKE_CONTRACT)?, STAKE_VALUE);
+
+    Ok(())
+}
+
+#[test]
+fn stake_client() -> Result<(), Error> {
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let sk = BlsSecretKey::random(rng);
+    let pk = BlsPublicKey::from(&sk);
+
+    let stake_data = StakeData {
+        reward: 0,
+        amount: Some(StakeAmount {
+            value: STAKE_VALUE,
+            eligibility: 0,
+            locked: 0,
+        }),
+        faults: 0,
+        hard_faults: 0,
+    };
+
+    let mut chain = TestChain::new(
+        Genesis::new(CHAIN_ID).stake(StakeKeys::single_key(pk), stake_data),
+    );
+    let mut client = StakeClient::new(&mut chain);
+
+    assert_eq!(client.get_stake(&pk)?, Some(stake_data));
+
+    let stakes = client.stakes()?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(stakes.len(), 1, "There should be a single stake");
+    assert_eq!(stakes[0].1, stake_data);
+
+    let rewards = vec![Reward {
+        account: pk,
+        value: dusk(3.0),
+        reason: RewardReason::Other,
+    }];
+    let receipt = client.reward(rewards.clone())?;
+
+    assert_eq!(
+        decode_events(&receipt.events)?,
+        vec![GenesisEvent::Stake(StakeContractEvent::Reward(rewards))]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unknown_topic() {
+    match StakeContractEvent::decode("unknown", &[]) {
+        Err(Error::UnknownTopic { source, topic }) => {
+            assert_eq!(source, STAKE_CONTRACT);
+            assert_eq!(topic, "unknown");
+        }
+        _ => panic!("An unknown topic should fail to decode"),
+    }
+}
@@ -0,0 +1,153 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Buildfile for the fuzz harness, to generate the list of the messages the
+//! fuzzed contracts deliberately panic with.
+
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// The sources of the contracts called by the fuzz targets, relative to the
+/// root of the repository.
+const SOURCES: [&str; 4] = [
+    "genesis/transfer/src",
+    "genesis/stake/src",
+    "tests/alice/src",
+    "tests/charlie/src",
+];
+
+/// The calls whose first string literal is a message the contracts panic
+/// with, directly or through the `Err` of a check.
+const PANICKING_CALLS: [&str; 5] =
+    ["panic!(", "assert!(", "assert_eq!(", ".expect(", "Err("];
+
+/// The constants of the messages the contracts panic with.
+const PANIC_CONSTANT: &str = "const PANIC_";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?).join("../..");
+
+    let mut messages = Vec::new();
+    for dir in SOURCES {
+        let dir = root.join(dir);
+        println!("cargo:rerun-if-changed={}", dir.display());
+        for path in sources(&dir)? {
+            messages.extend(panic_messages(&fs::read_to_string(path)?));
+        }
+    }
+    messages.sort();
+    messages.dedup();
+
+    let mut panics = String::from(
+        "// Generated by the build script from the sources of the contracts.\n\
+         \n\
+         /// The messages the fuzzed contracts deliberately panic with, up to \
+         their\n\
+         /// first formatted argument.\n\
+         const CONTRACT_PANICS: &[&str] = &[\n",
+    );
+    for message in messages {
+        panics.push_str(&format!("    {message:?},\n"));
+    }
+    panics.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    fs::write(out_dir.join("panics.rs"), panics)?;
+    Ok(())
+}
+
+/// The Rust sources in a directory, in order.
+fn sources(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "rs"));
+    paths.sort();
+    Ok(paths)
+}
+
+/// Read the messages panicked with in a source, skipping the comments.
+fn panic_messages(source: &str) -> Vec<String> {
+    let code: String = source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut messages = Vec::new();
+    for marker in PANICKING_CALLS.iter().chain([&PANIC_CONSTANT]) {
+        for (start, _) in code.match_indices(marker) {
+            let rest = &code[start + marker.len()..];
+            // the message is the first literal of the call, or of the
+            // definition of the constant
+            let end = match *marker {
+                PANIC_CONSTANT => rest.find(';'),
+                _ => call_end(rest),
+            }
+            .unwrap_or(rest.len());
+
+            let Some(literal) = rest[..end].find('"') else {
+                continue;
+            };
+            let message = string_literal(&rest[literal + 1..]);
+            // formatted messages are matched up to their first argument
+            let message = match message.find('{') {
+                Some(argument) => &message[..argument],
+                None => &message,
+            };
+            if !message.is_empty() {
+                messages.push(String::from(message));
+            }
+        }
+    }
+    messages
+}
+
+/// The position of the parenthesis closing a call, given the code following
+/// the opening one.
+fn call_end(code: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_literal = false;
+    let mut escaped = false;
+    for (i, c) in code.char_indices() {
+        match (in_literal, c) {
+            (true, _) if escaped => escaped = false,
+            (true, '\\') => escaped = true,
+            (_, '"') => in_literal = !in_literal,
+            (false, '(') => depth += 1,
+            (false, ')') if depth == 0 => return Some(i),
+            (false, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Read the content of a string literal, given the code following its
+/// opening quote.
+fn string_literal(code: &str) -> String {
+    let mut content = String::new();
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => content.push('\n'),
+                Some('t') => content.push('\t'),
+                // a line continuation skips the leading whitespace of the
+                // next line
+                Some('\n') => {
+                    let rest = chars.as_str().trim_start();
+                    chars = rest.chars();
+                }
+                Some(c) => content.push(c),
+                None => break,
+            },
+            c => content.push(c),
+        }
+    }
+    content
+}
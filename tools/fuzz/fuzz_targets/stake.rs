@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![no_main]
+
+use dusk_core::stake::STAKE_CONTRACT;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    contracts_fuzz::fuzz_entrypoint(
+        STAKE_CONTRACT,
+        stake_contract::ABI_SCHEMA,
+        input,
+    );
+});
@@ -0,0 +1,21 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Write the seeds of the corpus of every fuzz target to `corpus/<target>`.
+
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+
+    for seed in contracts_fuzz::corpus::seeds() {
+        let dir = corpus.join(seed.target);
+        fs::create_dir_all(&dir).expect("Creating the corpus should succeed");
+        fs::write(dir.join(&seed.name), &seed.input)
+            .expect("Writing the seed should succeed");
+    }
+}
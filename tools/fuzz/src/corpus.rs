@@ -0,0 +1,263 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Seeds of the corpus of the fuzz targets.
+//!
+//! The seeds are valid inputs, made of the arguments the integration tests of
+//! the contracts call them with, signed with the [`Keys`] funded in the
+//! genesis state of the harness. Starting from them, the fuzzer reaches the
+//! code past the deserialization of the arguments much sooner.
+
+use contracts_abi_schema::functions;
+use contracts_test_kit::notes::transparent_note;
+use contracts_test_kit::CHAIN_ID;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{
+    Reward, RewardReason, Stake, StakeConfig, StakeData, StakeKeys,
+    Withdraw as StakeWithdraw, STAKE_CONTRACT,
+};
+use dusk_core::transfer::data::TransactionData;
+use dusk_core::transfer::withdraw::{
+    Withdraw, WithdrawReceiver, WithdrawReplayToken,
+};
+use dusk_core::transfer::{Transaction, TRANSFER_CONTRACT};
+use dusk_core::{dusk, LUX};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::Serialize;
+use stake_contract::migration::ExportCursor;
+
+use crate::{
+    Keys, ALICE_ID, BLOCK_HEIGHT, CHARLIE_ID, GAS_LIMIT, RECEIVERS,
+    RECEIVE_VALUE,
+};
+
+const SCRATCH_SIZE: usize = 1024;
+
+/// An input of the corpus of a fuzz target.
+pub struct Seed {
+    /// The name of the fuzz target.
+    pub target: &'static str,
+    /// The name of the input, unique for the target.
+    pub name: String,
+    /// The input.
+    pub input: Vec<u8>,
+}
+
+/// Generate the seeds of the corpus of every fuzz target.
+#[must_use]
+pub fn seeds() -> Vec<Seed> {
+    let mut seeds = transfer_seeds();
+    seeds.extend(stake_seeds());
+    seeds.extend(receiver_seeds());
+    seeds
+}
+
+fn transfer_seeds() -> Vec<Seed> {
+    let keys = Keys::new();
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+    let mut seeds = Seeds::new("transfer", transfer_contract::ABI_SCHEMA);
+
+    let pk = keys.account_pk();
+    let receiver_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+    let tx = Transaction::moonlight(
+        &keys.account_sk,
+        Some(receiver_pk),
+        dusk(1.0),
+        0,
+        GAS_LIMIT,
+        LUX,
+        1,
+        CHAIN_ID,
+        None::<TransactionData>,
+    )
+    .expect("Creating moonlight transaction should succeed");
+    let withdraw = Withdraw::new(
+        rng,
+        &keys.account_sk,
+        TRANSFER_CONTRACT,
+        dusk(1.0),
+        WithdrawReceiver::Moonlight(pk),
+        WithdrawReplayToken::Moonlight(1),
+    );
+    let note = transparent_note(rng, &keys.phoenix_pk(), dusk(1.0));
+
+    seeds.push("spend_and_execute", &tx);
+    seeds.push("validate_transaction", &tx);
+    seeds.push("refund", &GAS_LIMIT);
+    seeds.push("mint", &withdraw);
+    seeds.push("convert", &withdraw);
+    seeds.push("deposit", &dusk(1.0));
+    seeds.push("push_note", &(BLOCK_HEIGHT, note));
+    seeds.push("update_root", &());
+    seeds.push("add_account_balance", &(pk, dusk(1.0)));
+    seeds.push("sub_account_balance", &(pk, dusk(1.0)));
+    seeds.push("add_contract_balance", &(ALICE_ID, dusk(1.0)));
+    seeds.push("sub_contract_balance", &(ALICE_ID, dusk(1.0)));
+    seeds.push("root", &());
+    seeds.push("account", &pk);
+    seeds.push("contract_balance", &ALICE_ID);
+    seeds.push("opening", &0u64);
+    seeds.push("leaves_from_height", &0u64);
+    seeds.push("leaves_from_pos", &0u64);
+    seeds.push("sync", &(0u64, 0u64));
+    seeds.push("sync_nullifiers", &(0u64, 0u64));
+    seeds.push("sync_contract_balances", &(0u64, 0u64));
+    seeds.push("sync_accounts", &(0u64, 0u64));
+
+    seeds.into_inner()
+}
+
+fn stake_seeds() -> Vec<Seed> {
+    let keys = Keys::new();
+    let mut seeds = Seeds::new("stake", stake_contract::ABI_SCHEMA);
+
+    let sk = &keys.account_sk;
+    let pk = keys.account_pk();
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+
+    let stake = Stake::new(sk, sk, dusk(1.0), CHAIN_ID);
+    let withdraw = StakeWithdraw::new(
+        sk,
+        sk,
+        Withdraw::new(
+            rng,
+            sk,
+            STAKE_CONTRACT,
+            dusk(1.0),
+            WithdrawReceiver::Moonlight(pk),
+            WithdrawReplayToken::Moonlight(1),
+        ),
+    );
+    let reward = Reward {
+        account: pk,
+        value: dusk(1.0),
+        reason: RewardReason::Other,
+    };
+
+    seeds.push("stake", &stake);
+    seeds.push("unstake", &withdraw);
+    seeds.push("withdraw", &withdraw);
+    seeds.push("get_stake", &pk);
+    seeds.push("get_stake_keys", &pk);
+    seeds.push("burnt_amount", &());
+    seeds.push("get_config", &());
+    seeds.push("set_config", &StakeConfig::new());
+    seeds.push(
+        "insert_stake",
+        &(StakeKeys::single_key(pk), StakeData::new(dusk(1.0), 0, 0)),
+    );
+    seeds.push("reward", &vec![reward]);
+    seeds.push("slash", &(pk, Some(dusk(1.0))));
+    seeds.push("hard_slash", &(pk, Some(dusk(1.0)), Some(1u8)));
+    seeds.push("stakes", &());
+    seeds.push("export_stakes", &(ExportCursor::START, 0u64));
+
+    seeds.into_inner()
+}
+
+fn receiver_seeds() -> Vec<Seed> {
+    let keys = Keys::new();
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+    let sk = &keys.account_sk;
+
+    RECEIVERS
+        .iter()
+        .enumerate()
+        .map(|(selector, (contract, fn_name))| {
+            // a stake is owned by the contract sending it to the stake
+            // contract, which is charlie when it forwards it, while the
+            // withdrawals are from charlie
+            let data = match *fn_name {
+                "stake_from_contract" => {
+                    let owner = if *contract == STAKE_CONTRACT {
+                        ALICE_ID
+                    } else {
+                        CHARLIE_ID
+                    };
+                    serialize(&Stake::new_from_contract(
+                        sk,
+                        owner,
+                        RECEIVE_VALUE,
+                        CHAIN_ID,
+                    ))
+                }
+                _ => serialize(&Withdraw::new(
+                    rng,
+                    sk,
+                    CHARLIE_ID,
+                    RECEIVE_VALUE,
+                    WithdrawReceiver::Moonlight(keys.account_pk()),
+                    WithdrawReplayToken::Moonlight(1),
+                )),
+            };
+
+            let mut input = vec![selector as u8];
+            input.extend(data);
+            Seed {
+                target: "receive_from_contract",
+                name: format!("{selector}_{fn_name}"),
+                input,
+            }
+        })
+        .collect()
+}
+
+/// The seeds of a fuzz target calling the entrypoints of a contract.
+struct Seeds {
+    target: &'static str,
+    names: Vec<String>,
+    seeds: Vec<Seed>,
+}
+
+impl Seeds {
+    fn new(target: &'static str, schema: &str) -> Self {
+        Self {
+            target,
+            names: functions(schema)
+                .into_iter()
+                .map(|function| function.name)
+                .collect(),
+            seeds: Vec::new(),
+        }
+    }
+
+    /// Add an input calling the given entrypoint with the given argument.
+    fn push<A>(&mut self, fn_name: &str, arg: &A)
+    where
+        A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    {
+        let selector = self
+            .names
+            .iter()
+            .position(|name| name == fn_name)
+            .unwrap_or_else(|| panic!("`{fn_name}` should be an entrypoint"));
+
+        let mut input = vec![selector as u8];
+        input.extend(serialize(arg));
+        self.seeds.push(Seed {
+            target: self.target,
+            name: String::from(fn_name),
+            input,
+        });
+    }
+
+    fn into_inner(self) -> Vec<Seed> {
+        self.seeds
+    }
+}
+
+fn serialize<A>(arg: &A) -> Vec<u8>
+where
+    A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+{
+    rkyv::to_bytes::<_, SCRATCH_SIZE>(arg)
+        .expect("Serializing should succeed")
+        .to_vec()
+}
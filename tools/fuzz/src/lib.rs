@@ -0,0 +1,267 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Harness of the fuzz targets of the genesis contracts.
+//!
+//! Every input is run in a new session, started from a genesis state that is
+//! committed once per process, so inputs can't affect each other. The first
+//! byte of an input selects the function to call, and the rest of it is
+//! passed to the function as the bytes of its argument.
+//!
+//! The contracts reject invalid arguments by panicking, so a call that
+//! panics with one of the messages the contracts deliberately panic with, or
+//! runs out of gas, is an expected outcome. Any other panic or error of the
+//! VM is a crash, and makes the fuzz target panic for libFuzzer to report it.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+pub mod corpus;
+
+use std::sync::mpsc;
+
+use contracts_abi_schema::functions;
+use contracts_test_kit::notes::transparent_note;
+use contracts_test_kit::{Genesis, TestChain, CHAIN_ID};
+use dusk_core::abi::ContractId;
+use dusk_core::dusk;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{
+    StakeData, StakeKeys, DEFAULT_MINIMUM_STAKE, STAKE_CONTRACT,
+};
+use dusk_core::transfer::phoenix::{
+    PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+};
+use dusk_core::transfer::{ContractToContract, PANIC_NONCE_NOT_READY};
+use dusk_vm::{Error as VMError, Session, VM};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The ID of the alice test contract, deployed at genesis with a balance.
+pub const ALICE_ID: ContractId = ContractId::from_bytes([3; 32]);
+
+/// The ID of the charlie test contract, deployed at genesis.
+pub const CHARLIE_ID: ContractId = ContractId::from_bytes([4; 32]);
+
+/// The gas limit of every call made by the fuzz targets.
+pub const GAS_LIMIT: u64 = 0x10000000;
+
+/// The height of the block the inputs are run in.
+pub const BLOCK_HEIGHT: u64 = 1;
+
+/// The value of the Moonlight account, the alice contract and the Phoenix
+/// note funded at genesis.
+pub const GENESIS_VALUE: u64 = dusk(1_000_000.0);
+
+/// The value sent along with the data passed to the receiving functions,
+/// enough to be staked.
+pub const RECEIVE_VALUE: u64 = DEFAULT_MINIMUM_STAKE;
+
+/// The functions that receive data from another contract through the
+/// transfer contract, and deserialize it themselves.
+pub const RECEIVERS: [(ContractId, &str); 4] = [
+    (STAKE_CONTRACT, "stake_from_contract"),
+    (CHARLIE_ID, "stake_from_contract"),
+    (CHARLIE_ID, "receive_unstake"),
+    (CHARLIE_ID, "receive_reward"),
+];
+
+/// The messages the dependencies of the contracts deliberately panic with.
+const DEPENDENCY_PANICS: [&str; 2] = [
+    // the argument of a call doesn't deserialize to the expected type
+    "Argument should correctly deserialize",
+    PANIC_NONCE_NOT_READY,
+];
+
+const ALICE_WASM: &str = "target/wasm32-unknown-unknown/release/alice.wasm";
+const CHARLIE_WASM: &str = "target/wasm32-unknown-unknown/release/charlie.wasm";
+
+/// The keys funded in the genesis state, the same for every process so that
+/// the corpus can use them.
+pub struct Keys {
+    /// The key of the funded Moonlight account, which also has a stake.
+    pub account_sk: AccountSecretKey,
+    /// The key owning the funded Phoenix note.
+    pub phoenix_sk: PhoenixSecretKey,
+}
+
+impl Keys {
+    /// Generate the keys.
+    #[must_use]
+    pub fn new() -> Self {
+        let rng = &mut StdRng::seed_from_u64(0xfeeb);
+        Self {
+            account_sk: AccountSecretKey::random(rng),
+            phoenix_sk: PhoenixSecretKey::random(rng),
+        }
+    }
+
+    /// The public key of the funded Moonlight account.
+    #[must_use]
+    pub fn account_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.account_sk)
+    }
+
+    /// The public key owning the funded Phoenix note.
+    #[must_use]
+    pub fn phoenix_pk(&self) -> PhoenixPublicKey {
+        PhoenixPublicKey::from(&self.phoenix_sk)
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The genesis state every input is run against.
+struct Harness {
+    vm: VM,
+    base: [u8; 32],
+}
+
+impl Harness {
+    fn new() -> Self {
+        let keys = Keys::new();
+        let rng = &mut StdRng::seed_from_u64(0xbeef);
+
+        let note = transparent_note(rng, &keys.phoenix_pk(), GENESIS_VALUE);
+        let stake_data = StakeData::new(DEFAULT_MINIMUM_STAKE, 0, 0);
+
+        let genesis = Genesis::new(CHAIN_ID)
+            .contract(ALICE_ID, read_bytecode(ALICE_WASM))
+            .contract(CHARLIE_ID, read_bytecode(CHARLIE_WASM))
+            .account(keys.account_pk(), GENESIS_VALUE)
+            .contract_balance(ALICE_ID, GENESIS_VALUE)
+            .note(note)
+            .stake(StakeKeys::single_key(keys.account_pk()), stake_data);
+        let chain = TestChain::starting_at(genesis, BLOCK_HEIGHT);
+        let (vm, base) = chain.commit();
+
+        Self { vm, base }
+    }
+
+    fn session(&self) -> Session {
+        self.vm
+            .session(self.base, CHAIN_ID, BLOCK_HEIGHT)
+            .expect("Instantiating new session should succeed")
+    }
+}
+
+thread_local! {
+    static HARNESS: Harness = Harness::new();
+}
+
+/// Call the entrypoint of a contract selected by the first byte of the
+/// input, out of the ones listed in its ABI schema, with the rest of the
+/// input as the bytes of its argument.
+///
+/// # Panics
+/// Panics if the call crashes.
+pub fn fuzz_entrypoint(contract: ContractId, schema: &str, input: &[u8]) {
+    let Some((selector, arg)) = input.split_first() else {
+        return;
+    };
+    let functions = functions(schema);
+    let entrypoint = &functions[*selector as usize % functions.len()];
+
+    HARNESS.with(|harness| {
+        let mut session = harness.session();
+
+        let result = if entrypoint.feeder {
+            let (feeder, _receiver) = mpsc::channel();
+            session
+                .feeder_call_raw(
+                    contract,
+                    &entrypoint.name,
+                    arg.to_vec(),
+                    GAS_LIMIT,
+                    feeder,
+                )
+                .map(|_| ())
+        } else {
+            session
+                .call_raw(contract, &entrypoint.name, arg.to_vec(), GAS_LIMIT)
+                .map(|_| ())
+        };
+
+        check(&entrypoint.name, result);
+    });
+}
+
+/// Send the rest of the input as the data of a transfer from the alice
+/// contract to the receiving function selected by its first byte, out of the
+/// [`RECEIVERS`].
+///
+/// # Panics
+/// Panics if the call crashes.
+pub fn fuzz_receiver(input: &[u8]) {
+    let Some((selector, data)) = input.split_first() else {
+        return;
+    };
+    let (contract, fn_name) = RECEIVERS[*selector as usize % RECEIVERS.len()];
+
+    let transfer = ContractToContract {
+        contract,
+        value: RECEIVE_VALUE,
+        fn_name: String::from(fn_name),
+        data: data.to_vec(),
+    };
+
+    HARNESS.with(|harness| {
+        let mut session = harness.session();
+        let result = session.call::<_, ()>(
+            ALICE_ID,
+            "contract_to_contract",
+            &transfer,
+            GAS_LIMIT,
+        );
+        check(fn_name, result);
+    });
+}
+
+/// Check the result of a call didn't crash.
+fn check<T>(fn_name: &str, result: Result<T, VMError>) {
+    match result {
+        Ok(_) | Err(VMError::OutOfGas) => {}
+        // a crash of a contract called by another one is reported to the
+        // caller as an unknown error, which the caller panics with
+        Err(VMError::Panic(msg))
+            if !msg.contains(": Unknown") && is_expected_panic(&msg) => {}
+        Err(err) => panic!("Calling `{fn_name}` crashed: {err:?}"),
+    }
+}
+
+/// Whether a panic message starts with one of the messages the contracts or
+/// their dependencies deliberately panic with.
+fn is_expected_panic(msg: &str) -> bool {
+    CONTRACT_PANICS
+        .iter()
+        .chain(&DEPENDENCY_PANICS)
+        .any(|expected| msg.starts_with(expected))
+}
+
+// Note: the messages of the contracts are read from their sources by the
+// build script
+include!(concat!(env!("OUT_DIR"), "/panics.rs"));
+
+/// Read the bytecode at the given path, relative to the root of the
+/// repository.
+fn read_bytecode(path: &str) -> Vec<u8> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path);
+    std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Reading {} should succeed, the contracts need to be built with \
+             `make wasm` first: {err}",
+            path.display()
+        )
+    })
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Wall time of every case of the gas benchmarks, each run in a new session
+//! that is started outside of the measurement.
+
+use contracts_gas_bench::{cases, run, Fixture, Sizes};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const SAMPLE_SIZE: usize = 10;
+
+fn config() -> Criterion {
+    Criterion::default().sample_size(SAMPLE_SIZE)
+}
+
+fn entrypoints(c: &mut Criterion) {
+    let fixture = Fixture::new(Sizes::REALISTIC);
+    let mut group = c.benchmark_group("entrypoints");
+
+    for case in cases(&fixture) {
+        group.bench_function(&case.name, |b| {
+            b.iter_batched(
+                || fixture.session(),
+                |mut session| {
+                    run(&mut session, &case)
+                        .unwrap_or_else(|err| panic!("{err}"))
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = config();
+    targets = entrypoints
+);
+criterion_main!(benches);
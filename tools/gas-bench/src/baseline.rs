@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The gas spent by every case of the benchmarks, by name.
+///
+/// A baseline is written one case per line, as its name followed by the gas
+/// it spent, sorted by name so that a change of the gas spent by a case is a
+/// one line diff. Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    gas: BTreeMap<String, u64>,
+}
+
+impl Baseline {
+    /// Parse a baseline.
+    ///
+    /// # Errors
+    /// Returns an error if a line isn't a case name followed by a gas amount,
+    /// or if a case is listed more than once.
+    pub fn parse(baseline: &str) -> Result<Self, String> {
+        let mut gas = BTreeMap::new();
+
+        for (i, line) in baseline.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let (Some(name), Some(gas_spent), None) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(format!("Line {}: expected `<case> <gas>`", i + 1));
+            };
+            let gas_spent = gas_spent.parse().map_err(|err| {
+                format!("Line {}: invalid gas `{gas_spent}`: {err}", i + 1)
+            })?;
+
+            if gas.insert(String::from(name), gas_spent).is_some() {
+                return Err(format!(
+                    "Line {}: the case `{name}` is listed twice",
+                    i + 1
+                ));
+            }
+        }
+
+        Ok(Self { gas })
+    }
+
+    /// Set the gas spent by a case.
+    pub fn insert(&mut self, name: &str, gas_spent: u64) {
+        self.gas.insert(String::from(name), gas_spent);
+    }
+
+    /// The gas spent by a case, if it is in the baseline.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.gas.get(name).copied()
+    }
+
+    /// The number of cases in the baseline.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.gas.len()
+    }
+
+    /// Whether the baseline has no cases.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.gas.is_empty()
+    }
+
+    /// Compare the gas spent by the cases measured against the baseline,
+    /// listing every case of either of them by name.
+    #[must_use]
+    pub fn compare(&self, measured: &Baseline) -> Vec<Comparison> {
+        let mut names: Vec<&String> =
+            self.gas.keys().chain(measured.gas.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| Comparison {
+                name: name.clone(),
+                baseline: self.get(name),
+                measured: measured.get(name),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, gas_spent) in &self.gas {
+            writeln!(f, "{name} {gas_spent}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The gas spent by a case in the baseline, and as measured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    /// The name of the case.
+    pub name: String,
+    /// The gas spent in the baseline, or `None` for a new case.
+    pub baseline: Option<u64>,
+    /// The gas spent as measured, or `None` for a case that was removed.
+    pub measured: Option<u64>,
+}
+
+impl Comparison {
+    /// The change of the gas spent relative to the baseline, in percent, if
+    /// the case is both in the baseline and measured.
+    #[must_use]
+    pub fn change(&self) -> Option<f64> {
+        let (baseline, measured) = (self.baseline?, self.measured?);
+        if baseline == 0 {
+            return Some(if measured == 0 { 0.0 } else { f64::INFINITY });
+        }
+        Some((measured as f64 - baseline as f64) * 100.0 / baseline as f64)
+    }
+
+    /// Whether the case spends more gas than in the baseline, by more than
+    /// the given tolerance in percent.
+    #[must_use]
+    pub fn is_regression(&self, tolerance: f64) -> bool {
+        self.change().is_some_and(|change| change > tolerance)
+    }
+}
@@ -0,0 +1,158 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Record the gas spent by the cases of the benchmarks as the baseline, or
+//! compare it against the baseline, flagging the regressions.
+//!
+//! ```text
+//! gas-bench record [--baseline <path>]
+//! gas-bench compare [--baseline <path>] [--tolerance <percent>]
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{env, fs};
+
+use contracts_gas_bench::{cases, measure, Baseline, Fixture, Sizes};
+
+const USAGE: &str = "Usage: gas-bench <record|compare> [--baseline <path>] \
+                     [--tolerance <percent>]";
+
+enum Mode {
+    Record,
+    Compare { tolerance: f64 },
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, String> {
+    let mut args = env::args().skip(1);
+
+    let mut mode = match args.next().as_deref() {
+        Some("record") => Mode::Record,
+        Some("compare") => Mode::Compare { tolerance: 0.0 },
+        _ => return Err(String::from(USAGE)),
+    };
+    let mut path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("baseline.txt");
+
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        match (arg.as_str(), &mut mode) {
+            ("--baseline", _) => path = PathBuf::from(value),
+            ("--tolerance", Mode::Compare { tolerance }) => {
+                *tolerance = value
+                    .parse()
+                    .map_err(|err| format!("Invalid tolerance: {err}"))?;
+            }
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let sizes = Sizes::REALISTIC;
+    eprintln!(
+        "Building the fixture with {} accounts, {} notes and {} stakes",
+        sizes.accounts, sizes.notes, sizes.stakes
+    );
+    let fixture = Fixture::new(sizes);
+    let measured = measure(&fixture, &cases(&fixture))?;
+
+    match mode {
+        Mode::Record => {
+            let baseline = format!(
+                "# Gas spent by the cases of the gas benchmarks, against {} \
+                 accounts,\n# {} notes and {} stakes. Recorded with `make \
+                 baseline`.\n{measured}",
+                sizes.accounts, sizes.notes, sizes.stakes
+            );
+            fs::write(&path, baseline).map_err(|err| {
+                format!("Writing {} failed: {err}", path.display())
+            })?;
+            eprintln!(
+                "Recorded {} cases to {}",
+                measured.len(),
+                path.display()
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+        Mode::Compare { tolerance } => {
+            let baseline = fs::read_to_string(&path).map_err(|err| {
+                format!("Reading {} failed: {err}", path.display())
+            })?;
+            let baseline = Baseline::parse(&baseline)?;
+            Ok(compare(&baseline, &measured, tolerance))
+        }
+    }
+}
+
+/// Print the comparison of every case, and fail if any of them regressed or
+/// is missing from the baseline.
+fn compare(
+    baseline: &Baseline,
+    measured: &Baseline,
+    tolerance: f64,
+) -> ExitCode {
+    let mut regressions = 0;
+    let mut missing = 0;
+
+    println!(
+        "{:<40} {:>14} {:>14} {:>9}",
+        "case", "baseline", "measured", "change"
+    );
+    for comparison in baseline.compare(measured) {
+        let gas = |gas: Option<u64>| {
+            gas.map_or(String::from("-"), |gas| gas.to_string())
+        };
+        let status = match (comparison.baseline, comparison.measured) {
+            (None, _) => {
+                missing += 1;
+                String::from("MISSING")
+            }
+            (_, None) => String::from("removed"),
+            _ => {
+                let change = comparison.change().unwrap_or_default();
+                if comparison.is_regression(tolerance) {
+                    regressions += 1;
+                    format!("{change:+.2}% REGRESSION")
+                } else {
+                    format!("{change:+.2}%")
+                }
+            }
+        };
+        println!(
+            "{:<40} {:>14} {:>14} {status:>9}",
+            comparison.name,
+            gas(comparison.baseline),
+            gas(comparison.measured),
+        );
+    }
+
+    if missing > 0 {
+        eprintln!(
+            "{missing} cases are missing from the baseline, record it with \
+             `make baseline`"
+        );
+    }
+    if regressions > 0 {
+        eprintln!(
+            "{regressions} cases spend more gas than in the baseline, by more \
+             than {tolerance}%"
+        );
+    }
+    if missing > 0 || regressions > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
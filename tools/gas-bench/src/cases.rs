@@ -0,0 +1,679 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use bytecheck::CheckBytes;
+use contracts_abi_schema::{functions, Function};
+use contracts_test_kit::notes::transparent_note;
+use contracts_test_kit::CHAIN_ID;
+use dusk_bytes::Serializable;
+use dusk_core::abi::{ContractId, StandardBufSerializer};
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{
+    Reward, RewardReason, Stake, StakeConfig, StakeData, StakeKeys,
+    Withdraw as StakeWithdraw, DEFAULT_MINIMUM_STAKE, STAKE_CONTRACT,
+};
+use dusk_core::transfer::data::{ContractCall, TransactionData};
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::transfer::withdraw::{
+    Withdraw, WithdrawReceiver, WithdrawReplayToken,
+};
+use dusk_core::transfer::{
+    ContractToAccount, ContractToContract, Transaction, TRANSFER_CONTRACT,
+};
+use dusk_core::{dusk, BlsScalar, JubJubScalar, LUX};
+use dusk_wallet_core::transaction::{
+    moonlight_stake, moonlight_stake_reward, moonlight_unstake,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::Serialize;
+use stake_contract::migration::{
+    ExportCursor as StakeExportCursor, ExportSummary as StakeExportSummary,
+};
+use transfer_contract::escrow::{CreateEscrow, EscrowBeneficiary};
+use transfer_contract::gas::GasConfig;
+use transfer_contract::migration::{ExportCursor, ExportSummary, StateItem};
+use transfer_contract::scheduled::ScheduledWithdraw;
+use transfer_contract::verifier::SetVerifier;
+
+use crate::fixture::{
+    CANCELLABLE_ESCROW, ESCROW_VALUE, RELEASABLE_ESCROW, REWARD_VALUE,
+};
+use crate::{
+    Fixture, Keys, ALICE_ID, BLOCK_HEIGHT, BOB_ID, CHARLIE_ID, GAS_LIMIT,
+    STAKE_IMPORT_ID, TRANSFER_IMPORT_ID,
+};
+
+/// The nonce of the transactions of the cases, each sent from the account
+/// of the cases in a new session.
+const NONCE: u64 = 1;
+
+/// The value transferred by the cases.
+const VALUE: u64 = dusk(1.0);
+
+/// The number of items imported, or exported, at once by the cases of the
+/// migration functions.
+const MIGRATION_BATCH: u64 = 100;
+
+/// The size of the verifier data set by the `set_verifier` case. The
+/// contract stores the data without reading it, so a placeholder does.
+const VERIFIER_DATA_SIZE: usize = 1024;
+
+const SCRATCH_SIZE: usize = 1024;
+
+/// The entrypoints that aren't measured, with the reason why.
+pub const SKIPPED: [(&str, &str); 2] = [
+    (
+        "transfer::migrate",
+        "the state is at the latest version, so there is no migration to run",
+    ),
+    (
+        "stake::migrate",
+        "the state is at the latest version, so there is no migration to run",
+    ),
+];
+
+/// A benchmark case, measuring one or more entrypoints of the contracts.
+pub struct Case {
+    /// The name of the case, which is the name of the first entrypoint it
+    /// covers, prefixed by its contract, as in `transfer::deposit`.
+    pub name: String,
+    /// The entrypoints the case covers, prefixed by their contract.
+    pub covers: Vec<String>,
+    /// What the case runs.
+    pub call: Call,
+}
+
+/// What a benchmark case runs.
+pub enum Call {
+    /// A direct call of an entrypoint.
+    Function {
+        /// The contract called.
+        contract: ContractId,
+        /// The name of the function called.
+        fn_name: String,
+        /// The serialized argument of the function.
+        arg: Vec<u8>,
+        /// Whether the function is a feeder.
+        feeder: bool,
+    },
+    /// A transaction, for the entrypoints that can only be called in the
+    /// context of one.
+    Transaction(Box<Transaction>),
+}
+
+/// Generate the cases of every entrypoint of the transfer and stake
+/// contracts, for the given fixture.
+#[must_use]
+pub fn cases(fixture: &Fixture) -> Vec<Case> {
+    let mut cases = transfer_cases(fixture);
+    cases.extend(stake_cases(fixture));
+    cases
+}
+
+fn transfer_cases(fixture: &Fixture) -> Vec<Case> {
+    let keys = fixture.keys();
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+    let mut cases = Cases::new(
+        "transfer",
+        TRANSFER_CONTRACT,
+        transfer_contract::ABI_SCHEMA,
+    );
+
+    let pk = keys.account_pk();
+    let receiver_pk = keys.setup_pk();
+    let withdraw = |rng: &mut StdRng, contract| {
+        Withdraw::new(
+            rng,
+            &keys.account_sk,
+            contract,
+            VALUE,
+            WithdrawReceiver::Moonlight(pk),
+            WithdrawReplayToken::Moonlight(NONCE),
+        )
+    };
+
+    // Transactions
+
+    let transfer = Transaction::moonlight(
+        &keys.account_sk,
+        Some(receiver_pk),
+        VALUE,
+        0,
+        GAS_LIMIT,
+        LUX,
+        NONCE,
+        CHAIN_ID,
+        None::<TransactionData>,
+    )
+    .expect("Creating moonlight transaction should succeed");
+    cases.transaction(
+        &["transfer::spend_and_execute", "transfer::refund"],
+        transfer.clone(),
+    );
+
+    cases.transaction(
+        &["transfer::deposit"],
+        moonlight_call(keys, VALUE, contract_call(ALICE_ID, "deposit", &VALUE)),
+    );
+    cases.transaction(
+        &["transfer::withdraw"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "withdraw", &withdraw(rng, ALICE_ID)),
+        ),
+    );
+
+    let scheduled = ScheduledWithdraw::new(
+        rng,
+        &keys.account_sk,
+        CHAIN_ID,
+        ALICE_ID,
+        VALUE,
+        WithdrawReceiver::Moonlight(pk),
+        BLOCK_HEIGHT + 10,
+        1,
+    );
+    cases.transaction(
+        &["transfer::withdraw_scheduled"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(
+                ALICE_ID,
+                "withdraw_scheduled",
+                &serialize(&scheduled),
+            ),
+        ),
+    );
+
+    let convert = {
+        let address = keys
+            .phoenix_pk()
+            .gen_stealth_address(&JubJubScalar::from(0xbeefu64));
+        let note_sk = keys.phoenix_sk.gen_note_sk(&address);
+        Withdraw::new(
+            rng,
+            &note_sk,
+            TRANSFER_CONTRACT,
+            VALUE,
+            WithdrawReceiver::Phoenix(address),
+            WithdrawReplayToken::Moonlight(NONCE),
+        )
+    };
+    cases.transaction(
+        &["transfer::convert"],
+        moonlight_call(
+            keys,
+            VALUE,
+            contract_call(TRANSFER_CONTRACT, "convert", &convert),
+        ),
+    );
+
+    let to_contract = ContractToContract {
+        contract: BOB_ID,
+        value: VALUE,
+        fn_name: String::from("recv_transfer"),
+        data: Vec::new(),
+    };
+    cases.transaction(
+        &["transfer::contract_to_contract"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "contract_to_contract", &to_contract),
+        ),
+    );
+
+    let to_account = ContractToAccount {
+        account: receiver_pk,
+        value: VALUE,
+    };
+    cases.transaction(
+        &["transfer::contract_to_account"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "contract_to_account", &to_account),
+        ),
+    );
+
+    let create = CreateEscrow {
+        beneficiary: EscrowBeneficiary::Account(receiver_pk),
+        value: ESCROW_VALUE,
+        release_height: BLOCK_HEIGHT + 1000,
+        cancel_authority: Some(ALICE_ID),
+    };
+    cases.transaction(
+        &["transfer::create_escrow"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "create_escrow", &serialize(&create)),
+        ),
+    );
+    cases.transaction(
+        &["transfer::release_escrow"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(
+                TRANSFER_CONTRACT,
+                "release_escrow",
+                &RELEASABLE_ESCROW,
+            ),
+        ),
+    );
+    cases.transaction(
+        &["transfer::cancel_escrow"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "cancel_escrow", &CANCELLABLE_ESCROW),
+        ),
+    );
+
+    // Queries
+
+    let nullifiers: Vec<BlsScalar> = (0..4u64).map(BlsScalar::from).collect();
+
+    cases.call("root", &());
+    cases.call("account", &pk);
+    cases.call("accounts_root", &());
+    cases.call("account_proof", &pk);
+    cases.call("contract_balance", &ALICE_ID);
+    cases.call("opening", &0u64);
+    cases.call("existing_nullifiers", &nullifiers);
+    cases.call("nullifiers_root", &());
+    cases.call("nullifier_root", &BLOCK_HEIGHT);
+    cases.call("nullifier_proof", &nullifiers[0]);
+    cases.call("block_event_digest", &BLOCK_HEIGHT);
+    cases.call("escrow", &CANCELLABLE_ESCROW);
+    cases.call("validate_transaction", &transfer);
+    cases.call("verifiers", &(1u32, 2u32));
+    cases.call("state_version", &());
+    cases.call(
+        "export_summary",
+        &(ExportSummary::EMPTY, ExportCursor::START, MIGRATION_BATCH),
+    );
+    cases.call("gas_price_info", &());
+    cases.call("num_notes", &());
+    cases.call("chain_id", &());
+
+    // "Feeder" queries, from the start and without a limit, as a node
+    // syncing from scratch does
+
+    cases.call("leaves_from_height", &0u64);
+    cases.call("leaves_from_pos", &0u64);
+    cases.call("sync", &(0u64, 0u64));
+    cases.call("sync_nullifiers", &(0u64, 0u64));
+    cases.call("sync_contract_balances", &(0u64, 0u64));
+    cases.call("sync_accounts", &(0u64, 0u64));
+    cases.call("sync_escrows", &(0u64, 0u64));
+    cases.call("export_state", &(ExportCursor::START, MIGRATION_BATCH));
+    cases.call("frozen_accounts", &());
+
+    // "Management" transactions
+
+    let note = transparent_note(rng, &keys.phoenix_pk(), VALUE);
+    let verifier = SetVerifier {
+        circuit_id: [0; 32],
+        inputs: 1,
+        outputs: 2,
+        vd: vec![0; VERIFIER_DATA_SIZE],
+        activation_height: BLOCK_HEIGHT + 1,
+    };
+    let account = AccountData {
+        nonce: 0,
+        balance: VALUE,
+    };
+    let items: Vec<StateItem> = (0..MIGRATION_BATCH)
+        .map(|_| {
+            let pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+            StateItem::Account(pk.to_bytes(), account)
+        })
+        .collect();
+
+    cases.call("push_note", &(BLOCK_HEIGHT, note));
+    cases.call("update_root", &());
+    cases.call("update_base_fee", &GAS_LIMIT);
+    cases.call("set_gas_config", &GasConfig::DEFAULT);
+    cases.call("add_account_balance", &(pk, VALUE));
+    cases.call("sub_account_balance", &(pk, VALUE));
+    cases.call("insert_account", &(keys.new_stake_pk(), account));
+    cases.call_at(TRANSFER_IMPORT_ID, "import_state", &items);
+    cases.call_at(
+        TRANSFER_IMPORT_ID,
+        "finish_import",
+        &fixture.transfer_summary(),
+    );
+    cases.call("set_verifier", &verifier);
+    cases.call("freeze_account", &receiver_pk);
+    cases.call("unfreeze_account", &keys.frozen_pk());
+    cases.call("add_contract_balance", &(ALICE_ID, VALUE));
+
+    cases.into_inner()
+}
+
+fn stake_cases(fixture: &Fixture) -> Vec<Case> {
+    let keys = fixture.keys();
+    let rng = &mut StdRng::seed_from_u64(0xfeeb);
+    let mut cases =
+        Cases::new("stake", STAKE_CONTRACT, stake_contract::ABI_SCHEMA);
+
+    let sk = &keys.account_sk;
+    let pk = keys.account_pk();
+
+    // Transactions
+
+    let tx = moonlight_stake(
+        sk,
+        sk,
+        sk,
+        DEFAULT_MINIMUM_STAKE,
+        GAS_LIMIT,
+        LUX,
+        NONCE,
+        CHAIN_ID,
+    )
+    .expect("Creating stake transaction should succeed");
+    cases.transaction(&["stake::stake"], tx);
+
+    let tx = moonlight_unstake(
+        rng,
+        sk,
+        sk,
+        sk,
+        DEFAULT_MINIMUM_STAKE,
+        GAS_LIMIT,
+        LUX,
+        NONCE,
+        CHAIN_ID,
+    )
+    .expect("Creating unstake transaction should succeed");
+    cases.transaction(&["stake::unstake"], tx);
+
+    let tx = moonlight_stake_reward(
+        rng,
+        sk,
+        sk,
+        sk,
+        REWARD_VALUE,
+        GAS_LIMIT,
+        LUX,
+        NONCE,
+        CHAIN_ID,
+    )
+    .expect("Creating withdraw transaction should succeed");
+    cases.transaction(&["stake::withdraw", "transfer::mint"], tx);
+
+    // alice stakes on behalf of a new key, owning the stake
+    let stake = Stake::new_from_contract(
+        &keys.new_stake_sk,
+        ALICE_ID,
+        DEFAULT_MINIMUM_STAKE,
+        CHAIN_ID,
+    );
+    let to_stake = ContractToContract {
+        contract: STAKE_CONTRACT,
+        value: DEFAULT_MINIMUM_STAKE,
+        fn_name: String::from("stake_from_contract"),
+        data: serialize(&stake),
+    };
+    cases.transaction(
+        &["stake::stake_from_contract"],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(ALICE_ID, "contract_to_contract", &to_stake),
+        ),
+    );
+
+    // charlie relays the withdrawals of its stake to the account of the
+    // cases
+    let contract_withdraw = |rng: &mut StdRng, value| {
+        StakeWithdraw::new(
+            &keys.contract_stake_sk,
+            &keys.contract_stake_sk,
+            Withdraw::new(
+                rng,
+                sk,
+                CHARLIE_ID,
+                value,
+                WithdrawReceiver::Moonlight(pk),
+                WithdrawReplayToken::Moonlight(NONCE),
+            ),
+        )
+    };
+    let unstake = contract_withdraw(rng, DEFAULT_MINIMUM_STAKE);
+    cases.transaction(
+        &["stake::unstake_from_contract"],
+        moonlight_call(keys, 0, contract_call(CHARLIE_ID, "unstake", &unstake)),
+    );
+    let withdraw = contract_withdraw(rng, REWARD_VALUE);
+    cases.transaction(
+        &[
+            "stake::withdraw_from_contract",
+            "transfer::mint_to_contract",
+        ],
+        moonlight_call(
+            keys,
+            0,
+            contract_call(CHARLIE_ID, "withdraw", &withdraw),
+        ),
+    );
+
+    // Queries
+
+    cases.call("get_stake", &pk);
+    cases.call("get_stake_keys", &pk);
+    cases.call("burnt_amount", &());
+    cases.call("get_version", &());
+    cases.call("block_event_digest", &BLOCK_HEIGHT);
+    cases.call("state_version", &());
+    cases.call("get_config", &());
+    cases.call(
+        "export_summary",
+        &(
+            StakeExportSummary::EMPTY,
+            StakeExportCursor::START,
+            MIGRATION_BATCH,
+        ),
+    );
+
+    // "Feeder" queries
+
+    cases.call("stakes", &());
+    cases.call("prev_state_changes", &());
+    cases.call(
+        "export_stakes",
+        &(StakeExportCursor::START, MIGRATION_BATCH),
+    );
+
+    // "Management" transactions
+
+    let reward = Reward {
+        account: pk,
+        value: REWARD_VALUE,
+        reason: RewardReason::Other,
+    };
+    let stakes: Vec<(StakeKeys, StakeData)> = (0..MIGRATION_BATCH)
+        .map(|_| {
+            let pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+            (
+                StakeKeys::single_key(pk),
+                StakeData::new(DEFAULT_MINIMUM_STAKE, 0, 0),
+            )
+        })
+        .collect();
+
+    cases.call("before_state_transition", &());
+    cases.call("set_config", &StakeConfig::new());
+    cases.call(
+        "insert_stake",
+        &(
+            StakeKeys::single_key(keys.new_stake_pk()),
+            StakeData::new(DEFAULT_MINIMUM_STAKE, 0, 0),
+        ),
+    );
+    cases.call("reward", &vec![reward]);
+    cases.call("slash", &(pk, Some(VALUE)));
+    cases.call_covering(
+        "hard_slash",
+        &(pk, Some(VALUE), Some(1u8)),
+        &["transfer::sub_contract_balance"],
+    );
+    cases.call("set_burnt_amount", &VALUE);
+    cases.call_at(STAKE_IMPORT_ID, "import_stakes", &stakes);
+    cases.call_at(STAKE_IMPORT_ID, "finish_import", &fixture.stake_summary());
+
+    cases.into_inner()
+}
+
+/// A moonlight transaction from the account of the cases, depositing the
+/// given value and making the given call.
+fn moonlight_call(
+    keys: &Keys,
+    deposit: u64,
+    call: ContractCall,
+) -> Transaction {
+    Transaction::moonlight(
+        &keys.account_sk,
+        None,
+        0,
+        deposit,
+        GAS_LIMIT,
+        LUX,
+        NONCE,
+        CHAIN_ID,
+        Some(call),
+    )
+    .expect("Creating moonlight transaction should succeed")
+}
+
+fn contract_call<A>(
+    contract: ContractId,
+    fn_name: &str,
+    arg: &A,
+) -> ContractCall
+where
+    A: for<'b> Serialize<StandardBufSerializer<'b>>,
+    A::Archived: for<'b> CheckBytes<DefaultValidator<'b>>,
+{
+    ContractCall::new(contract, fn_name)
+        .with_args(arg)
+        .expect("Serializing should succeed")
+}
+
+/// The cases of the entrypoints of a contract.
+struct Cases {
+    contract_name: &'static str,
+    contract: ContractId,
+    functions: Vec<Function>,
+    cases: Vec<Case>,
+}
+
+impl Cases {
+    fn new(
+        contract_name: &'static str,
+        contract: ContractId,
+        schema: &str,
+    ) -> Self {
+        Self {
+            contract_name,
+            contract,
+            functions: functions(schema),
+            cases: Vec::new(),
+        }
+    }
+
+    /// Add a case calling the given entrypoint with the given argument.
+    fn call<A>(&mut self, fn_name: &str, arg: &A)
+    where
+        A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    {
+        self.call_covering(fn_name, arg, &[]);
+    }
+
+    /// Add a case calling the given entrypoint with the given argument,
+    /// which also covers the given entrypoints called by it.
+    fn call_covering<A>(&mut self, fn_name: &str, arg: &A, also: &[&str])
+    where
+        A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    {
+        self.push(self.contract, fn_name, arg, also);
+    }
+
+    /// Add a case calling the given entrypoint with the given argument, on
+    /// another deployment of the contract under the given ID.
+    fn call_at<A>(&mut self, contract: ContractId, fn_name: &str, arg: &A)
+    where
+        A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    {
+        self.push(contract, fn_name, arg, &[]);
+    }
+
+    fn push<A>(
+        &mut self,
+        contract: ContractId,
+        fn_name: &str,
+        arg: &A,
+        also: &[&str],
+    ) where
+        A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    {
+        let function = self
+            .functions
+            .iter()
+            .find(|function| function.name == fn_name)
+            .unwrap_or_else(|| panic!("`{fn_name}` should be an entrypoint"));
+
+        let name = format!("{}::{fn_name}", self.contract_name);
+        let mut covers = vec![name.clone()];
+        covers.extend(also.iter().map(|name| String::from(*name)));
+
+        self.cases.push(Case {
+            name,
+            covers,
+            call: Call::Function {
+                contract,
+                fn_name: String::from(fn_name),
+                arg: serialize(arg),
+                feeder: function.feeder,
+            },
+        });
+    }
+
+    /// Add a case executing the given transaction, covering the given
+    /// entrypoints, the first of which names the case.
+    fn transaction(&mut self, covers: &[&str], tx: Transaction) {
+        let covers: Vec<String> =
+            covers.iter().map(|name| String::from(*name)).collect();
+        self.cases.push(Case {
+            name: covers[0].clone(),
+            covers,
+            call: Call::Transaction(Box::new(tx)),
+        });
+    }
+
+    fn into_inner(self) -> Vec<Case> {
+        self.cases
+    }
+}
+
+fn serialize<A>(arg: &A) -> Vec<u8>
+where
+    A: Serialize<AllocSerializer<SCRATCH_SIZE>>,
+{
+    rkyv::to_bytes::<_, SCRATCH_SIZE>(arg)
+        .expect("Serializing should succeed")
+        .to_vec()
+}
@@ -0,0 +1,425 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::path::Path;
+
+use contracts_test_kit::notes::transparent_note;
+use contracts_test_kit::{
+    stake, stake_bytecode, transfer, transfer_bytecode, Genesis, TestChain,
+    CHAIN_ID,
+};
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{StakeData, StakeKeys, DEFAULT_MINIMUM_STAKE};
+use dusk_core::transfer::data::ContractCall;
+use dusk_core::transfer::phoenix::{
+    PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+};
+use dusk_core::transfer::Transaction;
+use dusk_core::{dusk, LUX};
+use dusk_vm::{execute, ExecutionConfig, Session, VM};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use stake_contract::migration::{
+    ExportCursor as StakeExportCursor, ExportSummary as StakeExportSummary,
+};
+use transfer_contract::escrow::{CreateEscrow, EscrowBeneficiary};
+use transfer_contract::migration::{ExportCursor, ExportSummary};
+
+use crate::{
+    ALICE_ID, BLOCK_HEIGHT, BOB_ID, CHARLIE_ID, GAS_LIMIT, STAKE_IMPORT_ID,
+    TRANSFER_IMPORT_ID,
+};
+
+/// The value of the funded accounts and of the alice contract.
+pub(crate) const GENESIS_VALUE: u64 = dusk(1_000_000.0);
+
+/// The amount staked by the stakes the cases use.
+pub(crate) const STAKE_VALUE: u64 = 10 * DEFAULT_MINIMUM_STAKE;
+
+/// The reward of the stakes the cases use.
+pub(crate) const REWARD_VALUE: u64 = dusk(100.0);
+
+/// The value of every escrow created in the fixture.
+pub(crate) const ESCROW_VALUE: u64 = dusk(10.0);
+
+/// The ID of the escrow that can be released in the block of the cases.
+pub(crate) const RELEASABLE_ESCROW: u64 = 0;
+
+/// The ID of the escrow that can be cancelled by alice.
+pub(crate) const CANCELLABLE_ESCROW: u64 = 1;
+
+/// The number of items exported, and imported, at once when filling the
+/// contracts the migration cases import into.
+const IMPORT_CHUNK: u64 = 100;
+
+/// The value of every account, note and stake filling the state.
+const FILLER_VALUE: u64 = dusk(1.0);
+
+const ALICE_WASM: &str = "target/wasm32-unknown-unknown/release/alice.wasm";
+const BOB_WASM: &str = "target/wasm32-unknown-unknown/release/bob.wasm";
+const CHARLIE_WASM: &str = "target/wasm32-unknown-unknown/release/charlie.wasm";
+
+/// The amount of state filling the contracts, on top of the state used by
+/// the cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sizes {
+    /// The number of Moonlight accounts.
+    pub accounts: usize,
+    /// The number of Phoenix notes.
+    pub notes: usize,
+    /// The number of stakes.
+    pub stakes: usize,
+}
+
+impl Sizes {
+    /// The sizes the baseline is recorded with, in the order of magnitude of
+    /// a live network.
+    pub const REALISTIC: Self = Self {
+        accounts: 10_000,
+        notes: 100_000,
+        stakes: 1_000,
+    };
+
+    /// No state besides the one used by the cases, to check them quickly.
+    pub const MINIMAL: Self = Self {
+        accounts: 0,
+        notes: 0,
+        stakes: 0,
+    };
+}
+
+/// The keys used by the cases, the same for every process so that the
+/// signed transactions of the cases are too.
+pub struct Keys {
+    /// The key of the funded account sending the transactions of the cases,
+    /// which also has a stake with a reward.
+    pub account_sk: AccountSecretKey,
+    /// The key of the funded account sending the transactions setting up the
+    /// fixture, and receiving the transfers of the cases.
+    pub setup_sk: AccountSecretKey,
+    /// The key of the stake owned by the charlie contract.
+    pub contract_stake_sk: AccountSecretKey,
+    /// The key of a new stake, staked through the alice contract.
+    pub new_stake_sk: AccountSecretKey,
+    /// The key of the funded account frozen in the fixture.
+    pub frozen_sk: AccountSecretKey,
+    /// The key receiving the notes of the conversions.
+    pub phoenix_sk: PhoenixSecretKey,
+}
+
+impl Keys {
+    /// Generate the keys.
+    #[must_use]
+    pub fn new() -> Self {
+        let rng = &mut StdRng::seed_from_u64(0xfeeb);
+        Self {
+            account_sk: AccountSecretKey::random(rng),
+            setup_sk: AccountSecretKey::random(rng),
+            contract_stake_sk: AccountSecretKey::random(rng),
+            new_stake_sk: AccountSecretKey::random(rng),
+            frozen_sk: AccountSecretKey::random(rng),
+            phoenix_sk: PhoenixSecretKey::random(rng),
+        }
+    }
+
+    /// The public key of the account sending the transactions of the cases.
+    #[must_use]
+    pub fn account_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.account_sk)
+    }
+
+    /// The public key of the account setting up the fixture.
+    #[must_use]
+    pub fn setup_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.setup_sk)
+    }
+
+    /// The public key of the stake owned by the charlie contract.
+    #[must_use]
+    pub fn contract_stake_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.contract_stake_sk)
+    }
+
+    /// The public key of the new stake.
+    #[must_use]
+    pub fn new_stake_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.new_stake_sk)
+    }
+
+    /// The public key of the frozen account.
+    #[must_use]
+    pub fn frozen_pk(&self) -> AccountPublicKey {
+        AccountPublicKey::from(&self.frozen_sk)
+    }
+
+    /// The public key receiving the notes of the conversions.
+    #[must_use]
+    pub fn phoenix_pk(&self) -> PhoenixPublicKey {
+        PhoenixPublicKey::from(&self.phoenix_sk)
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The committed state every case is run against.
+///
+/// Besides the filling state, the fixture has:
+/// - the alice, bob and charlie test contracts deployed, with alice funded
+/// - the funded accounts of the [`Keys`], with the one of the cases staking
+/// - a stake owned by charlie
+/// - an escrow that can be released, and one that can be cancelled by alice
+/// - a frozen account
+/// - fresh transfer and stake contracts, holding the whole state of the
+///   transfer and stake contracts imported but not finished
+pub struct Fixture {
+    vm: VM,
+    base: [u8; 32],
+    keys: Keys,
+    transfer_summary: ExportSummary,
+    stake_summary: StakeExportSummary,
+}
+
+impl Fixture {
+    /// Build the fixture with the given amount of filling state.
+    ///
+    /// # Panics
+    /// Panics if setting up the state fails, for example if the contracts
+    /// weren't built with `make wasm`.
+    #[must_use]
+    pub fn new(sizes: Sizes) -> Self {
+        Self::with_bytecode(sizes, transfer_bytecode(), stake_bytecode())
+    }
+
+    /// Build the fixture with the given amount of filling state, deploying
+    /// the given bytecode as the transfer and stake contracts.
+    ///
+    /// # Panics
+    /// Panics if setting up the state fails, for example if the test
+    /// contracts weren't built with `make wasm`.
+    #[must_use]
+    pub fn with_bytecode(
+        sizes: Sizes,
+        transfer_bytecode: Vec<u8>,
+        stake_bytecode: Vec<u8>,
+    ) -> Self {
+        let keys = Keys::new();
+        let rng = &mut StdRng::seed_from_u64(0xbeef);
+
+        let mut genesis = Genesis::new(CHAIN_ID)
+            .transfer_bytecode(transfer_bytecode.clone())
+            .stake_bytecode(stake_bytecode.clone())
+            .contract(TRANSFER_IMPORT_ID, transfer_bytecode)
+            .contract(STAKE_IMPORT_ID, stake_bytecode)
+            .contract(ALICE_ID, read_bytecode(ALICE_WASM))
+            .contract_with_init_arg(BOB_ID, read_bytecode(BOB_WASM), 1u8)
+            .contract(CHARLIE_ID, read_bytecode(CHARLIE_WASM))
+            .account(keys.account_pk(), GENESIS_VALUE)
+            .account(keys.setup_pk(), GENESIS_VALUE)
+            .account(keys.frozen_pk(), GENESIS_VALUE)
+            .contract_balance(ALICE_ID, GENESIS_VALUE)
+            .stake(
+                StakeKeys::single_key(keys.account_pk()),
+                StakeData::new(STAKE_VALUE, REWARD_VALUE, 0),
+            )
+            .stake(
+                StakeKeys::new(keys.contract_stake_pk(), CHARLIE_ID),
+                StakeData::new(STAKE_VALUE, REWARD_VALUE, 0),
+            );
+
+        for _ in 0..sizes.accounts {
+            let sk = AccountSecretKey::random(rng);
+            genesis =
+                genesis.account(AccountPublicKey::from(&sk), FILLER_VALUE);
+        }
+
+        // the notes are all owned by the same key, which is irrelevant to
+        // the contract and much faster to generate
+        let phoenix_pk = PhoenixPublicKey::from(&PhoenixSecretKey::random(rng));
+        for _ in 0..sizes.notes {
+            genesis =
+                genesis.note(transparent_note(rng, &phoenix_pk, FILLER_VALUE));
+        }
+
+        for _ in 0..sizes.stakes {
+            let sk = AccountSecretKey::random(rng);
+            genesis = genesis.stake(
+                StakeKeys::single_key(AccountPublicKey::from(&sk)),
+                StakeData::new(DEFAULT_MINIMUM_STAKE, FILLER_VALUE, 0),
+            );
+        }
+
+        let mut chain = TestChain::starting_at(genesis, BLOCK_HEIGHT);
+
+        let escrows = [
+            CreateEscrow {
+                beneficiary: EscrowBeneficiary::Account(keys.setup_pk()),
+                value: ESCROW_VALUE,
+                release_height: BLOCK_HEIGHT,
+                cancel_authority: None,
+            },
+            CreateEscrow {
+                beneficiary: EscrowBeneficiary::Contract(BOB_ID),
+                value: ESCROW_VALUE,
+                release_height: BLOCK_HEIGHT + 1000,
+                cancel_authority: Some(ALICE_ID),
+            },
+        ];
+        for (nonce, create) in (1..).zip(escrows) {
+            let create = rkyv::to_bytes::<_, 1024>(&create)
+                .expect("Serializing the escrow should succeed")
+                .to_vec();
+            let call = ContractCall::new(ALICE_ID, "create_escrow")
+                .with_args(&create)
+                .expect("Serializing should succeed");
+            let tx = Transaction::moonlight(
+                &keys.setup_sk,
+                None,
+                0,
+                0,
+                GAS_LIMIT,
+                LUX,
+                nonce,
+                CHAIN_ID,
+                Some(call),
+            )
+            .expect("Creating moonlight transaction should succeed");
+
+            let receipt = execute(&mut chain, &tx, &ExecutionConfig::DEFAULT)
+                .expect("Executing the transaction should succeed");
+            receipt.data.expect("Creating the escrow should succeed");
+        }
+
+        transfer::freeze_account(&mut chain, &keys.frozen_pk())
+            .expect("Freezing the account should succeed");
+
+        let (transfer_summary, stake_summary) = import_state(&mut chain);
+
+        let (vm, base) = chain.commit();
+
+        Self {
+            vm,
+            base,
+            keys,
+            transfer_summary,
+            stake_summary,
+        }
+    }
+
+    /// Start a new session from the fixture, in the block the cases are run
+    /// in.
+    ///
+    /// # Panics
+    /// Panics if the session can't be started.
+    #[must_use]
+    pub fn session(&self) -> Session {
+        self.vm
+            .session(self.base, CHAIN_ID, BLOCK_HEIGHT)
+            .expect("Instantiating new session should succeed")
+    }
+
+    /// The keys used by the cases.
+    #[must_use]
+    pub fn keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    /// The export summary of the state of the transfer contract.
+    #[must_use]
+    pub fn transfer_summary(&self) -> ExportSummary {
+        self.transfer_summary
+    }
+
+    /// The export summary of the state of the stake contract.
+    #[must_use]
+    pub fn stake_summary(&self) -> StakeExportSummary {
+        self.stake_summary
+    }
+}
+
+/// Import the whole state of the transfer and stake contracts into the
+/// contracts the migration cases import into, without finishing the imports,
+/// and return the export summaries of the transfer and stake contracts.
+fn import_state(session: &mut Session) -> (ExportSummary, StakeExportSummary) {
+    let mut transfer_summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    while let Some(cursor) = from {
+        let items = transfer::export_state(session, cursor, IMPORT_CHUNK)
+            .expect("Exporting the state should succeed");
+        session
+            .call::<_, ()>(
+                TRANSFER_IMPORT_ID,
+                "import_state",
+                &items,
+                GAS_LIMIT,
+            )
+            .expect("Importing the state should succeed");
+
+        let chunk = transfer::export_summary(
+            session,
+            transfer_summary,
+            cursor,
+            IMPORT_CHUNK,
+        )
+        .expect("Querying the export summary should succeed");
+        transfer_summary = chunk.summary;
+        from = chunk.next;
+    }
+
+    let mut stake_summary = StakeExportSummary::EMPTY;
+    let mut from = Some(StakeExportCursor::START);
+    while let Some(cursor) = from {
+        let stakes = stake::export_stakes(session, cursor, IMPORT_CHUNK)
+            .expect("Exporting the stakes should succeed");
+        session
+            .call::<_, ()>(STAKE_IMPORT_ID, "import_stakes", &stakes, GAS_LIMIT)
+            .expect("Importing the stakes should succeed");
+
+        let chunk =
+            stake::export_summary(session, stake_summary, cursor, IMPORT_CHUNK)
+                .expect("Querying the export summary should succeed");
+        stake_summary = chunk.summary;
+        from = chunk.next;
+    }
+
+    let config = stake::get_config(session)
+        .expect("Querying the stake config should succeed");
+    session
+        .call::<_, ()>(STAKE_IMPORT_ID, "set_config", &config, GAS_LIMIT)
+        .expect("Setting the stake config should succeed");
+    let burnt_amount = stake::burnt_amount(session)
+        .expect("Querying the burnt amount should succeed");
+    session
+        .call::<_, ()>(
+            STAKE_IMPORT_ID,
+            "set_burnt_amount",
+            &burnt_amount,
+            GAS_LIMIT,
+        )
+        .expect("Setting the burnt amount should succeed");
+
+    (transfer_summary, stake_summary)
+}
+
+/// Read the bytecode at the given path, relative to the root of the
+/// repository.
+fn read_bytecode(path: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path);
+    std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Reading {} should succeed, the contracts need to be built with \
+             `make wasm` first: {err}",
+            path.display()
+        )
+    })
+}
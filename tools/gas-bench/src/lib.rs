@@ -0,0 +1,113 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Gas benchmarks of the entrypoints of the genesis contracts.
+//!
+//! Every entrypoint is measured by a [`Case`], run in a new session started
+//! from a [`Fixture`] with a realistic amount of state. Queries, feeders and
+//! management transactions are called directly, while the entrypoints that
+//! can only be called in the context of a transaction are measured through
+//! the moonlight transaction calling them, and report the gas spent by the
+//! whole transaction.
+//!
+//! The gas spent by every case is deterministic, and is compared against the
+//! [`Baseline`] checked in with the benchmarks to flag regressions.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod baseline;
+mod cases;
+mod fixture;
+
+pub use baseline::{Baseline, Comparison};
+pub use cases::{cases, Call, Case, SKIPPED};
+pub use fixture::{Fixture, Keys, Sizes};
+
+use std::sync::mpsc;
+
+use dusk_core::abi::ContractId;
+use dusk_vm::{execute, ExecutionConfig, Session};
+
+/// The ID of the alice test contract, deployed with a balance.
+pub const ALICE_ID: ContractId = ContractId::from_bytes([3; 32]);
+
+/// The ID of the bob test contract, receiving transfers from alice.
+pub const BOB_ID: ContractId = ContractId::from_bytes([5; 32]);
+
+/// The ID of the charlie test contract, owning a stake.
+pub const CHARLIE_ID: ContractId = ContractId::from_bytes([4; 32]);
+
+/// The ID of the fresh transfer contract the migration cases import into,
+/// holding the whole state of the transfer contract imported but not
+/// finished.
+pub const TRANSFER_IMPORT_ID: ContractId = ContractId::from_bytes([6; 32]);
+
+/// The ID of the fresh stake contract the migration cases import into,
+/// holding the whole state of the stake contract imported but not finished.
+pub const STAKE_IMPORT_ID: ContractId = ContractId::from_bytes([7; 32]);
+
+/// The gas limit of every call and transaction of the benchmarks.
+pub const GAS_LIMIT: u64 = 0x10000000;
+
+/// The height of the block the cases are run in.
+pub const BLOCK_HEIGHT: u64 = 1;
+
+/// Run a case in the given session, returning the gas it spent.
+///
+/// # Errors
+/// Returns an error if the call or transaction of the case fails, since the
+/// gas spent by a failing call isn't representative of the entrypoint.
+pub fn run(session: &mut Session, case: &Case) -> Result<u64, String> {
+    match &case.call {
+        Call::Function {
+            contract,
+            fn_name,
+            arg,
+            feeder,
+        } => {
+            let receipt = if *feeder {
+                let (feeder, _receiver) = mpsc::channel();
+                session.feeder_call_raw(
+                    *contract,
+                    fn_name,
+                    arg.clone(),
+                    GAS_LIMIT,
+                    feeder,
+                )
+            } else {
+                session.call_raw(*contract, fn_name, arg.clone(), GAS_LIMIT)
+            };
+            receipt
+                .map(|receipt| receipt.gas_spent)
+                .map_err(|err| format!("`{}` failed: {err:?}", case.name))
+        }
+        Call::Transaction(tx) => {
+            let receipt = execute(session, tx, &ExecutionConfig::DEFAULT)
+                .map_err(|err| {
+                    format!("`{}` was rejected: {err:?}", case.name)
+                })?;
+            match receipt.data {
+                Ok(_) => Ok(receipt.gas_spent),
+                Err(err) => Err(format!("`{}` failed: {err:?}", case.name)),
+            }
+        }
+    }
+}
+
+/// Run every case in a new session started from the given fixture,
+/// returning the gas spent by each of them.
+///
+/// # Errors
+/// Returns an error if any case fails.
+pub fn measure(fixture: &Fixture, cases: &[Case]) -> Result<Baseline, String> {
+    let mut measured = Baseline::default();
+    for case in cases {
+        let gas_spent = run(&mut fixture.session(), case)?;
+        measured.insert(&case.name, gas_spent);
+    }
+    Ok(measured)
+}
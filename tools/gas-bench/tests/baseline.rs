@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_gas_bench::{Baseline, Comparison};
+
+const BASELINE: &str = "\
+# recorded by hand
+transfer::root 1000
+
+stake::stakes 2000
+transfer::account 500
+";
+
+#[test]
+fn parse_and_write() {
+    let baseline =
+        Baseline::parse(BASELINE).expect("Parsing the baseline should succeed");
+
+    assert_eq!(baseline.len(), 3);
+    assert_eq!(baseline.get("transfer::root"), Some(1000));
+    assert_eq!(baseline.get("transfer::sync"), None);
+
+    // written sorted by name, without the comments
+    assert_eq!(
+        baseline.to_string(),
+        "stake::stakes 2000\ntransfer::account 500\ntransfer::root 1000\n"
+    );
+    assert_eq!(Baseline::parse(&baseline.to_string()), Ok(baseline));
+}
+
+#[test]
+fn invalid_lines() {
+    assert_eq!(
+        Baseline::parse("transfer::root"),
+        Err(String::from("Line 1: expected `<case> <gas>`"))
+    );
+    assert_eq!(
+        Baseline::parse("transfer::root 1 2"),
+        Err(String::from("Line 1: expected `<case> <gas>`"))
+    );
+    assert!(Baseline::parse("transfer::root -1").is_err());
+    assert_eq!(
+        Baseline::parse("transfer::root 1\ntransfer::root 2"),
+        Err(String::from(
+            "Line 2: the case `transfer::root` is listed twice"
+        ))
+    );
+}
+
+#[test]
+fn compare() {
+    let baseline =
+        Baseline::parse(BASELINE).expect("Parsing the baseline should succeed");
+
+    let mut measured = Baseline::default();
+    measured.insert("transfer::root", 1010);
+    measured.insert("stake::stakes", 1500);
+    measured.insert("transfer::sync", 3000);
+
+    let comparisons = baseline.compare(&measured);
+    let comparison = |name: &str, baseline, measured| Comparison {
+        name: String::from(name),
+        baseline,
+        measured,
+    };
+    assert_eq!(
+        comparisons,
+        vec![
+            comparison("stake::stakes", Some(2000), Some(1500)),
+            comparison("transfer::account", Some(500), None),
+            comparison("transfer::root", Some(1000), Some(1010)),
+            comparison("transfer::sync", None, Some(3000)),
+        ]
+    );
+
+    // only an increase beyond the tolerance is a regression, and new or
+    // removed cases never are
+    assert_eq!(comparisons[0].change(), Some(-25.0));
+    assert_eq!(comparisons[2].change(), Some(1.0));
+    assert!(comparisons[2].is_regression(0.0));
+    assert!(!comparisons[2].is_regression(1.0));
+    assert!(!comparisons[0].is_regression(0.0));
+    assert!(!comparisons[1].is_regression(0.0));
+    assert!(!comparisons[3].is_regression(0.0));
+}
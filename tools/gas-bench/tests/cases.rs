@@ -0,0 +1,273 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::mpsc;
+
+use bytecheck::CheckBytes;
+use contracts_abi_schema::{functions, Function};
+use contracts_gas_bench::{
+    cases, run, Baseline, Call, Fixture, Sizes, GAS_LIMIT, SKIPPED,
+};
+use dusk_core::stake::STAKE_CONTRACT;
+use dusk_core::transfer::TRANSFER_CONTRACT;
+use dusk_vm::{execute, ExecutionConfig};
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Infallible, Serialize};
+
+const SCRATCH_SIZE: usize = 1024;
+
+/// The functions of both contracts, by name prefixed by their contract.
+fn declared_functions() -> BTreeMap<String, Function> {
+    let transfer = functions(transfer_contract::ABI_SCHEMA)
+        .into_iter()
+        .map(|function| (format!("transfer::{}", function.name), function));
+    let stake = functions(stake_contract::ABI_SCHEMA)
+        .into_iter()
+        .map(|function| (format!("stake::{}", function.name), function));
+    transfer.chain(stake).collect()
+}
+
+/// Every entrypoint of the contracts, prefixed by its contract.
+fn entrypoints() -> BTreeSet<String> {
+    declared_functions().into_keys().collect()
+}
+
+/// Whether the given bytes are the archive of a value of the type declared
+/// in a schema, deserializing the value and serializing it back to the same
+/// bytes.
+fn round_trips(declared: &str, bytes: &[u8]) -> bool {
+    macro_rules! declared_types {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if declared == stringify!($ty) {
+                    return round_trips_as::<$ty>(bytes);
+                }
+            )*
+            panic!("`{declared}` should be a known type")
+        };
+    }
+
+    declared_types!(
+        (),
+        u8,
+        u64,
+        (u32, u32),
+        (u64, u64),
+        dusk_core::BlsScalar,
+        Option<dusk_core::BlsScalar>,
+        Vec<dusk_core::BlsScalar>,
+        dusk_core::abi::ContractId,
+        (dusk_core::abi::ContractId, u64),
+        Result<Vec<u8>, dusk_core::abi::ContractError>,
+        dusk_core::signatures::bls::PublicKey,
+        (dusk_core::signatures::bls::PublicKey, u64),
+        (dusk_core::signatures::bls::PublicKey, Option<u64>),
+        (
+            dusk_core::signatures::bls::PublicKey,
+            Option<u64>,
+            Option<u8>
+        ),
+        (
+            dusk_core::signatures::bls::PublicKey,
+            dusk_core::transfer::moonlight::AccountData
+        ),
+        (
+            dusk_core::signatures::bls::PublicKey,
+            Option<dusk_core::stake::StakeData>
+        ),
+        dusk_core::stake::Stake,
+        dusk_core::stake::StakeConfig,
+        dusk_core::stake::Withdraw,
+        dusk_core::stake::WithdrawToContract,
+        Vec<dusk_core::stake::Reward>,
+        Option<dusk_core::stake::StakeData>,
+        Option<dusk_core::stake::StakeKeys>,
+        (dusk_core::stake::StakeKeys, dusk_core::stake::StakeData),
+        Vec<(dusk_core::stake::StakeKeys, dusk_core::stake::StakeData)>,
+        dusk_core::transfer::Transaction,
+        dusk_core::transfer::ContractToAccount,
+        dusk_core::transfer::ContractToContract,
+        dusk_core::transfer::ReceiveFromContract,
+        dusk_core::transfer::withdraw::Withdraw,
+        dusk_core::transfer::moonlight::AccountData,
+        (dusk_core::transfer::moonlight::AccountData, [u8; 96]),
+        dusk_core::transfer::phoenix::NoteLeaf,
+        Option<dusk_core::transfer::phoenix::Note>,
+        (u64, dusk_core::transfer::phoenix::Note),
+        Option<dusk_core::transfer::phoenix::NoteOpening>,
+        Option<transfer_contract::accounts::AccountProof>,
+        transfer_contract::escrow::CreateEscrow,
+        Option<transfer_contract::escrow::Escrow>,
+        (u64, transfer_contract::escrow::Escrow),
+        transfer_contract::gas::GasConfig,
+        transfer_contract::gas::GasPriceInfo,
+        transfer_contract::migration::ExportSummary,
+        transfer_contract::migration::ExportChunk,
+        (transfer_contract::migration::ExportCursor, u64),
+        (
+            transfer_contract::migration::ExportSummary,
+            transfer_contract::migration::ExportCursor,
+            u64
+        ),
+        transfer_contract::migration::StateItem,
+        Vec<transfer_contract::migration::StateItem>,
+        transfer_contract::nullifiers::NullifierProof,
+        transfer_contract::receipt::TransactionReceipt,
+        transfer_contract::scheduled::ScheduledWithdraw,
+        transfer_contract::validation::ValidationReport,
+        transfer_contract::verifier::SetVerifier,
+        Vec<transfer_contract::verifier::Verifier>,
+        stake_contract::migration::ExportSummary,
+        stake_contract::migration::ExportChunk,
+        (stake_contract::migration::ExportCursor, u64),
+        (
+            stake_contract::migration::ExportSummary,
+            stake_contract::migration::ExportCursor,
+            u64
+        ),
+    )
+}
+
+fn round_trips_as<T>(bytes: &[u8]) -> bool
+where
+    T: Archive + Serialize<AllocSerializer<SCRATCH_SIZE>>,
+    T::Archived:
+        Deserialize<T, Infallible> + for<'b> CheckBytes<DefaultValidator<'b>>,
+{
+    let Ok(archived) = rkyv::check_archived_root::<T>(bytes) else {
+        return false;
+    };
+    let value: T = archived
+        .deserialize(&mut Infallible)
+        .expect("Deserializing should succeed");
+    let serialized = rkyv::to_bytes::<_, SCRATCH_SIZE>(&value)
+        .expect("Serializing should succeed");
+    serialized.as_slice() == bytes
+}
+
+#[test]
+fn every_entrypoint_is_covered() {
+    let fixture = Fixture::new(Sizes::MINIMAL);
+
+    let mut covered = BTreeSet::new();
+    for case in cases(&fixture) {
+        for name in case.covers {
+            assert!(covered.insert(name), "Covered twice: {}", case.name);
+        }
+    }
+    for (name, _) in SKIPPED {
+        assert!(covered.insert(String::from(name)), "Skipped: {name}");
+    }
+
+    assert_eq!(covered, entrypoints());
+}
+
+#[test]
+fn every_case_succeeds() {
+    let fixture = Fixture::new(Sizes::MINIMAL);
+
+    for case in cases(&fixture) {
+        let mut session = fixture.session();
+        let gas_spent =
+            run(&mut session, &case).unwrap_or_else(|err| panic!("{err}"));
+        assert!(gas_spent > 0, "{} should spend gas", case.name);
+    }
+}
+
+#[test]
+fn baseline_is_valid() {
+    let baseline = include_str!("../baseline.txt");
+    let baseline =
+        Baseline::parse(baseline).expect("The baseline should be valid");
+
+    // the baseline may not be recorded yet, but only lists existing cases
+    let entrypoints = entrypoints();
+    for comparison in baseline.compare(&Baseline::default()) {
+        assert!(
+            entrypoints.contains(&comparison.name),
+            "`{}` isn't an entrypoint",
+            comparison.name
+        );
+    }
+}
+
+#[test]
+fn declared_types_round_trip() {
+    let fixture = Fixture::new(Sizes::MINIMAL);
+    let declared = declared_functions();
+
+    // the entrypoint called directly by each case, either by a call or by
+    // the contract call of a transaction, with its argument and the data it
+    // returned, or fed
+    for case in cases(&fixture) {
+        let mut session = fixture.session();
+        let (name, arg, returned) = match &case.call {
+            Call::Function {
+                contract,
+                fn_name,
+                arg,
+                feeder,
+            } => {
+                let returned = if *feeder {
+                    let (sender, receiver) = mpsc::channel();
+                    session
+                        .feeder_call_raw(
+                            *contract,
+                            fn_name,
+                            arg.clone(),
+                            GAS_LIMIT,
+                            sender,
+                        )
+                        .unwrap_or_else(|err| panic!("{err:?}"));
+                    receiver.try_iter().collect()
+                } else {
+                    let receipt = session
+                        .call_raw(*contract, fn_name, arg.clone(), GAS_LIMIT)
+                        .unwrap_or_else(|err| panic!("{err:?}"));
+                    vec![receipt.data]
+                };
+                (case.name.clone(), arg.clone(), returned)
+            }
+            Call::Transaction(tx) => {
+                let Some(call) = tx.call() else { continue };
+                // the test contracts don't have a schema
+                let contract = if call.contract == TRANSFER_CONTRACT {
+                    "transfer"
+                } else if call.contract == STAKE_CONTRACT {
+                    "stake"
+                } else {
+                    continue;
+                };
+                let receipt =
+                    execute(&mut session, tx, &ExecutionConfig::DEFAULT)
+                        .unwrap_or_else(|err| panic!("{err:?}"));
+                let returned =
+                    receipt.data.unwrap_or_else(|err| panic!("{err:?}"));
+                (
+                    format!("{contract}::{}", call.fn_name),
+                    call.fn_args.clone(),
+                    vec![returned],
+                )
+            }
+        };
+
+        let function = &declared[&name];
+        assert!(
+            round_trips(&function.argument, &arg),
+            "The argument of `{name}` should be a `{}`",
+            function.argument
+        );
+        for returned in returned {
+            assert!(
+                round_trips(&function.returns, &returned),
+                "`{name}` should return a `{}`",
+                function.returns
+            );
+        }
+    }
+}
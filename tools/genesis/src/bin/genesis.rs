@@ -0,0 +1,76 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Build the genesis state described by a spec, writing the state of the VM
+//! to `<out>/state` and its summary to `<out>/summary.toml`.
+//!
+//! ```text
+//! genesis <spec> <out> [--transfer <wasm>] [--stake <wasm>]
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{env, fs};
+
+use contracts_genesis::{build, Bytecode, Spec};
+use dusk_vm::VM;
+
+const USAGE: &str = "Usage: genesis <spec> <out> [--transfer <wasm>] \
+                     [--stake <wasm>]";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+
+    let spec_path = PathBuf::from(args.next().ok_or(USAGE)?);
+    let out = PathBuf::from(args.next().ok_or(USAGE)?);
+
+    let mut bytecode = Bytecode::default();
+    while let Some(arg) = args.next() {
+        let path = PathBuf::from(args.next().ok_or(USAGE)?);
+        let wasm = fs::read(&path).map_err(|err| {
+            format!("Reading {} failed: {err}", path.display())
+        })?;
+        match arg.as_str() {
+            "--transfer" => bytecode.transfer = Some(wasm),
+            "--stake" => bytecode.stake = Some(wasm),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let spec = Spec::read(&spec_path).map_err(|err| {
+        format!("Reading {} failed: {err}", spec_path.display())
+    })?;
+
+    let state = out.join("state");
+    if state.exists() {
+        return Err(format!("{} already exists", state.display()));
+    }
+    fs::create_dir_all(&state)
+        .map_err(|err| format!("Creating {} failed: {err}", state.display()))?;
+
+    let vm = VM::new(&state)
+        .map_err(|err| format!("Opening the VM failed: {err:?}"))?;
+    let summary = build(&spec, bytecode, &vm).map_err(|err| err.to_string())?;
+
+    let summary_path = out.join("summary.toml");
+    fs::write(&summary_path, summary.to_string()).map_err(|err| {
+        format!("Writing {} failed: {err}", summary_path.display())
+    })?;
+
+    print!("{summary}");
+    eprintln!("Wrote the genesis state to {}", state.display());
+    Ok(())
+}
@@ -0,0 +1,310 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::path::{Path, PathBuf};
+
+use contracts_client::{Error as ClientError, StakeClient, TransferClient};
+use dusk_core::abi::{ContractId, StandardBufSerializer};
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::stake::{StakeConfig, StakeData, StakeKeys, STAKE_CONTRACT};
+use dusk_core::transfer::phoenix::{Note, PublicKey as PhoenixPublicKey};
+use dusk_core::transfer::TRANSFER_CONTRACT;
+use dusk_core::JubJubScalar;
+use dusk_vm::{ContractData, Error as VMError, Session, VM};
+use ff::Field;
+use rand::{CryptoRng, RngCore};
+use rkyv::Serialize;
+
+/// The gas limit of every call building a genesis state.
+pub const GAS_LIMIT: u64 = 0x100_000_000;
+
+/// The owner of the contracts deployed in a genesis state.
+pub const OWNER: [u8; 32] = [0; 32];
+
+const TRANSFER_WASM: &str =
+    "target/dusk/wasm64-unknown-unknown/release/transfer_contract.wasm";
+const STAKE_WASM: &str =
+    "target/dusk/wasm32-unknown-unknown/release/stake_contract.wasm";
+
+type DeployFn = Box<dyn FnOnce(&mut Session) -> Result<(), VMError>>;
+
+/// Builder of a genesis state.
+///
+/// The transfer and stake contracts are always deployed. Everything else is
+/// added to the genesis block in the order: extra contracts, stake config,
+/// Moonlight accounts, contract balances, Phoenix notes and stakes.
+pub struct Genesis {
+    chain_id: u8,
+    transfer_bytecode: Option<Vec<u8>>,
+    stake_bytecode: Option<Vec<u8>>,
+    contracts: Vec<DeployFn>,
+    stake_config: Option<StakeConfig>,
+    accounts: Vec<(AccountPublicKey, u64)>,
+    contract_balances: Vec<(ContractId, u64)>,
+    notes: Vec<Note>,
+    stakes: Vec<(StakeKeys, StakeData)>,
+}
+
+impl Genesis {
+    /// A genesis with only the transfer and stake contracts deployed, for a
+    /// chain with the given chain ID.
+    #[must_use]
+    pub fn new(chain_id: u8) -> Self {
+        Self {
+            chain_id,
+            transfer_bytecode: None,
+            stake_bytecode: None,
+            contracts: Vec::new(),
+            stake_config: None,
+            accounts: Vec::new(),
+            contract_balances: Vec::new(),
+            notes: Vec::new(),
+            stakes: Vec::new(),
+        }
+    }
+
+    /// The chain ID of the chain.
+    #[must_use]
+    pub fn chain_id(&self) -> u8 {
+        self.chain_id
+    }
+
+    /// Deploy the given bytecode as the transfer contract, instead of the one
+    /// built with `make wasm`.
+    #[must_use]
+    pub fn transfer_bytecode(mut self, bytecode: impl Into<Vec<u8>>) -> Self {
+        self.transfer_bytecode = Some(bytecode.into());
+        self
+    }
+
+    /// Deploy the given bytecode as the stake contract, instead of the one
+    /// built with `make wasm`.
+    #[must_use]
+    pub fn stake_bytecode(mut self, bytecode: impl Into<Vec<u8>>) -> Self {
+        self.stake_bytecode = Some(bytecode.into());
+        self
+    }
+
+    /// Deploy an extra contract with the given bytecode under the given ID.
+    #[must_use]
+    pub fn contract(
+        mut self,
+        id: ContractId,
+        bytecode: impl Into<Vec<u8>>,
+    ) -> Self {
+        let bytecode = bytecode.into();
+        self.contracts.push(Box::new(move |session| {
+            deploy_contract(session, id, &bytecode)
+        }));
+        self
+    }
+
+    /// Deploy an extra contract with the given bytecode under the given ID,
+    /// passing the given argument to its `init` function.
+    #[must_use]
+    pub fn contract_with_init_arg<A>(
+        mut self,
+        id: ContractId,
+        bytecode: impl Into<Vec<u8>>,
+        init_arg: A,
+    ) -> Self
+    where
+        A: for<'b> Serialize<StandardBufSerializer<'b>> + 'static,
+    {
+        let bytecode = bytecode.into();
+        self.contracts.push(Box::new(move |session| {
+            session.deploy(
+                &bytecode,
+                ContractData::builder()
+                    .owner(OWNER)
+                    .contract_id(id)
+                    .init_arg(&init_arg),
+                GAS_LIMIT,
+            )?;
+            Ok(())
+        }));
+        self
+    }
+
+    /// Set the config of the stake contract, instead of the default one.
+    #[must_use]
+    pub fn stake_config(mut self, config: StakeConfig) -> Self {
+        self.stake_config = Some(config);
+        self
+    }
+
+    /// Fund a Moonlight account with the given value.
+    #[must_use]
+    pub fn account(mut self, pk: AccountPublicKey, value: u64) -> Self {
+        self.accounts.push((pk, value));
+        self
+    }
+
+    /// Fund a contract with the given value.
+    #[must_use]
+    pub fn contract_balance(
+        mut self,
+        contract: ContractId,
+        value: u64,
+    ) -> Self {
+        self.contract_balances.push((contract, value));
+        self
+    }
+
+    /// Add a Phoenix note to the tree of notes, see [`transparent_note`].
+    #[must_use]
+    pub fn note(mut self, note: Note) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    /// Insert a stake, funding the stake contract with its staked amount.
+    #[must_use]
+    pub fn stake(mut self, keys: StakeKeys, stake_data: StakeData) -> Self {
+        self.stakes.push((keys, stake_data));
+        self
+    }
+
+    /// Build the genesis block in a genesis session of the given VM, and
+    /// commit it, returning the commit.
+    ///
+    /// # Errors
+    /// Returns an error if any call setting up the genesis state fails.
+    ///
+    /// # Panics
+    /// Panics if no bytecode is given for the transfer or stake contract, and
+    /// they weren't built with `make wasm`.
+    pub fn commit(self, vm: &VM) -> Result<[u8; 32], VMError> {
+        let mut session = vm.genesis_session(self.chain_id);
+
+        let transfer_bytecode =
+            self.transfer_bytecode.unwrap_or_else(transfer_bytecode);
+        deploy_contract(&mut session, TRANSFER_CONTRACT, &transfer_bytecode)?;
+
+        let stake_bytecode = self.stake_bytecode.unwrap_or_else(stake_bytecode);
+        deploy_contract(&mut session, STAKE_CONTRACT, &stake_bytecode)?;
+
+        for deploy in self.contracts {
+            deploy(&mut session)?;
+        }
+
+        if let Some(config) = &self.stake_config {
+            stake(&mut session).set_config(config).map_err(vm_error)?;
+        }
+
+        for (pk, value) in &self.accounts {
+            transfer(&mut session)
+                .add_account_balance(pk, *value)
+                .map_err(vm_error)?;
+        }
+
+        for (contract, value) in &self.contract_balances {
+            transfer(&mut session)
+                .add_contract_balance(*contract, *value)
+                .map_err(vm_error)?;
+        }
+
+        for note in self.notes {
+            transfer(&mut session)
+                .push_note(0, note)
+                .map_err(vm_error)?;
+        }
+        transfer(&mut session).update_root().map_err(vm_error)?;
+
+        for (keys, stake_data) in self.stakes {
+            let value =
+                stake_data.amount.map_or(0, |amount| amount.total_funds());
+            transfer(&mut session)
+                .add_contract_balance(STAKE_CONTRACT, value)
+                .map_err(vm_error)?;
+            stake(&mut session)
+                .insert_stake(keys, stake_data)
+                .map_err(vm_error)?;
+        }
+
+        session.commit()
+    }
+}
+
+/// Create a transparent note with the given value, owned by the given public
+/// key, as used in the genesis state.
+pub fn transparent_note<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    pk: &PhoenixPublicKey,
+    value: u64,
+) -> Note {
+    let sender_blinder = [
+        JubJubScalar::random(&mut *rng),
+        JubJubScalar::random(&mut *rng),
+    ];
+    Note::transparent(rng, pk, pk, value, sender_blinder)
+}
+
+/// Read the bytecode of the transfer contract, as built with `make wasm`.
+#[must_use]
+pub fn transfer_bytecode() -> Vec<u8> {
+    read_bytecode(TRANSFER_WASM)
+}
+
+/// Read the bytecode of the stake contract, as built with `make wasm`.
+#[must_use]
+pub fn stake_bytecode() -> Vec<u8> {
+    read_bytecode(STAKE_WASM)
+}
+
+/// Deploy a contract with the given bytecode under the given ID, owned by
+/// [`OWNER`].
+///
+/// # Errors
+/// Returns an error if the VM fails to deploy the contract.
+pub fn deploy_contract(
+    session: &mut Session,
+    id: ContractId,
+    bytecode: &[u8],
+) -> Result<(), VMError> {
+    session.deploy(
+        bytecode,
+        ContractData::builder().owner(OWNER).contract_id(id),
+        GAS_LIMIT,
+    )?;
+
+    Ok(())
+}
+
+/// Read the bytecode at the given path, relative to the root of the
+/// workspace.
+fn read_bytecode(path: &str) -> Vec<u8> {
+    let path: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(path);
+    std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Reading {} should succeed, the contracts need to be built with \
+             `make wasm` first: {err}",
+            path.display()
+        )
+    })
+}
+
+fn transfer(session: &mut Session) -> TransferClient<'_> {
+    TransferClient::new(session).with_gas_limit(GAS_LIMIT)
+}
+
+fn stake(session: &mut Session) -> StakeClient<'_> {
+    StakeClient::new(session).with_gas_limit(GAS_LIMIT)
+}
+
+/// Extract the error of the VM from an error of the contracts client.
+///
+/// # Panics
+/// Panics if the client failed to deserialize the data returned by the
+/// contracts, which would be a bug in the contracts or in the client.
+fn vm_error(err: ClientError) -> VMError {
+    match err {
+        ClientError::VM(err) => err,
+        err => panic!("The client should deserialize the call: {err}"),
+    }
+}
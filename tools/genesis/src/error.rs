@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+use std::io;
+
+use dusk_vm::Error as VMError;
+
+/// The errors returned when building a genesis state.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// The spec isn't valid TOML or JSON, or doesn't have the fields of a
+    /// spec.
+    Parse(String),
+    /// A field of the spec has an invalid value.
+    Invalid(String),
+    /// The VM failed to build the state.
+    VM(VMError),
+}
+
+impl From<VMError> for Error {
+    fn from(e: VMError) -> Self {
+        Self::VM(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+            Self::Parse(err) => write!(f, "Invalid spec: {err}"),
+            Self::Invalid(err) => write!(f, "Invalid spec: {err}"),
+            Self::VM(err) => write!(f, "Building the state failed: {err:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
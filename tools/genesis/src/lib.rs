@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Builder of the genesis state of a chain from a declarative [`Spec`].
+//!
+//! The spec, written in TOML or JSON, lists the Moonlight accounts, Phoenix
+//! notes, contract balances and stakes of the genesis state, together with
+//! the config of the stake contract and the chain ID. [`build`] deploys the
+//! transfer and stake contracts in a genesis session of a VM, fills them with
+//! the content of the spec and commits the state, returning a [`Summary`] of
+//! it.
+//!
+//! The state is deployed and filled by the [`Genesis`] builder, which the
+//! test kit of the contracts also sets up its test chains with.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod builder;
+mod error;
+mod spec;
+mod summary;
+
+pub use builder::{
+    deploy_contract, stake_bytecode, transfer_bytecode, transparent_note,
+    Genesis, GAS_LIMIT, OWNER,
+};
+pub use error::Error;
+pub use spec::{
+    BalanceSpec, ContractBalanceSpec, OwnerSpec, Spec, StakeConfigSpec,
+    StakeSpec,
+};
+pub use summary::Summary;
+
+use contracts_client::{Error as ClientError, StakeClient, TransferClient};
+use dusk_vm::{Session, VM};
+use stake_contract::migration::{
+    ExportCursor as StakeExportCursor, ExportSummary as StakeExportSummary,
+};
+use transfer_contract::migration::{ExportCursor, ExportSummary};
+
+/// The number of items summarized per call when summarizing an export.
+const EXPORT_CHUNK: u64 = 1024;
+
+/// The bytecode of the genesis contracts to deploy, the one built with
+/// `make wasm` for any contract not given.
+#[derive(Debug, Clone, Default)]
+pub struct Bytecode {
+    /// The bytecode of the transfer contract.
+    pub transfer: Option<Vec<u8>>,
+    /// The bytecode of the stake contract.
+    pub stake: Option<Vec<u8>>,
+}
+
+/// Build the genesis state described by the spec in a genesis session of the
+/// given VM, and commit it.
+///
+/// # Errors
+/// Returns an error if the spec is invalid, or if the VM fails to build the
+/// state.
+///
+/// # Panics
+/// Panics if the bytecode of a contract isn't given and it wasn't built with
+/// `make wasm`.
+pub fn build(
+    spec: &Spec,
+    bytecode: Bytecode,
+    vm: &VM,
+) -> Result<Summary, Error> {
+    let total_value = spec.total_value()?;
+    let total_rewards = spec.total_rewards()?;
+
+    let mut genesis = spec.genesis()?;
+    if let Some(bytecode) = bytecode.transfer {
+        genesis = genesis.transfer_bytecode(bytecode);
+    }
+    if let Some(bytecode) = bytecode.stake {
+        genesis = genesis.stake_bytecode(bytecode);
+    }
+    let commit = genesis.commit(vm)?;
+
+    let mut session = vm.session(commit, spec.chain_id, 1)?;
+    let transfer = summarize_transfer(&mut session)?;
+    let stake = summarize_stake(&mut session)?;
+
+    Ok(Summary {
+        chain_id: spec.chain_id,
+        commit,
+        total_value,
+        total_rewards,
+        transfer,
+        stake,
+    })
+}
+
+/// Return the summary of the whole exported state of the transfer contract,
+/// chained one chunk of [`EXPORT_CHUNK`] items at a time.
+fn summarize_transfer(session: &mut Session) -> Result<ExportSummary, Error> {
+    let mut client = TransferClient::new(session).with_gas_limit(GAS_LIMIT);
+
+    let mut summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    while let Some(cursor) = from {
+        let chunk = client
+            .export_summary(summary, cursor, EXPORT_CHUNK)
+            .map_err(client_error)?;
+        summary = chunk.summary;
+        from = chunk.next;
+    }
+    Ok(summary)
+}
+
+/// Return the summary of the whole exported state of the stake contract,
+/// chained one chunk of [`EXPORT_CHUNK`] stakes at a time.
+fn summarize_stake(session: &mut Session) -> Result<StakeExportSummary, Error> {
+    let mut client = StakeClient::new(session).with_gas_limit(GAS_LIMIT);
+
+    let mut summary = StakeExportSummary::EMPTY;
+    let mut from = Some(StakeExportCursor::START);
+    while let Some(cursor) = from {
+        let chunk = client
+            .export_summary(summary, cursor, EXPORT_CHUNK)
+            .map_err(client_error)?;
+        summary = chunk.summary;
+        from = chunk.next;
+    }
+    Ok(summary)
+}
+
+/// Extract the error of the VM from an error of the contracts client.
+///
+/// # Panics
+/// Panics if the client failed to deserialize the data returned by the
+/// contracts, which would be a bug in the contracts or in the client.
+fn client_error(err: ClientError) -> Error {
+    match err {
+        ClientError::VM(err) => Error::VM(err),
+        err => panic!("The client should deserialize the call: {err}"),
+    }
+}
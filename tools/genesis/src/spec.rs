@@ -0,0 +1,326 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use dusk_bytes::Serializable;
+use dusk_core::abi::ContractId;
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::stake::{StakeConfig, StakeData, StakeFundOwner, StakeKeys};
+use dusk_core::transfer::phoenix::PublicKey as PhoenixPublicKey;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+
+use crate::{transparent_note, Error, Genesis};
+
+/// Declarative spec of the genesis state of a chain.
+///
+/// Keys are given as the hex encoding of their compressed bytes, contract
+/// IDs as the hex encoding of their 32 bytes, with or without a `0x` prefix,
+/// and every value in Lux. Only the chain ID is required.
+///
+/// ```toml
+/// chain_id = 1
+///
+/// [stake_config]
+/// warnings = 1
+/// minimum_stake = 1000000000000
+///
+/// [[moonlight]]
+/// address = "a1b2..."
+/// value = 1000000000000
+///
+/// [[stake]]
+/// account = "c3d4..."
+/// owner = { contract = "0400..." }
+/// value = 1000000000000
+/// eligibility = 4320
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spec {
+    /// The chain ID of the chain.
+    pub chain_id: u8,
+    /// The seed of the randomness of the Phoenix notes, so that the same spec
+    /// always builds the same state.
+    #[serde(default)]
+    pub seed: u64,
+    /// The config of the stake contract, the default one if not given.
+    #[serde(default)]
+    pub stake_config: Option<StakeConfigSpec>,
+    /// The funded Moonlight accounts.
+    #[serde(default)]
+    pub moonlight: Vec<BalanceSpec>,
+    /// The Phoenix notes, one per entry, owned by the given public key.
+    #[serde(default)]
+    pub phoenix: Vec<BalanceSpec>,
+    /// The funded contracts.
+    #[serde(default)]
+    pub contract: Vec<ContractBalanceSpec>,
+    /// The stakes, funding the stake contract with their value.
+    #[serde(default)]
+    pub stake: Vec<StakeSpec>,
+}
+
+/// The config of the stake contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StakeConfigSpec {
+    /// The number of warnings before a provisioner is penalized.
+    pub warnings: u8,
+    /// The minimum value of a stake.
+    pub minimum_stake: u64,
+}
+
+/// A value owned by a public key, either a Moonlight account or a Phoenix
+/// note.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BalanceSpec {
+    /// The public key owning the value.
+    pub address: String,
+    /// The value.
+    pub value: u64,
+}
+
+/// The balance of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractBalanceSpec {
+    /// The ID of the contract.
+    pub id: String,
+    /// The balance of the contract.
+    pub value: u64,
+}
+
+/// A stake.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StakeSpec {
+    /// The consensus key of the stake.
+    pub account: String,
+    /// The owner of the funds of the stake, the consensus key if not given.
+    #[serde(default)]
+    pub owner: Option<OwnerSpec>,
+    /// The value staked.
+    pub value: u64,
+    /// The reward accumulated by the stake.
+    #[serde(default)]
+    pub reward: u64,
+    /// The block height from which the stake is eligible, from the genesis
+    /// block if not given.
+    #[serde(default)]
+    pub eligibility: u64,
+}
+
+/// The owner of the funds of a stake.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum OwnerSpec {
+    /// An account, by its public key.
+    Account(String),
+    /// A contract, by its ID.
+    Contract(String),
+}
+
+impl Spec {
+    /// Parse a spec written in TOML.
+    ///
+    /// # Errors
+    /// Returns an error if the given string isn't a spec.
+    pub fn from_toml(spec: &str) -> Result<Self, Error> {
+        toml::from_str(spec).map_err(|err| Error::Parse(err.to_string()))
+    }
+
+    /// Parse a spec written in JSON.
+    ///
+    /// # Errors
+    /// Returns an error if the given string isn't a spec.
+    pub fn from_json(spec: &str) -> Result<Self, Error> {
+        serde_json::from_str(spec).map_err(|err| Error::Parse(err.to_string()))
+    }
+
+    /// Read the spec at the given path, in JSON if its extension is `json`,
+    /// and in TOML otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, or isn't a spec.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let spec = fs::read_to_string(path).map_err(Error::Io)?;
+
+        match path.extension() {
+            Some(extension) if extension == "json" => Self::from_json(&spec),
+            _ => Self::from_toml(&spec),
+        }
+    }
+
+    /// The total value of the genesis state: the balances of the accounts,
+    /// notes and contracts, and the value of the stakes.
+    ///
+    /// The rewards of the stakes aren't part of it, since they are only
+    /// minted once withdrawn, see [`Spec::total_rewards`].
+    ///
+    /// # Errors
+    /// Returns an error if the total doesn't fit in a `u64`.
+    pub fn total_value(&self) -> Result<u64, Error> {
+        let balances = self.moonlight.iter().chain(&self.phoenix);
+        let contracts = self.contract.iter().map(|contract| contract.value);
+        let stakes = self.stake.iter().map(|stake| stake.value);
+
+        checked_sum(
+            balances
+                .map(|balance| balance.value)
+                .chain(contracts)
+                .chain(stakes),
+            "value",
+        )
+    }
+
+    /// The total reward accumulated by the stakes of the genesis state.
+    ///
+    /// # Errors
+    /// Returns an error if the total doesn't fit in a `u64`.
+    pub fn total_rewards(&self) -> Result<u64, Error> {
+        checked_sum(self.stake.iter().map(|stake| stake.reward), "reward")
+    }
+
+    /// Validate the spec and turn it into a [`Genesis`] with its chain ID,
+    /// not built yet.
+    ///
+    /// # Errors
+    /// Returns an error if a key or contract ID is invalid, if two stakes
+    /// have the same consensus key, or if the total value or rewards of the
+    /// state overflow.
+    pub fn genesis(&self) -> Result<Genesis, Error> {
+        self.total_value()?;
+        self.total_rewards()?;
+
+        let mut genesis = Genesis::new(self.chain_id);
+
+        if let Some(config) = self.stake_config {
+            genesis = genesis.stake_config(StakeConfig {
+                warnings: config.warnings,
+                minimum_stake: config.minimum_stake,
+            });
+        }
+
+        for (i, account) in self.moonlight.iter().enumerate() {
+            let pk = parse_account(&account.address, || {
+                format!("moonlight[{i}].address")
+            })?;
+            genesis = genesis.account(pk, account.value);
+        }
+
+        for (i, contract) in self.contract.iter().enumerate() {
+            let id =
+                parse_contract(&contract.id, || format!("contract[{i}].id"))?;
+            genesis = genesis.contract_balance(id, contract.value);
+        }
+
+        let rng = &mut StdRng::seed_from_u64(self.seed);
+        for (i, note) in self.phoenix.iter().enumerate() {
+            let pk: PhoenixPublicKey =
+                parse_key::<_, { PhoenixPublicKey::SIZE }>(
+                    &note.address,
+                    || format!("phoenix[{i}].address"),
+                )?;
+            genesis = genesis.note(transparent_note(rng, &pk, note.value));
+        }
+
+        let mut accounts = BTreeSet::new();
+        for (i, stake) in self.stake.iter().enumerate() {
+            let account = parse_account(&stake.account, || {
+                format!("stake[{i}].account")
+            })?;
+            if !accounts.insert(account.to_bytes()) {
+                return Err(Error::Invalid(format!(
+                    "stake[{i}].account: the account has another stake"
+                )));
+            }
+
+            let owner = match &stake.owner {
+                None => StakeFundOwner::Account(account),
+                Some(OwnerSpec::Account(owner)) => {
+                    StakeFundOwner::Account(parse_account(owner, || {
+                        format!("stake[{i}].owner.account")
+                    })?)
+                }
+                Some(OwnerSpec::Contract(owner)) => {
+                    StakeFundOwner::Contract(parse_contract(owner, || {
+                        format!("stake[{i}].owner.contract")
+                    })?)
+                }
+            };
+
+            let stake_data = StakeData::with_eligibility(
+                stake.value,
+                stake.reward,
+                stake.eligibility,
+            );
+            genesis = genesis.stake(StakeKeys::new(account, owner), stake_data);
+        }
+
+        Ok(genesis)
+    }
+}
+
+/// Sum the given values of the genesis state, failing if the total of the
+/// named quantity overflows.
+fn checked_sum(
+    values: impl IntoIterator<Item = u64>,
+    name: &str,
+) -> Result<u64, Error> {
+    values
+        .into_iter()
+        .try_fold(0u64, u64::checked_add)
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "the total {name} of the genesis state overflows"
+            ))
+        })
+}
+
+/// Parse a key from the hex encoding of its bytes.
+fn parse_key<T, const N: usize>(
+    hex: &str,
+    field: impl Fn() -> String,
+) -> Result<T, Error>
+where
+    T: Serializable<N>,
+{
+    let invalid =
+        |reason: &str| Error::Invalid(format!("{}: {reason}", field()));
+
+    let bytes = hex::decode(hex.trim_start_matches("0x"))
+        .map_err(|_| invalid("not a hex string"))?;
+    let bytes: [u8; N] = bytes
+        .try_into()
+        .map_err(|_| invalid(&format!("expected {N} bytes")))?;
+
+    T::from_bytes(&bytes).map_err(|_| invalid("not a valid key"))
+}
+
+/// Parse a Moonlight public key from the hex encoding of its bytes.
+fn parse_account(
+    hex: &str,
+    field: impl Fn() -> String,
+) -> Result<AccountPublicKey, Error> {
+    parse_key::<_, { AccountPublicKey::SIZE }>(hex, field)
+}
+
+/// Parse a contract ID from the hex encoding of its bytes.
+fn parse_contract(
+    hex: &str,
+    field: impl Fn() -> String,
+) -> Result<ContractId, Error> {
+    ContractId::try_from(String::from(hex)).map_err(|_| {
+        Error::Invalid(format!("{}: expected 32 hex encoded bytes", field()))
+    })
+}
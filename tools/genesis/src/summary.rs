@@ -0,0 +1,95 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+
+use dusk_bytes::Serializable;
+use dusk_core::BlsScalar;
+use dusk_poseidon::{Domain, Hash};
+use stake_contract::migration::ExportSummary as StakeExportSummary;
+use transfer_contract::migration::ExportSummary;
+
+/// Summary of a built genesis state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// The chain ID of the chain.
+    pub chain_id: u8,
+    /// The commit of the state in the VM.
+    pub commit: [u8; 32],
+    /// The total value of the state, see
+    /// [`Spec::total_value`](crate::Spec::total_value).
+    pub total_value: u64,
+    /// The total reward of the stakes, see
+    /// [`Spec::total_rewards`](crate::Spec::total_rewards).
+    pub total_rewards: u64,
+    /// The export summary of the transfer contract.
+    pub transfer: ExportSummary,
+    /// The export summary of the stake contract.
+    pub stake: StakeExportSummary,
+}
+
+impl Summary {
+    /// The hash of the chain ID and of the export summaries of both
+    /// contracts.
+    ///
+    /// Unlike the commit, it only depends on the content of the state, and
+    /// not on how the VM stores it, so the same spec gives the same hash
+    /// across versions of the VM.
+    #[must_use]
+    pub fn hash(&self) -> BlsScalar {
+        let input = [
+            BlsScalar::from(u64::from(self.chain_id)),
+            BlsScalar::from(u64::from(self.transfer.version)),
+            BlsScalar::from(self.transfer.items),
+            self.transfer.checksum,
+            BlsScalar::from(u64::from(self.stake.version)),
+            BlsScalar::from(self.stake.items),
+            self.stake.checksum,
+        ];
+        Hash::digest(Domain::Other, &input)[0]
+    }
+}
+
+/// Writes the summary as TOML, with the hashes encoded in hex.
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "chain_id = {}", self.chain_id)?;
+        writeln!(f, "commit = \"{}\"", hex::encode(self.commit))?;
+        writeln!(f, "hash = \"{}\"", hex::encode(self.hash().to_bytes()))?;
+        writeln!(f, "total_value = {}", self.total_value)?;
+        writeln!(f, "total_rewards = {}", self.total_rewards)?;
+
+        write_export(
+            f,
+            "transfer",
+            self.transfer.version,
+            self.transfer.items,
+            &self.transfer.checksum,
+        )?;
+        write_export(
+            f,
+            "stake",
+            self.stake.version,
+            self.stake.items,
+            &self.stake.checksum,
+        )
+    }
+}
+
+/// Writes the export summary of a contract as a TOML table.
+fn write_export(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    version: u32,
+    items: u64,
+    checksum: &BlsScalar,
+) -> fmt::Result {
+    writeln!(f)?;
+    writeln!(f, "[{name}]")?;
+    writeln!(f, "version = {version}")?;
+    writeln!(f, "items = {items}")?;
+    writeln!(f, "checksum = \"{}\"", hex::encode(checksum.to_bytes()))
+}
@@ -0,0 +1,335 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_genesis::{build, Bytecode, Error, OwnerSpec, Spec};
+use contracts_test_kit::notes::owned_notes_value;
+use contracts_test_kit::{stake, transfer};
+use dusk_bytes::Serializable;
+use dusk_core::abi::ContractId;
+use dusk_core::dusk;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{StakeFundOwner, STAKE_CONTRACT};
+use dusk_core::transfer::phoenix::{
+    PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+    ViewKey as PhoenixViewKey,
+};
+use dusk_vm::VM;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CHAIN_ID: u8 = 0x2a;
+
+const CONTRACT_ID: ContractId = ContractId::from_bytes([7; 32]);
+
+struct Keys {
+    account_pk: AccountPublicKey,
+    provisioner_pk: AccountPublicKey,
+    phoenix_sk: PhoenixSecretKey,
+}
+
+impl Keys {
+    fn new() -> Self {
+        let rng = &mut StdRng::seed_from_u64(0x5eed);
+        Self {
+            account_pk: AccountPublicKey::from(&AccountSecretKey::random(rng)),
+            provisioner_pk: AccountPublicKey::from(&AccountSecretKey::random(
+                rng,
+            )),
+            phoenix_sk: PhoenixSecretKey::random(rng),
+        }
+    }
+
+    fn account(&self) -> String {
+        hex::encode(self.account_pk.to_bytes())
+    }
+
+    fn provisioner(&self) -> String {
+        hex::encode(self.provisioner_pk.to_bytes())
+    }
+
+    fn phoenix(&self) -> String {
+        let pk = PhoenixPublicKey::from(&self.phoenix_sk);
+        hex::encode(pk.to_bytes())
+    }
+}
+
+fn toml_spec(keys: &Keys) -> String {
+    format!(
+        r#"
+chain_id = {CHAIN_ID}
+seed = 1
+
+[stake_config]
+warnings = 2
+minimum_stake = {minimum_stake}
+
+[[moonlight]]
+address = "{account}"
+value = 1000
+
+[[moonlight]]
+address = "0x{provisioner}"
+value = 2000
+
+[[phoenix]]
+address = "{phoenix}"
+value = 300
+
+[[phoenix]]
+address = "{phoenix}"
+value = 400
+
+[[contract]]
+id = "{contract}"
+value = 50
+
+[[stake]]
+account = "{provisioner}"
+value = {stake}
+reward = 10
+eligibility = 4320
+
+[[stake]]
+account = "{account}"
+owner = {{ contract = "{contract}" }}
+value = {stake}
+"#,
+        minimum_stake = dusk(500.0),
+        account = keys.account(),
+        provisioner = keys.provisioner(),
+        phoenix = keys.phoenix(),
+        contract = CONTRACT_ID,
+        stake = dusk(1000.0),
+    )
+}
+
+fn json_spec(keys: &Keys) -> String {
+    format!(
+        r#"{{
+    "chain_id": {CHAIN_ID},
+    "seed": 1,
+    "stake_config": {{ "warnings": 2, "minimum_stake": {minimum_stake} }},
+    "moonlight": [
+        {{ "address": "{account}", "value": 1000 }},
+        {{ "address": "0x{provisioner}", "value": 2000 }}
+    ],
+    "phoenix": [
+        {{ "address": "{phoenix}", "value": 300 }},
+        {{ "address": "{phoenix}", "value": 400 }}
+    ],
+    "contract": [{{ "id": "{contract}", "value": 50 }}],
+    "stake": [
+        {{
+            "account": "{provisioner}",
+            "value": {stake},
+            "reward": 10,
+            "eligibility": 4320
+        }},
+        {{
+            "account": "{account}",
+            "owner": {{ "contract": "{contract}" }},
+            "value": {stake}
+        }}
+    ]
+}}"#,
+        minimum_stake = dusk(500.0),
+        account = keys.account(),
+        provisioner = keys.provisioner(),
+        phoenix = keys.phoenix(),
+        contract = CONTRACT_ID,
+        stake = dusk(1000.0),
+    )
+}
+
+#[test]
+fn toml_and_json_specs_match() {
+    let keys = Keys::new();
+
+    let spec = Spec::from_toml(&toml_spec(&keys))
+        .expect("Parsing the TOML spec should succeed");
+    assert_eq!(
+        Spec::from_json(&json_spec(&keys))
+            .expect("Parsing the JSON spec should succeed"),
+        spec
+    );
+
+    assert_eq!(spec.chain_id, CHAIN_ID);
+    assert_eq!(spec.moonlight.len(), 2);
+    assert_eq!(spec.phoenix.len(), 2);
+    assert_eq!(
+        spec.stake[1].owner,
+        Some(OwnerSpec::Contract(CONTRACT_ID.to_string()))
+    );
+    assert_eq!(spec.stake[1].reward, 0);
+    assert_eq!(spec.stake[1].eligibility, 0);
+    assert_eq!(
+        spec.total_value().ok(),
+        Some(1000 + 2000 + 300 + 400 + 50 + 2 * dusk(1000.0))
+    );
+    assert_eq!(spec.total_rewards().ok(), Some(10));
+
+    // only the chain ID is required
+    let spec = Spec::from_toml("chain_id = 1")
+        .expect("Parsing the minimal spec should succeed");
+    assert!(spec.moonlight.is_empty() && spec.stake_config.is_none());
+}
+
+#[test]
+fn invalid_specs() {
+    let keys = Keys::new();
+    let invalid = |spec: &str| match Spec::from_toml(spec)
+        .and_then(|spec| spec.genesis().map(|_| ()))
+    {
+        Ok(()) => panic!("The spec should be invalid: {spec}"),
+        Err(err) => err.to_string(),
+    };
+
+    assert!(matches!(Spec::from_toml(""), Err(Error::Parse(_))));
+    assert!(matches!(
+        Spec::from_toml("chain_id = 1\nchain = 2"),
+        Err(Error::Parse(_))
+    ));
+    assert!(matches!(
+        Spec::from_toml("chain_id = 256"),
+        Err(Error::Parse(_))
+    ));
+
+    assert_eq!(
+        invalid("chain_id = 1\n[[moonlight]]\naddress = \"xyz\"\nvalue = 1"),
+        "Invalid spec: moonlight[0].address: not a hex string"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "chain_id = 1\n[[phoenix]]\naddress = \"{}\"\nvalue = 1",
+            keys.account()
+        )),
+        "Invalid spec: phoenix[0].address: expected 64 bytes"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "chain_id = 1\n[[phoenix]]\naddress = \"{}\"\nvalue = 1",
+            "ff".repeat(64)
+        )),
+        "Invalid spec: phoenix[0].address: not a valid key"
+    );
+    assert_eq!(
+        invalid("chain_id = 1\n[[contract]]\nid = \"0102\"\nvalue = 1"),
+        "Invalid spec: contract[0].id: expected 32 hex encoded bytes"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "chain_id = 1\n\
+             [[stake]]\naccount = \"{0}\"\nvalue = 1\n\
+             [[stake]]\naccount = \"{0}\"\nvalue = 2",
+            keys.account()
+        )),
+        "Invalid spec: stake[1].account: the account has another stake"
+    );
+    let account = format!(
+        "[[moonlight]]\naddress = \"{}\"\nvalue = {}\n",
+        keys.account(),
+        i64::MAX
+    );
+    assert_eq!(
+        invalid(&format!("chain_id = 1\n{}", account.repeat(3))),
+        "Invalid spec: the total value of the genesis state overflows"
+    );
+    let stake = |account: String| {
+        format!(
+            "[[stake]]\naccount = \"{account}\"\nvalue = 1\nreward = {}\n",
+            u64::MAX
+        )
+    };
+    assert_eq!(
+        invalid(&format!(
+            "chain_id = 1\n{}{}",
+            stake(keys.account()),
+            stake(keys.provisioner())
+        )),
+        "Invalid spec: the total reward of the genesis state overflows"
+    );
+}
+
+#[test]
+fn build_state() {
+    let keys = Keys::new();
+    let spec = Spec::from_toml(&toml_spec(&keys))
+        .expect("Parsing the TOML spec should succeed");
+
+    let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+    let summary = build(&spec, Bytecode::default(), &vm)
+        .expect("Building the state should succeed");
+    assert_eq!(summary.chain_id, CHAIN_ID);
+    assert_eq!(
+        summary.total_value,
+        spec.total_value().expect("The total value should fit")
+    );
+    assert_eq!(summary.total_rewards, 10);
+    assert_eq!(summary.stake.items, 2);
+
+    let mut session = vm
+        .session(summary.commit, CHAIN_ID, 1)
+        .expect("Instantiating new session should succeed");
+
+    let chain_id = transfer::chain_id(&mut session)
+        .expect("Querying the chain ID should succeed");
+    assert_eq!(chain_id, CHAIN_ID);
+
+    for (pk, value) in [(&keys.account_pk, 1000), (&keys.provisioner_pk, 2000)]
+    {
+        let account = transfer::account(&mut session, pk)
+            .expect("Querying the account should succeed");
+        assert_eq!(account.balance, value);
+    }
+    for (contract, value) in
+        [(CONTRACT_ID, 50), (STAKE_CONTRACT, 2 * dusk(1000.0))]
+    {
+        let balance = transfer::contract_balance(&mut session, contract)
+            .expect("Querying the contract balance should succeed");
+        assert_eq!(balance, value);
+    }
+
+    let notes = transfer::leaves_from_height(&mut session, 0)
+        .expect("Querying the notes should succeed")
+        .into_iter()
+        .map(|leaf| leaf.note)
+        .collect::<Vec<_>>();
+    assert_eq!(notes.len(), 2);
+    let vk = PhoenixViewKey::from(&keys.phoenix_sk);
+    assert_eq!(owned_notes_value(vk, &notes), 700);
+
+    let config = stake::get_config(&mut session)
+        .expect("Querying the config should succeed");
+    assert_eq!(config.warnings, 2);
+    assert_eq!(config.minimum_stake, dusk(500.0));
+
+    let provisioner = stake::get_stake(&mut session, &keys.provisioner_pk)
+        .expect("Querying the stake should succeed")
+        .expect("The provisioner should have a stake");
+    let amount = provisioner.amount.expect("The stake should have an amount");
+    assert_eq!(amount.value, dusk(1000.0));
+    assert_eq!(amount.eligibility, 4320);
+    assert_eq!(provisioner.reward, 10);
+
+    let owned = stake::get_stake(&mut session, &keys.account_pk)
+        .expect("Querying the stake should succeed")
+        .expect("The account should have a stake");
+    assert_eq!(owned.amount.map(|amount| amount.eligibility), Some(0));
+    let owner = stake::get_stake_keys(&mut session, &keys.account_pk)
+        .expect("Querying the stake keys should succeed")
+        .expect("The account should have stake keys")
+        .owner;
+    assert_eq!(owner, StakeFundOwner::Contract(CONTRACT_ID));
+
+    // the same spec always builds the same state
+    let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+    let rebuilt = build(&spec, Bytecode::default(), &vm)
+        .expect("Building the state should succeed");
+    assert_eq!(rebuilt, summary);
+    assert_eq!(rebuilt.hash(), summary.hash());
+}
@@ -0,0 +1,73 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Open the state of a VM at a commit, and print a part of the state of the
+//! genesis contracts as a table, or as JSON.
+//!
+//! ```text
+//! inspect <state> <commit> <query> [--json]
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use contracts_inspect::Query;
+use dusk_vm::VM;
+
+/// The chain ID and block height of the session the queries are run in,
+/// which none of them depend on.
+const CHAIN_ID: u8 = 0;
+const BLOCK_HEIGHT: u64 = 0;
+
+fn usage() -> String {
+    let queries: Vec<_> = Query::ALL.iter().map(|query| query.name()).collect();
+    format!(
+        "Usage: inspect <state> <commit> <query> [--json]\n\nQueries: {}",
+        queries.join(", ")
+    )
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let (json, args) = match args.as_slice() {
+        [args @ .., flag] if flag == "--json" => (true, args),
+        args => (false, args),
+    };
+    let [state, commit, query] = args else {
+        return Err(usage());
+    };
+
+    let query = Query::from_name(query).ok_or_else(usage)?;
+    let commit: [u8; 32] = hex::decode(commit)
+        .ok()
+        .and_then(|commit| commit.try_into().ok())
+        .ok_or("The commit should be 32 hex encoded bytes")?;
+
+    let vm = VM::new(PathBuf::from(state))
+        .map_err(|err| format!("Opening the VM failed: {err:?}"))?;
+    let mut session = vm
+        .session(commit, CHAIN_ID, BLOCK_HEIGHT)
+        .map_err(|err| format!("Opening the commit failed: {err:?}"))?;
+
+    let report = query.run(&mut session).map_err(|err| err.to_string())?;
+    if json {
+        print!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_table());
+    }
+    Ok(())
+}
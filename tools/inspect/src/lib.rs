@@ -0,0 +1,231 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Inspection of the state of the genesis contracts in a session.
+//!
+//! Each [`Query`] reads a part of the state through the feeder and query
+//! entrypoints of the transfer and stake contracts, and returns it as a
+//! [`Report`], written either as a table or as JSON. Keys, contract IDs and
+//! hashes are written as the hex encoding of their bytes.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod report;
+
+pub use report::Report;
+
+use contracts_client::{Error, StakeClient, TransferClient};
+use dusk_bytes::Serializable;
+use dusk_core::stake::StakeFundOwner;
+use dusk_core::BlsScalar;
+use dusk_vm::Session;
+use transfer_contract::migration::{ExportCursor, StateItem};
+
+/// The parts of the state that can be inspected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// The Moonlight accounts, with their balance and nonce.
+    Accounts,
+    /// The balances of the contracts.
+    ContractBalances,
+    /// The number of spent nullifiers and the root of their tree.
+    Nullifiers,
+    /// The number of notes and the root of their tree.
+    Notes,
+    /// The recent roots of the trees of notes and nullifiers, from oldest to
+    /// newest.
+    Roots,
+    /// The stakes, with their owner and eligibility.
+    Stakes,
+    /// The total amount burnt by slashes.
+    BurntAmount,
+    /// The config of the stake contract.
+    StakeConfig,
+}
+
+impl Query {
+    /// Every query, in the order they are listed in the usage.
+    pub const ALL: [Self; 8] = [
+        Self::Accounts,
+        Self::ContractBalances,
+        Self::Nullifiers,
+        Self::Notes,
+        Self::Roots,
+        Self::Stakes,
+        Self::BurntAmount,
+        Self::StakeConfig,
+    ];
+
+    /// The name of the query on the command line.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Accounts => "accounts",
+            Self::ContractBalances => "contract-balances",
+            Self::Nullifiers => "nullifiers",
+            Self::Notes => "notes",
+            Self::Roots => "roots",
+            Self::Stakes => "stakes",
+            Self::BurntAmount => "burnt-amount",
+            Self::StakeConfig => "stake-config",
+        }
+    }
+
+    /// The query with the given name on the command line.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|query| query.name() == name)
+    }
+
+    /// Run the query in the given session.
+    ///
+    /// # Errors
+    /// Returns an error if calling the contracts fails.
+    pub fn run(self, session: &mut Session) -> Result<Report, Error> {
+        match self {
+            Self::Accounts => accounts(session),
+            Self::ContractBalances => contract_balances(session),
+            Self::Nullifiers => nullifiers(session),
+            Self::Notes => notes(session),
+            Self::Roots => roots(session),
+            Self::Stakes => stakes(session),
+            Self::BurntAmount => burnt_amount(session),
+            Self::StakeConfig => stake_config(session),
+        }
+    }
+}
+
+fn accounts(session: &mut Session) -> Result<Report, Error> {
+    let mut report = Report::new(&["key", "balance", "nonce"]);
+    for item in TransferClient::new(session).sync_accounts(0, 0)? {
+        let (account, key) = item?;
+        report.push(vec![
+            Some(hex::encode(key)),
+            Some(account.balance.to_string()),
+            Some(account.nonce.to_string()),
+        ]);
+    }
+    Ok(report)
+}
+
+fn contract_balances(session: &mut Session) -> Result<Report, Error> {
+    let mut report = Report::new(&["contract", "balance"]);
+    for item in TransferClient::new(session).sync_contract_balances(0, 0)? {
+        let (contract, balance) = item?;
+        report
+            .push(vec![Some(contract.to_string()), Some(balance.to_string())]);
+    }
+    Ok(report)
+}
+
+fn nullifiers(session: &mut Session) -> Result<Report, Error> {
+    let mut client = TransferClient::new(session);
+    let count = client.sync_nullifiers(0, 0)?.count();
+    let root = client.nullifiers_root()?;
+
+    let mut report = Report::new(&["count", "root"]);
+    report.push(vec![Some(count.to_string()), Some(scalar(&root))]);
+    Ok(report)
+}
+
+fn notes(session: &mut Session) -> Result<Report, Error> {
+    let mut client = TransferClient::new(session);
+    let count = client.num_notes()?;
+    let root = client.root()?;
+
+    let mut report = Report::new(&["count", "root"]);
+    report.push(vec![Some(count.to_string()), Some(scalar(&root))]);
+    Ok(report)
+}
+
+/// The roots are exported after the notes, and followed by the nullifiers
+/// and the roots of their tree.
+fn roots(session: &mut Session) -> Result<Report, Error> {
+    let mut client = TransferClient::new(session);
+
+    let mut report = Report::new(&["tree", "block_height", "root"]);
+    for item in client.export_state(ExportCursor::Root(0), 0)? {
+        match item? {
+            StateItem::Root(root) => {
+                report.push(vec![
+                    Some(String::from("notes")),
+                    None,
+                    Some(scalar(&root)),
+                ]);
+            }
+            StateItem::Nullifier(_) => {}
+            StateItem::NullifierRoot(block_height, root) => {
+                report.push(vec![
+                    Some(String::from("nullifiers")),
+                    Some(block_height.to_string()),
+                    Some(scalar(&root)),
+                ]);
+            }
+            _ => break,
+        }
+    }
+    Ok(report)
+}
+
+fn stakes(session: &mut Session) -> Result<Report, Error> {
+    let mut report = Report::new(&[
+        "account",
+        "owner",
+        "value",
+        "locked",
+        "eligibility",
+        "reward",
+        "faults",
+        "hard_faults",
+    ]);
+    for item in StakeClient::new(session).stakes()? {
+        let (keys, stake_data) = item?;
+        let owner = match keys.owner {
+            StakeFundOwner::Account(pk) => {
+                format!("account:{}", hex::encode(pk.to_bytes()))
+            }
+            StakeFundOwner::Contract(contract) => {
+                format!("contract:{contract}")
+            }
+        };
+        let amount = stake_data.amount;
+        report.push(vec![
+            Some(hex::encode(keys.account.to_bytes())),
+            Some(owner),
+            amount.map(|amount| amount.value.to_string()),
+            amount.map(|amount| amount.locked.to_string()),
+            amount.map(|amount| amount.eligibility.to_string()),
+            Some(stake_data.reward.to_string()),
+            Some(stake_data.faults.to_string()),
+            Some(stake_data.hard_faults.to_string()),
+        ]);
+    }
+    Ok(report)
+}
+
+fn burnt_amount(session: &mut Session) -> Result<Report, Error> {
+    let burnt_amount = StakeClient::new(session).burnt_amount()?;
+
+    let mut report = Report::new(&["burnt_amount"]);
+    report.push(vec![Some(burnt_amount.to_string())]);
+    Ok(report)
+}
+
+fn stake_config(session: &mut Session) -> Result<Report, Error> {
+    let config = StakeClient::new(session).get_config()?;
+
+    let mut report = Report::new(&["warnings", "minimum_stake"]);
+    report.push(vec![
+        Some(config.warnings.to_string()),
+        Some(config.minimum_stake.to_string()),
+    ]);
+    Ok(report)
+}
+
+fn scalar(scalar: &BlsScalar) -> String {
+    hex::encode(scalar.to_bytes())
+}
@@ -0,0 +1,108 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use serde_json::{Map, Value};
+
+/// The rows returned by a query, written either as a table or as JSON.
+///
+/// Every cell is a string, numbers included so that a `u64` keeps its
+/// precision in JSON, or is missing, for example the eligibility of a stake
+/// with nothing staked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl Report {
+    /// An empty report with the given columns.
+    #[must_use]
+    pub fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row, with one cell per column.
+    ///
+    /// # Panics
+    /// Panics if the number of cells doesn't match the number of columns.
+    pub fn push(&mut self, row: Vec<Option<String>>) {
+        assert_eq!(row.len(), self.columns.len(), "One cell per column");
+        self.rows.push(row);
+    }
+
+    /// The names of the columns.
+    #[must_use]
+    pub fn columns(&self) -> &[&'static str] {
+        &self.columns
+    }
+
+    /// The rows, in the order they were pushed.
+    #[must_use]
+    pub fn rows(&self) -> &[Vec<Option<String>>] {
+        &self.rows
+    }
+
+    /// Write the report as a table, with a header and a missing cell written
+    /// as `-`.
+    #[must_use]
+    pub fn to_table(&self) -> String {
+        let cell = |cell: &Option<String>| cell.as_deref().unwrap_or("-");
+
+        let mut widths: Vec<_> =
+            self.columns.iter().map(|column| column.len()).collect();
+        for row in &self.rows {
+            for (width, cell_value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell(cell_value).len());
+            }
+        }
+
+        let line = |cells: Vec<&str>| {
+            let cells: Vec<_> = cells
+                .into_iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            format!("{}\n", cells.join("  ").trim_end())
+        };
+
+        let mut table = line(self.columns.clone());
+        for row in &self.rows {
+            table.push_str(&line(row.iter().map(cell).collect()));
+        }
+        table
+    }
+
+    /// Write the report as a JSON array with an object per row, keyed by
+    /// column, and a missing cell written as `null`.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<_, _> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| {
+                        let value =
+                            cell.clone().map_or(Value::Null, Value::String);
+                        (String::from(*column), value)
+                    })
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+
+        let mut json = serde_json::to_string_pretty(&Value::Array(rows))
+            .expect("Serializing JSON values should succeed");
+        json.push('\n');
+        json
+    }
+}
@@ -0,0 +1,178 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_inspect::{Query, Report};
+use contracts_test_kit::notes::transparent_note;
+use contracts_test_kit::{Genesis, TestChain, CHAIN_ID};
+use dusk_bytes::Serializable;
+use dusk_core::abi::ContractId;
+use dusk_core::dusk;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::stake::{StakeConfig, StakeData, StakeKeys, STAKE_CONTRACT};
+use dusk_core::transfer::phoenix::{
+    PublicKey as PhoenixPublicKey, SecretKey as PhoenixSecretKey,
+};
+use dusk_vm::Session;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CONTRACT_ID: ContractId = ContractId::from_bytes([7; 32]);
+
+const ACCOUNT_VALUE: u64 = dusk(1_000.0);
+const CONTRACT_VALUE: u64 = dusk(20.0);
+const NOTE_VALUE: u64 = dusk(3.0);
+const STAKE_VALUE: u64 = dusk(1_000.0);
+const REWARD_VALUE: u64 = dusk(5.0);
+const ELIGIBILITY: u64 = 4320;
+
+const CONFIG: StakeConfig = StakeConfig {
+    warnings: 3,
+    minimum_stake: dusk(100.0),
+};
+
+struct Fixture {
+    session: Session,
+    account_pk: AccountPublicKey,
+    provisioner_pk: AccountPublicKey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let rng = &mut StdRng::seed_from_u64(0xfeeb);
+        let account_pk = AccountPublicKey::from(&AccountSecretKey::random(rng));
+        let provisioner_pk =
+            AccountPublicKey::from(&AccountSecretKey::random(rng));
+        let phoenix_pk = PhoenixPublicKey::from(&PhoenixSecretKey::random(rng));
+
+        let notes =
+            (0..3).map(|_| transparent_note(rng, &phoenix_pk, NOTE_VALUE));
+        let genesis = notes.fold(Genesis::new(CHAIN_ID), Genesis::note);
+        let genesis = genesis
+            .stake_config(CONFIG)
+            .account(account_pk, ACCOUNT_VALUE)
+            .contract_balance(CONTRACT_ID, CONTRACT_VALUE)
+            .stake(
+                StakeKeys::new(provisioner_pk, CONTRACT_ID),
+                StakeData::with_eligibility(
+                    STAKE_VALUE,
+                    REWARD_VALUE,
+                    ELIGIBILITY,
+                ),
+            );
+        let (vm, base) = TestChain::new(genesis).commit();
+
+        let session = vm
+            .session(base, CHAIN_ID, 2)
+            .expect("Instantiating new session should succeed");
+
+        Self {
+            session,
+            account_pk,
+            provisioner_pk,
+        }
+    }
+
+    fn run(&mut self, query: Query) -> Report {
+        query.run(&mut self.session).unwrap_or_else(|err| {
+            panic!("{} should succeed: {err}", query.name())
+        })
+    }
+}
+
+/// The cells of the rows of a report.
+fn cells(report: &Report) -> Vec<Vec<Option<&str>>> {
+    report
+        .rows()
+        .iter()
+        .map(|row| row.iter().map(Option::as_deref).collect())
+        .collect()
+}
+
+#[test]
+fn transfer_queries() {
+    let mut fixture = Fixture::new();
+
+    let accounts = fixture.run(Query::Accounts);
+    assert_eq!(accounts.columns(), ["key", "balance", "nonce"]);
+    let key = hex::encode(fixture.account_pk.to_bytes());
+    let balance = ACCOUNT_VALUE.to_string();
+    assert_eq!(
+        cells(&accounts),
+        [[Some(key.as_str()), Some(balance.as_str()), Some("0")]]
+    );
+
+    let balances = fixture.run(Query::ContractBalances);
+    let mut balances: Vec<_> = cells(&balances)
+        .into_iter()
+        .map(|row| (row[0].unwrap().to_owned(), row[1].unwrap().to_owned()))
+        .collect();
+    balances.sort();
+    let mut expected = vec![
+        (CONTRACT_ID.to_string(), CONTRACT_VALUE.to_string()),
+        (STAKE_CONTRACT.to_string(), STAKE_VALUE.to_string()),
+    ];
+    expected.sort();
+    assert_eq!(balances, expected);
+
+    let notes = fixture.run(Query::Notes);
+    assert_eq!(notes.columns(), ["count", "root"]);
+    assert_eq!(notes.rows()[0][0].as_deref(), Some("3"));
+    let root = notes.rows()[0][1].clone();
+
+    let nullifiers = fixture.run(Query::Nullifiers);
+    assert_eq!(nullifiers.rows()[0][0].as_deref(), Some("0"));
+
+    // one root of each tree for the genesis block, and one for the block
+    // committed by the test chain
+    let roots = fixture.run(Query::Roots);
+    let trees: Vec<_> = roots.rows().iter().map(|row| row[0].clone()).collect();
+    assert_eq!(
+        trees,
+        ["notes", "notes", "nullifiers", "nullifiers"]
+            .map(|tree| Some(String::from(tree)))
+    );
+    assert_eq!(roots.rows()[0][1], None);
+    assert_eq!(
+        roots.rows()[1][2],
+        root,
+        "The newest root is the current one"
+    );
+    assert_eq!(roots.rows()[3][1].as_deref(), Some("1"));
+}
+
+#[test]
+fn stake_queries() {
+    let mut fixture = Fixture::new();
+
+    let stakes = fixture.run(Query::Stakes);
+    let account = hex::encode(fixture.provisioner_pk.to_bytes());
+    let owner = format!("contract:{CONTRACT_ID}");
+    let value = STAKE_VALUE.to_string();
+    let eligibility = ELIGIBILITY.to_string();
+    let reward = REWARD_VALUE.to_string();
+    assert_eq!(
+        cells(&stakes),
+        [[
+            Some(account.as_str()),
+            Some(owner.as_str()),
+            Some(value.as_str()),
+            Some("0"),
+            Some(eligibility.as_str()),
+            Some(reward.as_str()),
+            Some("0"),
+            Some("0"),
+        ]]
+    );
+
+    let burnt_amount = fixture.run(Query::BurntAmount);
+    assert_eq!(cells(&burnt_amount), [[Some("0")]]);
+
+    let config = fixture.run(Query::StakeConfig);
+    let minimum_stake = CONFIG.minimum_stake.to_string();
+    assert_eq!(cells(&config), [[Some("3"), Some(minimum_stake.as_str())]]);
+}
@@ -0,0 +1,70 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_inspect::{Query, Report};
+
+fn report() -> Report {
+    let mut report = Report::new(&["tree", "block_height", "root"]);
+    report.push(vec![Some(String::from("notes")), None, Some("ab".into())]);
+    report.push(vec![
+        Some(String::from("nullifiers")),
+        Some(String::from("3")),
+        Some(String::from("cd")),
+    ]);
+    report
+}
+
+#[test]
+fn table() {
+    assert_eq!(
+        report().to_table(),
+        "\
+tree        block_height  root
+notes       -             ab
+nullifiers  3             cd
+"
+    );
+
+    let report = Report::new(&["count", "root"]);
+    assert_eq!(report.to_table(), "count  root\n");
+}
+
+#[test]
+fn json() {
+    assert_eq!(
+        report().to_json(),
+        r#"[
+  {
+    "block_height": null,
+    "root": "ab",
+    "tree": "notes"
+  },
+  {
+    "block_height": "3",
+    "root": "cd",
+    "tree": "nullifiers"
+  }
+]
+"#
+    );
+
+    let report = Report::new(&["count", "root"]);
+    assert_eq!(report.to_json(), "[]\n");
+}
+
+#[test]
+#[should_panic]
+fn cell_per_column() {
+    report().push(vec![None]);
+}
+
+#[test]
+fn query_names() {
+    for query in Query::ALL {
+        assert_eq!(Query::from_name(query.name()), Some(query));
+    }
+    assert_eq!(Query::from_name("Accounts"), None);
+}
@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Replay a trace against the contracts built with `make wasm`, or the given
+//! bytecode, printing every divergence from the recording. Exits with a
+//! failure if there is any.
+//!
+//! ```text
+//! replay <trace> [--transfer <wasm>] [--stake <wasm>]
+//!     [--gas-tolerance <percent>]
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{env, fs};
+
+use contracts_replay::{replay, Bytecode, Trace};
+use dusk_vm::VM;
+
+const USAGE: &str = "Usage: replay <trace> [--transfer <wasm>] \
+                     [--stake <wasm>] [--gas-tolerance <percent>]";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, String> {
+    let mut args = env::args().skip(1);
+
+    let trace_path = PathBuf::from(args.next().ok_or(USAGE)?);
+
+    let mut bytecode = Bytecode::default();
+    let mut gas_tolerance = 0.0;
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+        match arg.as_str() {
+            "--transfer" => bytecode.transfer = Some(read_wasm(&value)?),
+            "--stake" => bytecode.stake = Some(read_wasm(&value)?),
+            "--gas-tolerance" => {
+                gas_tolerance = value
+                    .parse()
+                    .map_err(|_| format!("Invalid tolerance: {value}"))?;
+            }
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let trace = Trace::read(&trace_path).map_err(|err| {
+        format!("Reading {} failed: {err}", trace_path.display())
+    })?;
+
+    let vm = VM::ephemeral()
+        .map_err(|err| format!("Opening the VM failed: {err:?}"))?;
+    let replay = replay(&trace, bytecode, &vm, gas_tolerance)
+        .map_err(|err| err.to_string())?;
+
+    for divergence in &replay.divergences {
+        println!("{divergence}");
+    }
+    eprintln!(
+        "Replayed {} calls in {} blocks, with {} divergences",
+        replay.outcomes.len(),
+        trace.block.len(),
+        replay.divergences.len()
+    );
+
+    Ok(if replay.divergences.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn read_wasm(path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|err| format!("Reading {path} failed: {err}"))
+}
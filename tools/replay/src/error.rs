@@ -0,0 +1,53 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+use std::io;
+
+use contracts_genesis::Error as GenesisError;
+use dusk_vm::Error as VMError;
+
+/// The errors returned when replaying a trace.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading a file failed.
+    Io(io::Error),
+    /// The trace isn't valid TOML or JSON, or doesn't have the fields of a
+    /// trace.
+    Parse(String),
+    /// A field of the trace has an invalid value.
+    Invalid(String),
+    /// Building the genesis state of the trace failed.
+    Genesis(GenesisError),
+    /// The VM failed to start or commit a block.
+    VM(VMError),
+}
+
+impl From<GenesisError> for Error {
+    fn from(e: GenesisError) -> Self {
+        Self::Genesis(e)
+    }
+}
+
+impl From<VMError> for Error {
+    fn from(e: VMError) -> Self {
+        Self::VM(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+            Self::Parse(err) => write!(f, "Invalid trace: {err}"),
+            Self::Invalid(err) => write!(f, "Invalid trace: {err}"),
+            Self::Genesis(err) => write!(f, "{err}"),
+            Self::VM(err) => write!(f, "Replaying the trace failed: {err:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
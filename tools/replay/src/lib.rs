@@ -0,0 +1,260 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Replay of recorded traces of calls to the genesis contracts.
+//!
+//! A [`Trace`], written in TOML or JSON, starts from a genesis [`Spec`] and
+//! lists blocks of transactions and management calls, as the node makes them
+//! when accepting a block, together with the gas spent and events emitted by
+//! each call when it was recorded. [`replay`] builds the genesis state with
+//! the given bytecode of the contracts, runs every block in a session of its
+//! own at its height, and reports every [`Divergence`] from the recording.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod error;
+mod trace;
+
+pub use contracts_genesis::{Bytecode, Spec};
+pub use error::Error;
+pub use trace::{
+    Block, BlockTrace, Call, CallTrace, EventTrace, RewardReasonTrace,
+    RewardTrace, Step, StepTrace, Trace,
+};
+
+use core::fmt;
+
+use contracts_genesis::build;
+use dusk_core::abi::Event;
+use dusk_vm::{Session, VM};
+
+/// The number of leading hex characters of bytes written in a divergence.
+const HEX_PREFIX: usize = 16;
+
+/// What a call did when replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The height of the block of the call.
+    pub height: u64,
+    /// The index of the call in its block.
+    pub step: usize,
+    /// The name of the entrypoint called.
+    pub fn_name: &'static str,
+    /// The gas spent, or the error the call failed with.
+    pub result: Result<u64, String>,
+    /// The events emitted.
+    pub events: Vec<Event>,
+}
+
+/// A difference between what a call did when recorded and when replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The height of the block of the call.
+    pub height: u64,
+    /// The index of the call in its block.
+    pub step: usize,
+    /// The name of the entrypoint called.
+    pub fn_name: &'static str,
+    /// What differs.
+    pub what: String,
+    /// What was recorded.
+    pub recorded: String,
+    /// What was replayed.
+    pub replayed: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {}, step {} ({}): {} differs, recorded {} against replayed \
+             {}",
+            self.height,
+            self.step,
+            self.fn_name,
+            self.what,
+            self.recorded,
+            self.replayed
+        )
+    }
+}
+
+/// The result of replaying a trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// The commit of the genesis state.
+    pub genesis: [u8; 32],
+    /// The commit of the state after the last block.
+    pub commit: [u8; 32],
+    /// What every call did, in order.
+    pub outcomes: Vec<Outcome>,
+    /// The differences from the recording.
+    pub divergences: Vec<Divergence>,
+}
+
+/// Replay the trace in the given VM, deploying the given bytecode of the
+/// contracts in the genesis state.
+///
+/// The gas spent by a call diverges if it differs from the recorded one by
+/// more than the given tolerance in percent. The events have to match
+/// exactly, and a call failing always diverges, since the calls of an
+/// accepted block don't fail.
+///
+/// # Errors
+/// Returns an error if the trace is invalid, or if the VM fails to build the
+/// genesis state or to start or commit a block.
+///
+/// # Panics
+/// Panics if the bytecode of a contract isn't given and it wasn't built with
+/// `make wasm`.
+pub fn replay(
+    trace: &Trace,
+    bytecode: Bytecode,
+    vm: &VM,
+    gas_tolerance: f64,
+) -> Result<Replay, Error> {
+    let blocks = trace.blocks()?;
+    let chain_id = trace.genesis.chain_id;
+
+    let genesis = build(&trace.genesis, bytecode, vm)?.commit;
+    let mut commit = genesis;
+    let mut outcomes = Vec::new();
+    let mut divergences = Vec::new();
+
+    for block in blocks {
+        let mut session = vm.session(commit, chain_id, block.height)?;
+        for (i, step) in block.steps.iter().enumerate() {
+            let outcome = run(&mut session, block.height, i, &step.call);
+            compare(step, &outcome, gas_tolerance, &mut divergences);
+            outcomes.push(outcome);
+        }
+        commit = session.commit()?;
+    }
+
+    Ok(Replay {
+        genesis,
+        commit,
+        outcomes,
+        divergences,
+    })
+}
+
+fn run(
+    session: &mut Session,
+    height: u64,
+    step: usize,
+    call: &Call,
+) -> Outcome {
+    let receipt = session.call_raw(
+        call.contract(),
+        call.fn_name(),
+        call.arg(),
+        call.gas_limit(),
+    );
+    let (result, events) = match receipt {
+        Ok(receipt) => (Ok(receipt.gas_spent), receipt.events),
+        Err(err) => (Err(format!("{err:?}")), Vec::new()),
+    };
+
+    Outcome {
+        height,
+        step,
+        fn_name: call.fn_name(),
+        result,
+        events,
+    }
+}
+
+fn compare(
+    step: &Step,
+    outcome: &Outcome,
+    gas_tolerance: f64,
+    divergences: &mut Vec<Divergence>,
+) {
+    let mut diverge = |what: String, recorded: String, replayed: String| {
+        divergences.push(Divergence {
+            height: outcome.height,
+            step: outcome.step,
+            fn_name: outcome.fn_name,
+            what,
+            recorded,
+            replayed,
+        });
+    };
+
+    let gas_spent = match &outcome.result {
+        Ok(gas_spent) => *gas_spent,
+        Err(err) => {
+            diverge(
+                String::from("result"),
+                String::from("success"),
+                format!("failure {err}"),
+            );
+            return;
+        }
+    };
+
+    if let Some(recorded) = step.gas_spent {
+        let change = gas_change(recorded, gas_spent);
+        if change.abs() > gas_tolerance {
+            diverge(
+                format!("gas spent ({change:+.1}%)"),
+                recorded.to_string(),
+                gas_spent.to_string(),
+            );
+        }
+    }
+
+    let Some(recorded) = &step.events else {
+        return;
+    };
+    if recorded.len() != outcome.events.len() {
+        diverge(
+            String::from("number of events"),
+            recorded.len().to_string(),
+            outcome.events.len().to_string(),
+        );
+        return;
+    }
+    for (i, (recorded, replayed)) in
+        recorded.iter().zip(&outcome.events).enumerate()
+    {
+        if recorded.source != replayed.source
+            || recorded.topic != replayed.topic
+        {
+            diverge(
+                format!("event {i}"),
+                format!("{}::{}", recorded.source, recorded.topic),
+                format!("{}::{}", replayed.source, replayed.topic),
+            );
+        } else if recorded.data != replayed.data {
+            diverge(
+                format!("data of event {i} ({})", recorded.topic),
+                bytes(&recorded.data),
+                bytes(&replayed.data),
+            );
+        }
+    }
+}
+
+/// The change of the gas spent relative to the recorded one, in percent.
+fn gas_change(recorded: u64, replayed: u64) -> f64 {
+    if recorded == 0 {
+        return if replayed == 0 { 0.0 } else { f64::INFINITY };
+    }
+    (replayed as f64 - recorded as f64) * 100.0 / recorded as f64
+}
+
+/// The hex encoding of the given bytes, abbreviated if they're long.
+fn bytes(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    if hex.len() > HEX_PREFIX {
+        format!("0x{}.. ({} bytes)", &hex[..HEX_PREFIX], bytes.len())
+    } else {
+        format!("0x{hex}")
+    }
+}
@@ -0,0 +1,443 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::fs;
+use std::path::Path;
+
+use contracts_genesis::Spec;
+use dusk_bytes::Serializable;
+use dusk_core::abi::{ContractId, Event};
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::stake::{Reward, RewardReason, STAKE_CONTRACT};
+use dusk_core::transfer::{Transaction, TRANSFER_CONTRACT};
+use rkyv::ser::serializers::AllocSerializer;
+use serde::Deserialize;
+
+use crate::Error;
+
+/// The gas limit of the management calls, as the node calls them.
+const MANAGEMENT_GAS_LIMIT: u64 = u64::MAX;
+
+const SCRATCH_SIZE: usize = 1024;
+
+/// A recorded sequence of blocks of calls to the genesis contracts, starting
+/// from a genesis state.
+///
+/// Transactions are given as the hex encoding of their bytes, keys as the hex
+/// encoding of their compressed bytes and contract IDs as the hex encoding of
+/// their 32 bytes, with or without a `0x` prefix. The gas spent and events of
+/// a step are what was recorded, and are only compared when given.
+///
+/// ```toml
+/// [genesis]
+/// chain_id = 1
+///
+/// [[genesis.moonlight]]
+/// address = "a1b2..."
+/// value = 1000000000000
+///
+/// [[block]]
+/// height = 1
+///
+/// [[block.step]]
+/// call = { fn = "spend_and_execute", tx = "0100..." }
+/// gas_spent = 1234567
+/// events = [{ source = "0100...", topic = "moonlight", data = "c3d4..." }]
+///
+/// [[block.step]]
+/// call = { fn = "refund", gas_spent = 1234567 }
+///
+/// [[block.step]]
+/// call = { fn = "update_root" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Trace {
+    /// The genesis state the trace starts from, at block height 0.
+    pub genesis: Spec,
+    /// The blocks, by increasing height.
+    #[serde(default)]
+    pub block: Vec<BlockTrace>,
+}
+
+/// A block of a trace, run in a session of its own and committed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockTrace {
+    /// The height of the block.
+    pub height: u64,
+    /// The calls made in the block, in order.
+    #[serde(default)]
+    pub step: Vec<StepTrace>,
+}
+
+/// A call made in a block, with what it did when recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepTrace {
+    /// The call.
+    pub call: CallTrace,
+    /// The gas spent by the call, if recorded.
+    #[serde(default)]
+    pub gas_spent: Option<u64>,
+    /// The events emitted by the call, in order, if recorded.
+    #[serde(default)]
+    pub events: Option<Vec<EventTrace>>,
+}
+
+/// A call to an entrypoint of the genesis contracts, by the name of the
+/// entrypoint.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "fn", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CallTrace {
+    /// `transfer::spend_and_execute`, with the gas limit of the transaction.
+    SpendAndExecute {
+        /// The transaction.
+        tx: String,
+    },
+    /// `transfer::refund`.
+    Refund {
+        /// The gas spent by the transaction being refunded.
+        gas_spent: u64,
+    },
+    /// `transfer::update_root`.
+    UpdateRoot,
+    /// `stake::reward`.
+    Reward {
+        /// The rewards.
+        rewards: Vec<RewardTrace>,
+    },
+    /// `stake::slash`.
+    Slash {
+        /// The consensus key of the stake slashed.
+        account: String,
+        /// The value slashed, the default of the contract if not given.
+        #[serde(default)]
+        value: Option<u64>,
+    },
+    /// `stake::before_state_transition`.
+    BeforeStateTransition,
+}
+
+/// A reward of a stake.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RewardTrace {
+    /// The consensus key of the stake rewarded.
+    pub account: String,
+    /// The value of the reward.
+    pub value: u64,
+    /// The reason of the reward.
+    pub reason: RewardReasonTrace,
+}
+
+/// The reason of a reward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardReasonTrace {
+    /// The fixed reward of the generator of the block.
+    GeneratorFixed,
+    /// The extra reward of the generator, for the credits it included.
+    GeneratorExtra,
+    /// The reward of a voter.
+    Voter,
+    /// Any other reason.
+    Other,
+}
+
+/// An event emitted by a call.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventTrace {
+    /// The contract emitting the event.
+    pub source: String,
+    /// The topic of the event.
+    pub topic: String,
+    /// The hex encoding of the data of the event.
+    pub data: String,
+}
+
+/// A block of a validated trace.
+#[derive(Debug, Clone)]
+pub struct Block {
+    /// The height of the block.
+    pub height: u64,
+    /// The calls made in the block, in order.
+    pub steps: Vec<Step>,
+}
+
+/// A call of a validated trace, with what it did when recorded.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// The call.
+    pub call: Call,
+    /// The gas spent by the call, if recorded.
+    pub gas_spent: Option<u64>,
+    /// The events emitted by the call, if recorded.
+    pub events: Option<Vec<Event>>,
+}
+
+/// A call of a validated trace.
+#[derive(Debug, Clone)]
+pub enum Call {
+    /// `transfer::spend_and_execute`.
+    SpendAndExecute(Box<Transaction>),
+    /// `transfer::refund`, with the gas spent by the transaction.
+    Refund(u64),
+    /// `transfer::update_root`.
+    UpdateRoot,
+    /// `stake::reward`.
+    Reward(Vec<Reward>),
+    /// `stake::slash`, with the consensus key and value slashed.
+    Slash(AccountPublicKey, Option<u64>),
+    /// `stake::before_state_transition`.
+    BeforeStateTransition,
+}
+
+impl Call {
+    /// The contract called.
+    #[must_use]
+    pub fn contract(&self) -> ContractId {
+        match self {
+            Self::SpendAndExecute(_) | Self::Refund(_) | Self::UpdateRoot => {
+                TRANSFER_CONTRACT
+            }
+            Self::Reward(_) | Self::Slash(..) | Self::BeforeStateTransition => {
+                STAKE_CONTRACT
+            }
+        }
+    }
+
+    /// The name of the entrypoint called.
+    #[must_use]
+    pub fn fn_name(&self) -> &'static str {
+        match self {
+            Self::SpendAndExecute(_) => "spend_and_execute",
+            Self::Refund(_) => "refund",
+            Self::UpdateRoot => "update_root",
+            Self::Reward(_) => "reward",
+            Self::Slash(..) => "slash",
+            Self::BeforeStateTransition => "before_state_transition",
+        }
+    }
+
+    /// The gas limit of the call: the one of the transaction for
+    /// `spend_and_execute`, and no limit for the management calls.
+    #[must_use]
+    pub fn gas_limit(&self) -> u64 {
+        match self {
+            Self::SpendAndExecute(tx) => tx.gas_limit(),
+            _ => MANAGEMENT_GAS_LIMIT,
+        }
+    }
+
+    /// The serialized argument of the call.
+    ///
+    /// # Panics
+    /// Panics if serializing the argument fails.
+    #[must_use]
+    pub fn arg(&self) -> Vec<u8> {
+        match self {
+            Self::SpendAndExecute(tx) => serialize(tx.as_ref()),
+            Self::Refund(gas_spent) => serialize(gas_spent),
+            Self::UpdateRoot | Self::BeforeStateTransition => serialize(&()),
+            Self::Reward(rewards) => serialize(rewards),
+            Self::Slash(pk, value) => serialize(&(*pk, *value)),
+        }
+    }
+}
+
+impl Trace {
+    /// Parse a trace written in TOML.
+    ///
+    /// # Errors
+    /// Returns an error if the given string isn't a trace.
+    pub fn from_toml(trace: &str) -> Result<Self, Error> {
+        toml::from_str(trace).map_err(|err| Error::Parse(err.to_string()))
+    }
+
+    /// Parse a trace written in JSON.
+    ///
+    /// # Errors
+    /// Returns an error if the given string isn't a trace.
+    pub fn from_json(trace: &str) -> Result<Self, Error> {
+        serde_json::from_str(trace).map_err(|err| Error::Parse(err.to_string()))
+    }
+
+    /// Read the trace at the given path, in JSON if its extension is `json`,
+    /// and in TOML otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, or isn't a trace.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let trace = fs::read_to_string(path).map_err(Error::Io)?;
+
+        match path.extension() {
+            Some(extension) if extension == "json" => Self::from_json(&trace),
+            _ => Self::from_toml(&trace),
+        }
+    }
+
+    /// Validate the blocks of the trace, decoding their calls and events.
+    ///
+    /// # Errors
+    /// Returns an error if a block isn't above the genesis block and the
+    /// previous one, or if a transaction, key, contract ID or event data is
+    /// invalid.
+    pub fn blocks(&self) -> Result<Vec<Block>, Error> {
+        let mut blocks = Vec::with_capacity(self.block.len());
+        let mut previous = 0;
+
+        for (i, block) in self.block.iter().enumerate() {
+            if block.height <= previous {
+                return Err(Error::Invalid(format!(
+                    "block[{i}].height: expected a height above {previous}"
+                )));
+            }
+            previous = block.height;
+
+            let steps = block
+                .step
+                .iter()
+                .enumerate()
+                .map(|(j, step)| {
+                    step.validate(&format!("block[{i}].step[{j}]"))
+                })
+                .collect::<Result<_, _>>()?;
+            blocks.push(Block {
+                height: block.height,
+                steps,
+            });
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl StepTrace {
+    fn validate(&self, field: &str) -> Result<Step, Error> {
+        let call = self.call.validate(&format!("{field}.call"))?;
+
+        let events = match &self.events {
+            None => None,
+            Some(events) => Some(
+                events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, event)| {
+                        event.validate(&format!("{field}.events[{i}]"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        Ok(Step {
+            call,
+            gas_spent: self.gas_spent,
+            events,
+        })
+    }
+}
+
+impl CallTrace {
+    fn validate(&self, field: &str) -> Result<Call, Error> {
+        Ok(match self {
+            Self::SpendAndExecute { tx } => {
+                let bytes = parse_hex(tx, || format!("{field}.tx"))?;
+                let tx = Transaction::from_slice(&bytes).map_err(|_| {
+                    Error::Invalid(format!(
+                        "{field}.tx: not a valid transaction"
+                    ))
+                })?;
+                Call::SpendAndExecute(Box::new(tx))
+            }
+            Self::Refund { gas_spent } => Call::Refund(*gas_spent),
+            Self::UpdateRoot => Call::UpdateRoot,
+            Self::Reward { rewards } => Call::Reward(
+                rewards
+                    .iter()
+                    .enumerate()
+                    .map(|(i, reward)| {
+                        Ok(Reward {
+                            account: parse_account(&reward.account, || {
+                                format!("{field}.rewards[{i}].account")
+                            })?,
+                            value: reward.value,
+                            reason: reward.reason.into(),
+                        })
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Self::Slash { account, value } => Call::Slash(
+                parse_account(account, || format!("{field}.account"))?,
+                *value,
+            ),
+            Self::BeforeStateTransition => Call::BeforeStateTransition,
+        })
+    }
+}
+
+impl From<RewardReasonTrace> for RewardReason {
+    fn from(reason: RewardReasonTrace) -> Self {
+        match reason {
+            RewardReasonTrace::GeneratorFixed => Self::GeneratorFixed,
+            RewardReasonTrace::GeneratorExtra => Self::GeneratorExtra,
+            RewardReasonTrace::Voter => Self::Voter,
+            RewardReasonTrace::Other => Self::Other,
+        }
+    }
+}
+
+impl EventTrace {
+    fn validate(&self, field: &str) -> Result<Event, Error> {
+        let source =
+            ContractId::try_from(self.source.clone()).map_err(|_| {
+                Error::Invalid(format!(
+                    "{field}.source: expected 32 hex encoded bytes"
+                ))
+            })?;
+        let data = parse_hex(&self.data, || format!("{field}.data"))?;
+
+        Ok(Event {
+            source,
+            topic: self.topic.clone(),
+            data,
+        })
+    }
+}
+
+/// Parse bytes from their hex encoding.
+fn parse_hex(hex: &str, field: impl Fn() -> String) -> Result<Vec<u8>, Error> {
+    hex::decode(hex.trim_start_matches("0x"))
+        .map_err(|_| Error::Invalid(format!("{}: not a hex string", field())))
+}
+
+/// Parse a Moonlight public key from the hex encoding of its bytes.
+fn parse_account(
+    hex: &str,
+    field: impl Fn() -> String,
+) -> Result<AccountPublicKey, Error> {
+    let invalid =
+        |reason: &str| Error::Invalid(format!("{}: {reason}", field()));
+
+    let bytes = parse_hex(hex, &field)?;
+    let bytes: [u8; AccountPublicKey::SIZE] =
+        bytes.try_into().map_err(|_| {
+            invalid(&format!("expected {} bytes", AccountPublicKey::SIZE))
+        })?;
+
+    AccountPublicKey::from_bytes(&bytes).map_err(|_| invalid("not a valid key"))
+}
+
+fn serialize<T>(arg: &T) -> Vec<u8>
+where
+    T: rkyv::Serialize<AllocSerializer<SCRATCH_SIZE>>,
+{
+    rkyv::to_bytes::<_, SCRATCH_SIZE>(arg)
+        .expect("Serializing the argument should succeed")
+        .to_vec()
+}
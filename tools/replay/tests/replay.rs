@@ -0,0 +1,389 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_replay::{
+    replay, Bytecode, CallTrace, Error, Outcome, RewardReasonTrace, Trace,
+};
+use contracts_test_kit::{stake, transfer};
+use dusk_bytes::Serializable;
+use dusk_core::signatures::bls::{
+    PublicKey as AccountPublicKey, SecretKey as AccountSecretKey,
+};
+use dusk_core::transfer::Transaction;
+use dusk_core::{dusk, LUX};
+use dusk_vm::VM;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CHAIN_ID: u8 = 0x2a;
+
+const GAS_LIMIT: u64 = 0x10000000;
+
+struct Keys {
+    sender_sk: AccountSecretKey,
+    receiver_pk: AccountPublicKey,
+    provisioner_pk: AccountPublicKey,
+}
+
+impl Keys {
+    fn new() -> Self {
+        let rng = &mut StdRng::seed_from_u64(0xfeed);
+        Self {
+            sender_sk: AccountSecretKey::random(rng),
+            receiver_pk: AccountPublicKey::from(&AccountSecretKey::random(rng)),
+            provisioner_pk: AccountPublicKey::from(&AccountSecretKey::random(
+                rng,
+            )),
+        }
+    }
+
+    fn sender(&self) -> String {
+        hex::encode(AccountPublicKey::from(&self.sender_sk).to_bytes())
+    }
+
+    fn provisioner(&self) -> String {
+        hex::encode(self.provisioner_pk.to_bytes())
+    }
+
+    fn transfer(&self, nonce: u64) -> String {
+        let tx = Transaction::moonlight(
+            &self.sender_sk,
+            Some(self.receiver_pk),
+            dusk(10.0),
+            0,
+            GAS_LIMIT,
+            LUX,
+            nonce,
+            CHAIN_ID,
+            None,
+        )
+        .expect("Creating moonlight transaction should succeed");
+        hex::encode(tx.to_var_bytes())
+    }
+}
+
+/// The steps of the trace, each with what it records.
+fn trace(keys: &Keys, recorded: &[String]) -> String {
+    let step = |i: usize, call: String| {
+        let recorded = recorded.get(i).map_or("", String::as_str);
+        format!("[[block.step]]\ncall = {call}\n{recorded}\n")
+    };
+
+    format!(
+        r#"
+[genesis]
+chain_id = {CHAIN_ID}
+
+[[genesis.moonlight]]
+address = "{sender}"
+value = {value}
+
+[[genesis.stake]]
+account = "{provisioner}"
+value = {stake}
+
+[[block]]
+height = 1
+
+{spend}
+{refund}
+{update_root}
+
+[[block]]
+height = 2
+
+{before_state_transition}
+{reward}
+{slash}
+"#,
+        sender = keys.sender(),
+        value = dusk(1000.0),
+        provisioner = keys.provisioner(),
+        stake = dusk(1000.0),
+        spend = step(
+            0,
+            format!(
+                r#"{{ fn = "spend_and_execute", tx = "{}" }}"#,
+                keys.transfer(1)
+            )
+        ),
+        refund =
+            step(1, String::from(r#"{ fn = "refund", gas_spent = 1000 }"#)),
+        update_root = step(2, String::from(r#"{ fn = "update_root" }"#)),
+        before_state_transition =
+            step(3, String::from(r#"{ fn = "before_state_transition" }"#)),
+        reward = step(
+            4,
+            format!(
+                "{{ fn = \"reward\", rewards = [{{ account = \"{}\", \
+                 value = {}, reason = \"voter\" }}] }}",
+                keys.provisioner(),
+                dusk(1.0)
+            )
+        ),
+        slash = step(
+            5,
+            format!(
+                r#"{{ fn = "slash", account = "{}", value = {} }}"#,
+                keys.provisioner(),
+                dusk(1.0)
+            )
+        ),
+    )
+}
+
+/// Record the gas spent and events of the given outcome, as TOML fields of a
+/// step.
+fn record(outcome: &Outcome) -> String {
+    let gas_spent = outcome
+        .result
+        .as_ref()
+        .expect("The call should have succeeded");
+    let events = outcome
+        .events
+        .iter()
+        .map(|event| {
+            format!(
+                r#"{{ source = "{}", topic = "{}", data = "{}" }}"#,
+                event.source,
+                event.topic,
+                hex::encode(&event.data)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("gas_spent = {gas_spent}\nevents = [{events}]")
+}
+
+#[test]
+fn parse_trace() {
+    let keys = Keys::new();
+
+    let trace = Trace::from_toml(&trace(&keys, &[]))
+        .expect("Parsing the trace should succeed");
+    assert_eq!(trace.genesis.chain_id, CHAIN_ID);
+    assert_eq!(trace.block.len(), 2);
+    assert_eq!(trace.block[0].step.len(), 3);
+    assert_eq!(
+        trace.block[0].step[1].call,
+        CallTrace::Refund { gas_spent: 1000 }
+    );
+    assert_eq!(
+        trace.block[1].step[0].call,
+        CallTrace::BeforeStateTransition
+    );
+    match &trace.block[1].step[1].call {
+        CallTrace::Reward { rewards } => {
+            assert_eq!(rewards.len(), 1);
+            assert_eq!(rewards[0].reason, RewardReasonTrace::Voter);
+        }
+        call => panic!("Expected a reward, got {call:?}"),
+    }
+    assert!(trace
+        .block
+        .iter()
+        .flat_map(|block| &block.step)
+        .all(|step| step.gas_spent.is_none() && step.events.is_none()));
+
+    let blocks = trace.blocks().expect("The trace should be valid");
+    let fn_names = blocks
+        .iter()
+        .flat_map(|block| &block.steps)
+        .map(|step| step.call.fn_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fn_names,
+        [
+            "spend_and_execute",
+            "refund",
+            "update_root",
+            "before_state_transition",
+            "reward",
+            "slash",
+        ]
+    );
+    assert_eq!(blocks[0].steps[0].call.gas_limit(), GAS_LIMIT);
+    assert_eq!(blocks[0].steps[1].call.gas_limit(), u64::MAX);
+
+    let json = format!(
+        r#"{{
+    "genesis": {{ "chain_id": {CHAIN_ID} }},
+    "block": [{{
+        "height": 1,
+        "step": [
+            {{ "call": {{ "fn": "update_root" }}, "gas_spent": 10 }},
+            {{
+                "call": {{ "fn": "slash", "account": "{}", "value": 5 }},
+                "events": []
+            }}
+        ]
+    }}]
+}}"#,
+        keys.provisioner()
+    );
+    let trace =
+        Trace::from_json(&json).expect("Parsing the trace should succeed");
+    assert_eq!(trace.block[0].step[0].gas_spent, Some(10));
+    assert_eq!(trace.block[0].step[1].events, Some(vec![]));
+}
+
+#[test]
+fn invalid_traces() {
+    let invalid = |trace: &str| match Trace::from_toml(trace)
+        .and_then(|trace| trace.blocks().map(|_| ()))
+    {
+        Ok(()) => panic!("The trace should be invalid: {trace}"),
+        Err(err) => err.to_string(),
+    };
+    let genesis = "[genesis]\nchain_id = 1\n";
+
+    assert!(matches!(Trace::from_toml(""), Err(Error::Parse(_))));
+    assert!(matches!(
+        Trace::from_toml(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"mint\" }}"
+        )),
+        Err(Error::Parse(_))
+    ));
+
+    assert_eq!(
+        invalid(&format!("{genesis}[[block]]\nheight = 0")),
+        "Invalid trace: block[0].height: expected a height above 0"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 2\n[[block]]\nheight = 2"
+        )),
+        "Invalid trace: block[1].height: expected a height above 2"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"spend_and_execute\", tx = \"xyz\" }}"
+        )),
+        "Invalid trace: block[0].step[0].call.tx: not a hex string"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"spend_and_execute\", tx = \"0102\" }}"
+        )),
+        "Invalid trace: block[0].step[0].call.tx: not a valid transaction"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"slash\", account = \"0102\" }}"
+        )),
+        format!(
+            "Invalid trace: block[0].step[0].call.account: expected {} bytes",
+            AccountPublicKey::SIZE
+        )
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"reward\", rewards = [{{ account = \"{}\", \
+             value = 1, reason = \"other\" }}] }}",
+            "ff".repeat(AccountPublicKey::SIZE)
+        )),
+        "Invalid trace: block[0].step[0].call.rewards[0].account: not a \
+         valid key"
+    );
+    assert_eq!(
+        invalid(&format!(
+            "{genesis}[[block]]\nheight = 1\n[[block.step]]\n\
+             call = {{ fn = \"update_root\" }}\n\
+             events = [{{ source = \"01\", topic = \"t\", data = \"\" }}]"
+        )),
+        "Invalid trace: block[0].step[0].events[0].source: expected 32 hex \
+         encoded bytes"
+    );
+}
+
+#[test]
+fn replay_trace() {
+    let keys = Keys::new();
+
+    // nothing recorded, so nothing diverges
+    let trace = Trace::from_toml(&trace(&keys, &[]))
+        .expect("Parsing the trace should succeed");
+    let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+    let replayed = replay(&trace, Bytecode::default(), &vm, 0.0)
+        .expect("Replaying the trace should succeed");
+    assert_eq!(replayed.divergences, vec![]);
+    assert_eq!(replayed.outcomes.len(), 6);
+    assert!(replayed
+        .outcomes
+        .iter()
+        .all(|outcome| outcome.result.is_ok()));
+    assert_eq!(
+        replayed
+            .outcomes
+            .iter()
+            .map(|outcome| (outcome.height, outcome.step))
+            .collect::<Vec<_>>(),
+        [(1, 0), (1, 1), (1, 2), (2, 0), (2, 1), (2, 2)]
+    );
+
+    let mut session = vm
+        .session(replayed.commit, CHAIN_ID, 3)
+        .expect("Instantiating new session should succeed");
+    let account = transfer::account(&mut session, &keys.receiver_pk)
+        .expect("Querying the account should succeed");
+    assert_eq!(account.balance, dusk(10.0));
+    let stake = stake::get_stake(&mut session, &keys.provisioner_pk)
+        .expect("Querying the stake should succeed")
+        .expect("The provisioner should have a stake");
+    assert_eq!(stake.reward, dusk(1.0));
+    assert_eq!(stake.faults, 1);
+
+    // recording what was replayed, the replay matches the recording
+    let recorded = replayed.outcomes.iter().map(record).collect::<Vec<_>>();
+    let trace = Trace::from_toml(&trace(&keys, &recorded))
+        .expect("Parsing the trace should succeed");
+    let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+    let rereplayed = replay(&trace, Bytecode::default(), &vm, 0.0)
+        .expect("Replaying the trace should succeed");
+    assert_eq!(rereplayed.divergences, vec![]);
+    assert_eq!(rereplayed.commit, replayed.commit);
+
+    // the gas spent by the transaction and the events of the slash diverge
+    let mut tampered = recorded;
+    let gas_spent = replayed.outcomes[0]
+        .result
+        .clone()
+        .expect("The transaction should have succeeded");
+    tampered[0] = tampered[0].replacen(
+        &format!("gas_spent = {gas_spent}"),
+        &format!("gas_spent = {}", gas_spent / 2),
+        1,
+    );
+    tampered[5] = String::from("gas_spent = 1\nevents = []");
+    let trace = Trace::from_toml(&trace(&keys, &tampered))
+        .expect("Parsing the trace should succeed");
+    let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+    let divergences = replay(&trace, Bytecode::default(), &vm, 10.0)
+        .expect("Replaying the trace should succeed")
+        .divergences;
+
+    assert_eq!(divergences.len(), 3);
+    assert_eq!((divergences[0].height, divergences[0].step), (1, 0));
+    assert_eq!(divergences[0].fn_name, "spend_and_execute");
+    assert_eq!(divergences[0].recorded, (gas_spent / 2).to_string());
+    assert!(divergences[0].what.starts_with("gas spent (+"));
+    assert_eq!((divergences[1].height, divergences[1].step), (2, 2));
+    assert_eq!(divergences[1].fn_name, "slash");
+    assert!(divergences[1].what.starts_with("gas spent"));
+    assert_eq!(
+        divergences[2].to_string(),
+        format!(
+            "block 2, step 2 (slash): number of events differs, recorded 0 \
+             against replayed {}",
+            replayed.outcomes[5].events.len()
+        )
+    );
+}
@@ -0,0 +1,161 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::ops::{Deref, DerefMut};
+
+use dusk_core::stake::next_epoch;
+use dusk_vm::{Session, VM};
+
+use crate::{stake, transfer, Genesis};
+
+/// A chain set up with a [`Genesis`], made of a VM and a session executing the
+/// current block.
+///
+/// The chain dereferences to the session of the current block, so it can be
+/// passed to the wrappers in [`transfer`] and [`stake`] directly.
+pub struct TestChain {
+    vm: VM,
+    // Only `None` while moving to a new block.
+    session: Option<Session>,
+    chain_id: u8,
+    block_height: u64,
+}
+
+impl TestChain {
+    /// Build the genesis block on an ephemeral VM, and start the first block
+    /// following it, at height 1.
+    ///
+    /// # Panics
+    /// Panics if setting up any part of the genesis state fails, for example
+    /// if the contracts weren't built with `make wasm`.
+    #[must_use]
+    pub fn new(genesis: Genesis) -> Self {
+        Self::starting_at(genesis, 1)
+    }
+
+    /// Build the genesis block on an ephemeral VM, and start the block at the
+    /// given height following it.
+    ///
+    /// # Panics
+    /// Panics if setting up any part of the genesis state fails, for example
+    /// if the contracts weren't built with `make wasm`.
+    #[must_use]
+    pub fn starting_at(genesis: Genesis, block_height: u64) -> Self {
+        let chain_id = genesis.chain_id();
+
+        let vm = VM::ephemeral().expect("Creating ephemeral VM should work");
+        let base = genesis
+            .commit(&vm)
+            .expect("Building the genesis state should succeed");
+        let session = vm
+            .session(base, chain_id, block_height)
+            .expect("Instantiating new session should succeed");
+
+        Self {
+            vm,
+            session: Some(session),
+            chain_id,
+            block_height,
+        }
+    }
+
+    /// The VM the chain runs on.
+    pub fn vm(&self) -> &VM {
+        &self.vm
+    }
+
+    /// The chain ID of the chain.
+    pub fn chain_id(&self) -> u8 {
+        self.chain_id
+    }
+
+    /// The height of the current block.
+    pub fn block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    /// Finish the current block and start the next one.
+    pub fn next_block(&mut self) {
+        self.advance_to(self.block_height + 1);
+    }
+
+    /// Finish the current block and start the block `count` blocks later.
+    pub fn advance_blocks(&mut self, count: u64) {
+        self.advance_to(self.block_height + count);
+    }
+
+    /// Finish the current block and start the first block of the epoch
+    /// `count` epochs later.
+    pub fn advance_epochs(&mut self, count: u64) {
+        let mut block_height = self.block_height;
+        for _ in 0..count {
+            block_height = next_epoch(block_height);
+        }
+        self.advance_to(block_height);
+    }
+
+    /// Finish the current block and start the block at the given height,
+    /// skipping the blocks in between.
+    ///
+    /// The current block is finished by updating the roots of the transfer
+    /// contract and committing the session, and the new one is started by
+    /// notifying the stake contract, as a node does.
+    ///
+    /// # Panics
+    /// Panics if the given height is not after the current one, or if
+    /// finishing or starting a block fails.
+    pub fn advance_to(&mut self, block_height: u64) {
+        assert!(
+            block_height > self.block_height,
+            "The chain can only be advanced to a later block"
+        );
+
+        let base = self.finish_block();
+
+        let mut session = self
+            .vm
+            .session(base, self.chain_id, block_height)
+            .expect("Instantiating new session should succeed");
+        stake::before_state_transition(&mut session)
+            .expect("Starting the block should succeed");
+
+        self.session = Some(session);
+        self.block_height = block_height;
+    }
+
+    /// Finish the current block and commit it, returning the VM together with
+    /// the commit, to start any number of sessions from the resulting state.
+    ///
+    /// # Panics
+    /// Panics if finishing the block fails.
+    pub fn commit(mut self) -> (VM, [u8; 32]) {
+        let base = self.finish_block();
+        (self.vm, base)
+    }
+
+    /// Finish the current block by updating the roots of the transfer
+    /// contract and committing the session, leaving the chain without one.
+    fn finish_block(&mut self) -> [u8; 32] {
+        let mut session = self.session.take().expect("There is a session");
+        transfer::update_root(&mut session)
+            .expect("Updating the root should succeed");
+        session.commit().expect("Committing should succeed")
+    }
+}
+
+impl Deref for TestChain {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        self.session.as_ref().expect("There is a session")
+    }
+}
+
+impl DerefMut for TestChain {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.session.as_mut().expect("There is a session")
+    }
+}
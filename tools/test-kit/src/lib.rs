@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Harness for the integration tests of the genesis contracts.
+//!
+//! A test chain is set up with the [`Genesis`] builder of
+//! [`contracts_genesis`], which deploys the transfer and stake contracts
+//! together with any extra contracts, and funds the given accounts, notes and
+//! stakes. The resulting [`TestChain`] can then be advanced by blocks and
+//! epochs, while the [`transfer`] and [`stake`] modules wrap the
+//! [`contracts_client`] for calling every entrypoint of the contracts.
+
+#![deny(unused_extern_crates)]
+
+mod chain;
+
+pub mod notes;
+pub mod stake;
+pub mod transfer;
+
+pub use chain::TestChain;
+pub use contracts_genesis::{
+    deploy_contract, stake_bytecode, transfer_bytecode, Genesis, OWNER,
+};
+
+use contracts_client::{Error as ClientError, Feed};
+use dusk_vm::Error as VMError;
+
+/// The gas limit used for all calls made with the test kit.
+pub const GAS_LIMIT: u64 = 0x100_000_000;
+
+/// The number of items summarized per call when summarizing a whole export.
+pub const EXPORT_CHUNK: u64 = 1024;
+
+/// The chain ID of a test chain, unless set otherwise.
+pub const CHAIN_ID: u8 = 0xFA;
+
+/// Extract the error of the VM from an error of the contracts client.
+///
+/// # Panics
+/// Panics if the client failed to deserialize the data returned by the
+/// contracts, which would be a bug in the contracts or in the client.
+pub(crate) fn vm_error(err: ClientError) -> VMError {
+    match err {
+        ClientError::VM(err) => err,
+        err => panic!("The client should deserialize the call: {err}"),
+    }
+}
+
+/// Collect the items fed by a feeder call of the contracts client.
+pub(crate) fn collect<T>(
+    feed: Result<Feed<T>, ClientError>,
+) -> Result<Vec<T>, VMError>
+where
+    Feed<T>: Iterator<Item = Result<T, ClientError>>,
+{
+    feed.and_then(Iterator::collect).map_err(vm_error)
+}
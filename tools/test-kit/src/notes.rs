@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Helpers to create Phoenix notes and to find the ones owned by a key.
+
+pub use contracts_genesis::transparent_note;
+
+use dusk_core::transfer::phoenix::{Note, ViewKey as PhoenixViewKey};
+use dusk_vm::{Error as VMError, Session};
+
+use crate::transfer::leaves_from_height;
+
+/// Returns the notes owned by the given view key.
+pub fn filter_notes_owned_by<I: IntoIterator<Item = Note>>(
+    vk: PhoenixViewKey,
+    iter: I,
+) -> Vec<Note> {
+    iter.into_iter()
+        .filter(|note| vk.owns(note.stealth_address()))
+        .collect()
+}
+
+/// Returns the total value of the notes owned by the given view key.
+pub fn owned_notes_value<'a, I: IntoIterator<Item = &'a Note>>(
+    vk: PhoenixViewKey,
+    notes: I,
+) -> u64 {
+    notes.into_iter().fold(0, |acc, note| {
+        acc + if vk.owns(note.stealth_address()) {
+            note.value(Some(&vk))
+                .expect("The value of an owned note should be readable")
+        } else {
+            0
+        }
+    })
+}
+
+/// Returns the total value of the notes owned by the given view key, that
+/// were added to the tree from the given block height.
+pub fn new_owned_notes_value(
+    session: &mut Session,
+    block_height: u64,
+    vk: PhoenixViewKey,
+) -> Result<u64, VMError> {
+    let leaves = leaves_from_height(session, block_height)?;
+    let owned_notes =
+        filter_notes_owned_by(vk, leaves.into_iter().map(|leaf| leaf.note));
+    Ok(owned_notes_value(vk, &owned_notes))
+}
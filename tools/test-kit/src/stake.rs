@@ -0,0 +1,267 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Typed wrappers for the entrypoints of the stake contract.
+//!
+//! Queries and feeders return the data returned or fed by the contract, while
+//! transactions return the whole receipt of the call, with its events and
+//! the gas spent. Transactions that can only be called through the transfer
+//! contract fail when called through these wrappers, which is useful to check
+//! their caller restrictions.
+
+use contracts_client::StakeClient;
+use dusk_core::signatures::bls::PublicKey as BlsPublicKey;
+use dusk_core::stake::{
+    Reward, RewardReason, Stake, StakeConfig, StakeData, StakeKeys, Withdraw,
+    WithdrawToContract,
+};
+use dusk_core::transfer::ReceiveFromContract;
+use dusk_core::BlsScalar;
+use dusk_vm::{CallReceipt, Error as VMError, Session};
+use stake_contract::migration::{ExportChunk, ExportCursor, ExportSummary};
+
+use crate::{collect, vm_error, EXPORT_CHUNK, GAS_LIMIT};
+
+/// The client of the stake contract in the given session, with the
+/// [`GAS_LIMIT`] of the test kit.
+fn client(session: &mut Session) -> StakeClient<'_> {
+    StakeClient::new(session).with_gas_limit(GAS_LIMIT)
+}
+
+// Transactions
+
+pub fn stake(
+    session: &mut Session,
+    stake: &Stake,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).stake(stake).map_err(vm_error)
+}
+
+pub fn unstake(
+    session: &mut Session,
+    unstake: &Withdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).unstake(unstake).map_err(vm_error)
+}
+
+pub fn withdraw(
+    session: &mut Session,
+    withdraw: &Withdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).withdraw(withdraw).map_err(vm_error)
+}
+
+pub fn stake_from_contract(
+    session: &mut Session,
+    receive: &ReceiveFromContract,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .stake_from_contract(receive)
+        .map_err(vm_error)
+}
+
+pub fn unstake_from_contract(
+    session: &mut Session,
+    unstake: &WithdrawToContract,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .unstake_from_contract(unstake)
+        .map_err(vm_error)
+}
+
+pub fn withdraw_from_contract(
+    session: &mut Session,
+    withdraw: &WithdrawToContract,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .withdraw_from_contract(withdraw)
+        .map_err(vm_error)
+}
+
+// Queries
+
+pub fn get_stake(
+    session: &mut Session,
+    pk: &BlsPublicKey,
+) -> Result<Option<StakeData>, VMError> {
+    client(session).get_stake(pk).map_err(vm_error)
+}
+
+pub fn get_stake_keys(
+    session: &mut Session,
+    pk: &BlsPublicKey,
+) -> Result<Option<StakeKeys>, VMError> {
+    client(session).get_stake_keys(pk).map_err(vm_error)
+}
+
+pub fn burnt_amount(session: &mut Session) -> Result<u64, VMError> {
+    client(session).burnt_amount().map_err(vm_error)
+}
+
+pub fn get_version(session: &mut Session) -> Result<u64, VMError> {
+    client(session).get_version().map_err(vm_error)
+}
+
+pub fn block_event_digest(
+    session: &mut Session,
+    block_height: u64,
+) -> Result<Option<BlsScalar>, VMError> {
+    client(session)
+        .block_event_digest(block_height)
+        .map_err(vm_error)
+}
+
+pub fn state_version(session: &mut Session) -> Result<u64, VMError> {
+    client(session).state_version().map_err(vm_error)
+}
+
+pub fn get_config(session: &mut Session) -> Result<StakeConfig, VMError> {
+    client(session).get_config().map_err(vm_error)
+}
+
+pub fn export_summary(
+    session: &mut Session,
+    summary: ExportSummary,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<ExportChunk, VMError> {
+    client(session)
+        .export_summary(summary, from, count_limit)
+        .map_err(vm_error)
+}
+
+/// Return the summary of the whole exported state, chained one chunk of
+/// [`EXPORT_CHUNK`] stakes at a time.
+pub fn summarize_export(
+    session: &mut Session,
+) -> Result<ExportSummary, VMError> {
+    let mut summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    while let Some(cursor) = from {
+        let chunk = export_summary(session, summary, cursor, EXPORT_CHUNK)?;
+        summary = chunk.summary;
+        from = chunk.next;
+    }
+    Ok(summary)
+}
+
+// "Feeder" queries
+
+pub fn stakes(
+    session: &mut Session,
+) -> Result<Vec<(StakeKeys, StakeData)>, VMError> {
+    collect(client(session).stakes())
+}
+
+pub fn prev_state_changes(
+    session: &mut Session,
+) -> Result<Vec<(BlsPublicKey, Option<StakeData>)>, VMError> {
+    collect(client(session).prev_state_changes())
+}
+
+pub fn export_stakes(
+    session: &mut Session,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<Vec<(StakeKeys, StakeData)>, VMError> {
+    collect(client(session).export_stakes(from, count_limit))
+}
+
+// "Management" transactions
+
+pub fn before_state_transition(
+    session: &mut Session,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).before_state_transition().map_err(vm_error)
+}
+
+pub fn set_config(
+    session: &mut Session,
+    config: &StakeConfig,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).set_config(config).map_err(vm_error)
+}
+
+pub fn insert_stake(
+    session: &mut Session,
+    keys: StakeKeys,
+    stake_data: StakeData,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .insert_stake(keys, stake_data)
+        .map_err(vm_error)
+}
+
+pub fn reward(
+    session: &mut Session,
+    rewards: Vec<Reward>,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).reward(rewards).map_err(vm_error)
+}
+
+/// Reward the given account with the given value, for no particular reason.
+pub fn add_reward(
+    session: &mut Session,
+    pk: &BlsPublicKey,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    let rewards = vec![Reward {
+        account: *pk,
+        value,
+        reason: RewardReason::Other,
+    }];
+    reward(session, rewards)
+}
+
+pub fn slash(
+    session: &mut Session,
+    pk: &BlsPublicKey,
+    value: Option<u64>,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).slash(pk, value).map_err(vm_error)
+}
+
+pub fn hard_slash(
+    session: &mut Session,
+    pk: &BlsPublicKey,
+    value: Option<u64>,
+    severity: Option<u8>,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .hard_slash(pk, value, severity)
+        .map_err(vm_error)
+}
+
+pub fn set_burnt_amount(
+    session: &mut Session,
+    burnt_amount: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .set_burnt_amount(burnt_amount)
+        .map_err(vm_error)
+}
+
+pub fn import_stakes(
+    session: &mut Session,
+    stakes: Vec<(StakeKeys, StakeData)>,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).import_stakes(stakes).map_err(vm_error)
+}
+
+pub fn finish_import(
+    session: &mut Session,
+    summary: &ExportSummary,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).finish_import(summary).map_err(vm_error)
+}
+
+pub fn migrate(
+    session: &mut Session,
+    from: u64,
+    to: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).migrate(from, to).map_err(vm_error)
+}
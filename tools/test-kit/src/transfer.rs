@@ -0,0 +1,474 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Typed wrappers for the entrypoints of the transfer contract.
+//!
+//! Queries and feeders return the data returned or fed by the contract, while
+//! transactions return the whole receipt of the call, with its events and
+//! the gas spent. Transactions that can only be called by a contract fail
+//! when called through these wrappers, which is useful to check their caller
+//! restrictions.
+
+use contracts_client::TransferClient;
+use dusk_core::abi::{ContractError, ContractId};
+use dusk_core::signatures::bls::PublicKey as AccountPublicKey;
+use dusk_core::transfer::moonlight::AccountData;
+use dusk_core::transfer::phoenix::{Note, NoteLeaf, NoteOpening};
+use dusk_core::transfer::withdraw::Withdraw;
+use dusk_core::transfer::{ContractToAccount, ContractToContract, Transaction};
+use dusk_core::BlsScalar;
+use dusk_vm::{CallReceipt, Error as VMError, Session};
+use transfer_contract::accounts::AccountProof;
+use transfer_contract::escrow::{CreateEscrow, Escrow};
+use transfer_contract::gas::{GasConfig, GasPriceInfo};
+use transfer_contract::migration::{
+    ExportChunk, ExportCursor, ExportSummary, StateItem,
+};
+use transfer_contract::nullifiers::NullifierProof;
+use transfer_contract::receipt::TransactionReceipt;
+use transfer_contract::scheduled::ScheduledWithdraw;
+use transfer_contract::validation::ValidationReport;
+use transfer_contract::verifier::{SetVerifier, Verifier};
+
+use crate::{collect, vm_error, EXPORT_CHUNK, GAS_LIMIT};
+
+/// The client of the transfer contract in the given session, with the
+/// [`GAS_LIMIT`] of the test kit.
+fn client(session: &mut Session) -> TransferClient<'_> {
+    TransferClient::new(session).with_gas_limit(GAS_LIMIT)
+}
+
+// Transactions
+
+pub fn mint(
+    session: &mut Session,
+    mint: &Withdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).mint(mint).map_err(vm_error)
+}
+
+pub fn mint_to_contract(
+    session: &mut Session,
+    mint: &ContractToContract,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).mint_to_contract(mint).map_err(vm_error)
+}
+
+pub fn deposit(
+    session: &mut Session,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).deposit(value).map_err(vm_error)
+}
+
+pub fn withdraw(
+    session: &mut Session,
+    withdraw: &Withdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).withdraw(withdraw).map_err(vm_error)
+}
+
+pub fn withdraw_scheduled(
+    session: &mut Session,
+    withdraw: &ScheduledWithdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .withdraw_scheduled(withdraw)
+        .map_err(vm_error)
+}
+
+pub fn convert(
+    session: &mut Session,
+    convert: &Withdraw,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).convert(convert).map_err(vm_error)
+}
+
+pub fn contract_to_contract(
+    session: &mut Session,
+    transfer: &ContractToContract,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .contract_to_contract(transfer)
+        .map_err(vm_error)
+}
+
+pub fn contract_to_account(
+    session: &mut Session,
+    transfer: &ContractToAccount,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .contract_to_account(transfer)
+        .map_err(vm_error)
+}
+
+pub fn create_escrow(
+    session: &mut Session,
+    create: &CreateEscrow,
+) -> Result<CallReceipt<u64>, VMError> {
+    client(session).create_escrow(create).map_err(vm_error)
+}
+
+pub fn release_escrow(
+    session: &mut Session,
+    id: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).release_escrow(id).map_err(vm_error)
+}
+
+pub fn cancel_escrow(
+    session: &mut Session,
+    id: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).cancel_escrow(id).map_err(vm_error)
+}
+
+// Queries
+
+pub fn root(session: &mut Session) -> Result<BlsScalar, VMError> {
+    client(session).root().map_err(vm_error)
+}
+
+pub fn account(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+) -> Result<AccountData, VMError> {
+    client(session).account(pk).map_err(vm_error)
+}
+
+pub fn accounts_root(session: &mut Session) -> Result<BlsScalar, VMError> {
+    client(session).accounts_root().map_err(vm_error)
+}
+
+pub fn account_proof(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+) -> Result<Option<AccountProof>, VMError> {
+    client(session).account_proof(pk).map_err(vm_error)
+}
+
+pub fn contract_balance(
+    session: &mut Session,
+    contract: ContractId,
+) -> Result<u64, VMError> {
+    client(session).contract_balance(contract).map_err(vm_error)
+}
+
+pub fn opening(
+    session: &mut Session,
+    pos: u64,
+) -> Result<Option<NoteOpening>, VMError> {
+    client(session).opening(pos).map_err(vm_error)
+}
+
+pub fn existing_nullifiers(
+    session: &mut Session,
+    nullifiers: Vec<BlsScalar>,
+) -> Result<Vec<BlsScalar>, VMError> {
+    client(session)
+        .existing_nullifiers(nullifiers)
+        .map_err(vm_error)
+}
+
+pub fn nullifiers_root(session: &mut Session) -> Result<BlsScalar, VMError> {
+    client(session).nullifiers_root().map_err(vm_error)
+}
+
+pub fn nullifier_root(
+    session: &mut Session,
+    block_height: u64,
+) -> Result<Option<BlsScalar>, VMError> {
+    client(session)
+        .nullifier_root(block_height)
+        .map_err(vm_error)
+}
+
+pub fn nullifier_proof(
+    session: &mut Session,
+    nullifier: &BlsScalar,
+) -> Result<NullifierProof, VMError> {
+    client(session).nullifier_proof(nullifier).map_err(vm_error)
+}
+
+pub fn block_event_digest(
+    session: &mut Session,
+    block_height: u64,
+) -> Result<Option<BlsScalar>, VMError> {
+    client(session)
+        .block_event_digest(block_height)
+        .map_err(vm_error)
+}
+
+pub fn escrow(
+    session: &mut Session,
+    id: u64,
+) -> Result<Option<Escrow>, VMError> {
+    client(session).escrow(id).map_err(vm_error)
+}
+
+pub fn validate_transaction(
+    session: &mut Session,
+    tx: &Transaction,
+) -> Result<ValidationReport, VMError> {
+    client(session).validate_transaction(tx).map_err(vm_error)
+}
+
+pub fn verifiers(
+    session: &mut Session,
+    inputs: u32,
+    outputs: u32,
+) -> Result<Vec<Verifier>, VMError> {
+    client(session).verifiers(inputs, outputs).map_err(vm_error)
+}
+
+pub fn state_version(session: &mut Session) -> Result<u64, VMError> {
+    client(session).state_version().map_err(vm_error)
+}
+
+pub fn export_summary(
+    session: &mut Session,
+    summary: ExportSummary,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<ExportChunk, VMError> {
+    client(session)
+        .export_summary(summary, from, count_limit)
+        .map_err(vm_error)
+}
+
+/// Return the summary of the whole exported state, chained one chunk of
+/// [`EXPORT_CHUNK`] items at a time.
+pub fn summarize_export(
+    session: &mut Session,
+) -> Result<ExportSummary, VMError> {
+    let mut summary = ExportSummary::EMPTY;
+    let mut from = Some(ExportCursor::START);
+    while let Some(cursor) = from {
+        let chunk = export_summary(session, summary, cursor, EXPORT_CHUNK)?;
+        summary = chunk.summary;
+        from = chunk.next;
+    }
+    Ok(summary)
+}
+
+pub fn gas_price_info(session: &mut Session) -> Result<GasPriceInfo, VMError> {
+    client(session).gas_price_info().map_err(vm_error)
+}
+
+pub fn num_notes(session: &mut Session) -> Result<u64, VMError> {
+    client(session).num_notes().map_err(vm_error)
+}
+
+pub fn chain_id(session: &mut Session) -> Result<u8, VMError> {
+    client(session).chain_id().map_err(vm_error)
+}
+
+// "Feeder" queries
+
+pub fn leaves_from_height(
+    session: &mut Session,
+    block_height: u64,
+) -> Result<Vec<NoteLeaf>, VMError> {
+    collect(client(session).leaves_from_height(block_height))
+}
+
+pub fn leaves_from_pos(
+    session: &mut Session,
+    pos: u64,
+) -> Result<Vec<NoteLeaf>, VMError> {
+    collect(client(session).leaves_from_pos(pos))
+}
+
+pub fn sync(
+    session: &mut Session,
+    from: u64,
+    count_limit: u64,
+) -> Result<Vec<NoteLeaf>, VMError> {
+    collect(client(session).sync(from, count_limit))
+}
+
+pub fn sync_nullifiers(
+    session: &mut Session,
+    from: u64,
+    count_limit: u64,
+) -> Result<Vec<BlsScalar>, VMError> {
+    collect(client(session).sync_nullifiers(from, count_limit))
+}
+
+pub fn sync_contract_balances(
+    session: &mut Session,
+    from: u64,
+    count_limit: u64,
+) -> Result<Vec<(ContractId, u64)>, VMError> {
+    collect(client(session).sync_contract_balances(from, count_limit))
+}
+
+pub fn sync_accounts(
+    session: &mut Session,
+    from: u64,
+    count_limit: u64,
+) -> Result<Vec<(AccountData, [u8; AccountPublicKey::SIZE])>, VMError> {
+    collect(client(session).sync_accounts(from, count_limit))
+}
+
+pub fn sync_escrows(
+    session: &mut Session,
+    from: u64,
+    count_limit: u64,
+) -> Result<Vec<(u64, Escrow)>, VMError> {
+    collect(client(session).sync_escrows(from, count_limit))
+}
+
+pub fn export_state(
+    session: &mut Session,
+    from: ExportCursor,
+    count_limit: u64,
+) -> Result<Vec<StateItem>, VMError> {
+    collect(client(session).export_state(from, count_limit))
+}
+
+pub fn frozen_accounts(
+    session: &mut Session,
+) -> Result<Vec<AccountPublicKey>, VMError> {
+    collect(client(session).frozen_accounts())
+}
+
+// "Management" transactions
+
+pub fn spend_and_execute(
+    session: &mut Session,
+    tx: &Transaction,
+) -> Result<CallReceipt<Result<Vec<u8>, ContractError>>, VMError> {
+    client(session).spend_and_execute(tx).map_err(vm_error)
+}
+
+pub fn refund(
+    session: &mut Session,
+    gas_spent: u64,
+) -> Result<CallReceipt<TransactionReceipt>, VMError> {
+    client(session).refund(gas_spent).map_err(vm_error)
+}
+
+pub fn push_note(
+    session: &mut Session,
+    block_height: u64,
+    note: Note,
+) -> Result<CallReceipt<Option<Note>>, VMError> {
+    client(session)
+        .push_note(block_height, note)
+        .map_err(vm_error)
+}
+
+pub fn update_root(session: &mut Session) -> Result<CallReceipt<()>, VMError> {
+    client(session).update_root().map_err(vm_error)
+}
+
+pub fn update_base_fee(
+    session: &mut Session,
+    gas_used: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).update_base_fee(gas_used).map_err(vm_error)
+}
+
+pub fn set_gas_config(
+    session: &mut Session,
+    config: &GasConfig,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).set_gas_config(config).map_err(vm_error)
+}
+
+pub fn add_account_balance(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .add_account_balance(pk, value)
+        .map_err(vm_error)
+}
+
+pub fn sub_account_balance(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .sub_account_balance(pk, value)
+        .map_err(vm_error)
+}
+
+pub fn insert_account(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+    account: AccountData,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .insert_account(pk, account)
+        .map_err(vm_error)
+}
+
+pub fn import_state(
+    session: &mut Session,
+    items: Vec<StateItem>,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).import_state(items).map_err(vm_error)
+}
+
+pub fn finish_import(
+    session: &mut Session,
+    summary: &ExportSummary,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).finish_import(summary).map_err(vm_error)
+}
+
+pub fn set_verifier(
+    session: &mut Session,
+    set: &SetVerifier,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).set_verifier(set).map_err(vm_error)
+}
+
+pub fn freeze_account(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).freeze_account(pk).map_err(vm_error)
+}
+
+pub fn unfreeze_account(
+    session: &mut Session,
+    pk: &AccountPublicKey,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).unfreeze_account(pk).map_err(vm_error)
+}
+
+pub fn add_contract_balance(
+    session: &mut Session,
+    contract: ContractId,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .add_contract_balance(contract, value)
+        .map_err(vm_error)
+}
+
+pub fn sub_contract_balance(
+    session: &mut Session,
+    contract: ContractId,
+    value: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session)
+        .sub_contract_balance(contract, value)
+        .map_err(vm_error)
+}
+
+pub fn migrate(
+    session: &mut Session,
+    from: u64,
+    to: u64,
+) -> Result<CallReceipt<()>, VMError> {
+    client(session).migrate(from, to).map_err(vm_error)
+}
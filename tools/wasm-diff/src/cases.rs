@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_gas_bench::{Call, Case};
+use dusk_core::abi::ContractId;
+use dusk_core::stake::STAKE_CONTRACT;
+use dusk_core::transfer::TRANSFER_CONTRACT;
+use stake_contract::migration::ExportCursor as StakeExportCursor;
+use transfer_contract::migration::ExportCursor;
+
+/// The smallest offset that doesn't fit in 32 bits, which a truncating cast
+/// turns into 0 on wasm32.
+const WIDE: u64 = 1 << 32;
+
+/// The feeders taking a `(from, count_limit)` argument.
+const RANGE_FEEDERS: [(&str, ContractId, &str); 5] = [
+    ("transfer", TRANSFER_CONTRACT, "sync"),
+    ("transfer", TRANSFER_CONTRACT, "sync_nullifiers"),
+    ("transfer", TRANSFER_CONTRACT, "sync_contract_balances"),
+    ("transfer", TRANSFER_CONTRACT, "sync_accounts"),
+    ("transfer", TRANSFER_CONTRACT, "sync_escrows"),
+];
+
+/// The cases passing offsets and limits that don't fit in 32 bits to the
+/// feeders, which a build casting them to `usize` by truncation handles
+/// differently on wasm32 and wasm64.
+///
+/// Every range feeder is called from an offset past the end of the state,
+/// which feeds nothing, and with a limit of one more than `u32::MAX`, which
+/// feeds everything. The export feeders are called in the same way, from a
+/// cursor past the end of the notes for the transfer contract, whose stakes
+/// are keyed instead. `leaves_from_pos` is called from a position past the
+/// end of the tree.
+#[must_use]
+pub fn pointer_width_cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    for (prefix, contract, fn_name) in RANGE_FEEDERS {
+        for (suffix, arg) in [("from", (WIDE, 0)), ("limit", (0, WIDE + 1))] {
+            cases.push(feeder_case(
+                format!("{prefix}::{fn_name} (wide {suffix})"),
+                format!("{prefix}::{fn_name}"),
+                contract,
+                fn_name,
+                serialize(&arg),
+            ));
+        }
+    }
+
+    for (suffix, arg) in [
+        ("from", (ExportCursor::Note(WIDE), 0)),
+        ("limit", (ExportCursor::START, WIDE + 1)),
+    ] {
+        cases.push(feeder_case(
+            format!("transfer::export_state (wide {suffix})"),
+            String::from("transfer::export_state"),
+            TRANSFER_CONTRACT,
+            "export_state",
+            serialize(&arg),
+        ));
+    }
+
+    cases.push(feeder_case(
+        String::from("stake::export_stakes (wide limit)"),
+        String::from("stake::export_stakes"),
+        STAKE_CONTRACT,
+        "export_stakes",
+        serialize(&(StakeExportCursor::START, WIDE + 1)),
+    ));
+
+    cases.push(feeder_case(
+        String::from("transfer::leaves_from_pos (wide pos)"),
+        String::from("transfer::leaves_from_pos"),
+        TRANSFER_CONTRACT,
+        "leaves_from_pos",
+        serialize(&WIDE),
+    ));
+
+    cases
+}
+
+fn feeder_case(
+    name: String,
+    covers: String,
+    contract: ContractId,
+    fn_name: &str,
+    arg: Vec<u8>,
+) -> Case {
+    Case {
+        name,
+        covers: vec![covers],
+        call: Call::Function {
+            contract,
+            fn_name: String::from(fn_name),
+            arg,
+            feeder: true,
+        },
+    }
+}
+
+fn serialize<T>(arg: &T) -> Vec<u8>
+where
+    T: rkyv::Serialize<rkyv::ser::serializers::AllocSerializer<64>>,
+{
+    rkyv::to_bytes::<_, 64>(arg)
+        .expect("Serializing the argument should succeed")
+        .to_vec()
+}
@@ -0,0 +1,227 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt;
+
+use dusk_bytes::Serializable;
+use dusk_core::abi::Event;
+
+use crate::{Outcome, Run, State};
+
+/// The number of leading hex characters of bytes written in a divergence.
+const HEX_PREFIX: usize = 16;
+
+/// A difference between the outcomes of a case run against two builds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The name of the case, or `fixture` for the state of the fixture.
+    pub case: String,
+    /// What differs.
+    pub what: String,
+    /// The outcome with the reference builds.
+    pub reference: String,
+    /// The outcome with the other builds.
+    pub other: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} differs, {} against {}",
+            self.case, self.what, self.reference, self.other
+        )
+    }
+}
+
+/// Compare the run of the scenario against the reference builds with the
+/// run against the other builds.
+///
+/// The gas spent by a case diverges if it differs by more than the given
+/// tolerance in percent, since the instructions of the targets differ.
+/// Everything else has to match exactly.
+#[must_use]
+pub fn compare(
+    reference: &Run,
+    other: &Run,
+    gas_tolerance: f64,
+) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    compare_states(
+        "fixture",
+        &reference.fixture,
+        &other.fixture,
+        &mut divergences,
+    );
+
+    for (case, outcome) in &reference.outcomes {
+        match other.outcomes.get(case) {
+            Some(other) => compare_outcomes(
+                case,
+                outcome,
+                other,
+                gas_tolerance,
+                &mut divergences,
+            ),
+            None => {
+                divergences.push(divergence(case, "case", "run", "missing"))
+            }
+        }
+    }
+    for case in other.outcomes.keys() {
+        if !reference.outcomes.contains_key(case) {
+            divergences.push(divergence(case, "case", "missing", "run"));
+        }
+    }
+
+    divergences
+}
+
+fn compare_outcomes(
+    case: &str,
+    reference: &Outcome,
+    other: &Outcome,
+    gas_tolerance: f64,
+    divergences: &mut Vec<Divergence>,
+) {
+    if reference.result != other.result {
+        divergences.push(divergence(
+            case,
+            "result",
+            result(&reference.result),
+            result(&other.result),
+        ));
+    }
+
+    if reference.fed.len() != other.fed.len() {
+        divergences.push(divergence(
+            case,
+            "number of items fed",
+            reference.fed.len(),
+            other.fed.len(),
+        ));
+    } else if let Some(i) = reference
+        .fed
+        .iter()
+        .zip(&other.fed)
+        .position(|(r, o)| r != o)
+    {
+        divergences.push(divergence(
+            case,
+            format!("item {i} fed"),
+            bytes(&reference.fed[i]),
+            bytes(&other.fed[i]),
+        ));
+    }
+
+    compare_events(case, &reference.events, &other.events, divergences);
+
+    let change = gas_change(reference.gas_spent, other.gas_spent);
+    if change.abs() > gas_tolerance {
+        divergences.push(divergence(
+            case,
+            format!("gas spent ({change:+.1}%)"),
+            reference.gas_spent,
+            other.gas_spent,
+        ));
+    }
+
+    compare_states(case, &reference.state, &other.state, divergences);
+}
+
+fn compare_events(
+    case: &str,
+    reference: &[Event],
+    other: &[Event],
+    divergences: &mut Vec<Divergence>,
+) {
+    if reference.len() != other.len() {
+        divergences.push(divergence(
+            case,
+            "number of events",
+            reference.len(),
+            other.len(),
+        ));
+        return;
+    }
+
+    for (i, (reference, other)) in reference.iter().zip(other).enumerate() {
+        if reference.source != other.source || reference.topic != other.topic {
+            divergences.push(divergence(
+                case,
+                format!("event {i}"),
+                format!("{}::{}", reference.source, reference.topic),
+                format!("{}::{}", other.source, other.topic),
+            ));
+        } else if reference.data != other.data {
+            divergences.push(divergence(
+                case,
+                format!("data of event {i} ({})", reference.topic),
+                bytes(&reference.data),
+                bytes(&other.data),
+            ));
+        }
+    }
+}
+
+fn compare_states(
+    case: &str,
+    reference: &State,
+    other: &State,
+    divergences: &mut Vec<Divergence>,
+) {
+    for ((name, reference), (_, other)) in
+        reference.named().into_iter().zip(other.named())
+    {
+        if reference != other {
+            divergences.push(divergence(
+                case,
+                name,
+                bytes(&reference.to_bytes()),
+                bytes(&other.to_bytes()),
+            ));
+        }
+    }
+}
+
+/// The change of the gas spent relative to the reference, in percent.
+fn gas_change(reference: u64, other: u64) -> f64 {
+    if reference == 0 {
+        return if other == 0 { 0.0 } else { f64::INFINITY };
+    }
+    (other as f64 - reference as f64) * 100.0 / reference as f64
+}
+
+fn divergence(
+    case: &str,
+    what: impl Into<String>,
+    reference: impl ToString,
+    other: impl ToString,
+) -> Divergence {
+    Divergence {
+        case: String::from(case),
+        what: what.into(),
+        reference: reference.to_string(),
+        other: other.to_string(),
+    }
+}
+
+fn result(result: &Result<Vec<u8>, String>) -> String {
+    match result {
+        Ok(data) => format!("ok {}", bytes(data)),
+        Err(err) => format!("error {err}"),
+    }
+}
+
+/// The hex encoding of the given bytes, abbreviated if they're long.
+fn bytes(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    if hex.len() > HEX_PREFIX {
+        format!("0x{}.. ({} bytes)", &hex[..HEX_PREFIX], bytes.len())
+    } else {
+        format!("0x{hex}")
+    }
+}
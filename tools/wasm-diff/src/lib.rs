@@ -0,0 +1,300 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Differential testing of the wasm32 and wasm64 builds of the genesis
+//! contracts.
+//!
+//! The same scenario, the cases of the gas benchmarks and a few calls
+//! passing offsets that don't fit in 32 bits, is [`run`] against two
+//! [`Builds`] of the contracts, and the [`Outcome`] of every case is
+//! [`compare`]d: the data returned, the items fed, the events emitted, the
+//! [`State`] left behind, and the gas spent.
+//!
+//! The roots of the commits of the VM can't be compared, since they cover
+//! the bytecode and memory of the contracts, which differ between targets.
+//! The [`State`] is compared through the roots and export checksums the
+//! contracts compute instead, which only depend on their state.
+
+#![deny(missing_docs)]
+#![deny(unused_extern_crates)]
+
+mod cases;
+mod compare;
+
+pub use cases::pointer_width_cases;
+pub use compare::{compare, Divergence};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::mpsc;
+
+use contracts_gas_bench::{cases, Call, Case, Fixture, Sizes, GAS_LIMIT};
+use contracts_test_kit::{stake, transfer};
+use dusk_core::abi::Event;
+use dusk_core::BlsScalar;
+use dusk_vm::{execute, ExecutionConfig, Session};
+
+/// The target a contract is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// `wasm32-unknown-unknown`.
+    Wasm32,
+    /// `wasm64-unknown-unknown`.
+    Wasm64,
+}
+
+impl Target {
+    /// The target triple.
+    #[must_use]
+    pub const fn triple(self) -> &'static str {
+        match self {
+            Self::Wasm32 => "wasm32-unknown-unknown",
+            Self::Wasm64 => "wasm64-unknown-unknown",
+        }
+    }
+
+    /// The other target.
+    #[must_use]
+    pub const fn flip(self) -> Self {
+        match self {
+            Self::Wasm32 => Self::Wasm64,
+            Self::Wasm64 => Self::Wasm32,
+        }
+    }
+}
+
+/// The targets the transfer and stake contracts are built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Builds {
+    /// The target of the transfer contract.
+    pub transfer: Target,
+    /// The target of the stake contract.
+    pub stake: Target,
+}
+
+impl Builds {
+    /// The targets the contracts are deployed with, as built by the `wasm`
+    /// target of their Makefiles.
+    pub const DEPLOYED: Self = Self {
+        transfer: Target::Wasm64,
+        stake: Target::Wasm32,
+    };
+
+    /// The same builds, with the transfer contract built for the other
+    /// target.
+    #[must_use]
+    pub const fn flip_transfer(self) -> Self {
+        Self {
+            transfer: self.transfer.flip(),
+            stake: self.stake,
+        }
+    }
+
+    /// The same builds, with the stake contract built for the other target.
+    #[must_use]
+    pub const fn flip_stake(self) -> Self {
+        Self {
+            transfer: self.transfer,
+            stake: self.stake.flip(),
+        }
+    }
+
+    /// Build the fixture of the gas benchmarks with the given amount of
+    /// filling state, deploying these builds of the contracts.
+    ///
+    /// # Panics
+    /// Panics if the contracts weren't built for the targets with `make wasm`.
+    #[must_use]
+    pub fn fixture(self, sizes: Sizes) -> Fixture {
+        Fixture::with_bytecode(
+            sizes,
+            read_bytecode("transfer_contract", self.transfer),
+            read_bytecode("stake_contract", self.stake),
+        )
+    }
+}
+
+/// The state of the contracts, through the roots and export checksums they
+/// compute over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    /// The root of the tree of notes.
+    pub notes_root: BlsScalar,
+    /// The root of the tree of nullifiers.
+    pub nullifiers_root: BlsScalar,
+    /// The root of the tree of accounts.
+    pub accounts_root: BlsScalar,
+    /// The checksum of the export of the state of the transfer contract.
+    pub transfer_checksum: BlsScalar,
+    /// The checksum of the export of the state of the stake contract.
+    pub stake_checksum: BlsScalar,
+}
+
+impl State {
+    /// Read the state of the contracts in the given session.
+    ///
+    /// # Panics
+    /// Panics if querying the contracts fails.
+    #[must_use]
+    pub fn read(session: &mut Session) -> Self {
+        let query = "Querying the state should succeed";
+        Self {
+            notes_root: transfer::root(session).expect(query),
+            nullifiers_root: transfer::nullifiers_root(session).expect(query),
+            accounts_root: transfer::accounts_root(session).expect(query),
+            transfer_checksum: transfer::summarize_export(session)
+                .expect(query)
+                .checksum,
+            stake_checksum: stake::summarize_export(session)
+                .expect(query)
+                .checksum,
+        }
+    }
+
+    /// The named roots and checksums, in the order of the fields.
+    #[must_use]
+    pub fn named(&self) -> [(&'static str, BlsScalar); 5] {
+        [
+            ("notes root", self.notes_root),
+            ("nullifiers root", self.nullifiers_root),
+            ("accounts root", self.accounts_root),
+            ("transfer checksum", self.transfer_checksum),
+            ("stake checksum", self.stake_checksum),
+        ]
+    }
+}
+
+/// What running a case did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The data returned, or the error the call or transaction failed with.
+    pub result: Result<Vec<u8>, String>,
+    /// The events emitted.
+    pub events: Vec<Event>,
+    /// The items fed, by a feeder.
+    pub fed: Vec<Vec<u8>>,
+    /// The gas spent.
+    pub gas_spent: u64,
+    /// The state of the contracts after the case.
+    pub state: State,
+}
+
+impl Outcome {
+    /// Run the given case in the given session.
+    ///
+    /// A failing case has an outcome too, since a build failing where the
+    /// other succeeds is a divergence.
+    ///
+    /// # Panics
+    /// Panics if reading the state after the case fails.
+    #[must_use]
+    pub fn run(session: &mut Session, case: &Case) -> Self {
+        let mut fed = Vec::new();
+        let (result, events, gas_spent) = match &case.call {
+            Call::Function {
+                contract,
+                fn_name,
+                arg,
+                feeder,
+            } => {
+                let receipt = if *feeder {
+                    let (feeder, receiver) = mpsc::channel();
+                    let receipt = session.feeder_call_raw(
+                        *contract,
+                        fn_name,
+                        arg.clone(),
+                        GAS_LIMIT,
+                        feeder,
+                    );
+                    fed.extend(receiver.try_iter());
+                    receipt
+                } else {
+                    session.call_raw(*contract, fn_name, arg.clone(), GAS_LIMIT)
+                };
+                match receipt {
+                    Ok(receipt) => {
+                        (Ok(receipt.data), receipt.events, receipt.gas_spent)
+                    }
+                    Err(err) => (Err(format!("{err:?}")), Vec::new(), 0),
+                }
+            }
+            Call::Transaction(tx) => {
+                match execute(session, tx, &ExecutionConfig::DEFAULT) {
+                    Ok(receipt) => (
+                        receipt.data.map_err(|err| format!("{err:?}")),
+                        receipt.events,
+                        receipt.gas_spent,
+                    ),
+                    Err(err) => {
+                        (Err(format!("rejected: {err:?}")), Vec::new(), 0)
+                    }
+                }
+            }
+        };
+
+        Self {
+            result,
+            events,
+            fed,
+            gas_spent,
+            state: State::read(session),
+        }
+    }
+}
+
+/// The outcomes of running the scenario against some builds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    /// The state of the fixture, before any case.
+    pub fixture: State,
+    /// The outcome of every case, by name.
+    pub outcomes: BTreeMap<String, Outcome>,
+}
+
+/// Run the scenario against the given builds: every case of the gas
+/// benchmarks, and the [`pointer_width_cases`], each in a new session
+/// started from a fixture with the given amount of filling state.
+///
+/// # Panics
+/// Panics if the contracts weren't built for the targets with `make wasm`,
+/// or if setting up the fixture fails.
+#[must_use]
+pub fn run(builds: Builds, sizes: Sizes) -> Run {
+    let fixture = builds.fixture(sizes);
+
+    let mut cases = cases(&fixture);
+    cases.extend(pointer_width_cases());
+
+    let outcomes = cases
+        .iter()
+        .map(|case| {
+            let outcome = Outcome::run(&mut fixture.session(), case);
+            (case.name.clone(), outcome)
+        })
+        .collect();
+
+    Run {
+        fixture: State::read(&mut fixture.session()),
+        outcomes,
+    }
+}
+
+/// Read the contract with the given name, as built for the given target,
+/// relative to the root of the repository.
+fn read_bytecode(contract: &str, target: Target) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../target/dusk")
+        .join(target.triple())
+        .join("release")
+        .join(format!("{contract}.wasm"));
+    std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Reading {} should succeed, the contracts need to be built for \
+             both targets with `make wasm` in `tools/wasm-diff` first: {err}",
+            path.display()
+        )
+    })
+}
@@ -0,0 +1,116 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::BTreeMap;
+
+use contracts_wasm_diff::{compare, Divergence, Outcome, Run, State};
+use dusk_core::abi::Event;
+use dusk_core::transfer::TRANSFER_CONTRACT;
+use dusk_core::BlsScalar;
+
+fn state() -> State {
+    State {
+        notes_root: BlsScalar::from(1u64),
+        nullifiers_root: BlsScalar::from(2u64),
+        accounts_root: BlsScalar::from(3u64),
+        transfer_checksum: BlsScalar::from(4u64),
+        stake_checksum: BlsScalar::from(5u64),
+    }
+}
+
+fn outcome() -> Outcome {
+    Outcome {
+        result: Ok(vec![1, 2, 3]),
+        events: vec![Event {
+            source: TRANSFER_CONTRACT,
+            topic: String::from("deposit"),
+            data: vec![4, 5],
+        }],
+        fed: vec![vec![6], vec![7]],
+        gas_spent: 1000,
+        state: state(),
+    }
+}
+
+fn run(outcomes: impl IntoIterator<Item = (&'static str, Outcome)>) -> Run {
+    Run {
+        fixture: state(),
+        outcomes: outcomes
+            .into_iter()
+            .map(|(case, outcome)| (String::from(case), outcome))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+fn whats(divergences: &[Divergence]) -> Vec<&str> {
+    divergences
+        .iter()
+        .map(|divergence| divergence.what.as_str())
+        .collect()
+}
+
+#[test]
+fn identical_runs() {
+    let reference = run([("a", outcome()), ("b", outcome())]);
+    assert_eq!(compare(&reference, &reference, 0.0), vec![]);
+}
+
+#[test]
+fn gas_tolerance() {
+    let reference = run([("a", outcome())]);
+    let mut other = outcome();
+    other.gas_spent = 1050;
+    let other = run([("a", other)]);
+
+    assert_eq!(compare(&reference, &other, 10.0), vec![]);
+    assert_eq!(
+        compare(&reference, &other, 1.0),
+        vec![Divergence {
+            case: String::from("a"),
+            what: String::from("gas spent (+5.0%)"),
+            reference: String::from("1000"),
+            other: String::from("1050"),
+        }]
+    );
+}
+
+#[test]
+fn divergences() {
+    let reference = run([("a", outcome()), ("b", outcome())]);
+
+    let mut truncated = outcome();
+    truncated.fed.truncate(1);
+    truncated.state.notes_root = BlsScalar::from(10u64);
+    let mut failed = outcome();
+    failed.result = Err(String::from("Panic"));
+    failed.events[0].data = vec![4];
+    let mut other = run([("a", truncated), ("b", failed), ("c", outcome())]);
+    other.fixture.stake_checksum = BlsScalar::zero();
+
+    let divergences = compare(&reference, &other, 10.0);
+    assert_eq!(
+        whats(&divergences),
+        vec![
+            "stake checksum",
+            "number of items fed",
+            "notes root",
+            "result",
+            "data of event 0 (deposit)",
+            "case",
+        ]
+    );
+    assert_eq!(
+        divergences[0].to_string(),
+        "fixture: stake checksum differs, 0x0500000000000000.. (32 bytes) \
+         against 0x0000000000000000.. (32 bytes)"
+    );
+    assert_eq!(
+        divergences[3].to_string(),
+        "b: result differs, ok 0x010203 against error Panic"
+    );
+    assert_eq!(divergences[5].case, "c");
+    assert_eq!(divergences[5].reference, "missing");
+}
@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use contracts_gas_bench::Sizes;
+use contracts_wasm_diff::{compare, run, Builds};
+
+/// Enough filling state for the feeders to feed more than one item.
+const SIZES: Sizes = Sizes {
+    accounts: 16,
+    notes: 16,
+    stakes: 16,
+};
+
+/// The instructions of the targets differ, and so does the gas they spend.
+const GAS_TOLERANCE: f64 = 10.0;
+
+fn assert_builds_match(other: Builds) {
+    let reference = run(Builds::DEPLOYED, SIZES);
+    let other_run = run(other, SIZES);
+
+    let divergences = compare(&reference, &other_run, GAS_TOLERANCE);
+    assert!(
+        divergences.is_empty(),
+        "{other:?} diverges from {:?}:\n{}",
+        Builds::DEPLOYED,
+        divergences
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn transfer_builds_match() {
+    assert_builds_match(Builds::DEPLOYED.flip_transfer());
+}
+
+#[test]
+fn stake_builds_match() {
+    assert_builds_match(Builds::DEPLOYED.flip_stake());
+}